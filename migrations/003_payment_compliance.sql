@@ -0,0 +1,19 @@
+-- RBI payment compliance: per-customer payment tracking and KYC status
+-- Version: 3.0.0
+-- Created: 2024-01-20
+
+-- Track KYC verification on users so high-value payments can be gated
+ALTER TABLE users ADD COLUMN IF NOT EXISTS kyc_verified BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS kyc_verified_at TIMESTAMP WITH TIME ZONE;
+
+-- Payments record the paying customer directly so daily limits can be summed
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS customer_id UUID REFERENCES users(id);
+
+UPDATE payments p
+SET customer_id = o.customer_id
+FROM orders o
+WHERE p.order_id = o.id AND p.customer_id IS NULL;
+
+ALTER TABLE payments ALTER COLUMN customer_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_payments_customer_created ON payments(customer_id, created_at);
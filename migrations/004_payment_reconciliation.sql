@@ -0,0 +1,17 @@
+-- Payment reconciliation: gateway polling state, failure reasons and retries
+-- Version: 4.0.0
+-- Created: 2024-01-22
+
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS reconcile_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS next_reconcile_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS failure_reason TEXT;
+
+-- A retry is a new payment row for the same order, linked to the attempt it replaces
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS retry_of UUID REFERENCES payments(id);
+
+CREATE INDEX IF NOT EXISTS idx_payments_reconcile ON payments(status, next_reconcile_at)
+    WHERE status IN ('pending', 'processing');
+
+-- At most one live or settled payment per order
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payments_one_active_per_order ON payments(order_id)
+    WHERE status IN ('pending', 'processing', 'completed');
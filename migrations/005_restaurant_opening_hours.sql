@@ -0,0 +1,10 @@
+-- Restaurant opening hours: schedule state tracked by the opening hours scheduler
+-- Version: 5.0.0
+-- Created: 2024-01-23
+
+-- Last open/closed state the scheduler applied. is_accepting_orders is only
+-- changed when this flips, so a manual pause lasts until the next boundary.
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS schedule_is_open BOOLEAN;
+
+-- Legacy rows may hold NULL hours
+UPDATE restaurants SET opening_hours = '{}'::jsonb WHERE opening_hours IS NULL;
@@ -0,0 +1,34 @@
+-- Menu modifiers: variants (sizes, portions) and add-ons for menu items
+-- Version: 6.0.0
+-- Created: 2024-01-24
+
+CREATE TABLE IF NOT EXISTS menu_modifier_groups (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    menu_item_id UUID NOT NULL REFERENCES menu_items(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('variant', 'add_on')),
+    min_selections INTEGER NOT NULL DEFAULT 0 CHECK (min_selections >= 0),
+    max_selections INTEGER NOT NULL DEFAULT 1 CHECK (max_selections >= 1),
+    is_required BOOLEAN NOT NULL DEFAULT FALSE,
+    display_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (min_selections <= max_selections)
+);
+
+CREATE TABLE IF NOT EXISTS menu_modifier_options (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    group_id UUID NOT NULL REFERENCES menu_modifier_groups(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    price_delta DECIMAL(10, 2) NOT NULL DEFAULT 0.00,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    is_available BOOLEAN NOT NULL DEFAULT TRUE,
+    display_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (group_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_menu_modifier_groups_menu_item_id ON menu_modifier_groups(menu_item_id);
+CREATE INDEX IF NOT EXISTS idx_menu_modifier_options_group_id ON menu_modifier_options(group_id);
+
+CREATE TRIGGER update_menu_modifier_groups_updated_at BEFORE UPDATE ON menu_modifier_groups FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,9 @@
+-- Bulk menu import: restaurant-assigned SKUs for upserting menu items
+-- Version: 7.0.0
+-- Created: 2024-01-25
+
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS external_sku VARCHAR(100);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_menu_items_restaurant_sku
+    ON menu_items(restaurant_id, external_sku)
+    WHERE external_sku IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Menu inventory: per-item stock counts, automatic sell-out and daily restock
+-- Version: 8.0.0
+-- Created: 2024-01-26
+
+-- NULL stock_quantity means the item's stock is not tracked
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS stock_quantity INTEGER CHECK (stock_quantity >= 0);
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS daily_stock INTEGER CHECK (daily_stock > 0);
+-- Set when the item was switched off because it ran out, so restocking can switch it back on
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS sold_out_automatically BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS last_restocked_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_menu_items_daily_stock
+    ON menu_items(last_restocked_at)
+    WHERE daily_stock IS NOT NULL;
+
+-- Stock taken by confirmed orders, returned when the order is cancelled
+CREATE TABLE IF NOT EXISTS menu_stock_reservations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    menu_item_id UUID NOT NULL REFERENCES menu_items(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    released_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_menu_stock_reservations_order_id ON menu_stock_reservations(order_id);
@@ -0,0 +1,29 @@
+-- Search: full-text documents and trigram indexes for restaurants and dishes
+-- Version: 9.0.0
+-- Created: 2024-01-27
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- 'simple' configuration: no English stemming, which mangles Hindi dish names
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS search_document tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(cuisine_type, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'C')
+    ) STORED;
+
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS search_document tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(category, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'C') ||
+        setweight(jsonb_to_tsvector('simple', coalesce(ingredients, '[]'::jsonb), '["string"]'), 'D')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_restaurants_search_document ON restaurants USING GIN (search_document);
+CREATE INDEX IF NOT EXISTS idx_menu_items_search_document ON menu_items USING GIN (search_document);
+
+-- Typo-tolerant matching and autocomplete on names
+CREATE INDEX IF NOT EXISTS idx_restaurants_name_trgm ON restaurants USING GIN (LOWER(name) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_restaurants_cuisine_trgm ON restaurants USING GIN (LOWER(cuisine_type) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_menu_items_name_trgm ON menu_items USING GIN (LOWER(name) gin_trgm_ops);
@@ -0,0 +1,10 @@
+-- Serviceability: per-restaurant delivery radius or polygon
+-- Version: 10.0.0
+-- Created: 2024-01-28
+
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS delivery_area JSONB NOT NULL
+    DEFAULT '{"type": "radius", "radius_km": 5.0}';
+
+-- Bounding-box prefilter for nearby restaurant discovery
+CREATE INDEX IF NOT EXISTS idx_restaurants_location ON restaurants(latitude, longitude)
+    WHERE is_active = true;
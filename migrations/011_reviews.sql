@@ -0,0 +1,85 @@
+-- Ratings and reviews: replies, moderation and aggregate ratings
+-- Version: 11.0.0
+-- Created: 2024-01-29
+
+-- Restaurant reviews point at restaurants, not users
+ALTER TABLE reviews DROP CONSTRAINT IF EXISTS reviews_reviewee_id_fkey;
+
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS reply TEXT;
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS replied_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published'
+    CHECK (status IN ('published', 'reported', 'removed'));
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS report_count INTEGER NOT NULL DEFAULT 0;
+
+-- One review per order and reviewee. Where older data has more than one,
+-- the first review written is kept.
+DELETE FROM reviews
+WHERE id IN (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (
+            PARTITION BY order_id, reviewee_type ORDER BY created_at NULLS LAST, id
+        ) AS position
+        FROM reviews
+    ) ranked
+    WHERE position > 1
+);
+ALTER TABLE reviews DROP CONSTRAINT IF EXISTS reviews_order_reviewee_unique;
+ALTER TABLE reviews ADD CONSTRAINT reviews_order_reviewee_unique UNIQUE (order_id, reviewee_type);
+
+CREATE INDEX IF NOT EXISTS idx_reviews_reviewee ON reviews(reviewee_type, reviewee_id, created_at DESC)
+    WHERE status <> 'removed';
+CREATE INDEX IF NOT EXISTS idx_reviews_moderation ON reviews(updated_at)
+    WHERE status = 'reported';
+
+CREATE TABLE IF NOT EXISTS review_reports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    review_id UUID NOT NULL REFERENCES reviews(id) ON DELETE CASCADE,
+    reporter_id UUID NOT NULL REFERENCES users(id),
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (review_id, reporter_id)
+);
+
+-- Running sums let ratings be updated without rescanning reviews
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS rating_sum BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE delivery_persons ADD COLUMN IF NOT EXISTS rating_sum BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE delivery_persons ADD COLUMN IF NOT EXISTS total_ratings INTEGER NOT NULL DEFAULT 0;
+
+-- Start the sums from the reviews already written. Ratings use the same
+-- smoothing as the app: a prior of 3.8 weighted as 5 reviews
+-- (PRIOR_MEAN_RATING and PRIOR_WEIGHT in src/reviews/models.rs).
+UPDATE restaurants r
+SET rating_sum = totals.rating_sum,
+    total_reviews = totals.review_count,
+    rating = ROUND(((totals.rating_sum + 3.8 * 5) / (totals.review_count + 5))::numeric, 2)
+FROM (
+    SELECT restaurants.id, COALESCE(SUM(reviews.rating), 0) AS rating_sum, COUNT(reviews.id) AS review_count
+    FROM restaurants
+    LEFT JOIN reviews
+        ON reviews.reviewee_id = restaurants.id
+        AND reviews.reviewee_type = 'restaurant'
+        AND reviews.status <> 'removed'
+    GROUP BY restaurants.id
+) totals
+WHERE totals.id = r.id;
+
+-- Rider reviews point at the rider's user id
+UPDATE delivery_persons dp
+SET rating_sum = totals.rating_sum,
+    total_ratings = totals.review_count,
+    rating = ROUND(((totals.rating_sum + 3.8 * 5) / (totals.review_count + 5))::numeric, 2)
+FROM (
+    SELECT delivery_persons.id, COALESCE(SUM(reviews.rating), 0) AS rating_sum, COUNT(reviews.id) AS review_count
+    FROM delivery_persons
+    LEFT JOIN reviews
+        ON reviews.reviewee_id = delivery_persons.user_id
+        AND reviews.reviewee_type = 'delivery_person'
+        AND reviews.status <> 'removed'
+    GROUP BY delivery_persons.id
+) totals
+WHERE totals.id = dp.id;
+
+-- Last moderation decision, for the audit trail
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS moderated_by UUID REFERENCES users(id);
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS moderated_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS moderation_note TEXT;
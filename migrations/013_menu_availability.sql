@@ -0,0 +1,15 @@
+-- Time-based menu availability for items and categories (IST)
+-- Version: 13.0.0
+-- Created: 2024-01-31
+
+-- NULL means the item can be ordered whenever the restaurant is open
+ALTER TABLE menu_items ADD COLUMN IF NOT EXISTS availability JSONB;
+
+CREATE TABLE IF NOT EXISTS menu_category_availability (
+    restaurant_id UUID NOT NULL REFERENCES restaurants(id) ON DELETE CASCADE,
+    category VARCHAR(100) NOT NULL,
+    availability JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (restaurant_id, category)
+);
@@ -0,0 +1,35 @@
+-- Restaurant-run promotions and order bill breakdown
+-- Version: 14.0.0
+-- Created: 2024-02-01
+
+CREATE TABLE IF NOT EXISTS restaurant_promotions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    restaurant_id UUID NOT NULL REFERENCES restaurants(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    -- {"type": "percentage_off" | "flat_off" | "buy_one_get_one" | "free_delivery", ...}
+    kind JSONB NOT NULL,
+    -- {"item_ids": [...], "categories": [...]}; both empty means the whole menu
+    eligibility JSONB NOT NULL DEFAULT '{}',
+    min_order DECIMAL(10, 2),
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMP WITH TIME ZONE,
+    -- Recurring days and hours in IST, same shape as menu availability
+    availability JSONB,
+    stackable_with_coupons BOOLEAN NOT NULL DEFAULT TRUE,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_restaurant_promotions_restaurant_active
+    ON restaurant_promotions(restaurant_id, is_active);
+
+CREATE TRIGGER update_restaurant_promotions_updated_at BEFORE UPDATE ON restaurant_promotions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS discount_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS coupon_id UUID REFERENCES coupons(id);
+-- Items total, applied promotions, coupon and delivery fee as priced at checkout
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS bill JSONB;
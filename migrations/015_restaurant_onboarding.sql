@@ -0,0 +1,33 @@
+-- Restaurant onboarding and KYC review
+-- Version: 15.0.0
+-- Created: 2024-02-02
+
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS onboarding_status VARCHAR(30) NOT NULL DEFAULT 'draft'
+    CHECK (onboarding_status IN ('draft', 'documents_submitted', 'under_review', 'approved', 'live', 'rejected'));
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS onboarding_submitted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS onboarding_reviewed_by UUID REFERENCES users(id);
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS onboarding_reviewed_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS onboarding_review_note TEXT;
+-- is_active now only means "listed to customers"; deletion is tracked separately
+-- so that restaurants still onboarding stay manageable by their owners
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Restaurants that were already trading are live; inactive ones were deleted
+UPDATE restaurants SET onboarding_status = 'live' WHERE is_active = true;
+UPDATE restaurants SET deleted_at = updated_at WHERE is_active = false AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_restaurants_onboarding_status
+    ON restaurants(onboarding_status, onboarding_submitted_at)
+    WHERE onboarding_status IN ('documents_submitted', 'under_review');
+
+CREATE TABLE IF NOT EXISTS restaurant_kyc_documents (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    restaurant_id UUID NOT NULL REFERENCES restaurants(id) ON DELETE CASCADE,
+    document_type VARCHAR(30) NOT NULL
+        CHECK (document_type IN ('fssai_license', 'gst_certificate', 'pan_card', 'bank_proof')),
+    -- Storage key or URL of the uploaded file
+    reference TEXT NOT NULL,
+    uploaded_by UUID NOT NULL REFERENCES users(id),
+    uploaded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (restaurant_id, document_type)
+);
@@ -0,0 +1,38 @@
+-- Menu versions: drafts, scheduled publishing and rollback
+-- Version: 16.0.0
+-- Created: 2024-02-03
+
+CREATE TABLE IF NOT EXISTS menu_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    restaurant_id UUID NOT NULL REFERENCES restaurants(id) ON DELETE CASCADE,
+    version_number INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'draft'
+        CHECK (status IN ('draft', 'scheduled', 'published', 'archived')),
+    -- The whole menu, one entry per dish in the bulk import format
+    items JSONB NOT NULL DEFAULT '[]',
+    note TEXT,
+    -- Version a draft was started from, or the version a rollback restored
+    based_on UUID REFERENCES menu_versions(id) ON DELETE SET NULL,
+    publish_at TIMESTAMP WITH TIME ZONE,
+    published_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    published_by UUID REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (restaurant_id, version_number),
+    CHECK (status <> 'scheduled' OR publish_at IS NOT NULL)
+);
+
+-- A restaurant works on one version at a time and has at most one live version
+CREATE UNIQUE INDEX IF NOT EXISTS idx_menu_versions_pending
+    ON menu_versions(restaurant_id) WHERE status IN ('draft', 'scheduled');
+CREATE UNIQUE INDEX IF NOT EXISTS idx_menu_versions_published
+    ON menu_versions(restaurant_id) WHERE status = 'published';
+CREATE INDEX IF NOT EXISTS idx_menu_versions_due
+    ON menu_versions(publish_at) WHERE status = 'scheduled';
+
+CREATE TRIGGER update_menu_versions_updated_at BEFORE UPDATE ON menu_versions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Published menu version the order was priced against
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS menu_version_id UUID REFERENCES menu_versions(id) ON DELETE SET NULL;
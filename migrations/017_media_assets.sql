@@ -0,0 +1,22 @@
+-- Uploaded restaurant and menu images
+-- Version: 17.0.0
+-- Created: 2024-02-04
+
+CREATE TABLE IF NOT EXISTS media_assets (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    restaurant_id UUID NOT NULL REFERENCES restaurants(id) ON DELETE CASCADE,
+    purpose VARCHAR(20) NOT NULL CHECK (purpose IN ('logo', 'cover', 'menu_item')),
+    content_type VARCHAR(50) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    -- Every file of the asset lives under this storage key prefix; image URLs
+    -- containing it count as references when looking for orphans
+    key_prefix TEXT NOT NULL UNIQUE,
+    original_key TEXT NOT NULL,
+    -- [{"name": "small", "width": 160, "height": 120, "key": "..."}, ...]
+    variants JSONB NOT NULL DEFAULT '[]',
+    uploaded_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_assets_created_at ON media_assets(created_at);
+CREATE INDEX IF NOT EXISTS idx_media_assets_restaurant ON media_assets(restaurant_id);
@@ -0,0 +1,22 @@
+-- Customer dietary profiles and pure veg restaurants
+-- Version: 18.0.0
+-- Created: 2024-02-05
+
+CREATE TABLE IF NOT EXISTS dietary_profiles (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- vegetarian, vegan, jain, egg_free, gluten_free
+    diets TEXT[] NOT NULL DEFAULT '{}',
+    -- Lower-cased allergen names, e.g. {peanut,dairy}
+    avoid_allergens TEXT[] NOT NULL DEFAULT '{}',
+    max_spice_level INTEGER CHECK (max_spice_level BETWEEN 0 AND 5),
+    pure_veg_only BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TRIGGER update_dietary_profiles_updated_at BEFORE UPDATE ON dietary_profiles
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Certified pure veg: the kitchen serves no meat, fish or egg, and the menu
+-- is held to that
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS pure_veg BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,15 @@
+-- Prep time models learned from order history
+-- Version: 19.0.0
+-- Created: 2024-02-06
+
+CREATE TABLE IF NOT EXISTS prep_time_models (
+    restaurant_id UUID PRIMARY KEY REFERENCES restaurants(id) ON DELETE CASCADE,
+    -- Weights for [base, slowest item, extra items, kitchen load, peak hour]
+    coefficients DOUBLE PRECISION[] NOT NULL,
+    sample_count INTEGER NOT NULL,
+    mean_absolute_error_minutes DOUBLE PRECISION NOT NULL,
+    trained_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- When the kitchen is expected to have the order ready
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS estimated_ready_at TIMESTAMP WITH TIME ZONE;
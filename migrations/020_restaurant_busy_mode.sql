@@ -0,0 +1,12 @@
+-- Restaurant busy mode and order throttling
+-- Version: 20.0.0
+-- Created: 2024-02-07
+
+-- The current spell of busy mode, NULL when not busy. Spells carry their own
+-- expiry and are ignored once it passes.
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS busy_mode JSONB;
+
+-- When busy mode turns itself on, e.g. {"auto_threshold": 12, "extra_prep_minutes": 15}
+ALTER TABLE restaurants ADD COLUMN IF NOT EXISTS busy_settings JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_orders_restaurant_status ON orders(restaurant_id, status);
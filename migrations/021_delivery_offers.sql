@@ -0,0 +1,26 @@
+-- Rider offers that expire, with acceptance tracking and escalation
+-- Version: 21.0.0
+-- Created: 2024-02-08
+
+-- A new assignment is an offer until the rider accepts it. Offers not
+-- answered by offer_expires_at lapse and go to the next rider.
+ALTER TABLE delivery_assignments ADD COLUMN IF NOT EXISTS offer_expires_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE delivery_assignments ADD COLUMN IF NOT EXISTS responded_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE delivery_assignments DROP CONSTRAINT IF EXISTS delivery_assignments_status_check;
+ALTER TABLE delivery_assignments ADD CONSTRAINT delivery_assignments_status_check CHECK (
+    status IN (
+        'assigned', 'accepted', 'declined', 'expired', 'enroutetorestaurant', 'arrivedatrestaurant',
+        'pickedup', 'enroutetocustomer', 'arrivedatcustomer', 'delivered', 'cancelled', 'failed'
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_delivery_assignments_open_offers
+    ON delivery_assignments(offer_expires_at) WHERE status = 'assigned';
+
+-- Answered offers, for each rider's acceptance rate
+ALTER TABLE delivery_persons ADD COLUMN IF NOT EXISTS offers_received INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE delivery_persons ADD COLUMN IF NOT EXISTS offers_accepted INTEGER NOT NULL DEFAULT 0;
+
+-- Set when admins were told no rider would take the order
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS dispatch_escalated_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,42 @@
+-- Stacked deliveries: rider trips with ordered stops
+-- Version: 22.0.0
+-- Created: 2024-02-09
+
+-- Everything a rider is carrying at once. Accepted assignments join the
+-- rider's active trip, which ends when its last stop is made.
+CREATE TABLE IF NOT EXISTS delivery_trips (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    delivery_person_id UUID NOT NULL REFERENCES delivery_persons(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed')),
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- A rider has at most one trip under way
+CREATE UNIQUE INDEX IF NOT EXISTS idx_delivery_trips_active_rider
+    ON delivery_trips(delivery_person_id) WHERE status = 'active';
+
+CREATE TRIGGER update_delivery_trips_updated_at BEFORE UPDATE ON delivery_trips
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE delivery_assignments ADD COLUMN IF NOT EXISTS trip_id UUID REFERENCES delivery_trips(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_delivery_assignments_trip ON delivery_assignments(trip_id);
+
+-- Pickups and drop-offs in the order the rider makes them. Stops still to be
+-- made are renumbered whenever an order joins the trip.
+CREATE TABLE IF NOT EXISTS delivery_trip_stops (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    trip_id UUID NOT NULL REFERENCES delivery_trips(id) ON DELETE CASCADE,
+    assignment_id UUID NOT NULL REFERENCES delivery_assignments(id) ON DELETE CASCADE,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    sequence INTEGER NOT NULL,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('pickup', 'dropoff')),
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_delivery_trip_stops_trip ON delivery_trip_stops(trip_id, sequence);
+CREATE INDEX IF NOT EXISTS idx_delivery_trip_stops_assignment ON delivery_trip_stops(assignment_id);
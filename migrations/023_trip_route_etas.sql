@@ -0,0 +1,8 @@
+-- Leg distances and ETAs on planned trip stops
+-- Version: 23.0.0
+-- Created: 2024-02-10
+
+-- Road-distance estimate of the leg from the previous stop, and when the
+-- rider is expected there. Unknown for addresses without coordinates.
+ALTER TABLE delivery_trip_stops ADD COLUMN IF NOT EXISTS distance_km DOUBLE PRECISION;
+ALTER TABLE delivery_trip_stops ADD COLUMN IF NOT EXISTS eta TIMESTAMP WITH TIME ZONE;
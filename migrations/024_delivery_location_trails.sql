@@ -0,0 +1,33 @@
+-- Rider location trails per assignment
+-- Version: 24.0.0
+-- Created: 2024-02-11
+
+-- Where the rider went while handling an order, kept for the distance they
+-- covered and for replay in disputes. Points are stored as encoded
+-- polylines: every value is the change from the previous point written as a
+-- short varint, so a long delivery takes a few kilobytes.
+CREATE TABLE IF NOT EXISTS delivery_trails (
+    assignment_id UUID PRIMARY KEY REFERENCES delivery_assignments(id) ON DELETE CASCADE,
+    delivery_person_id UUID NOT NULL REFERENCES delivery_persons(id) ON DELETE CASCADE,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Latitude and longitude pairs to five decimal places
+    path TEXT NOT NULL DEFAULT '',
+    -- Seconds after started_at, one per point
+    offsets TEXT NOT NULL DEFAULT '',
+    point_count INTEGER NOT NULL DEFAULT 0,
+    -- The last point, which the next one is encoded against
+    last_latitude_e5 BIGINT,
+    last_longitude_e5 BIGINT,
+    last_offset_seconds BIGINT,
+    -- Set when the assignment ends and its distance is recorded
+    closed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_delivery_trails_rider ON delivery_trails(delivery_person_id);
+-- Retention purges go by age
+CREATE INDEX IF NOT EXISTS idx_delivery_trails_started ON delivery_trails(started_at);
+
+CREATE TRIGGER update_delivery_trails_updated_at BEFORE UPDATE ON delivery_trails
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
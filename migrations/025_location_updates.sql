@@ -0,0 +1,23 @@
+-- Raw rider location history
+-- Version: 25.0.0
+-- Created: 2024-02-12
+
+-- Every location a rider reports, written by the live tracking path and read
+-- for their latest position. Databases set up before the migrations covered
+-- it may already have the table. Rows are purged along with the trails once
+-- they outlive the retention period.
+CREATE TABLE IF NOT EXISTS location_updates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    delivery_person_id UUID NOT NULL REFERENCES delivery_persons(id) ON DELETE CASCADE,
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL,
+    -- km/h
+    speed DOUBLE PRECISION,
+    -- Degrees from north
+    heading DOUBLE PRECISION,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_location_updates_rider ON location_updates(delivery_person_id, timestamp DESC);
+-- Retention purges go by age
+CREATE INDEX IF NOT EXISTS idx_location_updates_timestamp ON location_updates(timestamp);
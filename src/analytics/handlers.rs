@@ -118,12 +118,12 @@ fn generate_csv_report(analytics: &BusinessAnalytics) -> Result<String> {
     csv.push_str(&format!("Order Completion Rate,{:.2}%\n", analytics.order_metrics.order_completion_rate));
     
     // Revenue metrics
-    csv.push_str(&format!("Total Revenue,₹{:.2}\n", analytics.revenue_metrics.total_revenue));
-    csv.push_str(&format!("Restaurant Revenue,₹{:.2}\n", analytics.revenue_metrics.restaurant_revenue));
-    csv.push_str(&format!("Delivery Fees,₹{:.2}\n", analytics.revenue_metrics.delivery_fees));
-    csv.push_str(&format!("Platform Commission,₹{:.2}\n", analytics.revenue_metrics.platform_commission));
-    csv.push_str(&format!("Taxes Collected,₹{:.2}\n", analytics.revenue_metrics.taxes_collected));
-    csv.push_str(&format!("Net Revenue,₹{:.2}\n", analytics.revenue_metrics.net_revenue));
+    csv.push_str(&format!("Total Revenue,{}\n", analytics.revenue_metrics.total_revenue));
+    csv.push_str(&format!("Restaurant Revenue,{}\n", analytics.revenue_metrics.restaurant_revenue));
+    csv.push_str(&format!("Delivery Fees,{}\n", analytics.revenue_metrics.delivery_fees));
+    csv.push_str(&format!("Platform Commission,{}\n", analytics.revenue_metrics.platform_commission));
+    csv.push_str(&format!("Taxes Collected,{}\n", analytics.revenue_metrics.taxes_collected));
+    csv.push_str(&format!("Net Revenue,{}\n", analytics.revenue_metrics.net_revenue));
     
     // Customer metrics
     csv.push_str(&format!("Total Customers,{}\n", analytics.customer_metrics.total_customers));
@@ -1,3 +1,4 @@
+use crate::money::Money;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -61,13 +62,13 @@ pub struct OrderMetrics {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RevenueMetrics {
-    pub total_revenue: f64,
-    pub restaurant_revenue: f64,
-    pub delivery_fees: f64,
-    pub platform_commission: f64,
-    pub taxes_collected: f64,
-    pub refunds_issued: f64,
-    pub net_revenue: f64,
+    pub total_revenue: Money,
+    pub restaurant_revenue: Money,
+    pub delivery_fees: Money,
+    pub platform_commission: Money,
+    pub taxes_collected: Money,
+    pub refunds_issued: Money,
+    pub net_revenue: Money,
     pub revenue_by_city: Vec<CityRevenue>,
 }
 
@@ -123,7 +124,7 @@ pub struct PeakHourData {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CityRevenue {
     pub city: String,
-    pub revenue: f64,
+    pub revenue: Money,
     pub order_count: i64,
 }
 
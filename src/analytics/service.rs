@@ -1,6 +1,7 @@
 use crate::analytics::models::*;
 use crate::database::Database;
 use crate::error::{AppError, Result};
+use crate::money::Money;
 use chrono::{DateTime, Datelike, Duration, Utc};
 use uuid::Uuid;
 
@@ -369,7 +370,7 @@ impl AnalyticsService {
             String::new()
         };
 
-        let total_revenue: Option<f64> = sqlx::query_scalar(&format!(
+        let total_revenue: Option<Money> = sqlx::query_scalar(&format!(
             "SELECT SUM(total_amount) FROM orders 
              WHERE status = 'delivered' AND created_at BETWEEN $1 AND $2 {}", restaurant_filter
         ))
@@ -378,7 +379,7 @@ impl AnalyticsService {
         .fetch_one(&mut *conn)
         .await?;
 
-        let delivery_fees: Option<f64> = sqlx::query_scalar(&format!(
+        let delivery_fees: Option<Money> = sqlx::query_scalar(&format!(
             "SELECT SUM(delivery_fee) FROM orders 
              WHERE status = 'delivered' AND created_at BETWEEN $1 AND $2 {}", restaurant_filter
         ))
@@ -387,7 +388,7 @@ impl AnalyticsService {
         .fetch_one(&mut *conn)
         .await?;
 
-        let taxes_collected: Option<f64> = sqlx::query_scalar(&format!(
+        let taxes_collected: Option<Money> = sqlx::query_scalar(&format!(
             "SELECT SUM(tax_amount) FROM orders 
              WHERE status = 'delivered' AND created_at BETWEEN $1 AND $2 {}", restaurant_filter
         ))
@@ -396,13 +397,13 @@ impl AnalyticsService {
         .fetch_one(&mut *conn)
         .await?;
 
-        let total_revenue_val = total_revenue.unwrap_or(0.0);
-        let delivery_fees_val = delivery_fees.unwrap_or(0.0);
-        let taxes_collected_val = taxes_collected.unwrap_or(0.0);
+        let total_revenue_val = total_revenue.unwrap_or(Money::ZERO);
+        let delivery_fees_val = delivery_fees.unwrap_or(Money::ZERO);
+        let taxes_collected_val = taxes_collected.unwrap_or(Money::ZERO);
         
-        let platform_commission = total_revenue_val * 0.15; // 15% commission
+        let platform_commission = total_revenue_val.percentage(15.0); // 15% commission
         let restaurant_revenue = total_revenue_val - delivery_fees_val - platform_commission;
-        let refunds_issued = 0.0; // TODO: Implement refunds tracking
+        let refunds_issued = Money::ZERO; // TODO: Implement refunds tracking
         let net_revenue = total_revenue_val - refunds_issued;
 
         let revenue_by_city = self.get_revenue_by_city(start_date, end_date, restaurant_id).await?;
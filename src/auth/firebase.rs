@@ -114,6 +114,9 @@ impl FirebaseAuth {
             photo_url: None,
             provider,
             role: "customer".to_string(), // Default role
+            auth_time: token
+                .auth_time
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0)),
             created_at: now,
             updated_at: now,
         }
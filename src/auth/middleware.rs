@@ -61,4 +61,36 @@ pub async fn auth_middleware(
     request.extensions_mut().insert(user);
 
     Ok(next.run(request).await)
-}
\ No newline at end of file
+}
+
+/// For public routes that personalise when the caller is signed in. A
+/// missing or unusable token leaves the request anonymous rather than
+/// failing it.
+pub async fn optional_auth_middleware(
+    State(firebase_auth): State<SharedFirebaseAuth>,
+    headers: HeaderMap,
+    mut request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let token = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    if let Some(token) = token {
+        let mut firebase_auth = firebase_auth.lock().await;
+        let verified = firebase_auth.verify_token(token).await.and_then(|firebase_token| {
+            firebase_auth.validate_user_requirements(&firebase_token)?;
+            Ok(firebase_token)
+        });
+        match verified {
+            Ok(firebase_token) => {
+                let user = firebase_auth.token_to_user(firebase_token);
+                request.extensions_mut().insert(user);
+            }
+            Err(e) => tracing::debug!("Ignoring invalid token on a public route: {:?}", e),
+        }
+    }
+
+    next.run(request).await
+}
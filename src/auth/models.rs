@@ -13,6 +13,9 @@ pub struct User {
     pub photo_url: Option<String>,
     pub provider: AuthProvider,
     pub role: String, // customer, restaurant, delivery_person, admin
+    /// When the user last actively signed in, used for step-up checks
+    #[serde(default)]
+    pub auth_time: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -31,6 +34,8 @@ pub struct FirebaseToken {
     pub email: Option<String>,
     pub phone_number: Option<String>,
     pub email_verified: bool,
+    #[serde(default)]
+    pub auth_time: Option<i64>,
     pub firebase: FirebaseClaims,
 }
 
@@ -1,3 +1,4 @@
+use crate::money::Money;
 use anyhow::Result;
 use dotenvy::dotenv;
 use std::env;
@@ -13,13 +14,66 @@ pub struct Config {
     // FCM now uses service account authentication instead of server key
     
     // Delivery configuration
+    /// Riders have this long to accept an offer before it passes on
     pub delivery_assignment_timeout_seconds: u64,
     pub delivery_person_location_update_interval_seconds: u64,
+    /// Pending orders are gathered and matched to riders this often
+    pub dispatch_batch_interval_seconds: u64,
+    /// Orders are escalated to admins after this many declined or expired offers
+    pub dispatch_max_offer_attempts: i64,
+    /// Most orders one rider carries on a stacked trip
+    pub stacking_max_orders: usize,
+    /// Extra distance stacking may add to any customer's ride
+    pub stacking_max_detour_km: f64,
+    /// Extra minutes stacking may add to any customer's wait
+    pub stacking_max_extra_delay_minutes: f64,
+    /// The in-memory rider index is rebuilt from the database this often
+    pub rider_index_resync_interval_seconds: u64,
+    /// Location history past its retention period is purged this often
+    pub location_trail_purge_interval_seconds: u64,
     pub max_delivery_distance_km: f64,
-    pub default_delivery_fee: f64,
+    pub default_delivery_fee: Money,
     pub peak_hour_surcharge_percentage: f64,
     pub weekend_surcharge_percentage: f64,
     pub festival_surcharge_percentage: f64,
+
+    // Payment reconciliation configuration
+    pub payment_gateway_base_url: String,
+    pub payment_gateway_api_key: String,
+    pub payment_reconcile_interval_seconds: u64,
+    pub payment_expiry_minutes: i64,
+    pub payment_max_reconcile_attempts: i32,
+    pub opening_hours_check_interval_seconds: u64,
+    pub busy_mode_check_interval_seconds: u64,
+
+    // Menu inventory configuration
+    /// Local (IST) time of day at which items with a daily stock level are restocked
+    pub inventory_restock_time: chrono::NaiveTime,
+    pub inventory_restock_check_interval_seconds: u64,
+
+    // Menu versioning configuration
+    pub menu_publish_check_interval_seconds: u64,
+
+    // Prep time prediction configuration
+    pub prep_time_training_interval_seconds: u64,
+
+    // Media storage configuration
+    /// `local` or `s3`
+    pub media_storage: String,
+    pub media_local_path: String,
+    /// Prefix for media URLs; empty serves them relative to this server
+    pub media_public_base_url: String,
+    pub media_s3_endpoint: String,
+    pub media_s3_bucket: String,
+    pub media_s3_region: String,
+    pub media_s3_access_key: String,
+    pub media_s3_secret_key: String,
+    pub media_max_upload_bytes: usize,
+    /// ImageMagick (or compatible) program used to make thumbnails
+    pub media_resize_command: String,
+    /// Unused uploads older than this are deleted
+    pub media_orphan_grace_hours: i64,
+    pub media_cleanup_interval_seconds: u64,
 }
 
 impl Config {
@@ -50,14 +104,42 @@ impl Config {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            dispatch_batch_interval_seconds: env::var("DISPATCH_BATCH_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            dispatch_max_offer_attempts: env::var("DISPATCH_MAX_OFFER_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            stacking_max_orders: env::var("STACKING_MAX_ORDERS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            stacking_max_detour_km: env::var("STACKING_MAX_DETOUR_KM")
+                .unwrap_or_else(|_| "2.0".to_string())
+                .parse()
+                .unwrap_or(2.0),
+            stacking_max_extra_delay_minutes: env::var("STACKING_MAX_EXTRA_DELAY_MINUTES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10.0),
+            rider_index_resync_interval_seconds: env::var("RIDER_INDEX_RESYNC_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            location_trail_purge_interval_seconds: env::var("LOCATION_TRAIL_PURGE_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
             max_delivery_distance_km: env::var("MAX_DELIVERY_DISTANCE_KM")
                 .unwrap_or_else(|_| "20.0".to_string())
                 .parse()
                 .unwrap_or(20.0),
             default_delivery_fee: env::var("DEFAULT_DELIVERY_FEE")
-                .unwrap_or_else(|_| "30.0".to_string())
+                .unwrap_or_else(|_| "30.00".to_string())
                 .parse()
-                .unwrap_or(30.0),
+                .unwrap_or(Money::from_rupees(30)),
             peak_hour_surcharge_percentage: env::var("PEAK_HOUR_SURCHARGE_PERCENTAGE")
                 .unwrap_or_else(|_| "25.0".to_string())
                 .parse()
@@ -70,6 +152,69 @@ impl Config {
                 .unwrap_or_else(|_| "35.0".to_string())
                 .parse()
                 .unwrap_or(35.0),
+
+            // Payment reconciliation configuration with defaults
+            payment_gateway_base_url: env::var("PAYMENT_GATEWAY_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:9000".to_string()),
+            payment_gateway_api_key: env::var("PAYMENT_GATEWAY_API_KEY")
+                .unwrap_or_default(),
+            payment_reconcile_interval_seconds: env::var("PAYMENT_RECONCILE_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            payment_expiry_minutes: env::var("PAYMENT_EXPIRY_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            payment_max_reconcile_attempts: env::var("PAYMENT_MAX_RECONCILE_ATTEMPTS")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()
+                .unwrap_or(8),
+            opening_hours_check_interval_seconds: env::var("OPENING_HOURS_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            busy_mode_check_interval_seconds: env::var("BUSY_MODE_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            inventory_restock_time: env::var("INVENTORY_RESTOCK_TIME")
+                .ok()
+                .and_then(|value| chrono::NaiveTime::parse_from_str(&value, "%H:%M").ok())
+                .unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(5, 0, 0).unwrap()),
+            inventory_restock_check_interval_seconds: env::var("INVENTORY_RESTOCK_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            menu_publish_check_interval_seconds: env::var("MENU_PUBLISH_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            prep_time_training_interval_seconds: env::var("PREP_TIME_TRAINING_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "21600".to_string())
+                .parse()
+                .unwrap_or(21600),
+            media_storage: env::var("MEDIA_STORAGE").unwrap_or_else(|_| "local".to_string()),
+            media_local_path: env::var("MEDIA_LOCAL_PATH").unwrap_or_else(|_| "./media".to_string()),
+            media_public_base_url: env::var("MEDIA_PUBLIC_BASE_URL").unwrap_or_default(),
+            media_s3_endpoint: env::var("MEDIA_S3_ENDPOINT").unwrap_or_default(),
+            media_s3_bucket: env::var("MEDIA_S3_BUCKET").unwrap_or_default(),
+            media_s3_region: env::var("MEDIA_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            media_s3_access_key: env::var("MEDIA_S3_ACCESS_KEY").unwrap_or_default(),
+            media_s3_secret_key: env::var("MEDIA_S3_SECRET_KEY").unwrap_or_default(),
+            media_max_upload_bytes: env::var("MEDIA_MAX_UPLOAD_BYTES")
+                .unwrap_or_else(|_| "5242880".to_string())
+                .parse()
+                .unwrap_or(5 * 1024 * 1024),
+            media_resize_command: env::var("MEDIA_RESIZE_COMMAND").unwrap_or_else(|_| "magick".to_string()),
+            media_orphan_grace_hours: env::var("MEDIA_ORPHAN_GRACE_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+            media_cleanup_interval_seconds: env::var("MEDIA_CLEANUP_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
         })
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use sqlx::PgPool;
 use crate::error::Result;
 use crate::orders::models::{Order, OrderStatus, Address};
+use crate::money::{Currency, Money};
 use crate::payments::models::{Payment, PaymentStatus};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
@@ -87,7 +88,9 @@ impl Database {
             delivery_person_id: None,
             items: vec![],
             status: OrderStatus::Placed,
-            total_amount: 299.0,
+            total_amount: Money::from_rupees(299),
+            bill: None,
+            menu_version_id: None,
             delivery_address: Address {
                 street: "123 MG Road".to_string(),
                 city: "Mumbai".to_string(),
@@ -109,6 +112,7 @@ impl Database {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             estimated_delivery_time: Some(chrono::Utc::now() + chrono::Duration::minutes(30)),
+            estimated_ready_at: None,
         }))
     }
 
@@ -121,7 +125,9 @@ impl Database {
             delivery_person_id: None,
             items: vec![],
             status,
-            total_amount: 299.0,
+            total_amount: Money::from_rupees(299),
+            bill: None,
+            menu_version_id: None,
             delivery_address: Address {
                 street: "123 MG Road".to_string(),
                 city: "Mumbai".to_string(),
@@ -143,6 +149,7 @@ impl Database {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             estimated_delivery_time: Some(chrono::Utc::now() + chrono::Duration::minutes(30)),
+            estimated_ready_at: None,
         })
     }
 
@@ -157,8 +164,8 @@ impl Database {
             id: payment_id,
             customer_id: Uuid::new_v4(),
             order_id: Uuid::new_v4(),
-            amount: 299.0,
-            currency: "INR".to_string(),
+            amount: Money::from_rupees(299),
+            currency: Currency::INR,
             payment_method: crate::payments::models::PaymentMethod::UPI,
             status: PaymentStatus::Completed,
             transaction_id: Some("txn_123456".to_string()),
@@ -177,7 +184,9 @@ impl Database {
                 delivery_person_id: None,
                 items: vec![],
                 status: OrderStatus::Delivered,
-                total_amount: 450.0,
+                total_amount: Money::from_rupees(450),
+                bill: None,
+                menu_version_id: None,
                 delivery_address: Address {
                     street: "15 Brigade Road".to_string(),
                     city: "Bangalore".to_string(),
@@ -199,6 +208,7 @@ impl Database {
                 created_at: chrono::Utc::now() - chrono::Duration::hours(2),
                 updated_at: chrono::Utc::now() - chrono::Duration::hours(1),
                 estimated_delivery_time: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+                estimated_ready_at: None,
             },
             Order {
                 id: Uuid::new_v4(),
@@ -207,7 +217,9 @@ impl Database {
                 delivery_person_id: None,
                 items: vec![],
                 status: OrderStatus::Preparing,
-                total_amount: 275.0,
+                total_amount: Money::from_rupees(275),
+                bill: None,
+                menu_version_id: None,
                 delivery_address: Address {
                     street: "42 Connaught Place".to_string(),
                     city: "New Delhi".to_string(),
@@ -229,6 +241,7 @@ impl Database {
                 created_at: chrono::Utc::now() - chrono::Duration::minutes(30),
                 updated_at: chrono::Utc::now() - chrono::Duration::minutes(15),
                 estimated_delivery_time: Some(chrono::Utc::now() + chrono::Duration::minutes(20)),
+                estimated_ready_at: None,
             },
         ])
     }
@@ -0,0 +1,487 @@
+use crate::database::Database;
+use crate::delivery::enhanced_service::EnhancedDeliveryService;
+use crate::delivery::models::{OrderAssignmentRequest, VehicleType};
+use crate::delivery::offers::{OfferService, FAILED_OFFER_STATUSES};
+use crate::delivery::trips::{plan_stacking, StackingLimits, TripService};
+use crate::delivery::websocket::DeliveryWebSocketManager;
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::orders::models::Address;
+use crate::restaurants::serviceability::GeoPoint;
+use crate::search::ranking::haversine_km;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Orders due to be ready within this long are matched now, so riders have
+/// time to get there
+const DISPATCH_LOOKAHEAD_MINUTES: i64 = 15;
+/// Most orders matched in one batch; the rest wait for the next
+const MAX_BATCH_ORDERS: i64 = 200;
+
+/// Costs are in minutes. A rider waiting at the counter costs less than food
+/// going cold while the rider is still on the way.
+const RIDER_WAIT_WEIGHT: f64 = 0.5;
+const FOOD_WAIT_WEIGHT: f64 = 1.5;
+/// Per star below five
+const RATING_WEIGHT: f64 = 2.0;
+/// Rating assumed for riders nobody has rated yet
+const UNRATED_RIDER_RATING: f64 = 4.0;
+/// Credit per idle minute, so work is spread across riders
+const IDLE_WEIGHT: f64 = 0.1;
+const MAX_IDLE_CREDIT_MINUTES: f64 = 30.0;
+/// Charged in full for a rider who never accepts, so reliable riders are
+/// offered first
+const ACCEPTANCE_WEIGHT: f64 = 10.0;
+/// Stands in for pairs that must not be matched
+const INFEASIBLE_COST: f64 = 1e6;
+
+/// An order waiting for a rider
+#[derive(Debug, Clone)]
+pub struct DispatchOrder {
+    pub order_id: Uuid,
+    pub pickup_latitude: f64,
+    pub pickup_longitude: f64,
+    /// When the kitchen expects to finish; `None` means now
+    pub ready_at: Option<DateTime<Utc>>,
+    /// Customer's location, needed to stack the order onto a trip
+    pub dropoff: Option<GeoPoint>,
+}
+
+/// A rider free to take an order
+#[derive(Debug, Clone)]
+pub struct DispatchRider {
+    pub delivery_person_id: Uuid,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub rating: f64,
+    pub vehicle_type: VehicleType,
+    /// Since their last delivery
+    pub idle_since: DateTime<Utc>,
+    /// Share of offers accepted, from 0 to 1; riders without offers count as 1
+    pub acceptance_rate: f64,
+}
+
+/// What sending this rider to this order costs, in minutes; `None` when the
+/// pickup is out of the rider's range
+pub fn dispatch_cost(order: &DispatchOrder, rider: &DispatchRider, now: DateTime<Utc>) -> Option<f64> {
+    let distance_km = haversine_km(rider.latitude, rider.longitude, order.pickup_latitude, order.pickup_longitude);
+    if distance_km > rider.vehicle_type.max_pickup_km() {
+        return None;
+    }
+
+    let travel_minutes = distance_km / rider.vehicle_type.city_speed_kmph() * 60.0;
+    let ready_in_minutes = order
+        .ready_at
+        .map_or(0.0, |ready_at| (ready_at - now).num_seconds() as f64 / 60.0)
+        .max(0.0);
+    let readiness = if travel_minutes < ready_in_minutes {
+        (ready_in_minutes - travel_minutes) * RIDER_WAIT_WEIGHT
+    } else {
+        (travel_minutes - ready_in_minutes) * FOOD_WAIT_WEIGHT
+    };
+
+    let rating = if rider.rating > 0.0 { rider.rating.min(5.0) } else { UNRATED_RIDER_RATING };
+    let idle_minutes = ((now - rider.idle_since).num_seconds() as f64 / 60.0).clamp(0.0, MAX_IDLE_CREDIT_MINUTES);
+
+    let unreliability = (1.0 - rider.acceptance_rate.clamp(0.0, 1.0)) * ACCEPTANCE_WEIGHT;
+
+    Some(travel_minutes + readiness + (5.0 - rating) * RATING_WEIGHT + unreliability - idle_minutes * IDLE_WEIGHT)
+}
+
+/// Minimum-cost assignment of rows to columns (Hungarian algorithm). Every
+/// row gets a column when there are at least as many columns as rows;
+/// otherwise some rows get `None`.
+pub fn solve_assignment(costs: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let rows = costs.len();
+    let columns = costs.first().map_or(0, Vec::len);
+    if rows == 0 || columns == 0 {
+        return vec![None; rows];
+    }
+    if rows > columns {
+        let transposed: Vec<Vec<f64>> = (0..columns)
+            .map(|column| costs.iter().map(|row| row[column]).collect())
+            .collect();
+        let mut assignment = vec![None; rows];
+        for (column, row) in solve_assignment(&transposed).into_iter().enumerate() {
+            if let Some(row) = row {
+                assignment[row] = Some(column);
+            }
+        }
+        return assignment;
+    }
+
+    // Potentials and matches are 1-based, with 0 as the virtual start column
+    let mut row_potential = vec![0.0; rows + 1];
+    let mut column_potential = vec![0.0; columns + 1];
+    let mut matched_row = vec![0usize; columns + 1];
+    let mut previous = vec![0usize; columns + 1];
+    for row in 1..=rows {
+        matched_row[0] = row;
+        let mut column = 0;
+        let mut slack = vec![f64::INFINITY; columns + 1];
+        let mut visited = vec![false; columns + 1];
+        loop {
+            visited[column] = true;
+            let current_row = matched_row[column];
+            let mut delta = f64::INFINITY;
+            let mut next_column = 0;
+            for candidate in 1..=columns {
+                if visited[candidate] {
+                    continue;
+                }
+                let reduced = costs[current_row - 1][candidate - 1]
+                    - row_potential[current_row]
+                    - column_potential[candidate];
+                if reduced < slack[candidate] {
+                    slack[candidate] = reduced;
+                    previous[candidate] = column;
+                }
+                if slack[candidate] < delta {
+                    delta = slack[candidate];
+                    next_column = candidate;
+                }
+            }
+            for candidate in 0..=columns {
+                if visited[candidate] {
+                    row_potential[matched_row[candidate]] += delta;
+                    column_potential[candidate] -= delta;
+                } else {
+                    slack[candidate] -= delta;
+                }
+            }
+            column = next_column;
+            if matched_row[column] == 0 {
+                break;
+            }
+        }
+        // Flip the augmenting path
+        while column != 0 {
+            let prior = previous[column];
+            matched_row[column] = matched_row[prior];
+            column = prior;
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for (column, &row) in matched_row.iter().enumerate().skip(1) {
+        if row != 0 {
+            assignment[row - 1] = Some(column - 1);
+        }
+    }
+    assignment
+}
+
+/// A rider chosen for an order
+#[derive(Debug, Clone, Serialize)]
+pub struct DispatchMatch {
+    pub order_id: Uuid,
+    pub delivery_person_id: Uuid,
+    pub cost: f64,
+}
+
+/// Match as many orders as possible at the lowest total cost. Orders no
+/// rider can reach are left for the next batch. `excluded` holds the
+/// (order, rider) pairs whose offer was already declined or let expire.
+pub fn plan_dispatch(
+    orders: &[DispatchOrder],
+    riders: &[DispatchRider],
+    excluded: &HashSet<(Uuid, Uuid)>,
+    now: DateTime<Utc>,
+) -> Vec<DispatchMatch> {
+    let costs: Vec<Vec<f64>> = orders
+        .iter()
+        .map(|order| {
+            riders
+                .iter()
+                .map(|rider| {
+                    if excluded.contains(&(order.order_id, rider.delivery_person_id)) {
+                        return INFEASIBLE_COST;
+                    }
+                    dispatch_cost(order, rider, now).unwrap_or(INFEASIBLE_COST)
+                })
+                .collect()
+        })
+        .collect();
+
+    solve_assignment(&costs)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(order_index, rider_index)| {
+            let rider_index = rider_index?;
+            let cost = costs[order_index][rider_index];
+            (cost < INFEASIBLE_COST).then(|| DispatchMatch {
+                order_id: orders[order_index].order_id,
+                delivery_person_id: riders[rider_index].delivery_person_id,
+                cost,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct DispatchConfig {
+    pub interval: std::time::Duration,
+    /// Declined or expired offers before an order is escalated to admins
+    pub max_offer_attempts: i64,
+    pub stacking: StackingLimits,
+}
+
+impl DispatchConfig {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            interval: std::time::Duration::from_secs(config.dispatch_batch_interval_seconds),
+            max_offer_attempts: config.dispatch_max_offer_attempts,
+            stacking: StackingLimits::from_config(config),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct DispatchSummary {
+    pub expired_offers: usize,
+    pub escalated_orders: usize,
+    pub pending_orders: usize,
+    pub available_riders: usize,
+    pub assigned: usize,
+    /// Of those assigned, orders offered to riders already out on a trip
+    pub stacked: usize,
+}
+
+impl DispatchSummary {
+    fn has_activity(&self) -> bool {
+        self.expired_offers > 0 || self.escalated_orders > 0 || self.assigned > 0
+    }
+}
+
+/// Background job that gathers the orders due for a rider and the riders
+/// free to take them, and offers them together rather than one at a time.
+/// Offers nobody answers lapse, and the order goes to the next-best rider.
+pub struct DispatchEngine {
+    db: Database,
+    delivery_service: Arc<EnhancedDeliveryService>,
+    websocket_manager: DeliveryWebSocketManager,
+    config: DispatchConfig,
+}
+
+impl DispatchEngine {
+    pub fn new(
+        db: Database,
+        delivery_service: Arc<EnhancedDeliveryService>,
+        websocket_manager: DeliveryWebSocketManager,
+        config: DispatchConfig,
+    ) -> Self {
+        Self {
+            db,
+            delivery_service,
+            websocket_manager,
+            config,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(summary) if summary.has_activity() => tracing::info!("Dispatch batch: {:?}", summary),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Dispatch batch failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<DispatchSummary> {
+        let now = Utc::now();
+        let offers = OfferService::new(self.db.clone());
+        let mut summary = DispatchSummary::default();
+
+        // Lapsed offers free their orders for this batch
+        let expired = offers.expire_offers(now).await?;
+        summary.expired_offers = expired.len();
+        for offer in &expired {
+            // No subscribers is not an error worth reporting
+            let _ = self
+                .websocket_manager
+                .broadcast_offer_expired(offer.assignment_id, offer.delivery_person_id, offer.order_id)
+                .await;
+        }
+
+        let escalated = offers.escalate_stuck_orders(self.config.max_offer_attempts).await?;
+        summary.escalated_orders = escalated.len();
+        for order in &escalated {
+            tracing::warn!("Order {} escalated after {} failed offers", order.order_id, order.failed_offers);
+            let _ = self
+                .websocket_manager
+                .broadcast_dispatch_escalation(order.order_id, order.failed_offers)
+                .await;
+        }
+
+        let orders = self.pending_orders(now).await?;
+        summary.pending_orders = orders.len();
+        if orders.is_empty() {
+            return Ok(summary);
+        }
+        let riders = self.available_riders().await?;
+        summary.available_riders = riders.len();
+        let excluded = self.failed_offers(&orders).await?;
+
+        let matches = plan_dispatch(&orders, &riders, &excluded, now);
+        let matched: HashSet<Uuid> = matches.iter().map(|matched| matched.order_id).collect();
+        for matched in matches {
+            if self.offer(&matched).await {
+                summary.assigned += 1;
+            }
+        }
+
+        // Orders no free rider took may still fit on a trip under way
+        let leftover: Vec<DispatchOrder> = orders
+            .into_iter()
+            .filter(|order| !matched.contains(&order.order_id))
+            .collect();
+        if leftover.is_empty() {
+            return Ok(summary);
+        }
+        let trips = TripService::new(self.db.clone()).stackable_trips().await?;
+        for stacked in plan_stacking(&leftover, &trips, &excluded, &self.config.stacking) {
+            if self.offer(&stacked).await {
+                summary.assigned += 1;
+                summary.stacked += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    async fn offer(&self, matched: &DispatchMatch) -> bool {
+        let request = OrderAssignmentRequest {
+            order_id: matched.order_id,
+            preferred_delivery_person_id: Some(matched.delivery_person_id),
+            max_distance_km: None,
+        };
+        match self.delivery_service.assign_order_with_broadcast(request).await {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to assign order {} to rider {}: {:?}",
+                    matched.order_id,
+                    matched.delivery_person_id,
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    /// Orders being prepared with no rider yet, whose food is ready or soon
+    /// will be
+    async fn pending_orders(&self, now: DateTime<Utc>) -> Result<Vec<DispatchOrder>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT o.id, o.restaurant_address, o.delivery_address, o.estimated_ready_at
+            FROM orders o
+            WHERE o.status IN ('confirmed', 'preparing', 'ready')
+              AND o.delivery_person_id IS NULL
+              AND (o.estimated_ready_at IS NULL OR o.estimated_ready_at <= $1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM delivery_assignments a
+                  WHERE a.order_id = o.id AND a.status NOT IN ('cancelled', 'declined', 'expired')
+              )
+            ORDER BY o.created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(now + Duration::minutes(DISPATCH_LOOKAHEAD_MINUTES))
+        .bind(MAX_BATCH_ORDERS)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                // Restaurants without coordinates cannot be matched by distance
+                let pickup: Address = serde_json::from_value(row.get("restaurant_address")).ok()?;
+                let dropoff = serde_json::from_value::<Address>(row.get("delivery_address"))
+                    .ok()
+                    .and_then(|address| Some(GeoPoint::new(address.latitude?, address.longitude?)));
+                Some(DispatchOrder {
+                    order_id: row.get("id"),
+                    pickup_latitude: pickup.latitude?,
+                    pickup_longitude: pickup.longitude?,
+                    ready_at: row.get("estimated_ready_at"),
+                    dropoff,
+                })
+            })
+            .collect())
+    }
+
+    /// Riders who already declined or let an offer for one of these orders
+    /// expire, so the order cascades to someone else
+    async fn failed_offers(&self, orders: &[DispatchOrder]) -> Result<HashSet<(Uuid, Uuid)>> {
+        let order_ids: Vec<Uuid> = orders.iter().map(|order| order.order_id).collect();
+        let rows = sqlx::query(&format!(
+            "SELECT order_id, delivery_person_id FROM delivery_assignments WHERE order_id = ANY($1) AND status IN {}",
+            FAILED_OFFER_STATUSES
+        ))
+        .bind(&order_ids)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("order_id"), row.get("delivery_person_id")))
+            .collect())
+    }
+
+    /// Riders on shift with a known location and no delivery or open offer
+    /// in hand
+    async fn available_riders(&self) -> Result<Vec<DispatchRider>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT dp.id, dp.current_latitude, dp.current_longitude, dp.rating::float8 AS rating,
+                   dp.vehicle_type,
+                   CASE WHEN dp.offers_received > 0
+                        THEN dp.offers_accepted::float8 / dp.offers_received
+                        ELSE 1.0
+                   END AS acceptance_rate,
+                   COALESCE(
+                       (SELECT MAX(a.delivered_at) FROM delivery_assignments a WHERE a.delivery_person_id = dp.id),
+                       dp.created_at
+                   ) AS idle_since
+            FROM delivery_persons dp
+            WHERE dp.is_available = true AND dp.is_active = true AND dp.is_verified = true
+              AND dp.current_latitude IS NOT NULL AND dp.current_longitude IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM delivery_assignments a
+                  WHERE a.delivery_person_id = dp.id
+                    AND a.status NOT IN ('delivered', 'cancelled', 'failed', 'declined', 'expired')
+              )
+            "#,
+        )
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let vehicle_type: String = row.get("vehicle_type");
+                Some(DispatchRider {
+                    delivery_person_id: row.get("id"),
+                    latitude: row.get("current_latitude"),
+                    longitude: row.get("current_longitude"),
+                    rating: row.get::<Option<f64>, _>("rating").unwrap_or(0.0),
+                    vehicle_type: VehicleType::from_str(&vehicle_type).ok()?,
+                    idle_since: row.get("idle_since"),
+                    acceptance_rate: row.get("acceptance_rate"),
+                })
+            })
+            .collect())
+    }
+}
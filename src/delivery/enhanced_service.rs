@@ -1,7 +1,12 @@
 use crate::database::Database;
+use crate::delivery::geo_index::RiderGeoIndex;
+use crate::delivery::location_trails::{TrailPoint, TrailService};
 use crate::delivery::models::*;
+use crate::delivery::offers::OfferService;
+use crate::delivery::trips::{publish_route, TripProgress, TripService};
 use crate::delivery::websocket::DeliveryWebSocketManager;
 use crate::error::{AppError, Result};
+use crate::restaurants::serviceability::GeoPoint;
 use chrono::Utc;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -10,11 +15,28 @@ use uuid::Uuid;
 pub struct EnhancedDeliveryService {
     db: Database,
     ws_manager: Arc<DeliveryWebSocketManager>,
+    /// How long a rider has to answer an offer
+    offer_timeout: chrono::Duration,
+    /// Where available riders are, kept current by location updates
+    rider_index: RiderGeoIndex,
 }
 
 impl EnhancedDeliveryService {
-    pub fn new(db: Database, ws_manager: Arc<DeliveryWebSocketManager>) -> Self {
-        Self { db, ws_manager }
+    pub fn new(db: Database, ws_manager: Arc<DeliveryWebSocketManager>, offer_timeout: chrono::Duration) -> Self {
+        Self {
+            db,
+            ws_manager,
+            offer_timeout,
+            rider_index: RiderGeoIndex::new(),
+        }
+    }
+
+    pub fn offer_timeout(&self) -> chrono::Duration {
+        self.offer_timeout
+    }
+
+    pub fn rider_index(&self) -> &RiderGeoIndex {
+        &self.rider_index
     }
 
     /// Update delivery person location with real-time broadcasting
@@ -25,11 +47,12 @@ impl EnhancedDeliveryService {
         request: UpdateLocationRequest,
     ) -> Result<()> {
         // First update the location in the database
-        sqlx::query(
+        let indexable: Option<bool> = sqlx::query_scalar(
             r#"
             UPDATE delivery_persons 
             SET current_latitude = $1, current_longitude = $2, updated_at = $3
             WHERE id = $4 AND user_id = $5
+            RETURNING is_available AND is_verified AND is_active
             "#,
         )
         .bind(request.latitude)
@@ -37,9 +60,29 @@ impl EnhancedDeliveryService {
         .bind(Utc::now())
         .bind(delivery_person_id)
         .bind(user_id)
-        .execute(self.db.pool())
+        .fetch_optional(self.db.pool())
         .await?;
 
+        match indexable {
+            Some(true) => self
+                .rider_index
+                .upsert(delivery_person_id, GeoPoint::new(request.latitude, request.longitude)),
+            Some(false) => self.rider_index.remove(delivery_person_id),
+            None => {}
+        }
+
+        // Orders the rider is handling keep a trail of where they went
+        if indexable.is_some() {
+            let point = TrailPoint {
+                latitude: request.latitude,
+                longitude: request.longitude,
+                recorded_at: Utc::now(),
+            };
+            TrailService::new(self.db.clone())
+                .record(delivery_person_id, point, request.accuracy)
+                .await?;
+        }
+
         // Create location update for broadcasting
         let location_update = LocationUpdate {
             delivery_person_id,
@@ -84,6 +127,21 @@ impl EnhancedDeliveryService {
     ) -> Result<DeliveryAssignment> {
         let now = Utc::now();
 
+        // Answering an offer goes through the same path as the rider socket
+        if matches!(request.status, DeliveryStatus::Accepted | DeliveryStatus::Declined) {
+            let accept = matches!(request.status, DeliveryStatus::Accepted);
+            let assignment = OfferService::new(self.db.clone())
+                .respond(&self.rider_index, assignment_id, delivery_person_id, None, accept)
+                .await?;
+            self.ws_manager
+                .broadcast_status_update(assignment_id, delivery_person_id, request.status, None, request.notes)
+                .await?;
+            if accept {
+                publish_route(&self.ws_manager, &self.db, delivery_person_id).await;
+            }
+            return Ok(assignment);
+        }
+
         // Update the assignment status in database
         let assignment = sqlx::query_as::<_, DeliveryAssignment>(
             r#"
@@ -104,6 +162,16 @@ impl EnhancedDeliveryService {
         .fetch_one(self.db.pool())
         .await?;
 
+        TripService::new(self.db.clone())
+            .advance(assignment_id, &request.status)
+            .await?;
+        if matches!(request.status, DeliveryStatus::Delivered | DeliveryStatus::Cancelled | DeliveryStatus::Failed) {
+            TrailService::new(self.db.clone()).close(assignment_id).await?;
+        }
+        if matches!(request.status, DeliveryStatus::Delivered | DeliveryStatus::Cancelled | DeliveryStatus::Failed) {
+            publish_route(&self.ws_manager, &self.db, delivery_person_id).await;
+        }
+
         // Calculate estimated arrival time based on status
         let estimated_arrival = match request.status {
             DeliveryStatus::PickedUp => {
@@ -138,6 +206,7 @@ impl EnhancedDeliveryService {
     ) -> Result<DeliveryAssignment> {
         let assignment_id = Uuid::new_v4();
         let now = Utc::now();
+        let offer_expires_at = now + self.offer_timeout;
 
         // Find a suitable delivery person if not specified
         let delivery_person_id = if let Some(preferred_id) = request.preferred_delivery_person_id {
@@ -149,12 +218,13 @@ impl EnhancedDeliveryService {
                 .ok_or_else(|| AppError::NotFound("No available delivery person found".to_string()))?
         };
 
-        // Create the assignment in database with basic schema matching the actual table
+        // The assignment starts as an offer the rider must accept in time
         let assignment = sqlx::query_as::<_, DeliveryAssignment>(
             r#"
             INSERT INTO delivery_assignments (
-                id, order_id, delivery_person_id, status, assigned_at, created_at, updated_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                id, order_id, delivery_person_id, status, assigned_at, created_at, updated_at,
+                offer_expires_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id, order_id, delivery_person_id, restaurant_id, customer_id,
                      pickup_address, delivery_address, status as status_str, 
                      assigned_at, accepted_at, picked_up_at, delivered_at,
@@ -170,20 +240,13 @@ impl EnhancedDeliveryService {
         .bind(now)
         .bind(now)
         .bind(now)
+        .bind(offer_expires_at)
         .fetch_one(self.db.pool())
         .await?;
 
         // Broadcast order assignment via WebSocket
         self.ws_manager
-            .broadcast_order_assignment(
-                assignment_id,
-                delivery_person_id,
-                request.order_id,
-                assignment.pickup_address.clone(),
-                assignment.delivery_address.clone(),
-                assignment.estimated_pickup_time,
-                assignment.estimated_delivery_time,
-            )
+            .broadcast_order_assignment(&assignment, Some(offer_expires_at))
             .await?;
 
         Ok(assignment)
@@ -294,11 +357,17 @@ impl EnhancedDeliveryService {
         .fetch_one(self.db.pool())
         .await?;
 
+        // Stacked orders show where this customer is in the rider's stops
+        let trip = TripService::new(self.db.clone())
+            .progress(assignment.order_id)
+            .await?;
+
         Ok(DeliveryTrackingInfo {
             assignment,
             delivery_person,
             current_location: location,
             estimated_arrival: None, // Calculate based on current location and destination
+            trip,
         })
     }
 
@@ -351,6 +420,7 @@ pub struct DeliveryTrackingInfo {
     pub delivery_person: DeliveryPerson,
     pub current_location: Option<LocationUpdate>,
     pub estimated_arrival: Option<chrono::DateTime<chrono::Utc>>,
+    pub trip: Option<TripProgress>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -0,0 +1,285 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::restaurants::serviceability::GeoPoint;
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_2;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+
+/// Bits of latitude and of longitude in a cell key. Fifteen of each makes
+/// every cell a six-character geohash, about 0.6 km by 1.2 km at the equator.
+const CELL_BITS: u32 = 15;
+const CELLS_PER_AXIS: i64 = 1 << CELL_BITS;
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const EARTH_RADIUS_KM: f64 = 6371.0;
+/// Nearest-rider searches start this wide and double until they have enough
+const INITIAL_SEARCH_KM: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Cell {
+    lat: i64,
+    lng: i64,
+}
+
+fn lat_cell(latitude: f64) -> i64 {
+    (((latitude + 90.0) / 180.0 * CELLS_PER_AXIS as f64).floor() as i64).clamp(0, CELLS_PER_AXIS - 1)
+}
+
+/// Not wrapped, so ranges that cross the antimeridian stay contiguous
+fn lng_cell(longitude: f64) -> i64 {
+    ((longitude + 180.0) / 360.0 * CELLS_PER_AXIS as f64).floor() as i64
+}
+
+impl Cell {
+    fn of(point: &GeoPoint) -> Self {
+        Cell {
+            lat: lat_cell(point.latitude),
+            lng: lng_cell(point.longitude).rem_euclid(CELLS_PER_AXIS),
+        }
+    }
+}
+
+/// Geohash of the index cell holding `point`
+pub fn geohash(point: &GeoPoint) -> String {
+    let cell = Cell::of(point);
+    // Geohash interleaves the bits, longitude first
+    let mut bits: u64 = 0;
+    for bit in (0..CELL_BITS).rev() {
+        bits = (bits << 1) | ((cell.lng >> bit) & 1) as u64;
+        bits = (bits << 1) | ((cell.lat >> bit) & 1) as u64;
+    }
+    (0..2 * CELL_BITS / 5)
+        .rev()
+        .map(|chunk| GEOHASH_ALPHABET[((bits >> (chunk * 5)) & 31) as usize] as char)
+        .collect()
+}
+
+/// A rider found by the index, with their straight-line distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearbyRider {
+    pub delivery_person_id: Uuid,
+    pub distance_km: f64,
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    riders: HashMap<Uuid, (GeoPoint, Cell)>,
+    cells: HashMap<Cell, Vec<Uuid>>,
+}
+
+impl IndexState {
+    fn upsert(&mut self, delivery_person_id: Uuid, location: GeoPoint) {
+        let cell = Cell::of(&location);
+        match self.riders.insert(delivery_person_id, (location, cell)) {
+            Some((_, previous)) if previous == cell => return,
+            Some((_, previous)) => self.unlink(delivery_person_id, previous),
+            None => {}
+        }
+        self.cells.entry(cell).or_default().push(delivery_person_id);
+    }
+
+    fn remove(&mut self, delivery_person_id: Uuid) {
+        if let Some((_, cell)) = self.riders.remove(&delivery_person_id) {
+            self.unlink(delivery_person_id, cell);
+        }
+    }
+
+    fn unlink(&mut self, delivery_person_id: Uuid, cell: Cell) {
+        if let Some(members) = self.cells.get_mut(&cell) {
+            members.retain(|id| *id != delivery_person_id);
+            if members.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn measure(&self, delivery_person_id: &Uuid, center: &GeoPoint, radius_km: f64) -> Option<NearbyRider> {
+        let (location, _) = self.riders.get(delivery_person_id)?;
+        let distance_km = center.distance_km(location);
+        (distance_km <= radius_km).then_some(NearbyRider {
+            delivery_person_id: *delivery_person_id,
+            distance_km,
+        })
+    }
+
+    /// Riders within `radius_km`, nearest first. Only the cells under the
+    /// circle's bounding box are read, unless that box holds more cells than
+    /// there are riders, in which case every rider is checked instead.
+    fn within(&self, center: &GeoPoint, radius_km: f64) -> Vec<NearbyRider> {
+        let mut found: Vec<NearbyRider> = match self.covering_cells(center, radius_km) {
+            Some((lat_cells, lng_cells)) => lat_cells
+                .flat_map(|lat| {
+                    lng_cells.clone().filter_map(move |lng| {
+                        self.cells.get(&Cell {
+                            lat,
+                            lng: lng.rem_euclid(CELLS_PER_AXIS),
+                        })
+                    })
+                })
+                .flatten()
+                .filter_map(|id| self.measure(id, center, radius_km))
+                .collect(),
+            None => self
+                .riders
+                .keys()
+                .filter_map(|id| self.measure(id, center, radius_km))
+                .collect(),
+        };
+        found.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        found
+    }
+
+    /// Cell ranges under the bounding box of the circle, or `None` when
+    /// scanning every rider is cheaper or the circle reaches a pole
+    fn covering_cells(
+        &self,
+        center: &GeoPoint,
+        radius_km: f64,
+    ) -> Option<(std::ops::RangeInclusive<i64>, std::ops::RangeInclusive<i64>)> {
+        let angular = radius_km / EARTH_RADIUS_KM;
+        let latitude = center.latitude.to_radians();
+        let (lat_min, lat_max) = (latitude - angular, latitude + angular);
+        let spread = angular.sin() / latitude.cos();
+        if lat_min <= -FRAC_PI_2 || lat_max >= FRAC_PI_2 || spread >= 1.0 {
+            return None;
+        }
+        // Widest longitude span of a circle on the sphere
+        let lng_delta = spread.asin().to_degrees();
+        let lat_cells = lat_cell(lat_min.to_degrees())..=lat_cell(lat_max.to_degrees());
+        let lng_cells = lng_cell(center.longitude - lng_delta)..=lng_cell(center.longitude + lng_delta);
+
+        let lng_span = lng_cells.end() - lng_cells.start() + 1;
+        let cell_count = (lat_cells.end() - lat_cells.start() + 1).saturating_mul(lng_span);
+        if lng_span >= CELLS_PER_AXIS || cell_count > self.riders.len() as i64 {
+            return None;
+        }
+        Some((lat_cells, lng_cells))
+    }
+}
+
+/// In-process index of where available riders are, bucketed by geohash cell.
+/// Location updates, accepted offers and profile changes keep it current, and
+/// `RiderIndexSync` reconciles it with Postgres for anything they miss.
+#[derive(Debug, Clone, Default)]
+pub struct RiderGeoIndex {
+    state: Arc<RwLock<IndexState>>,
+}
+
+impl RiderGeoIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Lookups never leave the index half-updated, so a poisoned lock is safe to reuse
+    fn read(&self) -> RwLockReadGuard<'_, IndexState> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, IndexState> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn upsert(&self, delivery_person_id: Uuid, location: GeoPoint) {
+        self.write().upsert(delivery_person_id, location);
+    }
+
+    pub fn remove(&self, delivery_person_id: Uuid) {
+        self.write().remove(delivery_person_id);
+    }
+
+    /// Swap the whole index for `riders` in one step
+    pub fn replace_all(&self, riders: impl IntoIterator<Item = (Uuid, GeoPoint)>) {
+        let mut state = IndexState::default();
+        for (delivery_person_id, location) in riders {
+            state.upsert(delivery_person_id, location);
+        }
+        *self.write() = state;
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().riders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().riders.is_empty()
+    }
+
+    pub fn location(&self, delivery_person_id: Uuid) -> Option<GeoPoint> {
+        self.read().riders.get(&delivery_person_id).map(|(location, _)| *location)
+    }
+
+    /// Every indexed rider within `radius_km` of `center`, nearest first
+    pub fn within(&self, center: &GeoPoint, radius_km: f64) -> Vec<NearbyRider> {
+        self.read().within(center, radius_km)
+    }
+
+    /// Up to `k` riders nearest to `center`, none further than `max_km`
+    pub fn nearest(&self, center: &GeoPoint, k: usize, max_km: f64) -> Vec<NearbyRider> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let state = self.read();
+        let mut radius_km = INITIAL_SEARCH_KM.min(max_km);
+        loop {
+            // Anyone outside the circle is further than everyone inside it
+            let mut found = state.within(center, radius_km);
+            if found.len() >= k || found.len() == state.riders.len() || radius_km >= max_km {
+                found.truncate(k);
+                return found;
+            }
+            radius_km = (radius_km * 2.0).min(max_km);
+        }
+    }
+}
+
+/// Rebuilds the rider index from Postgres, picking up riders whose
+/// availability changed without a location update
+pub struct RiderIndexSync {
+    db: Database,
+    index: RiderGeoIndex,
+    interval: std::time::Duration,
+}
+
+impl RiderIndexSync {
+    pub fn new(db: Database, index: RiderGeoIndex, interval: std::time::Duration) -> Self {
+        Self { db, index, interval }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(riders) => tracing::debug!("Rider index resynced with {} riders", riders),
+                    Err(e) => tracing::error!("Rider index resync failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        let riders: Vec<(Uuid, f64, f64)> = sqlx::query_as(
+            r#"
+            SELECT id, current_latitude, current_longitude
+            FROM delivery_persons
+            WHERE is_available = true
+              AND is_verified = true
+              AND is_active = true
+              AND current_latitude IS NOT NULL
+              AND current_longitude IS NOT NULL
+            "#,
+        )
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let count = riders.len();
+        self.index.replace_all(
+            riders
+                .into_iter()
+                .map(|(id, latitude, longitude)| (id, GeoPoint::new(latitude, longitude))),
+        );
+        Ok(count)
+    }
+}
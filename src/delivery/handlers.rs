@@ -11,7 +11,9 @@ use crate::auth::models::User;
 use crate::error::Result;
 use crate::delivery::{
     models::*,
+    location_trails::AssignmentTrail,
     service::DeliveryService,
+    trips::publish_route,
 };
 use crate::routes::AppState;
 
@@ -82,7 +84,7 @@ pub async fn update_delivery_person(
     let delivery_service = DeliveryService::new(state.database.clone());
     
     let delivery_person = delivery_service
-        .update_delivery_person(delivery_person_id, user.id, request)
+        .update_delivery_person(state.enhanced_delivery_service.rider_index(), delivery_person_id, user.id, request)
         .await?;
     
     Ok(Json(DeliveryPersonResponse::from(delivery_person)))
@@ -97,7 +99,7 @@ pub async fn update_location(
     let delivery_service = DeliveryService::new(state.database.clone());
     
     delivery_service
-        .update_location(delivery_person_id, user.id, request)
+        .update_location(state.enhanced_delivery_service.rider_index(), delivery_person_id, user.id, request)
         .await?;
     
     Ok(StatusCode::OK)
@@ -125,7 +127,11 @@ pub async fn assign_order(
     let delivery_service = DeliveryService::new(state.database.clone());
     
     let assignment = delivery_service
-        .assign_order(request)
+        .assign_order(
+            request,
+            state.enhanced_delivery_service.offer_timeout(),
+            state.enhanced_delivery_service.rider_index(),
+        )
         .await?;
     
     Ok(Json(assignment))
@@ -138,14 +144,50 @@ pub async fn update_delivery_status(
     Json(request): Json<UpdateDeliveryStatusRequest>,
 ) -> Result<Json<DeliveryAssignment>> {
     let delivery_service = DeliveryService::new(state.database.clone());
+    let route_changes = matches!(
+        request.status,
+        DeliveryStatus::Accepted | DeliveryStatus::Delivered | DeliveryStatus::Cancelled | DeliveryStatus::Failed
+    );
+    
+    let assignment = delivery_service
+        .update_delivery_status(state.enhanced_delivery_service.rider_index(), assignment_id, delivery_person_id, request)
+        .await?;
+
+    if route_changes {
+        publish_route(&state.delivery_websocket_manager, &state.database, delivery_person_id).await;
+    }
+    
+    Ok(Json(assignment))
+}
+
+pub async fn get_delivery_assignment(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(assignment_id): Path<Uuid>,
+) -> Result<Json<DeliveryAssignmentResponse>> {
+    let delivery_service = DeliveryService::new(state.database.clone());
     
     let assignment = delivery_service
-        .update_delivery_status(assignment_id, delivery_person_id, request)
+        .get_delivery_assignment(assignment_id, user.id)
         .await?;
     
     Ok(Json(assignment))
 }
 
+pub async fn get_assignment_trail(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(assignment_id): Path<Uuid>,
+) -> Result<Json<AssignmentTrail>> {
+    let delivery_service = DeliveryService::new(state.database.clone());
+    
+    let trail = delivery_service
+        .get_assignment_trail(assignment_id, user.id, user.role == "admin")
+        .await?;
+    
+    Ok(Json(trail))
+}
+
 pub async fn get_delivery_assignments(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
@@ -170,7 +212,12 @@ pub async fn get_nearby_delivery_persons(
     
     let radius = params.radius_km.unwrap_or(10.0);
     let nearby_persons = delivery_service
-        .get_nearby_delivery_persons(params.latitude, params.longitude, radius)
+        .get_nearby_delivery_persons(
+            state.enhanced_delivery_service.rider_index(),
+            params.latitude,
+            params.longitude,
+            radius,
+        )
         .await?;
     
     Ok(Json(nearby_persons))
@@ -255,6 +302,7 @@ pub async fn deactivate_delivery_person(
     .execute(state.database.pool())
     .await
     .map_err(|e| crate::error::AppError::DatabaseError(e.to_string()))?;
+    state.enhanced_delivery_service.rider_index().remove(delivery_person_id);
     
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,403 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::india::config::ComplianceConfig;
+use crate::restaurants::serviceability::GeoPoint;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Assignment statuses during which the rider's movements are recorded
+pub const TRAIL_STATUSES: &str =
+    "('accepted', 'enroutetorestaurant', 'arrivedatrestaurant', 'pickedup', 'enroutetocustomer', 'arrivedatcustomer')";
+/// Fixes the phone reports as less accurate than this are not recorded
+pub const MAX_FIX_ACCURACY_METRES: f64 = 50.0;
+/// Movement shorter than this from the last counted point is GPS jitter
+pub const MIN_MOVEMENT_KM: f64 = 0.02;
+/// Points that would need the rider to travel faster than this are spikes
+pub const MAX_PLAUSIBLE_SPEED_KMPH: f64 = 120.0;
+/// Coordinates are stored to five decimal places, about a metre
+const COORDINATE_SCALE: f64 = 1e5;
+
+/// Append `value` to `out` in Google's encoded polyline format
+fn encode_value(value: i64, out: &mut String) {
+    let mut rest = if value < 0 { !(value << 1) } else { value << 1 };
+    while rest >= 0x20 {
+        out.push((((rest & 0x1f) | 0x20) as u8 + 63) as char);
+        rest >>= 5;
+    }
+    out.push((rest as u8 + 63) as char);
+}
+
+fn decode_values(encoded: &str) -> Result<Vec<i64>> {
+    let mut values = Vec::new();
+    let mut value: i64 = 0;
+    let mut shift = 0;
+    for byte in encoded.bytes() {
+        let chunk = i64::from(byte)
+            .checked_sub(63)
+            .filter(|chunk| (0..0x40).contains(chunk) && shift < 64)
+            .ok_or_else(|| AppError::DatabaseError("Corrupt location trail".to_string()))?;
+        value |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            values.push(if value & 1 == 1 { !(value >> 1) } else { value >> 1 });
+            value = 0;
+            shift = 0;
+        }
+    }
+    Ok(values)
+}
+
+/// Encode `points` as a polyline, for drawing the trail on a map
+pub fn encode_polyline(points: &[GeoPoint]) -> String {
+    let mut encoded = String::new();
+    let mut previous = (0, 0);
+    for point in points {
+        let current = scaled(point);
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+    encoded
+}
+
+fn scaled(point: &GeoPoint) -> (i64, i64) {
+    (
+        (point.latitude * COORDINATE_SCALE).round() as i64,
+        (point.longitude * COORDINATE_SCALE).round() as i64,
+    )
+}
+
+/// One recorded rider position
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TrailPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl TrailPoint {
+    pub fn location(&self) -> GeoPoint {
+        GeoPoint::new(self.latitude, self.longitude)
+    }
+}
+
+/// The last point written to a trail, which the next one is encoded against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailCursor {
+    pub latitude_e5: i64,
+    pub longitude_e5: i64,
+    pub offset_seconds: i64,
+}
+
+/// Encoded additions for one new point: its coordinates go on the path and
+/// its seconds since the trail started go on the offsets
+pub fn encode_point(
+    previous: Option<TrailCursor>,
+    started_at: DateTime<Utc>,
+    point: &TrailPoint,
+) -> (String, String, TrailCursor) {
+    let (latitude_e5, longitude_e5) = scaled(&point.location());
+    let cursor = TrailCursor {
+        latitude_e5,
+        longitude_e5,
+        // Out-of-order fixes keep the previous time so offsets never go backwards
+        offset_seconds: (point.recorded_at - started_at)
+            .num_seconds()
+            .max(previous.map_or(0, |previous| previous.offset_seconds)),
+    };
+    let previous = previous.unwrap_or(TrailCursor {
+        latitude_e5: 0,
+        longitude_e5: 0,
+        offset_seconds: 0,
+    });
+
+    let mut path = String::new();
+    encode_value(cursor.latitude_e5 - previous.latitude_e5, &mut path);
+    encode_value(cursor.longitude_e5 - previous.longitude_e5, &mut path);
+    let mut offsets = String::new();
+    encode_value(cursor.offset_seconds - previous.offset_seconds, &mut offsets);
+    (path, offsets, cursor)
+}
+
+/// Turn a stored path and its offsets back into points
+pub fn decode_trail(path: &str, offsets: &str, started_at: DateTime<Utc>) -> Result<Vec<TrailPoint>> {
+    let coordinates = decode_values(path)?;
+    let offsets = decode_values(offsets)?;
+    if coordinates.len() != offsets.len() * 2 {
+        return Err(AppError::DatabaseError("Corrupt location trail".to_string()));
+    }
+
+    let (mut latitude, mut longitude, mut offset) = (0, 0, 0);
+    Ok(coordinates
+        .chunks(2)
+        .zip(offsets)
+        .map(|(delta, offset_delta)| {
+            latitude += delta[0];
+            longitude += delta[1];
+            offset += offset_delta;
+            TrailPoint {
+                latitude: latitude as f64 / COORDINATE_SCALE,
+                longitude: longitude as f64 / COORDINATE_SCALE,
+                recorded_at: started_at + Duration::seconds(offset),
+            }
+        })
+        .collect())
+}
+
+/// Distance covered along `points`, ignoring jitter while the rider stands
+/// still and spikes that would need an impossible speed
+pub fn travelled_km(points: &[TrailPoint]) -> f64 {
+    let Some(first) = points.first() else {
+        return 0.0;
+    };
+    let mut anchor = first;
+    let mut km = 0.0;
+    for point in &points[1..] {
+        let step_km = anchor.location().distance_km(&point.location());
+        if step_km < MIN_MOVEMENT_KM {
+            continue;
+        }
+        let hours = (point.recorded_at - anchor.recorded_at).num_milliseconds() as f64 / 3_600_000.0;
+        if hours <= 0.0 || step_km / hours > MAX_PLAUSIBLE_SPEED_KMPH {
+            continue;
+        }
+        km += step_km;
+        anchor = point;
+    }
+    km
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignmentTrail {
+    pub assignment_id: Uuid,
+    pub delivery_person_id: Uuid,
+    pub points: Vec<TrailPoint>,
+    /// The points as an encoded polyline, for map libraries
+    pub polyline: String,
+    pub distance_km: f64,
+    /// Set once the assignment is finished and its distance recorded
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+pub struct TrailService {
+    db: Database,
+}
+
+impl TrailService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Add a rider's position to the trail of every order they are carrying
+    /// or on their way to collect
+    pub async fn record(&self, delivery_person_id: Uuid, point: TrailPoint, accuracy_metres: Option<f64>) -> Result<()> {
+        if accuracy_metres.is_some_and(|accuracy| accuracy > MAX_FIX_ACCURACY_METRES) {
+            return Ok(());
+        }
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO delivery_trails (assignment_id, delivery_person_id, started_at)
+            SELECT id, delivery_person_id, $2
+            FROM delivery_assignments
+            WHERE delivery_person_id = $1 AND status IN {}
+            ON CONFLICT (assignment_id) DO NOTHING
+            "#,
+            TRAIL_STATUSES
+        ))
+        .bind(delivery_person_id)
+        .bind(point.recorded_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let trails = sqlx::query(&format!(
+            r#"
+            SELECT t.assignment_id, t.started_at, t.last_latitude_e5, t.last_longitude_e5, t.last_offset_seconds
+            FROM delivery_trails t
+            JOIN delivery_assignments a ON a.id = t.assignment_id
+            WHERE a.delivery_person_id = $1 AND a.status IN {} AND t.closed_at IS NULL
+            FOR UPDATE OF t
+            "#,
+            TRAIL_STATUSES
+        ))
+        .bind(delivery_person_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for trail in trails {
+            let previous = match (
+                trail.get::<Option<i64>, _>("last_latitude_e5"),
+                trail.get::<Option<i64>, _>("last_longitude_e5"),
+                trail.get::<Option<i64>, _>("last_offset_seconds"),
+            ) {
+                (Some(latitude_e5), Some(longitude_e5), Some(offset_seconds)) => Some(TrailCursor {
+                    latitude_e5,
+                    longitude_e5,
+                    offset_seconds,
+                }),
+                _ => None,
+            };
+            let (path, offsets, cursor) = encode_point(previous, trail.get("started_at"), &point);
+
+            sqlx::query(
+                r#"
+                UPDATE delivery_trails
+                SET path = path || $2, offsets = offsets || $3, point_count = point_count + 1,
+                    last_latitude_e5 = $4, last_longitude_e5 = $5, last_offset_seconds = $6
+                WHERE assignment_id = $1
+                "#,
+            )
+            .bind(trail.get::<Uuid, _>("assignment_id"))
+            .bind(path)
+            .bind(offsets)
+            .bind(cursor.latitude_e5)
+            .bind(cursor.longitude_e5)
+            .bind(cursor.offset_seconds)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The recorded trail of an assignment. `user_id`, when given, must own
+    /// the rider profile on the assignment.
+    pub async fn trail(&self, assignment_id: Uuid, user_id: Option<Uuid>) -> Result<AssignmentTrail> {
+        let row = sqlx::query(
+            r#"
+            SELECT a.delivery_person_id, t.started_at, t.path, t.offsets, t.closed_at
+            FROM delivery_assignments a
+            JOIN delivery_persons dp ON dp.id = a.delivery_person_id
+            LEFT JOIN delivery_trails t ON t.assignment_id = a.id
+            WHERE a.id = $1 AND ($2::uuid IS NULL OR dp.user_id = $2)
+            "#,
+        )
+        .bind(assignment_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Delivery assignment not found".to_string()))?;
+
+        // Assignments never accepted have no trail
+        let points = match row.get::<Option<DateTime<Utc>>, _>("started_at") {
+            Some(started_at) => decode_trail(
+                &row.get::<String, _>("path"),
+                &row.get::<String, _>("offsets"),
+                started_at,
+            )?,
+            None => Vec::new(),
+        };
+        let locations: Vec<GeoPoint> = points.iter().map(TrailPoint::location).collect();
+
+        Ok(AssignmentTrail {
+            assignment_id,
+            delivery_person_id: row.get("delivery_person_id"),
+            polyline: encode_polyline(&locations),
+            distance_km: travelled_km(&points),
+            closed_at: row.get("closed_at"),
+            points,
+        })
+    }
+
+    /// Stop recording an assignment that has ended and store the distance
+    /// the rider covered for it
+    pub async fn close(&self, assignment_id: Uuid) -> Result<Option<f64>> {
+        let trail = self.trail(assignment_id, None).await?;
+        if trail.closed_at.is_some() || trail.points.is_empty() {
+            return Ok(None);
+        }
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("UPDATE delivery_trails SET closed_at = NOW() WHERE assignment_id = $1")
+            .bind(assignment_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("UPDATE delivery_assignments SET actual_distance_km = $2, updated_at = NOW() WHERE id = $1")
+            .bind(assignment_id)
+            .bind(trail.distance_km)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(Some(trail.distance_km))
+    }
+
+    /// Delete trails and raw location history started before `before`, all
+    /// or nothing
+    pub async fn purge(&self, before: DateTime<Utc>) -> Result<u64> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let trails = sqlx::query("DELETE FROM delivery_trails WHERE started_at < $1")
+            .bind(before)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .rows_affected();
+
+        let updates = sqlx::query("DELETE FROM location_updates WHERE timestamp < $1")
+            .bind(before)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .rows_affected();
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(trails + updates)
+    }
+}
+
+/// Deletes location history once it is older than the customer data
+/// retention period
+pub struct TrailPurger {
+    service: TrailService,
+    interval: std::time::Duration,
+    retention: Duration,
+}
+
+impl TrailPurger {
+    pub fn new(db: Database, compliance: &ComplianceConfig, interval: std::time::Duration) -> Self {
+        Self {
+            service: TrailService::new(db),
+            interval,
+            retention: Duration::days(compliance.customer_data_retention_days as i64),
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Purged {} expired location records", removed),
+                    Err(e) => tracing::error!("Location trail purge failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<u64> {
+        self.service.purge(Utc::now() - self.retention).await
+    }
+}
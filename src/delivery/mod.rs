@@ -6,6 +6,12 @@ pub mod enhanced_handlers;
 pub mod websocket;
 pub mod websocket_handlers;
 pub mod metrics;
+pub mod dispatch;
+pub mod offers;
+pub mod trips;
+pub mod route_planner;
+pub mod geo_index;
+pub mod location_trails;
 
 #[cfg(test)]
 mod tests;
@@ -17,4 +23,10 @@ pub use enhanced_service::*;
 pub use enhanced_handlers::*;
 pub use websocket::*;
 pub use websocket_handlers::*;
-pub use metrics::*;
\ No newline at end of file
+pub use metrics::*;
+pub use dispatch::*;
+pub use offers::*;
+pub use trips::*;
+pub use route_planner::*;
+pub use geo_index::*;
+pub use location_trails::*;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::money::Money;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
@@ -78,6 +79,23 @@ impl VehicleType {
             _ => Err(format!("Invalid vehicle type: {}", s)),
         }
     }
+    /// Typical speed in city traffic
+    pub fn city_speed_kmph(&self) -> f64 {
+        match self {
+            VehicleType::Bicycle => 12.0,
+            VehicleType::Motorcycle | VehicleType::Scooter => 22.0,
+            VehicleType::Car => 18.0,
+            VehicleType::Van => 15.0,
+        }
+    }
+
+    /// Furthest a rider on this vehicle is sent to a pickup
+    pub fn max_pickup_km(&self) -> f64 {
+        match self {
+            VehicleType::Bicycle => 3.0,
+            _ => 10.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -98,8 +116,8 @@ pub struct DeliveryAssignment {
     pub estimated_pickup_time: Option<DateTime<Utc>>,
     pub estimated_delivery_time: Option<DateTime<Utc>>,
     pub actual_distance_km: Option<f64>,
-    pub delivery_fee: f64,
-    pub tip_amount: Option<f64>,
+    pub delivery_fee: Money,
+    pub tip_amount: Option<Money>,
     pub delivery_notes: Option<String>,
     pub proof_of_delivery: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
@@ -122,6 +140,12 @@ pub enum DeliveryStatus {
     Assigned,
     #[serde(rename = "accepted")]
     Accepted,
+    /// The rider turned the offer down
+    #[serde(rename = "declined")]
+    Declined,
+    /// The rider did not answer the offer in time
+    #[serde(rename = "expired")]
+    Expired,
     #[serde(rename = "enroutetorestaurant")]
     EnRouteToRestaurant,
     #[serde(rename = "arrivedatrestaurant")]
@@ -145,6 +169,8 @@ impl DeliveryStatus {
         match self {
             DeliveryStatus::Assigned => "assigned",
             DeliveryStatus::Accepted => "accepted",
+            DeliveryStatus::Declined => "declined",
+            DeliveryStatus::Expired => "expired",
             DeliveryStatus::EnRouteToRestaurant => "enroutetorestaurant",
             DeliveryStatus::ArrivedAtRestaurant => "arrivedatrestaurant",
             DeliveryStatus::PickedUp => "pickedup",
@@ -160,6 +186,8 @@ impl DeliveryStatus {
         match s {
             "assigned" => Ok(DeliveryStatus::Assigned),
             "accepted" => Ok(DeliveryStatus::Accepted),
+            "declined" => Ok(DeliveryStatus::Declined),
+            "expired" => Ok(DeliveryStatus::Expired),
             "enroutetorestaurant" => Ok(DeliveryStatus::EnRouteToRestaurant),
             "arrivedatrestaurant" => Ok(DeliveryStatus::ArrivedAtRestaurant),
             "pickedup" => Ok(DeliveryStatus::PickedUp),
@@ -217,6 +245,8 @@ pub struct UpdateLocationRequest {
     pub longitude: f64,
     pub speed: Option<f64>,
     pub heading: Option<f64>,
+    /// How far off the fix may be, in metres, as the phone reports it
+    pub accuracy: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -295,8 +325,10 @@ pub struct DeliveryAssignmentResponse {
     pub estimated_pickup_time: Option<DateTime<Utc>>,
     pub estimated_delivery_time: Option<DateTime<Utc>>,
     pub actual_distance_km: Option<f64>,
-    pub delivery_fee: f64,
-    pub tip_amount: Option<f64>,
+    pub delivery_fee: Money,
+    pub tip_amount: Option<Money>,
+    /// Stops and leg ETAs of the rider's trip, once the assignment is accepted
+    pub route: Option<crate::delivery::trips::TripRoute>,
 }
 
 #[derive(Debug, Serialize)]
@@ -318,6 +350,10 @@ pub struct DeliveryStatsResponse {
     pub earnings_this_month: f64,
     pub rating: f64,
     pub total_ratings: i32,
+    /// Offers answered or left to expire
+    pub offers_received: i32,
+    /// Share of those offers accepted, in percent
+    pub acceptance_rate: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -336,7 +372,7 @@ pub struct IndiaDeliveryZone {
     pub peak_hour_surcharge: f64,
     pub weekend_surcharge: f64,
     pub festival_surcharge: f64,
-    pub minimum_order_amount: f64,
+    pub minimum_order_amount: Money,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
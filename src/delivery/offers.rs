@@ -0,0 +1,300 @@
+use crate::database::Database;
+use crate::delivery::geo_index::RiderGeoIndex;
+use crate::delivery::models::{DeliveryAssignment, DeliveryStatus};
+use crate::delivery::trips::{publish_route, TripService};
+use crate::delivery::websocket::DeliveryWebSocketManager;
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+/// Assignment statuses that end an offer without a delivery
+pub const FAILED_OFFER_STATUSES: &str = "('declined', 'expired')";
+
+/// A rider's answer to an offer, as sent over the rider WebSocket
+#[derive(Debug, Clone)]
+pub struct OfferResponse {
+    pub assignment_id: Uuid,
+    pub delivery_person_id: Uuid,
+    /// Account the answer came from; it must own the rider profile
+    pub user_id: Uuid,
+    pub accept: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiredOffer {
+    pub assignment_id: Uuid,
+    pub order_id: Uuid,
+    pub delivery_person_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EscalatedOrder {
+    pub order_id: Uuid,
+    pub failed_offers: i64,
+}
+
+/// Share of answered offers accepted, in percent; 0 before any offers
+pub fn acceptance_rate(offers_received: i32, offers_accepted: i32) -> f64 {
+    if offers_received > 0 {
+        offers_accepted as f64 / offers_received as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+pub struct OfferService {
+    db: Database,
+}
+
+impl OfferService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Accept or decline an open offer. Accepting takes the rider off the
+    /// market, and out of the rider index, and adds the order to their trip.
+    /// `user_id`, when given, must own the rider profile.
+    pub async fn respond(
+        &self,
+        rider_index: &RiderGeoIndex,
+        assignment_id: Uuid,
+        delivery_person_id: Uuid,
+        user_id: Option<Uuid>,
+        accept: bool,
+    ) -> Result<DeliveryAssignment> {
+        let status = if accept { DeliveryStatus::Accepted } else { DeliveryStatus::Declined };
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let assignment = sqlx::query_as::<_, DeliveryAssignment>(
+            r#"
+            UPDATE delivery_assignments a
+            SET status = $1,
+                responded_at = NOW(),
+                accepted_at = CASE WHEN $1 = 'accepted' THEN NOW() ELSE accepted_at END,
+                updated_at = NOW()
+            WHERE a.id = $2 AND a.delivery_person_id = $3
+              AND a.status = 'assigned'
+              AND (a.offer_expires_at IS NULL OR a.offer_expires_at > NOW())
+              AND ($4::uuid IS NULL OR EXISTS (
+                  SELECT 1 FROM delivery_persons dp WHERE dp.id = a.delivery_person_id AND dp.user_id = $4
+              ))
+            RETURNING id, order_id, delivery_person_id, restaurant_id, customer_id,
+                     pickup_address, delivery_address, status as status_str, assigned_at,
+                     accepted_at, picked_up_at, delivered_at, estimated_pickup_time,
+                     estimated_delivery_time, actual_distance_km, delivery_fee,
+                     tip_amount, delivery_notes, proof_of_delivery, created_at, updated_at
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(assignment_id)
+        .bind(delivery_person_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::ValidationError("This offer is no longer open".to_string()))?;
+
+        sqlx::query(
+            r#"
+            UPDATE delivery_persons
+            SET offers_received = offers_received + 1,
+                offers_accepted = offers_accepted + CASE WHEN $2 THEN 1 ELSE 0 END,
+                is_available = CASE WHEN $2 THEN false ELSE is_available END,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery_person_id)
+        .bind(accept)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if accept {
+            Self::put_rider_on_order(&mut tx, delivery_person_id, assignment.order_id).await?;
+            TripService::attach(&mut tx, delivery_person_id, assignment.id).await?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if accept {
+            rider_index.remove(delivery_person_id);
+        }
+
+        Ok(assignment)
+    }
+
+    /// Record the accepting rider on the order and return the id stored.
+    /// `orders.delivery_person_id` refers to the rider's user account, which
+    /// is what order and review checks compare against, not their profile.
+    pub async fn put_rider_on_order(conn: &mut PgConnection, delivery_person_id: Uuid, order_id: Uuid) -> Result<Uuid> {
+        sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE orders o
+            SET delivery_person_id = dp.user_id, updated_at = NOW()
+            FROM delivery_persons dp
+            WHERE o.id = $2 AND dp.id = $1
+            RETURNING o.delivery_person_id
+            "#,
+        )
+        .bind(delivery_person_id)
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))
+    }
+
+    /// Lapse offers nobody answered in time; they count against the rider's
+    /// acceptance rate
+    pub async fn expire_offers(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredOffer>> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let rows = sqlx::query(
+            r#"
+            UPDATE delivery_assignments
+            SET status = 'expired', responded_at = $1, updated_at = $1
+            WHERE status = 'assigned' AND offer_expires_at <= $1
+            RETURNING id, order_id, delivery_person_id
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let expired: Vec<ExpiredOffer> = rows
+            .iter()
+            .map(|row| ExpiredOffer {
+                assignment_id: row.get("id"),
+                order_id: row.get("order_id"),
+                delivery_person_id: row.get("delivery_person_id"),
+            })
+            .collect();
+        let riders: Vec<Uuid> = expired.iter().map(|offer| offer.delivery_person_id).collect();
+        // A rider can let several offers lapse in one pass
+        sqlx::query(
+            r#"
+            UPDATE delivery_persons dp
+            SET offers_received = dp.offers_received + lapsed.count, updated_at = $2
+            FROM (SELECT id, COUNT(*)::int AS count FROM UNNEST($1::uuid[]) AS id GROUP BY id) lapsed
+            WHERE dp.id = lapsed.id
+            "#,
+        )
+        .bind(&riders)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(expired)
+    }
+
+    /// Flag orders that `max_failed_offers` riders have declined or let
+    /// expire and that still have no rider. Each order is flagged once.
+    pub async fn escalate_stuck_orders(&self, max_failed_offers: i64) -> Result<Vec<EscalatedOrder>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE orders o
+            SET dispatch_escalated_at = NOW()
+            FROM (
+                SELECT order_id, COUNT(*) AS failed_offers
+                FROM delivery_assignments
+                WHERE status IN {}
+                GROUP BY order_id
+            ) failed
+            WHERE failed.order_id = o.id
+              AND failed.failed_offers >= $1
+              AND o.dispatch_escalated_at IS NULL
+              AND o.delivery_person_id IS NULL
+              AND o.status IN ('confirmed', 'preparing', 'ready')
+            RETURNING o.id, failed.failed_offers
+            "#,
+            FAILED_OFFER_STATUSES
+        ))
+        .bind(max_failed_offers)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| EscalatedOrder {
+                order_id: row.get("id"),
+                failed_offers: row.get("failed_offers"),
+            })
+            .collect())
+    }
+}
+
+/// Applies offer answers that riders send over the WebSocket
+pub struct OfferResponder {
+    db: Database,
+    service: OfferService,
+    websocket_manager: DeliveryWebSocketManager,
+    rider_index: RiderGeoIndex,
+}
+
+impl OfferResponder {
+    pub fn new(db: Database, websocket_manager: DeliveryWebSocketManager, rider_index: RiderGeoIndex) -> Self {
+        Self {
+            service: OfferService::new(db.clone()),
+            db,
+            websocket_manager,
+            rider_index,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut responses = self.websocket_manager.subscribe_offer_responses();
+            loop {
+                let response = match responses.recv().await {
+                    Ok(response) => response,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Dropped {} offer responses", skipped);
+                        continue;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                self.apply(response).await;
+            }
+        })
+    }
+
+    async fn apply(&self, response: OfferResponse) {
+        let result = self
+            .service
+            .respond(
+                &self.rider_index,
+                response.assignment_id,
+                response.delivery_person_id,
+                Some(response.user_id),
+                response.accept,
+            )
+            .await;
+        match result {
+            Ok(assignment) => {
+                let status = if response.accept { DeliveryStatus::Accepted } else { DeliveryStatus::Declined };
+                // No subscribers is not an error worth reporting
+                let _ = self
+                    .websocket_manager
+                    .broadcast_status_update(assignment.id, response.delivery_person_id, status, None, None)
+                    .await;
+                if response.accept {
+                    publish_route(&self.websocket_manager, &self.db, response.delivery_person_id).await;
+                }
+            }
+            // Usually an answer that arrived after the offer lapsed
+            Err(e) => tracing::debug!("Offer response for {} not applied: {:?}", response.assignment_id, e),
+        }
+    }
+}
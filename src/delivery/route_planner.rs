@@ -0,0 +1,234 @@
+use crate::delivery::trips::{StackOrder, StopKind, StopRoute, TripStop};
+use crate::restaurants::serviceability::GeoPoint;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Trips up to this many orders are sequenced by trying every order of
+/// stops; longer ones use the heuristic
+pub const EXACT_SEARCH_MAX_ORDERS: usize = 3;
+/// City roads are rarely straight; road distance is taken as straight-line
+/// distance times this
+pub const ROAD_DETOUR_FACTOR: f64 = 1.3;
+/// Time spent at each stop handing over or collecting food
+pub const STOP_SERVICE_MINUTES: i64 = 3;
+/// Rounds of stop relocation before the heuristic settles for what it has
+const MAX_IMPROVEMENT_ROUNDS: usize = 50;
+
+/// How the distance between two stops is estimated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceModel {
+    /// Straight-line distance
+    Haversine,
+    /// Straight-line distance stretched to allow for the road network
+    Road { detour_factor: f64 },
+}
+
+impl DistanceModel {
+    pub const ROAD: DistanceModel = DistanceModel::Road {
+        detour_factor: ROAD_DETOUR_FACTOR,
+    };
+
+    pub fn km(&self, from: &GeoPoint, to: &GeoPoint) -> f64 {
+        match self {
+            DistanceModel::Haversine => from.distance_km(to),
+            DistanceModel::Road { detour_factor } => from.distance_km(to) * detour_factor,
+        }
+    }
+}
+
+fn route_km(start: GeoPoint, stops: &[TripStop], model: &DistanceModel) -> f64 {
+    let mut at = start;
+    let mut km = 0.0;
+    for stop in stops {
+        km += model.km(&at, &stop.location);
+        at = stop.location;
+    }
+    km
+}
+
+/// Every order still to be collected is collected before it is dropped off
+pub fn respects_precedence(stops: &[TripStop]) -> bool {
+    stops.iter().enumerate().all(|(index, stop)| {
+        stop.kind == StopKind::Pickup
+            || !stops[index + 1..]
+                .iter()
+                .any(|later| later.order_id == stop.order_id && later.kind == StopKind::Pickup)
+    })
+}
+
+/// Order the pickups and drop-offs of `orders` into a short route from
+/// `start`, never dropping an order off before collecting it
+pub fn sequence_stops(start: GeoPoint, orders: &[StackOrder], model: &DistanceModel) -> StopRoute {
+    if orders.len() <= EXACT_SEARCH_MAX_ORDERS {
+        return exact_route(start, orders, model);
+    }
+    let mut stops = nearest_neighbour_route(start, orders, model);
+    improve_route(start, &mut stops, model);
+    StopRoute {
+        total_km: route_km(start, &stops, model),
+        stops,
+    }
+}
+
+fn next_stops(orders: &[StackOrder], visited: &[u8]) -> Vec<(usize, TripStop)> {
+    orders
+        .iter()
+        .enumerate()
+        .filter_map(|(index, order)| {
+            // 0: waiting at the restaurant, 1: on board, 2: dropped off
+            let (kind, location) = match visited[index] {
+                0 => (StopKind::Pickup, order.pickup),
+                1 => (StopKind::Dropoff, order.dropoff),
+                _ => return None,
+            };
+            Some((
+                index,
+                TripStop {
+                    order_id: order.order_id,
+                    kind,
+                    location,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn initial_visits(orders: &[StackOrder]) -> Vec<u8> {
+    orders.iter().map(|order| u8::from(order.picked_up)).collect()
+}
+
+fn exact_route(start: GeoPoint, orders: &[StackOrder], model: &DistanceModel) -> StopRoute {
+    let mut best = StopRoute {
+        stops: Vec::new(),
+        total_km: f64::INFINITY,
+    };
+    let mut visited = initial_visits(orders);
+    search_routes(start, 0.0, orders, model, &mut visited, &mut Vec::new(), &mut best);
+    best
+}
+
+fn search_routes(
+    at: GeoPoint,
+    km: f64,
+    orders: &[StackOrder],
+    model: &DistanceModel,
+    visited: &mut [u8],
+    path: &mut Vec<TripStop>,
+    best: &mut StopRoute,
+) {
+    if km >= best.total_km {
+        return;
+    }
+    let candidates = next_stops(orders, visited);
+    if candidates.is_empty() {
+        best.stops = path.clone();
+        best.total_km = km;
+        return;
+    }
+    for (index, stop) in candidates {
+        let location = stop.location;
+        visited[index] += 1;
+        path.push(stop);
+        search_routes(location, km + model.km(&at, &location), orders, model, visited, path, best);
+        path.pop();
+        visited[index] -= 1;
+    }
+}
+
+/// Always head for the closest stop that may be made next
+fn nearest_neighbour_route(start: GeoPoint, orders: &[StackOrder], model: &DistanceModel) -> Vec<TripStop> {
+    let mut visited = initial_visits(orders);
+    let mut at = start;
+    let mut stops = Vec::new();
+    loop {
+        let nearest = next_stops(orders, &visited)
+            .into_iter()
+            .min_by(|a, b| model.km(&at, &a.1.location).total_cmp(&model.km(&at, &b.1.location)));
+        let Some((index, stop)) = nearest else { break };
+        visited[index] += 1;
+        at = stop.location;
+        stops.push(stop);
+    }
+    stops
+}
+
+/// Or-opt: move single stops to wherever they shorten the route, keeping
+/// each pickup ahead of its drop-off, until no move helps
+fn improve_route(start: GeoPoint, stops: &mut Vec<TripStop>, model: &DistanceModel) {
+    let mut best_km = route_km(start, stops, model);
+    for _ in 0..MAX_IMPROVEMENT_ROUNDS {
+        let mut improved = false;
+        for from in 0..stops.len() {
+            for to in 0..stops.len() {
+                if from == to {
+                    continue;
+                }
+                let mut candidate = stops.clone();
+                let stop = candidate.remove(from);
+                candidate.insert(to, stop);
+                if !respects_precedence(&candidate) {
+                    continue;
+                }
+                let km = route_km(start, &candidate, model);
+                // Ignore rounding-sized gains so the search settles
+                if km < best_km - 1e-9 {
+                    *stops = candidate;
+                    best_km = km;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+/// One stop on a planned route, with the leg that leads to it
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedLeg {
+    #[serde(flatten)]
+    pub stop: TripStop,
+    pub distance_km: f64,
+    pub eta: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutePlan {
+    pub legs: Vec<PlannedLeg>,
+    pub total_km: f64,
+    pub finish_at: DateTime<Utc>,
+}
+
+/// Sequence the stops and time each leg at `speed_kmph`, allowing for the
+/// time spent at every stop
+pub fn plan_route(
+    start: GeoPoint,
+    orders: &[StackOrder],
+    model: &DistanceModel,
+    speed_kmph: f64,
+    departure: DateTime<Utc>,
+) -> RoutePlan {
+    let route = sequence_stops(start, orders, model);
+    let mut at = start;
+    let mut clock = departure;
+    let mut legs = Vec::with_capacity(route.stops.len());
+    for (index, stop) in route.stops.into_iter().enumerate() {
+        let distance_km = model.km(&at, &stop.location);
+        if index > 0 {
+            clock += Duration::minutes(STOP_SERVICE_MINUTES);
+        }
+        clock += Duration::seconds((distance_km / speed_kmph * 3600.0).round() as i64);
+        at = stop.location;
+        legs.push(PlannedLeg {
+            stop,
+            distance_km,
+            eta: clock,
+        });
+    }
+    RoutePlan {
+        total_km: route.total_km,
+        finish_at: clock,
+        legs,
+    }
+}
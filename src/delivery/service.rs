@@ -1,11 +1,21 @@
 use crate::database::Database;
 use crate::error::{AppError, Result};
+use crate::delivery::geo_index::RiderGeoIndex;
+use crate::delivery::location_trails::{AssignmentTrail, TrailPoint, TrailService};
 use crate::delivery::models::*;
+use crate::delivery::offers::{acceptance_rate, OfferService};
+use crate::delivery::trips::TripService;
+use crate::money::Money;
+use crate::orders::prep_time::RIDER_APPROACH_MINUTES;
+use crate::restaurants::serviceability::GeoPoint;
 use uuid::Uuid;
-use chrono::{Utc, Timelike};
+use chrono::{DateTime, Utc, Timelike};
 
 use sqlx::Row;
 
+/// Most riders a nearby search returns
+pub const NEARBY_RIDER_LIMIT: usize = 10;
+
 pub struct DeliveryService {
     db: Database,
 }
@@ -86,7 +96,15 @@ impl DeliveryService {
         Ok(delivery_person)
     }
 
-    pub async fn update_delivery_person(&self, delivery_person_id: Uuid, user_id: Uuid, request: UpdateDeliveryPersonRequest) -> Result<DeliveryPerson> {
+    /// Update the rider's own profile. Going off duty takes them out of the
+    /// rider index straight away rather than at the next resync.
+    pub async fn update_delivery_person(
+        &self,
+        rider_index: &RiderGeoIndex,
+        delivery_person_id: Uuid,
+        user_id: Uuid,
+        request: UpdateDeliveryPersonRequest,
+    ) -> Result<DeliveryPerson> {
         // Verify ownership
         self.verify_delivery_person_ownership(delivery_person_id, user_id).await?;
 
@@ -185,30 +203,69 @@ impl DeliveryService {
             .map_err(|e| AppError::DatabaseError(e.to_string()))?
             .ok_or_else(|| AppError::NotFound("Delivery person not found or not owned by user".to_string()))?;
 
+        let location = delivery_person.current_latitude.zip(delivery_person.current_longitude);
+        match location {
+            Some((latitude, longitude))
+                if delivery_person.is_available && delivery_person.is_verified && delivery_person.is_active =>
+            {
+                rider_index.upsert(delivery_person_id, GeoPoint::new(latitude, longitude));
+            }
+            _ => rider_index.remove(delivery_person_id),
+        }
+
         Ok(delivery_person)
     }
 
-    pub async fn update_location(&self, delivery_person_id: Uuid, user_id: Uuid, request: UpdateLocationRequest) -> Result<()> {
+    pub async fn update_location(
+        &self,
+        rider_index: &RiderGeoIndex,
+        delivery_person_id: Uuid,
+        user_id: Uuid,
+        request: UpdateLocationRequest,
+    ) -> Result<()> {
         // Verify ownership
         self.verify_delivery_person_ownership(delivery_person_id, user_id).await?;
 
-        sqlx::query(
-            "UPDATE delivery_persons SET current_latitude = $1, current_longitude = $2, updated_at = $3 WHERE id = $4 AND user_id = $5"
+        let indexable: bool = sqlx::query_scalar(
+            "UPDATE delivery_persons SET current_latitude = $1, current_longitude = $2, updated_at = $3 WHERE id = $4 AND user_id = $5
+             RETURNING is_available AND is_verified AND is_active"
         )
         .bind(request.latitude)
         .bind(request.longitude)
         .bind(Utc::now())
         .bind(delivery_person_id)
         .bind(user_id)
-        .execute(self.db.pool())
+        .fetch_one(self.db.pool())
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        if indexable {
+            rider_index.upsert(delivery_person_id, GeoPoint::new(request.latitude, request.longitude));
+        } else {
+            rider_index.remove(delivery_person_id);
+        }
+
+        // Orders the rider is handling keep a trail of where they went
+        let point = TrailPoint {
+            latitude: request.latitude,
+            longitude: request.longitude,
+            recorded_at: Utc::now(),
+        };
+        TrailService::new(self.db.clone())
+            .record(delivery_person_id, point, request.accuracy)
+            .await?;
+
         Ok(())
     }
 
     // Order Assignment Logic
-    pub async fn assign_order(&self, request: OrderAssignmentRequest) -> Result<DeliveryAssignment> {
+    /// Offer the order to a rider; it lapses unless they accept within `offer_timeout`
+    pub async fn assign_order(
+        &self,
+        request: OrderAssignmentRequest,
+        offer_timeout: chrono::Duration,
+        rider_index: &RiderGeoIndex,
+    ) -> Result<DeliveryAssignment> {
         let assignment_id = Uuid::new_v4();
         let now = Utc::now();
 
@@ -221,14 +278,16 @@ impl DeliveryService {
             if self.is_delivery_person_available(preferred_id).await? {
                 preferred_id
             } else {
-                self.find_best_delivery_person(&order_details, request.max_distance_km).await?
+                self.find_best_delivery_person(rider_index, &order_details, request.max_distance_km).await?
             }
         } else {
-            self.find_best_delivery_person(&order_details, request.max_distance_km).await?
+            self.find_best_delivery_person(rider_index, &order_details, request.max_distance_km).await?
         };
 
-        // Calculate estimated times
-        let estimated_pickup_time = now + chrono::Duration::minutes(10); // 10 min to reach restaurant
+        // Calculate estimated times; the rider picks up once they reach the
+        // restaurant and the kitchen has finished
+        let estimated_pickup_time = (now + chrono::Duration::minutes(RIDER_APPROACH_MINUTES))
+            .max(order_details.estimated_ready_at.unwrap_or(now));
         let estimated_delivery_time = estimated_pickup_time + chrono::Duration::minutes(25); // 25 min to deliver
 
         let assignment = sqlx::query_as::<_, DeliveryAssignment>(
@@ -237,9 +296,9 @@ impl DeliveryService {
                 id, order_id, delivery_person_id, restaurant_id, customer_id,
                 pickup_address, delivery_address, status, assigned_at,
                 estimated_pickup_time, estimated_delivery_time, delivery_fee,
-                created_at, updated_at
+                created_at, updated_at, offer_expires_at
             ) VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
             ) RETURNING id, order_id, delivery_person_id, restaurant_id, customer_id,
                      pickup_address, delivery_address, status as status_str, assigned_at,
                      accepted_at, picked_up_at, delivered_at, estimated_pickup_time,
@@ -261,18 +320,31 @@ impl DeliveryService {
         .bind(order_details.delivery_fee)
         .bind(now)
         .bind(now)
+        .bind(now + offer_timeout)
         .fetch_one(self.db.pool())
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        // Update delivery person availability
-        self.update_delivery_person_availability(delivery_person_id, false).await?;
-
+        // The rider stays available until they accept the offer
         Ok(assignment)
     }
 
-    pub async fn update_delivery_status(&self, assignment_id: Uuid, delivery_person_id: Uuid, request: UpdateDeliveryStatusRequest) -> Result<DeliveryAssignment> {
+    pub async fn update_delivery_status(
+        &self,
+        rider_index: &RiderGeoIndex,
+        assignment_id: Uuid,
+        delivery_person_id: Uuid,
+        request: UpdateDeliveryStatusRequest,
+    ) -> Result<DeliveryAssignment> {
         let now = Utc::now();
+
+        // Answering an offer goes through the same path as the rider socket
+        if matches!(request.status, DeliveryStatus::Accepted | DeliveryStatus::Declined) {
+            let accept = matches!(request.status, DeliveryStatus::Accepted);
+            return OfferService::new(self.db.clone())
+                .respond(rider_index, assignment_id, delivery_person_id, None, accept)
+                .await;
+        }
         
         // Build update query based on status
         let (status_updates, time_field) = match request.status {
@@ -321,7 +393,7 @@ impl DeliveryService {
             query_builder = query_builder.bind(proof);
         }
 
-        let assignment = query_builder
+        let mut assignment = query_builder
             .bind(assignment_id)
             .bind(delivery_person_id)
             .fetch_optional(self.db.pool())
@@ -329,45 +401,129 @@ impl DeliveryService {
             .map_err(|e| AppError::DatabaseError(e.to_string()))?
             .ok_or_else(|| AppError::NotFound("Delivery assignment not found".to_string()))?;
 
+        // Riders on a stacked trip stay busy until its last stop
+        let trip_finished = TripService::new(self.db.clone())
+            .advance(assignment_id, &request.status)
+            .await?;
+
+        // The trail ends with the assignment and gives its actual distance
+        if matches!(request.status, DeliveryStatus::Delivered | DeliveryStatus::Cancelled | DeliveryStatus::Failed) {
+            if let Some(distance_km) = TrailService::new(self.db.clone()).close(assignment_id).await? {
+                assignment.actual_distance_km = Some(distance_km);
+            }
+        }
+
         // If delivered, update delivery person stats and make them available
         if matches!(request.status, DeliveryStatus::Delivered) {
             self.update_delivery_stats(delivery_person_id, true).await?;
-            self.update_delivery_person_availability(delivery_person_id, true).await?;
         } else if matches!(request.status, DeliveryStatus::Cancelled | DeliveryStatus::Failed) {
             self.update_delivery_stats(delivery_person_id, false).await?;
+        }
+        if trip_finished {
             self.update_delivery_person_availability(delivery_person_id, true).await?;
         }
 
         Ok(assignment)
     }
 
-    pub async fn get_nearby_delivery_persons(&self, latitude: f64, longitude: f64, radius_km: f64) -> Result<Vec<NearbyDeliveryPersonResponse>> {
-        // Using Haversine formula for distance calculation
+    /// Available riders near a point, nearest first, found through the
+    /// in-memory index. Riders the index still holds but who have since gone
+    /// off duty are dropped from it when their profiles are read, and the
+    /// search widens until it has enough riders who are still available.
+    pub async fn get_nearby_delivery_persons(
+        &self,
+        rider_index: &RiderGeoIndex,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> Result<Vec<NearbyDeliveryPersonResponse>> {
+        let center = GeoPoint::new(latitude, longitude);
+        let mut found = Vec::new();
+        loop {
+            let nearby = rider_index.nearest(&center, NEARBY_RIDER_LIMIT, radius_km);
+            if nearby.is_empty() {
+                return Ok(found);
+            }
+            let ids: Vec<Uuid> = nearby.iter().map(|rider| rider.delivery_person_id).collect();
+
+            let delivery_persons = sqlx::query_as::<_, DeliveryPerson>(
+                "SELECT id, user_id, name, phone, email, vehicle_type as vehicle_type_str, 
+                        vehicle_number, license_number, aadhar_number, pan_number, 
+                        bank_account_number, ifsc_code, current_latitude, current_longitude,
+                        is_available, is_verified, is_active, rating, total_deliveries,
+                        successful_deliveries, average_delivery_time, earnings_today,
+                        earnings_this_month, created_at, updated_at
+                 FROM delivery_persons
+                 WHERE id = ANY($1) AND is_available = true AND is_verified = true AND is_active = true"
+            )
+            .bind(&ids)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            let mut by_id: std::collections::HashMap<Uuid, DeliveryPerson> =
+                delivery_persons.into_iter().map(|person| (person.id, person)).collect();
+            let stale = by_id.len() < nearby.len();
+            found = nearby
+                .into_iter()
+                .filter_map(|rider| {
+                    let Some(delivery_person) = by_id.remove(&rider.delivery_person_id) else {
+                        rider_index.remove(rider.delivery_person_id);
+                        return None;
+                    };
+                    Some(NearbyDeliveryPersonResponse {
+                        delivery_person: DeliveryPersonResponse::from(delivery_person),
+                        distance_km: rider.distance_km,
+                        estimated_arrival_minutes: self.calculate_arrival_time(rider.distance_km),
+                    })
+                })
+                .collect();
+            // With the stale entries gone, the next pass reaches further out
+            if !stale {
+                return Ok(found);
+            }
+        }
+    }
+
+    /// The same search run entirely in Postgres, measuring every available rider
+    pub async fn get_nearby_delivery_persons_from_database(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> Result<Vec<NearbyDeliveryPersonResponse>> {
+        // Haversine by the spherical law of cosines; the distance is computed
+        // in a subquery so the outer query can filter on it, and the cosine is
+        // clamped because rounding can push it past 1 for riders on the spot
         let query = r#"
-            SELECT id, user_id, name, phone, email, vehicle_type as vehicle_type_str, 
-                   vehicle_number, license_number, aadhar_number, pan_number, 
-                   bank_account_number, ifsc_code, current_latitude, current_longitude,
-                   is_available, is_verified, is_active, rating, total_deliveries,
-                   successful_deliveries, average_delivery_time, earnings_today,
-                   earnings_this_month, created_at, updated_at,
-                   (6371 * acos(cos(radians($1)) * cos(radians(current_latitude)) * 
-                   cos(radians(current_longitude) - radians($2)) + 
-                   sin(radians($1)) * sin(radians(current_latitude)))) AS distance_km
-            FROM delivery_persons 
-            WHERE is_available = true 
-              AND is_verified = true 
-              AND is_active = true
-              AND current_latitude IS NOT NULL 
-              AND current_longitude IS NOT NULL
-            HAVING distance_km <= $3
+            SELECT * FROM (
+                SELECT id, user_id, name, phone, email, vehicle_type as vehicle_type_str, 
+                       vehicle_number, license_number, aadhar_number, pan_number, 
+                       bank_account_number, ifsc_code, current_latitude, current_longitude,
+                       is_available, is_verified, is_active, rating, total_deliveries,
+                       successful_deliveries, average_delivery_time, earnings_today,
+                       earnings_this_month, created_at, updated_at,
+                       (6371 * acos(LEAST(1.0, GREATEST(-1.0,
+                           cos(radians($1)) * cos(radians(current_latitude)) * 
+                           cos(radians(current_longitude) - radians($2)) + 
+                           sin(radians($1)) * sin(radians(current_latitude)))))) AS distance_km
+                FROM delivery_persons 
+                WHERE is_available = true 
+                  AND is_verified = true 
+                  AND is_active = true
+                  AND current_latitude IS NOT NULL 
+                  AND current_longitude IS NOT NULL
+            ) candidates
+            WHERE distance_km <= $3
             ORDER BY distance_km ASC
-            LIMIT 10
+            LIMIT $4
         "#;
 
         let rows = sqlx::query(query)
             .bind(latitude)
             .bind(longitude)
             .bind(radius_km)
+            .bind(NEARBY_RIDER_LIMIT as i64)
             .fetch_all(self.db.pool())
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
@@ -416,6 +572,14 @@ impl DeliveryService {
         Ok(nearby_persons)
     }
 
+    /// Where the rider went on an assignment, for replay in disputes. Only
+    /// the rider on the assignment or an admin may see it.
+    pub async fn get_assignment_trail(&self, assignment_id: Uuid, user_id: Uuid, is_admin: bool) -> Result<AssignmentTrail> {
+        TrailService::new(self.db.clone())
+            .trail(assignment_id, (!is_admin).then_some(user_id))
+            .await
+    }
+
     pub async fn get_delivery_person_stats(&self, delivery_person_id: Uuid, user_id: Uuid) -> Result<DeliveryStatsResponse> {
         // Verify ownership
         self.verify_delivery_person_ownership(delivery_person_id, user_id).await?;
@@ -432,6 +596,14 @@ impl DeliveryService {
         // Get weekly earnings (mock calculation for now)
         let earnings_this_week = delivery_person.earnings_today * 7.0; // Simplified
 
+        let offers = sqlx::query("SELECT offers_received, offers_accepted FROM delivery_persons WHERE id = $1")
+            .bind(delivery_person_id)
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let offers_received: i32 = offers.get("offers_received");
+        let offers_accepted: i32 = offers.get("offers_accepted");
+
         Ok(DeliveryStatsResponse {
             total_deliveries: delivery_person.total_deliveries,
             successful_deliveries: delivery_person.successful_deliveries,
@@ -442,6 +614,56 @@ impl DeliveryService {
             earnings_this_month: delivery_person.earnings_this_month,
             rating: delivery_person.rating,
             total_ratings: delivery_person.total_deliveries, // Simplified
+            offers_received,
+            acceptance_rate: acceptance_rate(offers_received, offers_accepted),
+        })
+    }
+
+    /// One assignment with its rider and, once accepted, the trip's stops and
+    /// leg ETAs
+    pub async fn get_delivery_assignment(&self, assignment_id: Uuid, user_id: Uuid) -> Result<DeliveryAssignmentResponse> {
+        let assignment = sqlx::query_as::<_, DeliveryAssignment>(
+            r#"
+            SELECT id, order_id, delivery_person_id, restaurant_id, customer_id,
+                   pickup_address, delivery_address, status as status_str, assigned_at,
+                   accepted_at, picked_up_at, delivered_at, estimated_pickup_time,
+                   estimated_delivery_time, actual_distance_km, delivery_fee,
+                   tip_amount, delivery_notes, proof_of_delivery, created_at, updated_at
+            FROM delivery_assignments WHERE id = $1
+            "#,
+        )
+        .bind(assignment_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Delivery assignment not found".to_string()))?;
+
+        // The route lists other customers' stops, so only the rider sees it
+        self.verify_delivery_person_ownership(assignment.delivery_person_id, user_id).await?;
+
+        let delivery_person = self.get_delivery_person(assignment.delivery_person_id).await?;
+        let route = TripService::new(self.db.clone())
+            .route_for_assignment(assignment_id)
+            .await?;
+        let status = assignment.status().map_err(AppError::DatabaseError)?;
+
+        Ok(DeliveryAssignmentResponse {
+            id: assignment.id,
+            order_id: assignment.order_id,
+            delivery_person: delivery_person.into(),
+            pickup_address: assignment.pickup_address,
+            delivery_address: assignment.delivery_address,
+            status,
+            assigned_at: assignment.assigned_at,
+            accepted_at: assignment.accepted_at,
+            picked_up_at: assignment.picked_up_at,
+            delivered_at: assignment.delivered_at,
+            estimated_pickup_time: assignment.estimated_pickup_time,
+            estimated_delivery_time: assignment.estimated_delivery_time,
+            actual_distance_km: assignment.actual_distance_km,
+            delivery_fee: assignment.delivery_fee,
+            tip_amount: assignment.tip_amount,
+            route,
         })
     }
 
@@ -495,7 +717,7 @@ impl DeliveryService {
                 peak_hour_surcharge: 15.0,
                 weekend_surcharge: 10.0,
                 festival_surcharge: 25.0,
-                minimum_order_amount: 99.0,
+                minimum_order_amount: Money::from_rupees(99),
             },
             IndiaDeliveryZone {
                 zone_name: "Tier 1 Cities".to_string(),
@@ -507,7 +729,7 @@ impl DeliveryService {
                 peak_hour_surcharge: 12.0,
                 weekend_surcharge: 8.0,
                 festival_surcharge: 20.0,
-                minimum_order_amount: 79.0,
+                minimum_order_amount: Money::from_rupees(79),
             },
             IndiaDeliveryZone {
                 zone_name: "Tier 2 Cities".to_string(),
@@ -519,7 +741,7 @@ impl DeliveryService {
                 peak_hour_surcharge: 10.0,
                 weekend_surcharge: 5.0,
                 festival_surcharge: 15.0,
-                minimum_order_amount: 59.0,
+                minimum_order_amount: Money::from_rupees(59),
             },
         ])
     }
@@ -575,24 +797,35 @@ impl DeliveryService {
     }
 
     async fn get_order_details(&self, order_id: Uuid) -> Result<OrderDetails> {
-        // Mock implementation - in production, this would query the orders table
+        // The pickup address is the one copied onto the order, with the
+        // restaurant's current map position filled in
+        let row = sqlx::query(
+            r#"
+            SELECT o.id, o.restaurant_id, o.customer_id, o.delivery_address, o.delivery_fee,
+                   o.estimated_ready_at,
+                   o.restaurant_address || jsonb_strip_nulls(jsonb_build_object(
+                       'latitude', r.latitude::float8,
+                       'longitude', r.longitude::float8
+                   )) AS pickup_address
+            FROM orders o
+            JOIN restaurants r ON r.id = o.restaurant_id
+            WHERE o.id = $1
+            "#,
+        )
+        .bind(order_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
         Ok(OrderDetails {
-            order_id,
-            restaurant_id: Uuid::new_v4(),
-            customer_id: Uuid::new_v4(),
-            pickup_address: serde_json::json!({
-                "street": "123 Restaurant Street",
-                "city": "Mumbai",
-                "latitude": 19.0760,
-                "longitude": 72.8777
-            }),
-            delivery_address: serde_json::json!({
-                "street": "456 Customer Street",
-                "city": "Mumbai", 
-                "latitude": 19.0825,
-                "longitude": 72.8811
-            }),
-            delivery_fee: 29.0,
+            order_id: row.get("id"),
+            restaurant_id: row.get("restaurant_id"),
+            customer_id: row.get("customer_id"),
+            pickup_address: row.get("pickup_address"),
+            delivery_address: row.get("delivery_address"),
+            delivery_fee: row.get("delivery_fee"),
+            estimated_ready_at: row.get("estimated_ready_at"),
         })
     }
 
@@ -609,7 +842,12 @@ impl DeliveryService {
         Ok(is_available)
     }
 
-    async fn find_best_delivery_person(&self, order_details: &OrderDetails, max_distance_km: Option<f64>) -> Result<Uuid> {
+    async fn find_best_delivery_person(
+        &self,
+        rider_index: &RiderGeoIndex,
+        order_details: &OrderDetails,
+        max_distance_km: Option<f64>,
+    ) -> Result<Uuid> {
         let pickup_lat: f64 = order_details.pickup_address["latitude"].as_f64().unwrap_or(0.0);
         let pickup_lng: f64 = order_details.pickup_address["longitude"].as_f64().unwrap_or(0.0);
         let max_dist = max_distance_km.unwrap_or(10.0);
@@ -622,7 +860,7 @@ impl DeliveryService {
             max_dist
         );
 
-        let nearby_persons = self.get_nearby_delivery_persons(pickup_lat, pickup_lng, max_dist).await?;
+        let nearby_persons = self.get_nearby_delivery_persons(rider_index, pickup_lat, pickup_lng, max_dist).await?;
         
         if nearby_persons.is_empty() {
             tracing::warn!("No available delivery persons found for order {}", order_details.order_id);
@@ -719,5 +957,6 @@ struct OrderDetails {
     customer_id: Uuid,
     pickup_address: serde_json::Value,
     delivery_address: serde_json::Value,
-    delivery_fee: f64,
+    delivery_fee: Money,
+    estimated_ready_at: Option<DateTime<Utc>>,
 }
\ No newline at end of file
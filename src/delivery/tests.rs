@@ -1,9 +1,18 @@
 #[cfg(test)]
 mod tests {
     use crate::database::Database;
+    use crate::delivery::dispatch::*;
     use crate::delivery::models::*;
+    use crate::delivery::offers::acceptance_rate;
+    use crate::delivery::trips::*;
+    use crate::delivery::route_planner::*;
+    use crate::delivery::geo_index::*;
+    use crate::delivery::location_trails::*;
+    use crate::restaurants::serviceability::GeoPoint;
+    use crate::money::Money;
     use uuid::Uuid;
     use chrono::Utc;
+    use std::collections::HashSet;
 
     async fn setup_test_db() -> Database {
         // This would be a test database setup
@@ -33,6 +42,11 @@ mod tests {
         
         let invalid = DeliveryStatus::from_str("invalid");
         assert!(invalid.is_err());
+
+        for status in [DeliveryStatus::Declined, DeliveryStatus::Expired] {
+            let parsed = DeliveryStatus::from_str(status.as_str()).unwrap();
+            assert_eq!(parsed.as_str(), status.as_str());
+        }
     }
 
     #[tokio::test]
@@ -92,7 +106,7 @@ mod tests {
             estimated_pickup_time: None,
             estimated_delivery_time: None,
             actual_distance_km: None,
-            delivery_fee: 30.0,
+            delivery_fee: Money::from_rupees(30),
             tip_amount: None,
             delivery_notes: None,
             proof_of_delivery: None,
@@ -198,7 +212,7 @@ mod tests {
             peak_hour_surcharge: 15.0,
             weekend_surcharge: 10.0,
             festival_surcharge: 25.0,
-            minimum_order_amount: 150.0,
+            minimum_order_amount: Money::from_rupees(150),
         };
 
         // Test serialization
@@ -281,4 +295,480 @@ mod tests {
         assert_eq!(deserialized.distance_km, 2.5);
         assert_eq!(deserialized.estimated_arrival_minutes, 8);
     }
-}
\ No newline at end of file
+
+    fn rider(latitude: f64, longitude: f64, rating: f64, vehicle_type: VehicleType) -> DispatchRider {
+        DispatchRider {
+            delivery_person_id: Uuid::new_v4(),
+            latitude,
+            longitude,
+            rating,
+            vehicle_type,
+            idle_since: Utc::now(),
+            acceptance_rate: 1.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assignment_beats_greedy_matching() {
+        // Greedy gives order 0 its nearest rider and leaves order 1 the far one
+        let costs = vec![vec![1.0, 2.0], vec![2.0, 100.0]];
+        assert_eq!(solve_assignment(&costs), vec![Some(1), Some(0)]);
+
+        // More orders than riders: the cheapest pairs win
+        let costs = vec![vec![5.0, 9.0], vec![1.0, 8.0], vec![7.0, 2.0]];
+        assert_eq!(solve_assignment(&costs), vec![None, Some(0), Some(1)]);
+
+        // More riders than orders
+        let costs = vec![vec![4.0, 3.0, 1.0]];
+        assert_eq!(solve_assignment(&costs), vec![Some(2)]);
+        assert!(solve_assignment(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_cost_weighs_distance_rating_and_readiness() {
+        let now = Utc::now();
+        let order = DispatchOrder {
+            order_id: Uuid::new_v4(),
+            pickup_latitude: 19.0760,
+            pickup_longitude: 72.8777,
+            ready_at: Some(now + chrono::Duration::minutes(10)),
+            dropoff: None,
+        };
+
+        // About 2.2 km away
+        let near = rider(19.0960, 72.8777, 4.8, VehicleType::Scooter);
+        let far = rider(19.1300, 72.8777, 4.8, VehicleType::Scooter);
+        assert!(dispatch_cost(&order, &near, now).unwrap() < dispatch_cost(&order, &far, now).unwrap());
+
+        let poorly_rated = rider(19.0960, 72.8777, 3.0, VehicleType::Scooter);
+        assert!(dispatch_cost(&order, &near, now).unwrap() < dispatch_cost(&order, &poorly_rated, now).unwrap());
+
+        let mut idle = near.clone();
+        idle.idle_since = now - chrono::Duration::minutes(20);
+        assert!(dispatch_cost(&order, &idle, now).unwrap() < dispatch_cost(&order, &near, now).unwrap());
+
+        let unreliable = DispatchRider { acceptance_rate: 0.4, ..near.clone() };
+        assert!(dispatch_cost(&order, &near, now).unwrap() < dispatch_cost(&order, &unreliable, now).unwrap());
+
+        // Bicycles are not sent across town
+        assert!(dispatch_cost(&order, &rider(19.1300, 72.8777, 5.0, VehicleType::Bicycle), now).is_none());
+
+        // Arriving early costs less than keeping the food waiting
+        let ready_now = DispatchOrder { ready_at: Some(now), ..order.clone() };
+        let ready_later = DispatchOrder { ready_at: Some(now + chrono::Duration::minutes(12)), ..order.clone() };
+        let travel = dispatch_cost(&DispatchOrder { ready_at: None, ..order.clone() }, &far, now).unwrap();
+        assert!(dispatch_cost(&ready_later, &near, now).unwrap() < dispatch_cost(&ready_now, &far, now).unwrap());
+        assert_eq!(dispatch_cost(&ready_now, &far, now).unwrap(), travel);
+    }
+
+    #[tokio::test]
+    async fn test_plan_dispatch_leaves_unreachable_orders() {
+        let now = Utc::now();
+        let mumbai = DispatchOrder {
+            order_id: Uuid::new_v4(),
+            pickup_latitude: 19.0760,
+            pickup_longitude: 72.8777,
+            ready_at: None,
+            dropoff: None,
+        };
+        let pune = DispatchOrder {
+            order_id: Uuid::new_v4(),
+            pickup_latitude: 18.5204,
+            pickup_longitude: 73.8567,
+            ready_at: None,
+            dropoff: None,
+        };
+        let riders = vec![rider(19.0800, 72.8800, 4.5, VehicleType::Motorcycle)];
+
+        let plan = plan_dispatch(&[pune.clone(), mumbai.clone()], &riders, &HashSet::new(), now);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].order_id, mumbai.order_id);
+        assert_eq!(plan[0].delivery_person_id, riders[0].delivery_person_id);
+
+        assert!(plan_dispatch(&[pune], &riders, &HashSet::new(), now).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_plan_dispatch_cascades_past_failed_offers() {
+        let now = Utc::now();
+        let order = DispatchOrder {
+            order_id: Uuid::new_v4(),
+            pickup_latitude: 19.0760,
+            pickup_longitude: 72.8777,
+            ready_at: None,
+            dropoff: None,
+        };
+        let nearest = rider(19.0770, 72.8780, 4.8, VehicleType::Scooter);
+        let next_best = rider(19.0900, 72.8800, 4.8, VehicleType::Scooter);
+        let riders = vec![nearest.clone(), next_best.clone()];
+
+        let plan = plan_dispatch(&[order.clone()], &riders, &HashSet::new(), now);
+        assert_eq!(plan[0].delivery_person_id, nearest.delivery_person_id);
+
+        // The nearest rider let the offer lapse, so it goes to the next one
+        let mut excluded = HashSet::from([(order.order_id, nearest.delivery_person_id)]);
+        let plan = plan_dispatch(&[order.clone()], &riders, &excluded, now);
+        assert_eq!(plan[0].delivery_person_id, next_best.delivery_person_id);
+
+        excluded.insert((order.order_id, next_best.delivery_person_id));
+        assert!(plan_dispatch(&[order], &riders, &excluded, now).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_acceptance_rate() {
+        assert_eq!(acceptance_rate(0, 0), 0.0);
+        assert_eq!(acceptance_rate(4, 3), 75.0);
+        assert_eq!(acceptance_rate(5, 5), 100.0);
+    }
+
+    fn limits() -> StackingLimits {
+        StackingLimits {
+            max_orders: 3,
+            max_detour_km: 2.0,
+            max_extra_delay_minutes: 10.0,
+        }
+    }
+
+    fn stack_order(pickup: (f64, f64), dropoff: (f64, f64)) -> StackOrder {
+        StackOrder {
+            order_id: Uuid::new_v4(),
+            pickup: GeoPoint::new(pickup.0, pickup.1),
+            dropoff: GeoPoint::new(dropoff.0, dropoff.1),
+            picked_up: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_best_route_collects_before_dropping_off() {
+        let start = GeoPoint::new(19.0700, 72.8700);
+        let restaurant = (19.0760, 72.8777);
+        let near = stack_order(restaurant, (19.0860, 72.8777));
+        let far = stack_order(restaurant, (19.0960, 72.8777));
+
+        let route = best_route(start, &[far.clone(), near.clone()]);
+        let kinds: Vec<(Uuid, StopKind)> = route.stops.iter().map(|stop| (stop.order_id, stop.kind)).collect();
+        assert_eq!(kinds.len(), 4);
+        assert_eq!(kinds[2], (near.order_id, StopKind::Dropoff));
+        assert_eq!(kinds[3], (far.order_id, StopKind::Dropoff));
+        assert!(kinds[..2].iter().all(|(_, kind)| *kind == StopKind::Pickup));
+
+        // Orders on board only need dropping off
+        let on_board = StackOrder { picked_up: true, ..near.clone() };
+        let route = best_route(start, &[on_board]);
+        assert_eq!(route.stops.len(), 1);
+        assert_eq!(route.stops[0].kind, StopKind::Dropoff);
+    }
+
+    #[tokio::test]
+    async fn test_plan_stack_respects_detour_and_delay_limits() {
+        let rider = GeoPoint::new(19.0750, 72.8770);
+        let restaurant = (19.0760, 72.8777);
+        let current = vec![stack_order(restaurant, (19.1000, 72.8777))];
+        let speed = VehicleType::Scooter.city_speed_kmph();
+
+        // Same restaurant, drop-off further along the same road
+        let compatible = stack_order(restaurant, (19.1050, 72.8777));
+        let plan = plan_stack(rider, &current, &compatible, speed, &limits()).unwrap();
+        assert_eq!(plan.route.stops.len(), 4);
+        assert!(plan.max_detour_km <= 2.0);
+        assert!(plan.max_extra_delay_minutes <= 10.0);
+
+        // Drop-off in the opposite direction: one customer or the other waits
+        // while the rider doubles back
+        let opposite = stack_order(restaurant, (19.0400, 72.8777));
+        assert!(plan_stack(rider, &current, &opposite, speed, &limits()).is_none());
+
+        // Restaurant across town
+        let elsewhere = stack_order((19.1300, 72.8777), (19.1050, 72.8777));
+        assert!(plan_stack(rider, &current, &elsewhere, speed, &limits()).is_none());
+
+        // Trip already full
+        let full = vec![
+            current[0].clone(),
+            stack_order(restaurant, (19.1010, 72.8777)),
+            stack_order(restaurant, (19.1020, 72.8777)),
+        ];
+        assert!(plan_stack(rider, &full, &compatible, speed, &limits()).is_none());
+
+        // Nothing to stack onto
+        assert!(plan_stack(rider, &[], &compatible, speed, &limits()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plan_stacking_picks_one_order_per_trip() {
+        let restaurant = (19.0760, 72.8777);
+        let trip = StackableTrip {
+            trip_id: Uuid::new_v4(),
+            delivery_person_id: Uuid::new_v4(),
+            location: GeoPoint::new(19.0750, 72.8770),
+            vehicle_type: VehicleType::Scooter,
+            orders: vec![stack_order(restaurant, (19.0900, 72.8777))],
+        };
+        let order = |dropoff: Option<GeoPoint>| DispatchOrder {
+            order_id: Uuid::new_v4(),
+            pickup_latitude: restaurant.0,
+            pickup_longitude: restaurant.1,
+            ready_at: None,
+            dropoff,
+        };
+        let first = order(Some(GeoPoint::new(19.0950, 72.8777)));
+        let second = order(Some(GeoPoint::new(19.0940, 72.8777)));
+        let unlocated = order(None);
+
+        let plan = plan_stacking(&[unlocated, first.clone(), second], &[trip.clone()], &HashSet::new(), &limits());
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].order_id, first.order_id);
+        assert_eq!(plan[0].delivery_person_id, trip.delivery_person_id);
+
+        let excluded = HashSet::from([(first.order_id, trip.delivery_person_id)]);
+        assert!(plan_stacking(&[first], &[trip], &excluded, &limits()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trip_progress_counts_stops_ahead() {
+        let trip_id = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let stop = |sequence, order_id, kind, completed| TripStopRecord { sequence, order_id, kind, completed };
+        let stops = vec![
+            stop(1, a, StopKind::Pickup, true),
+            stop(2, b, StopKind::Pickup, true),
+            stop(3, a, StopKind::Dropoff, false),
+            stop(4, b, StopKind::Dropoff, false),
+        ];
+
+        let progress = TripProgress::for_order(trip_id, b, &stops).unwrap();
+        assert_eq!(progress.orders_on_trip, 2);
+        assert_eq!(progress.total_stops, 4);
+        assert_eq!(progress.completed_stops, 2);
+        assert_eq!(progress.your_stop, 4);
+        assert_eq!(progress.stops_before_yours, 1);
+
+        let progress = TripProgress::for_order(trip_id, a, &stops).unwrap();
+        assert_eq!(progress.stops_before_yours, 0);
+        assert!(TripProgress::for_order(trip_id, Uuid::new_v4(), &stops).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_route_heuristic_keeps_pickups_ahead_of_dropoffs() {
+        let start = GeoPoint::new(19.0700, 72.8777);
+        let restaurant = (19.0760, 72.8777);
+        // Five orders from one kitchen, customers strung along one road
+        let orders: Vec<StackOrder> = [19.1000, 19.0850, 19.0950, 19.0800, 19.0900]
+            .iter()
+            .map(|latitude| stack_order(restaurant, (*latitude, 72.8777)))
+            .collect();
+        assert!(orders.len() > EXACT_SEARCH_MAX_ORDERS);
+
+        let route = sequence_stops(start, &orders, &DistanceModel::Haversine);
+        assert_eq!(route.stops.len(), 10);
+        assert!(respects_precedence(&route.stops));
+
+        // Collect everything, then drop off going up the road
+        let dropoffs: Vec<f64> = route
+            .stops
+            .iter()
+            .filter(|stop| stop.kind == StopKind::Dropoff)
+            .map(|stop| stop.location.latitude)
+            .collect();
+        assert_eq!(dropoffs, vec![19.0800, 19.0850, 19.0900, 19.0950, 19.1000]);
+        let straight = start.distance_km(&GeoPoint::new(19.1000, 72.8777));
+        assert!((route.total_km - straight).abs() < 1e-6);
+
+        let dropped_first = vec![
+            TripStop { kind: StopKind::Dropoff, ..route.stops[0].clone() },
+            TripStop { kind: StopKind::Pickup, ..route.stops[0].clone() },
+        ];
+        assert!(!respects_precedence(&dropped_first));
+    }
+
+    #[tokio::test]
+    async fn test_plan_route_times_each_leg() {
+        let start = GeoPoint::new(19.0700, 72.8777);
+        let departure = Utc::now();
+        let order = stack_order((19.0760, 72.8777), (19.0900, 72.8777));
+        let speed = VehicleType::Scooter.city_speed_kmph();
+
+        let straight = plan_route(start, &[order.clone()], &DistanceModel::Haversine, speed, departure);
+        let road = plan_route(start, &[order.clone()], &DistanceModel::ROAD, speed, departure);
+        assert_eq!(road.legs.len(), 2);
+        assert!((road.total_km - straight.total_km * ROAD_DETOUR_FACTOR).abs() < 1e-9);
+
+        let first = &road.legs[0];
+        let second = &road.legs[1];
+        assert_eq!(first.stop.kind, StopKind::Pickup);
+        let first_leg_seconds = (first.distance_km / speed * 3600.0).round() as i64;
+        assert_eq!(first.eta, departure + chrono::Duration::seconds(first_leg_seconds));
+        // Time at the restaurant counts before the next leg
+        let second_leg_seconds = (second.distance_km / speed * 3600.0).round() as i64;
+        assert_eq!(
+            second.eta,
+            first.eta + chrono::Duration::minutes(STOP_SERVICE_MINUTES) + chrono::Duration::seconds(second_leg_seconds)
+        );
+        assert_eq!(road.finish_at, second.eta);
+
+        // Nothing left to deliver
+        let empty = plan_route(start, &[], &DistanceModel::ROAD, speed, departure);
+        assert!(empty.legs.is_empty());
+        assert_eq!(empty.finish_at, departure);
+    }
+
+    #[tokio::test]
+    async fn test_geohash_matches_reference_cells() {
+        assert_eq!(geohash(&GeoPoint::new(57.64911, 10.40744)), "u4pruy");
+        assert_eq!(geohash(&GeoPoint::new(19.0760, 72.8777)), "te7ud2");
+    }
+
+    #[tokio::test]
+    async fn test_rider_index_agrees_with_brute_force() {
+        let index = RiderGeoIndex::new();
+        let mut riders = Vec::new();
+        // A fixed scatter of riders across roughly 30 km of Mumbai
+        let mut seed: u64 = 42;
+        let mut next = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        for _ in 0..500 {
+            let location = GeoPoint::new(18.95 + next() * 0.27, 72.80 + next() * 0.28);
+            let id = Uuid::new_v4();
+            index.upsert(id, location);
+            riders.push((id, location));
+        }
+        assert_eq!(index.len(), 500);
+
+        let center = GeoPoint::new(19.0760, 72.8777);
+        let mut expected: Vec<(Uuid, f64)> = riders
+            .iter()
+            .map(|(id, location)| (*id, center.distance_km(location)))
+            .collect();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let within: Vec<Uuid> = index.within(&center, 3.0).iter().map(|rider| rider.delivery_person_id).collect();
+        let expected_within: Vec<Uuid> = expected.iter().filter(|(_, km)| *km <= 3.0).map(|(id, _)| *id).collect();
+        assert_eq!(within, expected_within);
+
+        let nearest = index.nearest(&center, 10, 50.0);
+        let expected_nearest: Vec<Uuid> = expected.iter().take(10).map(|(id, _)| *id).collect();
+        assert_eq!(nearest.iter().map(|rider| rider.delivery_person_id).collect::<Vec<_>>(), expected_nearest);
+        assert!((nearest[0].distance_km - expected[0].1).abs() < 1e-9);
+
+        // The radius caps the search even when fewer than k riders are inside it
+        assert!(index.nearest(&center, 10, 0.01).len() < 10);
+        assert!(index.nearest(&center, 0, 50.0).is_empty());
+
+        // Moving the nearest rider far away and removing the next one
+        let (moved, _) = expected[0];
+        let (removed, _) = expected[1];
+        index.upsert(moved, GeoPoint::new(28.6139, 77.2090));
+        index.remove(removed);
+        assert_eq!(index.len(), 499);
+        let nearest = index.nearest(&center, 1, 50.0);
+        assert_eq!(nearest[0].delivery_person_id, expected[2].0);
+        assert_eq!(index.location(moved), Some(GeoPoint::new(28.6139, 77.2090)));
+        assert_eq!(index.location(removed), None);
+
+        index.replace_all(vec![(moved, center)]);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.nearest(&center, 5, 50.0)[0].delivery_person_id, moved);
+    }
+
+    #[tokio::test]
+    async fn test_rider_index_wraps_the_antimeridian() {
+        let index = RiderGeoIndex::new();
+        let east = Uuid::new_v4();
+        let west = Uuid::new_v4();
+        index.upsert(east, GeoPoint::new(-17.0, 179.99));
+        index.upsert(west, GeoPoint::new(-17.0, -179.99));
+        // Extra riders make the cell walk cheaper than a full scan
+        for offset in 0..300 {
+            index.upsert(Uuid::new_v4(), GeoPoint::new(10.0, offset as f64 * 0.5));
+        }
+
+        let found: HashSet<Uuid> = index
+            .within(&GeoPoint::new(-17.0, 179.999), 5.0)
+            .iter()
+            .map(|rider| rider.delivery_person_id)
+            .collect();
+        assert_eq!(found, HashSet::from([east, west]));
+    }
+
+    fn trail_point(latitude: f64, longitude: f64, at: chrono::DateTime<Utc>) -> TrailPoint {
+        TrailPoint { latitude, longitude, recorded_at: at }
+    }
+
+    #[tokio::test]
+    async fn test_polyline_matches_reference_encoding() {
+        let points = [
+            GeoPoint::new(38.5, -120.2),
+            GeoPoint::new(40.7, -120.95),
+            GeoPoint::new(43.252, -126.453),
+        ];
+        assert_eq!(encode_polyline(&points), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+        assert_eq!(encode_polyline(&[]), "");
+    }
+
+    #[tokio::test]
+    async fn test_trail_round_trips_through_encoding() {
+        let started_at = Utc::now() - chrono::Duration::minutes(10);
+        let points = vec![
+            trail_point(19.07600, 72.87770, started_at),
+            trail_point(19.07712, 72.87655, started_at + chrono::Duration::seconds(5)),
+            trail_point(19.06001, 72.90003, started_at + chrono::Duration::seconds(65)),
+        ];
+
+        let (mut path, mut offsets) = (String::new(), String::new());
+        let mut cursor = None;
+        for point in &points {
+            let (path_part, offsets_part, next) = encode_point(cursor, started_at, point);
+            path.push_str(&path_part);
+            offsets.push_str(&offsets_part);
+            cursor = Some(next);
+        }
+
+        let decoded = decode_trail(&path, &offsets, started_at).unwrap();
+        assert_eq!(decoded.len(), points.len());
+        for (decoded, original) in decoded.iter().zip(&points) {
+            assert!((decoded.latitude - original.latitude).abs() < 1e-9);
+            assert!((decoded.longitude - original.longitude).abs() < 1e-9);
+            assert_eq!(decoded.recorded_at.timestamp(), original.recorded_at.timestamp());
+        }
+
+        // A fix arriving out of order is not stored as going back in time
+        let late = trail_point(19.06, 72.90, started_at + chrono::Duration::seconds(30));
+        let (_, _, after_late) = encode_point(cursor, started_at, &late);
+        assert_eq!(after_late.offset_seconds, 65);
+
+        // A path and offsets that disagree on the number of points are rejected
+        assert!(decode_trail(&path, "", started_at).is_err());
+        assert!(decode_trail("\u{7f}", "", started_at).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_travelled_distance_filters_noise() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + chrono::Duration::seconds(seconds);
+        let a = GeoPoint::new(19.0700, 72.8777);
+        let b = GeoPoint::new(19.0800, 72.8777);
+        let leg = a.distance_km(&b);
+
+        let clean = vec![
+            trail_point(a.latitude, a.longitude, at(0)),
+            trail_point(b.latitude, b.longitude, at(120)),
+        ];
+        assert!((travelled_km(&clean) - leg).abs() < 1e-9);
+
+        let noisy = vec![
+            trail_point(a.latitude, a.longitude, at(0)),
+            // Jitter of a few metres while waiting at the restaurant
+            trail_point(19.07003, 72.87772, at(10)),
+            trail_point(19.06998, 72.87768, at(20)),
+            // A spike 5 km away, ten seconds later
+            trail_point(19.1150, 72.8777, at(30)),
+            trail_point(b.latitude, b.longitude, at(120)),
+        ];
+        assert!((travelled_km(&noisy) - leg).abs() < 1e-9);
+
+        assert_eq!(travelled_km(&[]), 0.0);
+        assert_eq!(travelled_km(&clean[..1]), 0.0);
+    }
+}
@@ -0,0 +1,718 @@
+use crate::config::Config;
+use crate::database::Database;
+use crate::delivery::dispatch::{DispatchMatch, DispatchOrder};
+use crate::delivery::models::{DeliveryStatus, VehicleType};
+use crate::delivery::route_planner::{plan_route, sequence_stops, DistanceModel};
+use crate::delivery::websocket::DeliveryWebSocketManager;
+use crate::error::{AppError, Result};
+use crate::orders::models::Address;
+use crate::restaurants::serviceability::GeoPoint;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Orders are only stacked when their restaurants are this close to a pickup
+/// already on the trip
+pub const MAX_PICKUP_SPREAD_KM: f64 = 1.5;
+
+/// How far stacking may stretch any one customer's delivery
+#[derive(Debug, Clone)]
+pub struct StackingLimits {
+    /// Orders on one trip, counting the one being added
+    pub max_orders: usize,
+    /// Extra distance between a customer's pickup and their drop-off
+    pub max_detour_km: f64,
+    /// Extra minutes before a customer's drop-off
+    pub max_extra_delay_minutes: f64,
+}
+
+impl StackingLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_orders: config.stacking_max_orders,
+            max_detour_km: config.stacking_max_detour_km,
+            max_extra_delay_minutes: config.stacking_max_extra_delay_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopKind {
+    Pickup,
+    Dropoff,
+}
+
+impl StopKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopKind::Pickup => "pickup",
+            StopKind::Dropoff => "dropoff",
+        }
+    }
+}
+
+impl FromStr for StopKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pickup" => Ok(StopKind::Pickup),
+            "dropoff" => Ok(StopKind::Dropoff),
+            _ => Err(format!("Invalid stop kind: {}", s)),
+        }
+    }
+}
+
+/// An order on a trip, or one being considered for it
+#[derive(Debug, Clone)]
+pub struct StackOrder {
+    pub order_id: Uuid,
+    pub pickup: GeoPoint,
+    pub dropoff: GeoPoint,
+    /// Already collected, so only the drop-off is left
+    pub picked_up: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TripStop {
+    pub order_id: Uuid,
+    pub kind: StopKind,
+    pub location: GeoPoint,
+}
+
+/// Stops in the order the rider makes them
+#[derive(Debug, Clone)]
+pub struct StopRoute {
+    pub stops: Vec<TripStop>,
+    pub total_km: f64,
+}
+
+impl StopRoute {
+    /// Distance from the start of the route to the given stop
+    pub fn km_to(&self, start: GeoPoint, order_id: Uuid, kind: StopKind) -> Option<f64> {
+        let mut at = start;
+        let mut km = 0.0;
+        for stop in &self.stops {
+            km += at.distance_km(&stop.location);
+            at = stop.location;
+            if stop.order_id == order_id && stop.kind == kind {
+                return Some(km);
+            }
+        }
+        None
+    }
+
+    /// Distance the order is on board; from the start for orders already
+    /// collected
+    fn ride_km(&self, start: GeoPoint, order_id: Uuid) -> Option<f64> {
+        let pickup = self.km_to(start, order_id, StopKind::Pickup).unwrap_or(0.0);
+        Some(self.km_to(start, order_id, StopKind::Dropoff)? - pickup)
+    }
+}
+
+/// Shortest sequence of stops that collects every order before dropping it
+/// off, by straight-line distance
+pub fn best_route(start: GeoPoint, orders: &[StackOrder]) -> StopRoute {
+    sequence_stops(start, orders, &DistanceModel::Haversine)
+}
+
+/// The route with an order added, and what adding it costs
+#[derive(Debug, Clone)]
+pub struct StackPlan {
+    pub route: StopRoute,
+    pub extra_km: f64,
+    /// Worst extra ride distance for any customer on the trip
+    pub max_detour_km: f64,
+    /// Worst extra wait for any customer on the trip
+    pub max_extra_delay_minutes: f64,
+}
+
+/// Add `candidate` to a rider's trip if no customer, the new one included,
+/// is pushed past the limits. The new customer is compared with a rider
+/// coming straight to them.
+pub fn plan_stack(
+    start: GeoPoint,
+    current: &[StackOrder],
+    candidate: &StackOrder,
+    speed_kmph: f64,
+    limits: &StackingLimits,
+) -> Option<StackPlan> {
+    if current.is_empty() || current.len() >= limits.max_orders {
+        return None;
+    }
+    if !current
+        .iter()
+        .any(|order| order.pickup.distance_km(&candidate.pickup) <= MAX_PICKUP_SPREAD_KM)
+    {
+        return None;
+    }
+
+    let before = best_route(start, current);
+    let mut orders = current.to_vec();
+    orders.push(candidate.clone());
+    let after = best_route(start, &orders);
+
+    let mut max_detour_km: f64 = 0.0;
+    let mut max_delay_km: f64 = 0.0;
+    for order in current {
+        let delay_km = after.km_to(start, order.order_id, StopKind::Dropoff)?
+            - before.km_to(start, order.order_id, StopKind::Dropoff)?;
+        let detour_km = after.ride_km(start, order.order_id)? - before.ride_km(start, order.order_id)?;
+        max_delay_km = max_delay_km.max(delay_km);
+        max_detour_km = max_detour_km.max(detour_km);
+    }
+
+    let direct_km = candidate.pickup.distance_km(&candidate.dropoff);
+    let solo_km = start.distance_km(&candidate.pickup) + direct_km;
+    max_delay_km = max_delay_km.max(after.km_to(start, candidate.order_id, StopKind::Dropoff)? - solo_km);
+    max_detour_km = max_detour_km.max(after.ride_km(start, candidate.order_id)? - direct_km);
+
+    let max_extra_delay_minutes = max_delay_km / speed_kmph * 60.0;
+    if max_detour_km > limits.max_detour_km || max_extra_delay_minutes > limits.max_extra_delay_minutes {
+        return None;
+    }
+
+    Some(StackPlan {
+        extra_km: after.total_km - before.total_km,
+        route: after,
+        max_detour_km,
+        max_extra_delay_minutes,
+    })
+}
+
+/// A rider's trip that can take another order
+#[derive(Debug, Clone)]
+pub struct StackableTrip {
+    pub trip_id: Uuid,
+    pub delivery_person_id: Uuid,
+    pub location: GeoPoint,
+    pub vehicle_type: VehicleType,
+    pub orders: Vec<StackOrder>,
+}
+
+/// Offer orders no free rider took to riders already out on a trip, at most
+/// one per trip per batch, choosing the trip each order stretches least
+pub fn plan_stacking(
+    orders: &[DispatchOrder],
+    trips: &[StackableTrip],
+    excluded: &HashSet<(Uuid, Uuid)>,
+    limits: &StackingLimits,
+) -> Vec<DispatchMatch> {
+    let mut taken = HashSet::new();
+    let mut matches = Vec::new();
+    for order in orders {
+        let Some(dropoff) = order.dropoff else { continue };
+        let candidate = StackOrder {
+            order_id: order.order_id,
+            pickup: GeoPoint::new(order.pickup_latitude, order.pickup_longitude),
+            dropoff,
+            picked_up: false,
+        };
+        let best = trips
+            .iter()
+            .filter(|trip| !taken.contains(&trip.trip_id))
+            .filter(|trip| !excluded.contains(&(order.order_id, trip.delivery_person_id)))
+            .filter_map(|trip| {
+                let speed = trip.vehicle_type.city_speed_kmph();
+                let plan = plan_stack(trip.location, &trip.orders, &candidate, speed, limits)?;
+                Some((trip, plan.extra_km / speed * 60.0))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((trip, cost)) = best {
+            taken.insert(trip.trip_id);
+            matches.push(DispatchMatch {
+                order_id: order.order_id,
+                delivery_person_id: trip.delivery_person_id,
+                cost,
+            });
+        }
+    }
+    matches
+}
+
+#[derive(Debug, Clone)]
+pub struct TripStopRecord {
+    pub sequence: i32,
+    pub order_id: Uuid,
+    pub kind: StopKind,
+    pub completed: bool,
+}
+
+/// Where a customer's drop-off falls on the rider's trip
+#[derive(Debug, Clone, Serialize)]
+pub struct TripProgress {
+    pub trip_id: Uuid,
+    pub orders_on_trip: usize,
+    pub total_stops: usize,
+    pub completed_stops: usize,
+    /// This customer's drop-off, counting from 1
+    pub your_stop: usize,
+    /// Stops still to be made before the rider reaches this customer
+    pub stops_before_yours: usize,
+}
+
+impl TripProgress {
+    /// `stops` must be in sequence order
+    pub fn for_order(trip_id: Uuid, order_id: Uuid, stops: &[TripStopRecord]) -> Option<Self> {
+        let position = stops
+            .iter()
+            .position(|stop| stop.order_id == order_id && stop.kind == StopKind::Dropoff)?;
+        Some(Self {
+            trip_id,
+            orders_on_trip: stops.iter().map(|stop| stop.order_id).collect::<HashSet<_>>().len(),
+            total_stops: stops.len(),
+            completed_stops: stops.iter().filter(|stop| stop.completed).count(),
+            your_stop: position + 1,
+            stops_before_yours: stops[..position].iter().filter(|stop| !stop.completed).count(),
+        })
+    }
+}
+
+/// A stop as the rider sees it, with the leg that leads to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteStop {
+    pub sequence: i32,
+    pub assignment_id: Uuid,
+    pub order_id: Uuid,
+    pub kind: StopKind,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Length of the leg from the previous stop; unknown for addresses
+    /// without coordinates
+    pub distance_km: Option<f64>,
+    pub eta: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Every stop on a rider's trip, made and still to make, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripRoute {
+    pub trip_id: Uuid,
+    pub delivery_person_id: Uuid,
+    pub stops: Vec<RouteStop>,
+}
+
+/// A stop about to be written, before it has a sequence number
+struct PendingStop {
+    order_id: Uuid,
+    kind: StopKind,
+    location: Option<GeoPoint>,
+    distance_km: Option<f64>,
+    eta: Option<DateTime<Utc>>,
+}
+
+fn address_point(value: serde_json::Value) -> Option<GeoPoint> {
+    let address: Address = serde_json::from_value(value).ok()?;
+    Some(GeoPoint::new(address.latitude?, address.longitude?))
+}
+
+pub struct TripService {
+    db: Database,
+}
+
+impl TripService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Put an accepted assignment on the rider's active trip, starting one if
+    /// needed, and re-plan the stops still to be made. Runs inside the
+    /// caller's transaction.
+    pub async fn attach(conn: &mut PgConnection, delivery_person_id: Uuid, assignment_id: Uuid) -> Result<Uuid> {
+        let existing = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM delivery_trips WHERE delivery_person_id = $1 AND status = 'active' FOR UPDATE",
+        )
+        .bind(delivery_person_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let trip_id = match existing {
+            Some(trip_id) => trip_id,
+            None => sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO delivery_trips (id, delivery_person_id) VALUES ($1, $2) RETURNING id",
+            )
+            .bind(Uuid::new_v4())
+            .bind(delivery_person_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?,
+        };
+
+        sqlx::query("UPDATE delivery_assignments SET trip_id = $1 WHERE id = $2")
+            .bind(trip_id)
+            .bind(assignment_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::resequence(conn, trip_id).await?;
+        Ok(trip_id)
+    }
+
+    /// Replace the trip's outstanding stops with a fresh route from the
+    /// rider's position, timing each leg by road. Orders without coordinates
+    /// go last, in the order they joined.
+    async fn resequence(conn: &mut PgConnection, trip_id: Uuid) -> Result<()> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.id, a.order_id, a.picked_up_at IS NOT NULL AS picked_up,
+                   o.restaurant_address, o.delivery_address,
+                   dp.current_latitude, dp.current_longitude, dp.vehicle_type
+            FROM delivery_assignments a
+            JOIN orders o ON o.id = a.order_id
+            JOIN delivery_persons dp ON dp.id = a.delivery_person_id
+            WHERE a.trip_id = $1 AND a.status NOT IN ('delivered', 'cancelled', 'failed')
+            ORDER BY a.accepted_at
+            "#,
+        )
+        .bind(trip_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut assignments = HashMap::new();
+        let mut located = Vec::new();
+        let mut unlocated = Vec::new();
+        let mut rider = None;
+        let mut speed_kmph = VehicleType::Motorcycle.city_speed_kmph();
+        for row in &rows {
+            if let Ok(vehicle_type) = VehicleType::from_str(&row.get::<String, _>("vehicle_type")) {
+                speed_kmph = vehicle_type.city_speed_kmph();
+            }
+            let order_id: Uuid = row.get("order_id");
+            let picked_up: bool = row.get("picked_up");
+            assignments.insert(order_id, row.get::<Uuid, _>("id"));
+            if let (Some(latitude), Some(longitude)) = (
+                row.get::<Option<f64>, _>("current_latitude"),
+                row.get::<Option<f64>, _>("current_longitude"),
+            ) {
+                rider = Some(GeoPoint::new(latitude, longitude));
+            }
+            match (address_point(row.get("restaurant_address")), address_point(row.get("delivery_address"))) {
+                (Some(pickup), Some(dropoff)) => located.push(StackOrder {
+                    order_id,
+                    pickup,
+                    dropoff,
+                    picked_up,
+                }),
+                _ => unlocated.push((order_id, picked_up)),
+            }
+        }
+
+        let start = rider.or_else(|| located.first().map(|order| order.pickup));
+        let mut stops: Vec<PendingStop> = match start {
+            Some(start) => plan_route(start, &located, &DistanceModel::ROAD, speed_kmph, Utc::now())
+                .legs
+                .into_iter()
+                .map(|leg| PendingStop {
+                    order_id: leg.stop.order_id,
+                    kind: leg.stop.kind,
+                    location: Some(leg.stop.location),
+                    distance_km: Some(leg.distance_km),
+                    eta: Some(leg.eta),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        for (order_id, picked_up) in unlocated {
+            let unplanned = |kind| PendingStop {
+                order_id,
+                kind,
+                location: None,
+                distance_km: None,
+                eta: None,
+            };
+            if !picked_up {
+                stops.push(unplanned(StopKind::Pickup));
+            }
+            stops.push(unplanned(StopKind::Dropoff));
+        }
+
+        sqlx::query("DELETE FROM delivery_trip_stops WHERE trip_id = $1 AND completed_at IS NULL")
+            .bind(trip_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let last_sequence = sqlx::query_scalar::<_, i32>(
+            "SELECT COALESCE(MAX(sequence), 0) FROM delivery_trip_stops WHERE trip_id = $1",
+        )
+        .bind(trip_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for (offset, stop) in stops.into_iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO delivery_trip_stops (
+                    id, trip_id, assignment_id, order_id, sequence, kind, latitude, longitude,
+                    distance_km, eta
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(trip_id)
+            .bind(assignments[&stop.order_id])
+            .bind(stop.order_id)
+            .bind(last_sequence + 1 + offset as i32)
+            .bind(stop.kind.as_str())
+            .bind(stop.location.map(|point| point.latitude))
+            .bind(stop.location.map(|point| point.longitude))
+            .bind(stop.distance_km)
+            .bind(stop.eta)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Tick off the stops an assignment's new status completes, re-planning
+    /// the rest of the trip once an order leaves it. Returns whether the rider
+    /// has nothing left to carry.
+    pub async fn advance(&self, assignment_id: Uuid, status: &DeliveryStatus) -> Result<bool> {
+        let query = match status {
+            DeliveryStatus::PickedUp => {
+                "UPDATE delivery_trip_stops SET completed_at = NOW() WHERE assignment_id = $1 AND kind = 'pickup' AND completed_at IS NULL"
+            }
+            DeliveryStatus::Delivered => {
+                "UPDATE delivery_trip_stops SET completed_at = NOW() WHERE assignment_id = $1 AND completed_at IS NULL"
+            }
+            // The order is off the trip; stops already made stay on record
+            DeliveryStatus::Cancelled | DeliveryStatus::Failed => {
+                "DELETE FROM delivery_trip_stops WHERE assignment_id = $1 AND completed_at IS NULL"
+            }
+            _ => return Ok(false),
+        };
+        sqlx::query(query)
+            .bind(assignment_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if matches!(status, DeliveryStatus::PickedUp) {
+            return Ok(false);
+        }
+
+        let trip_id = sqlx::query_scalar::<_, Option<Uuid>>("SELECT trip_id FROM delivery_assignments WHERE id = $1")
+            .bind(assignment_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .flatten();
+        let Some(trip_id) = trip_id else { return Ok(true) };
+
+        // The rest of the trip no longer has to work around this order, and
+        // its leg ETAs now run from where the rider is
+        let mut conn = self.db.pool().acquire().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::resequence(&mut conn, trip_id).await?;
+
+        let finished = sqlx::query(
+            r#"
+            UPDATE delivery_trips
+            SET status = 'completed', completed_at = NOW()
+            WHERE id = $1 AND status = 'active'
+              AND NOT EXISTS (
+                  SELECT 1 FROM delivery_trip_stops s WHERE s.trip_id = $1 AND s.completed_at IS NULL
+              )
+            "#,
+        )
+        .bind(trip_id)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .rows_affected()
+            > 0;
+
+        Ok(finished)
+    }
+
+    /// The rider's trip under way, if any
+    pub async fn route_for_rider(&self, delivery_person_id: Uuid) -> Result<Option<TripRoute>> {
+        let trip_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM delivery_trips WHERE delivery_person_id = $1 AND status = 'active'",
+        )
+        .bind(delivery_person_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        match trip_id {
+            Some(trip_id) => self.route(trip_id, delivery_person_id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The trip the assignment belongs to, once accepted
+    pub async fn route_for_assignment(&self, assignment_id: Uuid) -> Result<Option<TripRoute>> {
+        let trip = sqlx::query(
+            "SELECT trip_id, delivery_person_id FROM delivery_assignments WHERE id = $1 AND trip_id IS NOT NULL",
+        )
+        .bind(assignment_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        match trip {
+            Some(row) => self.route(row.get("trip_id"), row.get("delivery_person_id")).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn route(&self, trip_id: Uuid, delivery_person_id: Uuid) -> Result<TripRoute> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sequence, assignment_id, order_id, kind, latitude, longitude, distance_km, eta, completed_at
+            FROM delivery_trip_stops
+            WHERE trip_id = $1
+            ORDER BY sequence
+            "#,
+        )
+        .bind(trip_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let stops = rows
+            .iter()
+            .filter_map(|row| {
+                Some(RouteStop {
+                    sequence: row.get("sequence"),
+                    assignment_id: row.get("assignment_id"),
+                    order_id: row.get("order_id"),
+                    kind: row.get::<String, _>("kind").parse().ok()?,
+                    latitude: row.get("latitude"),
+                    longitude: row.get("longitude"),
+                    distance_km: row.get("distance_km"),
+                    eta: row.get("eta"),
+                    completed_at: row.get("completed_at"),
+                })
+            })
+            .collect();
+
+        Ok(TripRoute {
+            trip_id,
+            delivery_person_id,
+            stops,
+        })
+    }
+
+    /// Where the order's drop-off is on its rider's trip
+    pub async fn progress(&self, order_id: Uuid) -> Result<Option<TripProgress>> {
+        let trip_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT trip_id FROM delivery_assignments
+            WHERE order_id = $1 AND trip_id IS NOT NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(order_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let Some(trip_id) = trip_id else { return Ok(None) };
+
+        let rows = sqlx::query(
+            "SELECT sequence, order_id, kind, completed_at IS NOT NULL AS completed FROM delivery_trip_stops WHERE trip_id = $1 ORDER BY sequence",
+        )
+        .bind(trip_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let stops: Vec<TripStopRecord> = rows
+            .iter()
+            .filter_map(|row| {
+                Some(TripStopRecord {
+                    sequence: row.get("sequence"),
+                    order_id: row.get("order_id"),
+                    kind: row.get::<String, _>("kind").parse().ok()?,
+                    completed: row.get("completed"),
+                })
+            })
+            .collect();
+
+        Ok(TripProgress::for_order(trip_id, order_id, &stops))
+    }
+
+    /// Active trips whose rider has a known position and no open offer.
+    /// Trips with an order lacking coordinates cannot be re-planned and are
+    /// left alone.
+    pub async fn stackable_trips(&self) -> Result<Vec<StackableTrip>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id AS trip_id, dp.id AS delivery_person_id, dp.current_latitude, dp.current_longitude,
+                   dp.vehicle_type, a.order_id, a.picked_up_at IS NOT NULL AS picked_up,
+                   o.restaurant_address, o.delivery_address
+            FROM delivery_trips t
+            JOIN delivery_persons dp ON dp.id = t.delivery_person_id
+            JOIN delivery_assignments a
+              ON a.trip_id = t.id AND a.status NOT IN ('delivered', 'cancelled', 'failed')
+            JOIN orders o ON o.id = a.order_id
+            WHERE t.status = 'active' AND dp.is_active = true
+              AND dp.current_latitude IS NOT NULL AND dp.current_longitude IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM delivery_assignments offer
+                  WHERE offer.delivery_person_id = dp.id AND offer.status = 'assigned'
+              )
+            ORDER BY t.id
+            "#,
+        )
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut trips: Vec<StackableTrip> = Vec::new();
+        let mut unplannable = HashSet::new();
+        for row in &rows {
+            let trip_id: Uuid = row.get("trip_id");
+            let points = (address_point(row.get("restaurant_address")), address_point(row.get("delivery_address")));
+            let vehicle_type = VehicleType::from_str(&row.get::<String, _>("vehicle_type")).ok();
+            let (Some(pickup), Some(dropoff)) = points else {
+                unplannable.insert(trip_id);
+                continue;
+            };
+            let Some(vehicle_type) = vehicle_type else {
+                unplannable.insert(trip_id);
+                continue;
+            };
+            let order = StackOrder {
+                order_id: row.get("order_id"),
+                pickup,
+                dropoff,
+                picked_up: row.get("picked_up"),
+            };
+            match trips.last_mut() {
+                Some(trip) if trip.trip_id == trip_id => trip.orders.push(order),
+                _ => trips.push(StackableTrip {
+                    trip_id,
+                    delivery_person_id: row.get("delivery_person_id"),
+                    location: GeoPoint::new(row.get("current_latitude"), row.get("current_longitude")),
+                    vehicle_type,
+                    orders: vec![order],
+                }),
+            }
+        }
+        trips.retain(|trip| !unplannable.contains(&trip.trip_id));
+        Ok(trips)
+    }
+}
+
+/// Send the rider their current route after stops are added or dropped
+pub async fn publish_route(websocket_manager: &DeliveryWebSocketManager, db: &Database, delivery_person_id: Uuid) {
+    match TripService::new(db.clone()).route_for_rider(delivery_person_id).await {
+        Ok(Some(route)) => {
+            // No subscribers is not an error worth reporting
+            let _ = websocket_manager.broadcast_route_update(route).await;
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load route for rider {}: {:?}", delivery_person_id, e),
+    }
+}
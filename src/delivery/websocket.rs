@@ -7,8 +7,12 @@ use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 
-use crate::delivery::models::{DeliveryStatus, LocationUpdate};
+use crate::delivery::models::{DeliveryAssignment, DeliveryStatus, LocationUpdate};
+use crate::delivery::offers::OfferResponse;
+use crate::delivery::trips::TripRoute;
 use crate::error::Result;
+use crate::restaurants::busy::BusyMode;
+use crate::restaurants::inventory::StockChange;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -39,6 +43,20 @@ pub enum DeliveryWebSocketMessage {
         delivery_address: serde_json::Value,
         estimated_pickup_time: Option<DateTime<Utc>>,
         estimated_delivery_time: Option<DateTime<Utc>>,
+        offer_expires_at: Option<DateTime<Utc>>,
+    },
+    #[serde(rename = "offer_expired")]
+    OfferExpired {
+        assignment_id: Uuid,
+        delivery_person_id: Uuid,
+        order_id: Uuid,
+    },
+    #[serde(rename = "dispatch_escalation")]
+    DispatchEscalation { order_id: Uuid, failed_offers: i64 },
+    #[serde(rename = "route_update")]
+    RouteUpdate {
+        delivery_person_id: Uuid,
+        route: TripRoute,
     },
     #[serde(rename = "delivery_person_online")]
     DeliveryPersonOnline {
@@ -56,6 +74,19 @@ pub enum DeliveryWebSocketMessage {
         message: String,
         timestamp: DateTime<Utc>,
     },
+    #[serde(rename = "menu_stock_update")]
+    MenuStockUpdate {
+        restaurant_id: Uuid,
+        menu_item_id: Uuid,
+        stock_quantity: Option<i32>,
+        is_available: bool,
+    },
+    #[serde(rename = "restaurant_busy_update")]
+    RestaurantBusyUpdate {
+        restaurant_id: Uuid,
+        busy: Option<BusyMode>,
+        is_accepting_orders: bool,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -72,18 +103,26 @@ pub struct DeliveryWebSocketConnection {
 pub struct DeliveryWebSocketManager {
     connections: Arc<RwLock<HashMap<Uuid, DeliveryWebSocketConnection>>>,
     broadcast_tx: broadcast::Sender<DeliveryWebSocketMessage>,
+    offer_response_tx: broadcast::Sender<OfferResponse>,
 }
 
 impl DeliveryWebSocketManager {
     pub fn new() -> Self {
         let (broadcast_tx, _) = broadcast::channel(1000);
+        let (offer_response_tx, _) = broadcast::channel(1000);
 
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
             broadcast_tx,
+            offer_response_tx,
         }
     }
 
+    /// Offer answers riders send over their sockets
+    pub fn subscribe_offer_responses(&self) -> broadcast::Receiver<OfferResponse> {
+        self.offer_response_tx.subscribe()
+    }
+
     pub async fn add_connection(
         &self,
         connection_id: Uuid,
@@ -145,24 +184,66 @@ impl DeliveryWebSocketManager {
         Ok(())
     }
 
+    /// Offer `assignment` to its rider, open until `offer_expires_at`
     pub async fn broadcast_order_assignment(
+        &self,
+        assignment: &DeliveryAssignment,
+        offer_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let message = DeliveryWebSocketMessage::OrderAssigned {
+            assignment_id: assignment.id,
+            delivery_person_id: assignment.delivery_person_id,
+            order_id: assignment.order_id,
+            pickup_address: assignment.pickup_address.clone(),
+            delivery_address: assignment.delivery_address.clone(),
+            estimated_pickup_time: assignment.estimated_pickup_time,
+            estimated_delivery_time: assignment.estimated_delivery_time,
+            offer_expires_at,
+        };
+
+        self.broadcast_tx
+            .send(message)
+            .map_err(|e| crate::error::AppError::WebSocketError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn broadcast_offer_expired(
         &self,
         assignment_id: Uuid,
         delivery_person_id: Uuid,
         order_id: Uuid,
-        pickup_address: serde_json::Value,
-        delivery_address: serde_json::Value,
-        estimated_pickup_time: Option<DateTime<Utc>>,
-        estimated_delivery_time: Option<DateTime<Utc>>,
     ) -> Result<()> {
-        let message = DeliveryWebSocketMessage::OrderAssigned {
+        let message = DeliveryWebSocketMessage::OfferExpired {
             assignment_id,
             delivery_person_id,
             order_id,
-            pickup_address,
-            delivery_address,
-            estimated_pickup_time,
-            estimated_delivery_time,
+        };
+
+        self.broadcast_tx
+            .send(message)
+            .map_err(|e| crate::error::AppError::WebSocketError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn broadcast_dispatch_escalation(&self, order_id: Uuid, failed_offers: i64) -> Result<()> {
+        let message = DeliveryWebSocketMessage::DispatchEscalation {
+            order_id,
+            failed_offers,
+        };
+
+        self.broadcast_tx
+            .send(message)
+            .map_err(|e| crate::error::AppError::WebSocketError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn broadcast_route_update(&self, route: TripRoute) -> Result<()> {
+        let message = DeliveryWebSocketMessage::RouteUpdate {
+            delivery_person_id: route.delivery_person_id,
+            route,
         };
 
         self.broadcast_tx
@@ -194,6 +275,40 @@ impl DeliveryWebSocketManager {
         Ok(())
     }
 
+    pub async fn broadcast_stock_update(&self, change: StockChange) -> Result<()> {
+        let message = DeliveryWebSocketMessage::MenuStockUpdate {
+            restaurant_id: change.restaurant_id,
+            menu_item_id: change.menu_item_id,
+            stock_quantity: change.stock_quantity,
+            is_available: change.is_available,
+        };
+
+        self.broadcast_tx
+            .send(message)
+            .map_err(|e| crate::error::AppError::WebSocketError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn broadcast_busy_update(
+        &self,
+        restaurant_id: Uuid,
+        busy: Option<BusyMode>,
+        is_accepting_orders: bool,
+    ) -> Result<()> {
+        let message = DeliveryWebSocketMessage::RestaurantBusyUpdate {
+            restaurant_id,
+            busy,
+            is_accepting_orders,
+        };
+
+        self.broadcast_tx
+            .send(message)
+            .map_err(|e| crate::error::AppError::WebSocketError(e.to_string()))?;
+
+        Ok(())
+    }
+
     pub async fn handle_websocket_connection(
         &self,
         socket: WebSocket,
@@ -222,6 +337,7 @@ impl DeliveryWebSocketManager {
 
         // Handle incoming messages
         let broadcast_tx_clone = self.broadcast_tx.clone();
+        let offer_response_tx = self.offer_response_tx.clone();
         let tx_clone = tx.clone();
         let incoming_task = tokio::spawn(async move {
             while let Some(msg) = receiver.next().await {
@@ -258,6 +374,24 @@ impl DeliveryWebSocketManager {
                                         }
                                     }
                                 }
+                                Some("offer_response") => {
+                                    // Rider accepting or declining an offer
+                                    let assignment_id = parsed_msg
+                                        .get("assignment_id")
+                                        .and_then(|v| v.as_str())
+                                        .and_then(|v| Uuid::parse_str(v).ok());
+                                    let accept = parsed_msg.get("accept").and_then(|v| v.as_bool());
+                                    if let (Some(assignment_id), Some(accept), Some(delivery_person_id)) =
+                                        (assignment_id, accept, connection.delivery_person_id)
+                                    {
+                                        let _ = offer_response_tx.send(OfferResponse {
+                                            assignment_id,
+                                            delivery_person_id,
+                                            user_id: connection.user_id,
+                                            accept,
+                                        });
+                                    }
+                                }
                                 _ => {
                                     // Unknown message type, ignore
                                 }
@@ -315,6 +449,19 @@ impl DeliveryWebSocketManager {
                     ) => {
                         conn.delivery_person_id == Some(*delivery_person_id) || conn.role == "admin"
                     }
+                    (
+                        DeliveryWebSocketMessage::OfferExpired {
+                            delivery_person_id, ..
+                        }
+                        | DeliveryWebSocketMessage::RouteUpdate {
+                            delivery_person_id, ..
+                        },
+                        conn,
+                    ) => {
+                        conn.delivery_person_id == Some(*delivery_person_id) || conn.role == "admin"
+                    }
+                    // Orders no rider would take need a human
+                    (DeliveryWebSocketMessage::DispatchEscalation { .. }, conn) => conn.role == "admin",
                     // Send online/offline status to admins and nearby delivery persons
                     (DeliveryWebSocketMessage::DeliveryPersonOnline { .. }, conn) => {
                         conn.role == "admin" || conn.role == "delivery_person"
@@ -324,6 +471,17 @@ impl DeliveryWebSocketManager {
                     }
                     // Send emergency alerts to admins
                     (DeliveryWebSocketMessage::EmergencyAlert { .. }, conn) => conn.role == "admin",
+                    // Send stock changes and busy mode to the kitchen and anyone
+                    // viewing that menu
+                    (
+                        DeliveryWebSocketMessage::MenuStockUpdate { restaurant_id, .. }
+                        | DeliveryWebSocketMessage::RestaurantBusyUpdate { restaurant_id, .. },
+                        conn,
+                    ) => {
+                        conn.role == "admin"
+                            || (conn.restaurant_id == Some(*restaurant_id)
+                                && (conn.role == "restaurant" || conn.role == "menu_viewer"))
+                    }
                 };
 
                 if should_send {
@@ -124,6 +124,30 @@ pub async fn restaurant_websocket_handler(
     })
 }
 
+/// Live stock updates for a restaurant's menu, for customers browsing it
+pub async fn menu_websocket_handler(
+    ws: WebSocketUpgrade,
+    Path(restaurant_id): Path<Uuid>,
+    State(ws_manager): State<DeliveryWebSocketManager>,
+    auth_user: User,
+) -> Response {
+    let connection_id = Uuid::new_v4();
+    
+    let connection = DeliveryWebSocketConnection {
+        user_id: auth_user.id,
+        role: "menu_viewer".to_string(),
+        delivery_person_id: None,
+        restaurant_id: Some(restaurant_id),
+        customer_id: None,
+        connected_at: Utc::now(),
+    };
+
+    let ws_manager_clone = ws_manager.clone();
+    ws.on_upgrade(move |socket| async move {
+        ws_manager_clone.handle_websocket_connection(socket, connection_id, connection).await
+    })
+}
+
 pub async fn customer_websocket_handler(
     ws: WebSocketUpgrade,
     Path(customer_id): Path<Uuid>,
@@ -3,6 +3,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use crate::india::payments::PaymentComplianceError;
 use serde_json::json;
 use std::io;
 
@@ -44,6 +45,8 @@ pub enum AppError {
     DeliveryAssignmentNotFound,
     #[error("Location update failed: {0}")]
     LocationUpdateFailed(String),
+    #[error("Payment rejected: {0}")]
+    PaymentCompliance(#[from] PaymentComplianceError),
 }
 
 impl IntoResponse for AppError {
@@ -70,15 +73,35 @@ impl IntoResponse for AppError {
             AppError::NoDeliveryPersonsAvailable => (StatusCode::SERVICE_UNAVAILABLE, "No delivery persons available".to_string()),
             AppError::DeliveryAssignmentNotFound => (StatusCode::NOT_FOUND, "Delivery assignment not found".to_string()),
             AppError::LocationUpdateFailed(msg) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Location update failed: {}", msg)),
+            AppError::PaymentCompliance(err) => match err {
+                PaymentComplianceError::BelowNetworkMinimum { .. }
+                | PaymentComplianceError::AboveNetworkMaximum { .. } => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()),
+                PaymentComplianceError::DailyLimitExceeded { .. } => (StatusCode::TOO_MANY_REQUESTS, err.to_string()),
+                PaymentComplianceError::KycRequired { .. } => (StatusCode::FORBIDDEN, err.to_string()),
+                PaymentComplianceError::StepUpAuthRequired { .. } => (StatusCode::UNAUTHORIZED, err.to_string()),
+            },
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
             "status": status.as_u16(),
             "timestamp": chrono::Utc::now().to_rfc3339()
-        }));
+        });
+        if let Some(code) = self.code() {
+            body["code"] = json!(code);
+        }
 
-        (status, body).into_response()
+        (status, Json(body)).into_response()
+    }
+}
+
+impl AppError {
+    /// Machine-readable error code for errors clients are expected to act on
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            AppError::PaymentCompliance(err) => Some(err.code()),
+            _ => None,
+        }
     }
 }
 
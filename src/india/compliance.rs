@@ -0,0 +1,79 @@
+use crate::error::{AppError, Result};
+use crate::india::IndianState;
+
+const GSTIN_CHARSET: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Check an FSSAI licence or registration number and return it without
+/// spaces. Numbers are 14 digits; licences start with 1 and basic
+/// registrations with 2.
+pub fn validate_fssai_license(number: &str) -> Result<String> {
+    let number: String = number.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if number.len() != 14 || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(AppError::ValidationError(
+            "FSSAI licence number must be 14 digits".to_string(),
+        ));
+    }
+    if !matches!(number.as_bytes()[0], b'1' | b'2') {
+        return Err(AppError::ValidationError(
+            "FSSAI licence number must start with 1 (licence) or 2 (registration)".to_string(),
+        ));
+    }
+    Ok(number)
+}
+
+/// The check character GSTN computes over the first 14 characters: a
+/// Luhn-style mod 36 sum with every second character weighted double
+pub fn gstin_check_char(first_14: &[u8]) -> Option<char> {
+    let mut sum = 0u32;
+    for (i, c) in first_14.iter().enumerate() {
+        let value = GSTIN_CHARSET.iter().position(|x| x == c)? as u32;
+        let product = value * if i % 2 == 0 { 1 } else { 2 };
+        sum += product / 36 + product % 36;
+    }
+    Some(GSTIN_CHARSET[((36 - sum % 36) % 36) as usize] as char)
+}
+
+/// Check a GSTIN's layout and check character, and that it was issued in
+/// the restaurant's state. Returns it upper-cased without spaces.
+///
+/// Layout: two-digit state code, the holder's PAN (five letters, four
+/// digits, a letter), an entity number, the letter Z and the check character.
+pub fn validate_gstin(gstin: &str, state: &str) -> Result<String> {
+    let gstin: String = gstin
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let bytes = gstin.as_bytes();
+
+    let well_formed = bytes.len() == 15
+        && bytes[0..2].iter().all(u8::is_ascii_digit)
+        && bytes[2..7].iter().all(u8::is_ascii_uppercase)
+        && bytes[7..11].iter().all(u8::is_ascii_digit)
+        && bytes[11].is_ascii_uppercase()
+        && (bytes[12].is_ascii_uppercase() || (b'1'..=b'9').contains(&bytes[12]))
+        && bytes[13] == b'Z'
+        && bytes[14].is_ascii_alphanumeric();
+    if !well_formed {
+        return Err(AppError::ValidationError(format!("{} is not a valid GSTIN", gstin)));
+    }
+    if gstin_check_char(&bytes[..14]) != Some(bytes[14] as char) {
+        return Err(AppError::ValidationError(format!(
+            "GSTIN {} has an incorrect check character",
+            gstin
+        )));
+    }
+
+    let state = IndianState::from_name(state)
+        .ok_or_else(|| AppError::ValidationError(format!("Unrecognised state: {}", state)))?;
+    if !state.gst_state_codes().contains(&&gstin[0..2]) {
+        return Err(AppError::ValidationError(format!(
+            "GSTIN {} is not registered in {}",
+            gstin,
+            state.name()
+        )));
+    }
+
+    Ok(gstin)
+}
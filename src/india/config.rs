@@ -1,3 +1,5 @@
+use crate::money::Money;
+use chrono::FixedOffset;
 use serde::{Deserialize, Serialize};
 
 /// India-specific server configuration
@@ -11,11 +13,15 @@ pub struct IndiaConfig {
     pub fssai_license: Option<String>, // Food Safety and Standards Authority of India
     pub delivery_zones: Vec<String>,
     pub supported_payment_methods: Vec<String>,
-    pub minimum_order_amount: f64,
-    pub delivery_fee: f64,
-    pub free_delivery_above: f64,
-    pub peak_hour_surcharge: f64,
-    pub weekend_surcharge: f64,
+    pub minimum_order_amount: Money,
+    pub delivery_fee: Money,
+    pub free_delivery_above: Money,
+    /// Distance covered by a restaurant's own delivery fee
+    pub delivery_fee_included_km: f64,
+    /// Added for each started kilometre beyond the included distance
+    pub delivery_fee_per_extra_km: Money,
+    pub peak_hour_surcharge: Money,
+    pub weekend_surcharge: Money,
 }
 
 impl Default for IndiaConfig {
@@ -47,11 +53,13 @@ impl Default for IndiaConfig {
                 "Google Pay".to_string(),
                 "Cash on Delivery".to_string(),
             ],
-            minimum_order_amount: 99.0,  // ₹99
-            delivery_fee: 29.0,          // ₹29
-            free_delivery_above: 299.0,  // Free delivery above ₹299
-            peak_hour_surcharge: 15.0,   // ₹15 during peak hours
-            weekend_surcharge: 10.0,     // ₹10 on weekends
+            minimum_order_amount: Money::from_rupees(99), // ₹99
+            delivery_fee: Money::from_rupees(29),         // ₹29
+            free_delivery_above: Money::from_rupees(299), // Free delivery above ₹299
+            delivery_fee_included_km: 3.0,
+            delivery_fee_per_extra_km: Money::from_rupees(6), // ₹6 per km after 3 km
+            peak_hour_surcharge: Money::from_rupees(15),  // ₹15 during peak hours
+            weekend_surcharge: Money::from_rupees(10),    // ₹10 on weekends
         }
     }
 }
@@ -74,6 +82,13 @@ impl Default for ISTConfig {
     }
 }
 
+impl ISTConfig {
+    pub fn offset(&self) -> FixedOffset {
+        let seconds = self.offset_hours as i32 * 3600 + self.offset_minutes as i32 * 60;
+        FixedOffset::east_opt(seconds).unwrap_or_else(|| FixedOffset::east_opt(19800).unwrap())
+    }
+}
+
 /// Business hours configuration for Indian market
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BusinessHours {
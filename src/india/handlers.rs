@@ -1,6 +1,7 @@
 use axum::{extract::Query, Json};
 use serde::{Deserialize, Serialize};
 use crate::error::Result;
+use crate::money::Money;
 use crate::india::{IndianCity, IndianState, IndianCuisine, GSTRate, DeliveryTimeZone};
 use crate::india::payments::{UPIApp, IndianBank, PaymentGatewayFees};
 use crate::india::config::{IndiaConfig, LocalizationConfig};
@@ -215,16 +216,16 @@ pub struct CuisineInfo {
 
 #[derive(Debug, Deserialize)]
 pub struct GSTCalculationRequest {
-    pub amount: f64,
+    pub amount: Money,
     pub category: String,
 }
 
 #[derive(Debug, Serialize)]
 pub struct GSTCalculationResponse {
-    pub base_amount: f64,
+    pub base_amount: Money,
     pub gst_rate: f64,
-    pub gst_amount: f64,
-    pub total_amount: f64,
+    pub gst_amount: Money,
+    pub total_amount: Money,
     pub category: String,
 }
 
@@ -1,13 +1,15 @@
+use crate::money::Money;
 use serde::{Deserialize, Serialize};
 
 
 /// India-specific configuration and utilities
+pub mod compliance;
 pub mod config;
 pub mod payments;
 pub mod handlers;
 
 /// Indian states and their codes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IndianState {
     AndhraPradesh,
     ArunachalPradesh,
@@ -50,6 +52,101 @@ pub enum IndianState {
 }
 
 impl IndianState {
+    pub const ALL: [IndianState; 37] = [
+        IndianState::AndhraPradesh,
+        IndianState::ArunachalPradesh,
+        IndianState::Assam,
+        IndianState::Bihar,
+        IndianState::Chhattisgarh,
+        IndianState::Goa,
+        IndianState::Gujarat,
+        IndianState::Haryana,
+        IndianState::HimachalPradesh,
+        IndianState::Jharkhand,
+        IndianState::Karnataka,
+        IndianState::Kerala,
+        IndianState::MadhyaPradesh,
+        IndianState::Maharashtra,
+        IndianState::Manipur,
+        IndianState::Meghalaya,
+        IndianState::Mizoram,
+        IndianState::Nagaland,
+        IndianState::Odisha,
+        IndianState::Punjab,
+        IndianState::Rajasthan,
+        IndianState::Sikkim,
+        IndianState::TamilNadu,
+        IndianState::Telangana,
+        IndianState::Tripura,
+        IndianState::UttarPradesh,
+        IndianState::Uttarakhand,
+        IndianState::WestBengal,
+        IndianState::AndamanAndNicobarIslands,
+        IndianState::Chandigarh,
+        IndianState::DadraAndNagarHaveliAndDamanAndDiu,
+        IndianState::Delhi,
+        IndianState::Jammu,
+        IndianState::Kashmir,
+        IndianState::Ladakh,
+        IndianState::Lakshadweep,
+        IndianState::Puducherry,
+    ];
+
+    /// Look up a state by its name or two-letter code, ignoring case
+    pub fn from_name(name: &str) -> Option<IndianState> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("Jammu and Kashmir") {
+            return Some(IndianState::Jammu);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|state| state.name().eq_ignore_ascii_case(name) || state.code().eq_ignore_ascii_case(name))
+    }
+
+    /// Two-digit codes that start GSTINs registered in the state. Andhra
+    /// Pradesh and Daman and Diu registrations from before reorganisation
+    /// keep their old codes, so those states accept two.
+    pub fn gst_state_codes(&self) -> &'static [&'static str] {
+        match self {
+            IndianState::Jammu | IndianState::Kashmir => &["01"],
+            IndianState::HimachalPradesh => &["02"],
+            IndianState::Punjab => &["03"],
+            IndianState::Chandigarh => &["04"],
+            IndianState::Uttarakhand => &["05"],
+            IndianState::Haryana => &["06"],
+            IndianState::Delhi => &["07"],
+            IndianState::Rajasthan => &["08"],
+            IndianState::UttarPradesh => &["09"],
+            IndianState::Bihar => &["10"],
+            IndianState::Sikkim => &["11"],
+            IndianState::ArunachalPradesh => &["12"],
+            IndianState::Nagaland => &["13"],
+            IndianState::Manipur => &["14"],
+            IndianState::Mizoram => &["15"],
+            IndianState::Tripura => &["16"],
+            IndianState::Meghalaya => &["17"],
+            IndianState::Assam => &["18"],
+            IndianState::WestBengal => &["19"],
+            IndianState::Jharkhand => &["20"],
+            IndianState::Odisha => &["21"],
+            IndianState::Chhattisgarh => &["22"],
+            IndianState::MadhyaPradesh => &["23"],
+            IndianState::Gujarat => &["24"],
+            IndianState::DadraAndNagarHaveliAndDamanAndDiu => &["26", "25"],
+            IndianState::Maharashtra => &["27"],
+            IndianState::Karnataka => &["29"],
+            IndianState::Goa => &["30"],
+            IndianState::Lakshadweep => &["31"],
+            IndianState::Kerala => &["32"],
+            IndianState::TamilNadu => &["33"],
+            IndianState::Puducherry => &["34"],
+            IndianState::AndamanAndNicobarIslands => &["35"],
+            IndianState::Telangana => &["36"],
+            IndianState::AndhraPradesh => &["37", "28"],
+            IndianState::Ladakh => &["38"],
+        }
+    }
+
     pub fn code(&self) -> &'static str {
         match self {
             IndianState::AndhraPradesh => "AP",
@@ -355,8 +452,9 @@ impl GSTRate {
         ]
     }
 
-    pub fn calculate_gst(&self, amount: f64) -> f64 {
-        amount * (self.rate / 100.0)
+    /// GST on `amount`, rounded half-to-even to the paisa
+    pub fn calculate_gst(&self, amount: Money) -> Money {
+        amount.percentage(self.rate)
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::money::Money;
 use crate::payments::models::PaymentMethod;
 use serde::{Deserialize, Serialize};
 
@@ -23,12 +24,12 @@ impl Default for UPIConfig {
 
 impl UPIConfig {
     /// Generate UPI payment URL
-    pub fn generate_payment_url(&self, amount: f64, transaction_id: &str) -> String {
+    pub fn generate_payment_url(&self, amount: Money, transaction_id: &str) -> String {
         format!(
-            "upi://pay?pa={}&pn={}&am={:.2}&cu={}&tn={}&tr={}",
+            "upi://pay?pa={}&pn={}&am={}&cu={}&tn={}&tr={}",
             self.merchant_id,
             self.merchant_name,
-            amount,
+            amount.to_decimal(),
             self.currency,
             self.transaction_note,
             transaction_id
@@ -111,23 +112,50 @@ impl BankingNetwork {
         }
     }
 
-    pub fn min_amount(&self) -> f64 {
+    pub fn min_amount(&self) -> Money {
         match self {
-            BankingNetwork::IMPS => 1.0,
-            BankingNetwork::NEFT => 1.0,
-            BankingNetwork::RTGS => 200000.0, // 2 lakhs minimum
-            BankingNetwork::UPI => 1.0,
+            BankingNetwork::IMPS => Money::from_rupees(1),
+            BankingNetwork::NEFT => Money::from_rupees(1),
+            BankingNetwork::RTGS => Money::from_rupees(200_000), // 2 lakhs minimum
+            BankingNetwork::UPI => Money::from_rupees(1),
         }
     }
 
-    pub fn max_amount(&self) -> f64 {
+    pub fn max_amount(&self) -> Money {
         match self {
-            BankingNetwork::IMPS => 500000.0,   // 5 lakhs
-            BankingNetwork::NEFT => 1000000.0,  // 10 lakhs
-            BankingNetwork::RTGS => 10000000.0, // 1 crore
-            BankingNetwork::UPI => 100000.0,    // 1 lakh
+            BankingNetwork::IMPS => Money::from_rupees(500_000),    // 5 lakhs
+            BankingNetwork::NEFT => Money::from_rupees(1_000_000),  // 10 lakhs
+            BankingNetwork::RTGS => Money::from_rupees(10_000_000), // 1 crore
+            BankingNetwork::UPI => Money::from_rupees(100_000),     // 1 lakh
         }
     }
+
+    /// Banking network a payment method settles over, if it has network limits
+    pub fn for_payment_method(method: &PaymentMethod) -> Option<BankingNetwork> {
+        match method {
+            PaymentMethod::UPI | PaymentMethod::GooglePay => Some(BankingNetwork::UPI),
+            PaymentMethod::IMPS => Some(BankingNetwork::IMPS),
+            PaymentMethod::NEFT | PaymentMethod::BankTransfer => Some(BankingNetwork::NEFT),
+            PaymentMethod::RTGS => Some(BankingNetwork::RTGS),
+            _ => None,
+        }
+    }
+
+    pub fn validate_amount(&self, amount: Money) -> Result<(), PaymentComplianceError> {
+        if amount < self.min_amount() {
+            return Err(PaymentComplianceError::BelowNetworkMinimum {
+                network: self.name(),
+                minimum: self.min_amount(),
+            });
+        }
+        if amount > self.max_amount() {
+            return Err(PaymentComplianceError::AboveNetworkMaximum {
+                network: self.name(),
+                maximum: self.max_amount(),
+            });
+        }
+        Ok(())
+    }
 }
 
 /// Payment gateway fees structure for India
@@ -135,7 +163,7 @@ impl BankingNetwork {
 pub struct PaymentGatewayFees {
     pub payment_method: PaymentMethod,
     pub percentage_fee: f64,
-    pub fixed_fee: f64, // in INR
+    pub fixed_fee: Money,
     pub gst_applicable: bool,
 }
 
@@ -145,46 +173,46 @@ impl PaymentGatewayFees {
             PaymentGatewayFees {
                 payment_method: PaymentMethod::UPI,
                 percentage_fee: 0.0, // UPI is typically free for merchants
-                fixed_fee: 0.0,
+                fixed_fee: Money::from_rupees(0),
                 gst_applicable: false,
             },
             PaymentGatewayFees {
                 payment_method: PaymentMethod::CreditCard,
                 percentage_fee: 2.0,
-                fixed_fee: 3.0,
+                fixed_fee: Money::from_rupees(3),
                 gst_applicable: true,
             },
             PaymentGatewayFees {
                 payment_method: PaymentMethod::DebitCard,
                 percentage_fee: 1.0,
-                fixed_fee: 2.0,
+                fixed_fee: Money::from_rupees(2),
                 gst_applicable: true,
             },
             PaymentGatewayFees {
                 payment_method: PaymentMethod::NetBanking,
                 percentage_fee: 1.5,
-                fixed_fee: 5.0,
+                fixed_fee: Money::from_rupees(5),
                 gst_applicable: true,
             },
             PaymentGatewayFees {
                 payment_method: PaymentMethod::Paytm,
                 percentage_fee: 1.0,
-                fixed_fee: 1.0,
+                fixed_fee: Money::from_rupees(1),
                 gst_applicable: true,
             },
             PaymentGatewayFees {
                 payment_method: PaymentMethod::PhonePe,
                 percentage_fee: 1.0,
-                fixed_fee: 1.0,
+                fixed_fee: Money::from_rupees(1),
                 gst_applicable: true,
             },
         ]
     }
 
-    pub fn calculate_fee(&self, amount: f64) -> f64 {
-        let base_fee = (amount * self.percentage_fee / 100.0) + self.fixed_fee;
+    pub fn calculate_fee(&self, amount: Money) -> Money {
+        let base_fee = amount.percentage(self.percentage_fee) + self.fixed_fee;
         if self.gst_applicable {
-            base_fee * 1.18 // Adding 18% GST
+            base_fee + base_fee.percentage(18.0) // Adding 18% GST
         } else {
             base_fee
         }
@@ -195,18 +223,77 @@ impl PaymentGatewayFees {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RBICompliance {
     pub two_factor_auth_required: bool,
-    pub transaction_limit_per_day: f64,
-    pub kyc_required_above: f64,
-    pub additional_auth_above: f64,
+    pub transaction_limit_per_day: Money,
+    pub kyc_required_above: Money,
+    pub additional_auth_above: Money,
 }
 
 impl Default for RBICompliance {
     fn default() -> Self {
         Self {
             two_factor_auth_required: true,
-            transaction_limit_per_day: 100000.0, // 1 lakh per day
-            kyc_required_above: 50000.0,         // 50k
-            additional_auth_above: 10000.0,      // 10k
+            transaction_limit_per_day: Money::from_rupees(100_000), // 1 lakh per day
+            kyc_required_above: Money::from_rupees(50_000),         // 50k
+            additional_auth_above: Money::from_rupees(10_000),      // 10k
+        }
+    }
+}
+
+impl RBICompliance {
+    /// Check a payment against the daily limit, KYC threshold and step-up
+    /// authentication rules. `spent_today` is what the customer has already
+    /// committed since midnight IST, excluding this payment.
+    pub fn validate_payment(
+        &self,
+        amount: Money,
+        spent_today: Money,
+        kyc_verified: bool,
+        step_up_verified: bool,
+    ) -> Result<(), PaymentComplianceError> {
+        if spent_today + amount > self.transaction_limit_per_day {
+            return Err(PaymentComplianceError::DailyLimitExceeded {
+                limit: self.transaction_limit_per_day,
+                remaining: self.transaction_limit_per_day.saturating_sub(spent_today),
+            });
+        }
+        if amount > self.kyc_required_above && !kyc_verified {
+            return Err(PaymentComplianceError::KycRequired {
+                threshold: self.kyc_required_above,
+            });
+        }
+        if self.two_factor_auth_required && amount > self.additional_auth_above && !step_up_verified {
+            return Err(PaymentComplianceError::StepUpAuthRequired {
+                threshold: self.additional_auth_above,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Reasons a payment is refused under RBI and banking network rules
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PaymentComplianceError {
+    #[error("Amount is below the {network} minimum of {minimum}")]
+    BelowNetworkMinimum { network: &'static str, minimum: Money },
+    #[error("Amount exceeds the {network} maximum of {maximum}")]
+    AboveNetworkMaximum { network: &'static str, maximum: Money },
+    #[error("Daily payment limit of {limit} exceeded, {remaining} remaining today")]
+    DailyLimitExceeded { limit: Money, remaining: Money },
+    #[error("KYC verification is required for payments above {threshold}")]
+    KycRequired { threshold: Money },
+    #[error("Additional authentication is required for payments above {threshold}")]
+    StepUpAuthRequired { threshold: Money },
+}
+
+impl PaymentComplianceError {
+    /// Stable machine-readable code returned to clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            PaymentComplianceError::BelowNetworkMinimum { .. } => "PAYMENT_BELOW_METHOD_MINIMUM",
+            PaymentComplianceError::AboveNetworkMaximum { .. } => "PAYMENT_ABOVE_METHOD_MAXIMUM",
+            PaymentComplianceError::DailyLimitExceeded { .. } => "PAYMENT_DAILY_LIMIT_EXCEEDED",
+            PaymentComplianceError::KycRequired { .. } => "PAYMENT_KYC_REQUIRED",
+            PaymentComplianceError::StepUpAuthRequired { .. } => "PAYMENT_STEP_UP_AUTH_REQUIRED",
         }
     }
 }
@@ -7,13 +7,18 @@ pub mod delivery;
 pub mod docs;
 pub mod error;
 pub mod india;
+pub mod media;
 pub mod metrics;
 pub mod middleware;
+pub mod money;
 pub mod monitoring;
 pub mod notifications;
 pub mod orders;
 pub mod payments;
+pub mod promotions;
 pub mod restaurants;
+pub mod reviews;
 pub mod routes;
+pub mod search;
 pub mod server;
 pub mod websocket;
\ No newline at end of file
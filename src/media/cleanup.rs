@@ -0,0 +1,40 @@
+use crate::database::Database;
+use crate::error::Result;
+use crate::media::service::{MediaService, MediaStorage};
+use chrono::{Duration, Utc};
+
+/// Background job that deletes uploads nothing refers to. Uploads get a
+/// grace period so a client has time to attach the image after uploading it.
+pub struct MediaCleaner {
+    service: MediaService,
+    interval: std::time::Duration,
+    grace: Duration,
+}
+
+impl MediaCleaner {
+    pub fn new(db: Database, storage: MediaStorage, interval: std::time::Duration, grace: Duration) -> Self {
+        Self {
+            service: MediaService::new(db, storage),
+            interval,
+            grace,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} orphaned media uploads", removed),
+                    Err(e) => tracing::error!("Media cleanup failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        self.service.cleanup_orphans(Utc::now() - self.grace).await
+    }
+}
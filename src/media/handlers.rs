@@ -0,0 +1,72 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::auth::models::User;
+use crate::error::Result;
+use crate::media::{
+    models::*,
+    service::MediaService,
+};
+use crate::routes::AppState;
+
+/// Keys never change content, so clients and CDNs may keep files forever
+const MEDIA_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub async fn upload_media(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<UploadMediaQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<MediaAsset>)> {
+    let media_service = MediaService::new(state.database.clone(), state.media.clone());
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+
+    let asset = media_service
+        .upload(restaurant_id, user.id, params.purpose, content_type, &body)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(asset)))
+}
+
+pub async fn serve_media(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let media_service = MediaService::new(state.database.clone(), state.media.clone());
+
+    let blob = media_service.fetch(&key).await?;
+    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(&blob.bytes)[..16]));
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|candidate| candidate.trim() == etag));
+    if not_modified {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, MEDIA_CACHE_CONTROL.to_string())],
+        )
+            .into_response());
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, blob.content_type),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, MEDIA_CACHE_CONTROL.to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        blob.bytes,
+    )
+        .into_response())
+}
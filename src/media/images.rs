@@ -0,0 +1,226 @@
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Image formats accepted for upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "image/jpeg" | "image/jpg" => Some(ImageFormat::Jpeg),
+            "image/png" => Some(ImageFormat::Png),
+            "image/webp" => Some(ImageFormat::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            "webp" => Some(ImageFormat::Webp),
+            _ => None,
+        }
+    }
+}
+
+/// Format and pixel size read from an image's header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn be16(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn le16(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn le24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+}
+
+/// Identify an image from its content rather than trusting the declared
+/// type. Returns `None` for anything that is not a JPEG, PNG or WebP.
+pub fn sniff_image(bytes: &[u8]) -> Option<ImageInfo> {
+    let (format, width, height) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        if bytes.get(12..16)? != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+        (ImageFormat::Png, width, height)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        let (width, height) = jpeg_dimensions(bytes)?;
+        (ImageFormat::Jpeg, width, height)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12)? == b"WEBP" {
+        let (width, height) = match bytes.get(12..16)? {
+            b"VP8 " if bytes.get(23..26)? == [0x9D, 0x01, 0x2A] => {
+                (le16(bytes, 26)? & 0x3FFF, le16(bytes, 28)? & 0x3FFF)
+            }
+            b"VP8L" if *bytes.get(20)? == 0x2F => {
+                let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+                ((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1)
+            }
+            b"VP8X" => (le24(bytes, 24)? + 1, le24(bytes, 27)? + 1),
+            _ => return None,
+        };
+        (ImageFormat::Webp, width, height)
+    } else {
+        return None;
+    };
+
+    (width > 0 && height > 0).then_some(ImageInfo { format, width, height })
+}
+
+/// Walk the JPEG segments up to the first start-of-frame marker
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    loop {
+        if *bytes.get(at)? != 0xFF {
+            return None;
+        }
+        let mut marker = *bytes.get(at + 1)?;
+        // Markers may be padded with extra 0xFF bytes
+        while marker == 0xFF {
+            at += 1;
+            marker = *bytes.get(at + 1)?;
+        }
+        at += 2;
+
+        match marker {
+            // Standalone markers carry no length
+            0x01 | 0xD0..=0xD7 => continue,
+            // Start of scan: image data follows without a frame header
+            0xD9 | 0xDA => return None,
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((be16(bytes, at + 5)?, be16(bytes, at + 3)?));
+            }
+            _ => at += be16(bytes, at)? as usize,
+        }
+    }
+}
+
+/// A resized copy of an upload, named after its intended use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailSize {
+    pub name: &'static str,
+    pub max_width: u32,
+}
+
+pub const THUMBNAIL_SIZES: [ThumbnailSize; 3] = [
+    ThumbnailSize { name: "small", max_width: 160 },
+    ThumbnailSize { name: "medium", max_width: 480 },
+    ThumbnailSize { name: "large", max_width: 1080 },
+];
+
+/// Thumbnails worth making for an image `width` pixels wide; images are
+/// never scaled up
+pub fn thumbnail_sizes(width: u32) -> Vec<ThumbnailSize> {
+    THUMBNAIL_SIZES.into_iter().filter(|size| size.max_width < width).collect()
+}
+
+#[async_trait]
+pub trait ImageResizer: Send + Sync {
+    /// Scale `image` down to at most `max_width` pixels wide, keeping its
+    /// aspect ratio and format
+    async fn resize(&self, image: &[u8], format: ImageFormat, max_width: u32) -> Result<Vec<u8>>;
+
+    /// Copy `image` at its full size without metadata such as EXIF location
+    async fn strip(&self, image: &[u8], format: ImageFormat) -> Result<Vec<u8>>;
+}
+
+/// Resizes by piping the image through ImageMagick (`magick` or `convert`).
+/// Metadata such as EXIF location is stripped from the output.
+pub struct CommandImageResizer {
+    program: String,
+    timeout: Duration,
+}
+
+impl CommandImageResizer {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CommandImageResizer {
+    async fn convert(&self, image: &[u8], format: ImageFormat, operations: &[String]) -> Result<Vec<u8>> {
+        let resize_error = |e: String| AppError::Internal(anyhow::anyhow!("Image resize failed: {}", e));
+
+        let mut child = tokio::process::Command::new(&self.program)
+            .arg(format!("{}:-", format.extension()))
+            .args(["-auto-orient", "-strip"])
+            .args(operations)
+            .arg(format!("{}:-", format.extension()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| resize_error(format!("{}: {}", self.program, e)))?;
+
+        let mut stdin = child.stdin.take().ok_or_else(|| resize_error("no stdin".to_string()))?;
+        let input = image.to_vec();
+        let writer = tokio::spawn(async move {
+            let _ = stdin.write_all(&input).await;
+        });
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| resize_error("timed out".to_string()))?
+            .map_err(|e| resize_error(e.to_string()))?;
+        let _ = writer.await;
+
+        if !output.status.success() {
+            return Err(resize_error(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[async_trait]
+impl ImageResizer for CommandImageResizer {
+    async fn resize(&self, image: &[u8], format: ImageFormat, max_width: u32) -> Result<Vec<u8>> {
+        self.convert(image, format, &["-resize".to_string(), format!("{}x>", max_width)])
+            .await
+    }
+
+    async fn strip(&self, image: &[u8], format: ImageFormat) -> Result<Vec<u8>> {
+        self.convert(image, format, &[]).await
+    }
+}
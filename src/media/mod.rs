@@ -0,0 +1,16 @@
+pub mod cleanup;
+pub mod handlers;
+pub mod images;
+pub mod models;
+pub mod service;
+pub mod store;
+
+pub use cleanup::*;
+pub use handlers::*;
+pub use images::*;
+pub use models::*;
+pub use service::*;
+pub use store::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,103 @@
+use crate::error::{AppError, Result};
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+/// What an uploaded image is for, which decides who may upload it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaPurpose {
+    /// The restaurant's `image_url`
+    Logo,
+    /// The restaurant's `cover_image_url`
+    Cover,
+    /// A menu item's `image_url`
+    MenuItem,
+}
+
+impl MediaPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaPurpose::Logo => "logo",
+            MediaPurpose::Cover => "cover",
+            MediaPurpose::MenuItem => "menu_item",
+        }
+    }
+
+    pub fn permission(&self) -> Permission {
+        match self {
+            MediaPurpose::Logo | MediaPurpose::Cover => Permission::ManageRestaurant,
+            MediaPurpose::MenuItem => Permission::EditMenu,
+        }
+    }
+}
+
+impl std::str::FromStr for MediaPurpose {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "logo" => Ok(MediaPurpose::Logo),
+            "cover" => Ok(MediaPurpose::Cover),
+            "menu_item" => Ok(MediaPurpose::MenuItem),
+            _ => Err(format!("Invalid media purpose: {}", s)),
+        }
+    }
+}
+
+/// One stored rendition of an upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaVariant {
+    /// `original`, or the thumbnail size name
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub key: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaAsset {
+    pub id: Uuid,
+    pub restaurant_id: Uuid,
+    pub purpose: MediaPurpose,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// URL of the original; set this as the restaurant or menu item image
+    pub url: String,
+    /// Thumbnails smallest first, then the original
+    pub variants: Vec<MediaVariant>,
+    pub uploaded_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `url_for` turns a storage key into the URL it is served from
+pub fn media_asset_from_row(row: &PgRow, url_for: impl Fn(&str) -> String) -> Result<MediaAsset> {
+    let purpose: String = row.get("purpose");
+    let mut variants: Vec<MediaVariant> = serde_json::from_value(row.get("variants"))
+        .map_err(|e| AppError::DatabaseError(format!("Invalid media variants: {}", e)))?;
+    for variant in &mut variants {
+        variant.url = url_for(&variant.key);
+    }
+    let original_key: String = row.get("original_key");
+
+    Ok(MediaAsset {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        purpose: purpose.parse().map_err(AppError::DatabaseError)?,
+        content_type: row.get("content_type"),
+        size_bytes: row.get("size_bytes"),
+        url: url_for(&original_key),
+        variants,
+        uploaded_by: row.get("uploaded_by"),
+        created_at: row.get("created_at"),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadMediaQuery {
+    pub purpose: MediaPurpose,
+}
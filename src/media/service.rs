@@ -0,0 +1,270 @@
+use crate::config::Config;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::media::images::{
+    sniff_image, thumbnail_sizes, CommandImageResizer, ImageFormat, ImageInfo, ImageResizer,
+};
+use crate::media::models::{media_asset_from_row, MediaAsset, MediaPurpose, MediaVariant};
+use crate::media::store::{blob_store_from_config, validate_key, BlobStore, StoredBlob};
+use crate::restaurants::service::RestaurantService;
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Larger images are refused outright; they are expensive to resize and no
+/// screen needs them
+pub const MAX_IMAGE_DIMENSION: u32 = 8000;
+
+/// Orphaned assets removed per cleanup pass
+const CLEANUP_BATCH_SIZE: i64 = 100;
+
+/// The blob store, resizer and limits shared by every request
+#[derive(Clone)]
+pub struct MediaStorage {
+    pub store: Arc<dyn BlobStore>,
+    pub resizer: Arc<dyn ImageResizer>,
+    pub public_base_url: String,
+    pub max_upload_bytes: usize,
+}
+
+impl MediaStorage {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            store: blob_store_from_config(config)?,
+            resizer: Arc::new(CommandImageResizer::new(&config.media_resize_command)),
+            public_base_url: config.media_public_base_url.trim_end_matches('/').to_string(),
+            max_upload_bytes: config.media_max_upload_bytes,
+        })
+    }
+
+    pub fn url_for(&self, key: &str) -> String {
+        format!("{}/media/{}", self.public_base_url, key)
+    }
+}
+
+/// Check an upload's size, real type and dimensions. `declared` is the
+/// request's Content-Type, which must agree with the file's content.
+pub fn inspect_upload(bytes: &[u8], declared: Option<&str>, max_bytes: usize) -> Result<ImageInfo> {
+    if bytes.is_empty() {
+        return Err(AppError::ValidationError("The upload is empty".to_string()));
+    }
+    if bytes.len() > max_bytes {
+        return Err(AppError::ValidationError(format!(
+            "Images must be at most {} KB",
+            max_bytes / 1024
+        )));
+    }
+
+    let info = sniff_image(bytes).ok_or_else(|| {
+        AppError::ValidationError("Only JPEG, PNG and WebP images are supported".to_string())
+    })?;
+    if let Some(declared) = declared {
+        if ImageFormat::from_content_type(declared) != Some(info.format) {
+            return Err(AppError::ValidationError(format!(
+                "The file is {} but was sent as {}",
+                info.format.content_type(),
+                declared
+            )));
+        }
+    }
+    if info.width > MAX_IMAGE_DIMENSION || info.height > MAX_IMAGE_DIMENSION {
+        return Err(AppError::ValidationError(format!(
+            "Images must be at most {} pixels on each side",
+            MAX_IMAGE_DIMENSION
+        )));
+    }
+
+    Ok(info)
+}
+
+pub struct MediaService {
+    db: Database,
+    storage: MediaStorage,
+}
+
+impl MediaService {
+    pub fn new(db: Database, storage: MediaStorage) -> Self {
+        Self { db, storage }
+    }
+
+    /// Store an image and its thumbnails. The returned URLs can then be set
+    /// as the restaurant's or a menu item's image; uploads that never are
+    /// get cleaned up.
+    pub async fn upload(
+        &self,
+        restaurant_id: Uuid,
+        user_id: Uuid,
+        purpose: MediaPurpose,
+        content_type: Option<&str>,
+        bytes: &[u8],
+    ) -> Result<MediaAsset> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, purpose.permission())
+            .await?;
+
+        let info = inspect_upload(bytes, content_type, self.storage.max_upload_bytes)?;
+        // Everything stored is served publicly, so even the original is kept
+        // only as a copy without EXIF location and other metadata
+        let stripped = self.storage.resizer.strip(bytes, info.format).await?;
+        let info = sniff_image(&stripped)
+            .filter(|stripped| stripped.format == info.format)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Resizer returned an unreadable image")))?;
+        let bytes = stripped.as_slice();
+        let asset_id = Uuid::new_v4();
+        let key_prefix = format!("restaurants/{}/{}", restaurant_id, asset_id);
+        let extension = info.format.extension();
+
+        let original_key = format!("{}/original.{}", key_prefix, extension);
+        self.storage
+            .store
+            .put(&original_key, bytes, info.format.content_type())
+            .await?;
+        let mut variants = vec![MediaVariant {
+            name: "original".to_string(),
+            width: info.width,
+            height: info.height,
+            key: original_key.clone(),
+            url: String::new(),
+        }];
+
+        // A missing thumbnail is not worth failing the upload over; clients
+        // fall back to the next size up
+        for size in thumbnail_sizes(info.width) {
+            let resized = match self.storage.resizer.resize(bytes, info.format, size.max_width).await {
+                Ok(resized) => resized,
+                Err(e) => {
+                    tracing::warn!("Could not make {} thumbnail for media {}: {:?}", size.name, asset_id, e);
+                    continue;
+                }
+            };
+            let Some(thumbnail) = sniff_image(&resized).filter(|thumbnail| thumbnail.format == info.format) else {
+                tracing::warn!("Resizer returned an unreadable {} thumbnail for media {}", size.name, asset_id);
+                continue;
+            };
+
+            let key = format!("{}/{}.{}", key_prefix, size.name, extension);
+            if let Err(e) = self.storage.store.put(&key, &resized, info.format.content_type()).await {
+                self.delete_blobs(&variants).await;
+                return Err(e);
+            }
+            variants.push(MediaVariant {
+                name: size.name.to_string(),
+                width: thumbnail.width,
+                height: thumbnail.height,
+                key,
+                url: String::new(),
+            });
+        }
+        // Smallest first, with the original last
+        variants.rotate_left(1);
+
+        let variants_json = serde_json::to_value(&variants)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode media variants: {}", e)))?;
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO media_assets (
+                id, restaurant_id, purpose, content_type, size_bytes, key_prefix, original_key, variants, uploaded_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(asset_id)
+        .bind(restaurant_id)
+        .bind(purpose.as_str())
+        .bind(info.format.content_type())
+        .bind(bytes.len() as i64)
+        .bind(&key_prefix)
+        .bind(&original_key)
+        .bind(variants_json)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await;
+
+        let row = match inserted {
+            Ok(row) => row,
+            Err(e) => {
+                self.delete_blobs(&variants).await;
+                return Err(AppError::DatabaseError(e.to_string()));
+            }
+        };
+
+        media_asset_from_row(&row, |key| self.storage.url_for(key))
+    }
+
+    pub async fn fetch(&self, key: &str) -> Result<StoredBlob> {
+        validate_key(key)?;
+        self.storage
+            .store
+            .get(key)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Media not found".to_string()))
+    }
+
+    /// Delete uploads older than `uploaded_before` that no restaurant, menu
+    /// item or saved menu version points at. Returns how many were removed.
+    pub async fn cleanup_orphans(&self, uploaded_before: DateTime<Utc>) -> Result<usize> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.id, a.variants
+            FROM media_assets a
+            WHERE a.created_at < $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM restaurants r
+                  WHERE r.image_url LIKE '%' || a.key_prefix || '/%'
+                     OR r.cover_image_url LIKE '%' || a.key_prefix || '/%'
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM menu_items m WHERE m.image_url LIKE '%' || a.key_prefix || '/%'
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM menu_versions v, jsonb_array_elements(v.items) item
+                  WHERE item->>'image_url' LIKE '%' || a.key_prefix || '/%'
+              )
+            ORDER BY a.created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(uploaded_before)
+        .bind(CLEANUP_BATCH_SIZE)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut removed = 0;
+        for row in rows {
+            let asset_id: Uuid = row.get("id");
+            let variants: Vec<MediaVariant> = serde_json::from_value(row.get("variants"))
+                .map_err(|e| AppError::DatabaseError(format!("Invalid media variants: {}", e)))?;
+
+            // Keep the record until every file is gone so a failed delete is retried
+            let mut deleted_all = true;
+            for variant in &variants {
+                if let Err(e) = self.storage.store.delete(&variant.key).await {
+                    tracing::warn!("Failed to delete orphaned media {}: {:?}", variant.key, e);
+                    deleted_all = false;
+                }
+            }
+            if !deleted_all {
+                continue;
+            }
+
+            sqlx::query("DELETE FROM media_assets WHERE id = $1")
+                .bind(asset_id)
+                .execute(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    async fn delete_blobs(&self, variants: &[MediaVariant]) {
+        for variant in variants {
+            if let Err(e) = self.storage.store.delete(&variant.key).await {
+                tracing::warn!("Failed to remove media {} after a failed upload: {:?}", variant.key, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::media::images::ImageFormat;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A stored file and the type it should be served as
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub bytes: Vec<u8>,
+    pub content_type: String,
+}
+
+/// Where uploaded files live. Keys are relative, `/`-separated paths such
+/// as `restaurants/{id}/{asset}/small.jpg`.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<()>;
+    /// `None` when nothing is stored under the key
+    async fn get(&self, key: &str) -> Result<Option<StoredBlob>>;
+    /// Deleting a missing key is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Reject keys that could escape the store's root or that we never generate
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 512
+        && key.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::NotFound("Media not found".to_string()))
+    }
+}
+
+fn storage_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Media storage error: {}", e))
+}
+
+/// Build the store selected by `MEDIA_STORAGE`
+pub fn blob_store_from_config(config: &Config) -> Result<Arc<dyn BlobStore>> {
+    match config.media_storage.as_str() {
+        "local" => Ok(Arc::new(LocalBlobStore::new(&config.media_local_path))),
+        "s3" => Ok(Arc::new(S3BlobStore::new(config)?)),
+        other => Err(AppError::Internal(anyhow::anyhow!(
+            "Unknown MEDIA_STORAGE '{}'; expected local or s3",
+            other
+        ))),
+    }
+}
+
+/// Files under a directory on local disk
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, bytes: &[u8], _content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(storage_error)?;
+        }
+
+        // Write then rename so readers never see a partial file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, bytes).await.map_err(storage_error)?;
+        tokio::fs::rename(&partial, &path).await.map_err(storage_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<StoredBlob>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(StoredBlob {
+                bytes,
+                content_type: content_type_for(&path).to_string(),
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error(e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(storage_error(e)),
+        }
+
+        // Tidy up the asset's directory once its last file is gone
+        if let Some(parent) = path.parent() {
+            let _ = tokio::fs::remove_dir(parent).await;
+        }
+        Ok(())
+    }
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(ImageFormat::from_extension)
+        .map(|format| format.content_type())
+        .unwrap_or("application/octet-stream")
+}
+
+/// Any S3-compatible service (AWS S3, MinIO, Ceph), addressed path-style
+/// as `{endpoint}/{bucket}/{key}` so a local stand-in works unchanged
+pub struct S3BlobStore {
+    client: Client,
+    endpoint: reqwest::Url,
+    bucket: String,
+    credentials: S3Credentials,
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+}
+
+impl S3BlobStore {
+    pub fn new(config: &Config) -> Result<Self> {
+        let endpoint = reqwest::Url::parse(&config.media_s3_endpoint).map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Invalid MEDIA_S3_ENDPOINT: {}", e))
+        })?;
+        if config.media_s3_bucket.is_empty() {
+            return Err(AppError::Internal(anyhow::anyhow!("MEDIA_S3_BUCKET is required for S3 storage")));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Ok(Self {
+            client,
+            endpoint,
+            bucket: config.media_s3_bucket.clone(),
+            credentials: S3Credentials {
+                access_key: config.media_s3_access_key.clone(),
+                secret_key: config.media_s3_secret_key.clone(),
+                region: config.media_s3_region.clone(),
+            },
+        })
+    }
+
+    async fn send(&self, method: reqwest::Method, key: &str, body: Option<(&[u8], &str)>) -> Result<reqwest::Response> {
+        validate_key(key)?;
+
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket),
+            key.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let payload_hash = hex::encode(Sha256::digest(body.map(|(bytes, _)| bytes).unwrap_or_default()));
+        let now = Utc::now();
+        let headers = [
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date(now)),
+        ];
+        let authorization = sigv4_authorization(&self.credentials, method.as_str(), url.path(), &headers, &payload_hash, now);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", &headers[1].1)
+            .header("x-amz-date", &headers[2].1)
+            .header("authorization", authorization);
+        if let Some((bytes, content_type)) = body {
+            request = request.header("content-type", content_type).body(bytes.to_vec());
+        }
+
+        request
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Media storage request failed: {}", e)))
+    }
+}
+
+fn unexpected_status(response: &reqwest::Response) -> AppError {
+    AppError::Network(format!("Media storage returned {}", response.status()))
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<()> {
+        let response = self.send(reqwest::Method::PUT, key, Some((bytes, content_type))).await?;
+        if !response.status().is_success() {
+            return Err(unexpected_status(&response));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<StoredBlob>> {
+        let response = self.send(reqwest::Method::GET, key, None).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(unexpected_status(&response));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Network(format!("Media storage request failed: {}", e)))?;
+
+        Ok(Some(StoredBlob {
+            bytes: bytes.to_vec(),
+            content_type,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.send(reqwest::Method::DELETE, key, None).await?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            return Err(unexpected_status(&response));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn amz_date(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Percent-encode one path segment the way SigV4 expects
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// AWS Signature Version 4 `Authorization` header for a request without a
+/// query string. `headers` are the lower-cased headers to sign, sorted by name.
+pub(crate) fn sigv4_authorization(
+    credentials: &S3Credentials,
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    at: DateTime<Utc>,
+) -> String {
+    let date = at.format("%Y%m%d").to_string();
+    let scope = format!("{}/{}/s3/aws4_request", date, credentials.region);
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method, path, canonical_headers, signed_headers, payload_hash
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date(at),
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = ["s3", "aws4_request"].iter().fold(
+        hmac_sha256(
+            hmac_sha256(format!("AWS4{}", credentials.secret_key).as_bytes(), &date).as_slice(),
+            &credentials.region,
+        ),
+        |key, part| hmac_sha256(&key, part),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key, scope, signed_headers, signature
+    )
+}
@@ -12,6 +12,7 @@ use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use crate::delivery::metrics::DeliveryMetrics;
+use crate::money::Money;
 
 #[derive(Clone)]
 pub struct MetricsCollector {
@@ -227,9 +228,9 @@ impl MetricsCollector {
         self.active_orders.dec();
     }
 
-    pub fn record_payment_processed(&self, amount: f64) {
+    pub fn record_payment_processed(&self, amount: Money) {
         self.payments_processed_total.inc();
-        self.payment_amount_total.inc_by(amount);
+        self.payment_amount_total.inc_by(amount.to_rupees_f64());
     }
 
     pub fn record_payment_failed(&self) {
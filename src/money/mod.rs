@@ -0,0 +1,371 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// Currencies the platform settles in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum Currency {
+    #[default]
+    INR,
+}
+
+impl Currency {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::INR => "INR",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::INR => "₹",
+        }
+    }
+
+    /// Number of minor units (paise) per major unit (rupee), as a power of ten
+    pub fn minor_unit_scale(&self) -> u32 {
+        match self {
+            Currency::INR => 2,
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "INR" => Ok(Currency::INR),
+            other => Err(MoneyError::UnsupportedCurrency(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MoneyError {
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Amount has more than two decimal places: {0}")]
+    TooPrecise(String),
+    #[error("Amount out of range: {0}")]
+    OutOfRange(String),
+    #[error("Unsupported currency: {0}")]
+    UnsupportedCurrency(String),
+}
+
+impl From<MoneyError> for crate::error::AppError {
+    fn from(err: MoneyError) -> Self {
+        crate::error::AppError::ValidationError(err.to_string())
+    }
+}
+
+/// An exact monetary amount stored as an integer number of paise.
+///
+/// All order, payment and fee arithmetic goes through this type so that
+/// totals never drift the way repeated `f64` additions do. Percentages
+/// (GST, gateway fees, commissions) are rounded half-to-even to the paisa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Money {
+    paise: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub const ZERO: Money = Money {
+        paise: 0,
+        currency: Currency::INR,
+    };
+
+    pub const fn from_paise(paise: i64) -> Self {
+        Self {
+            paise,
+            currency: Currency::INR,
+        }
+    }
+
+    pub const fn from_rupees(rupees: i64) -> Self {
+        Self::from_paise(rupees * 100)
+    }
+
+    pub const fn new(paise: i64, currency: Currency) -> Self {
+        Self { paise, currency }
+    }
+
+    /// Convert a decimal rupee amount, rejecting fractions of a paisa
+    pub fn from_decimal(amount: Decimal) -> Result<Self, MoneyError> {
+        let scaled = amount * Decimal::ONE_HUNDRED;
+        if scaled.fract() != Decimal::ZERO {
+            return Err(MoneyError::TooPrecise(amount.to_string()));
+        }
+        i64::try_from(scaled)
+            .map(Self::from_paise)
+            .map_err(|_| MoneyError::OutOfRange(amount.to_string()))
+    }
+
+    /// Convert a floating point rupee amount, rounding to the nearest paisa.
+    ///
+    /// Only meant for boundaries that still hand us floats (JSON numbers,
+    /// legacy configuration); never use it for arithmetic.
+    pub fn from_rupees_f64(rupees: f64) -> Result<Self, MoneyError> {
+        if !rupees.is_finite() {
+            return Err(MoneyError::InvalidAmount(rupees.to_string()));
+        }
+        let paise = (rupees * 100.0).round();
+        if paise.abs() >= i64::MAX as f64 {
+            return Err(MoneyError::OutOfRange(rupees.to_string()));
+        }
+        Ok(Self::from_paise(paise as i64))
+    }
+
+    pub fn paise(&self) -> i64 {
+        self.paise
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn to_decimal(&self) -> Decimal {
+        Decimal::new(self.paise, self.currency.minor_unit_scale())
+    }
+
+    /// Rupee value as a float, for metrics and display only
+    pub fn to_rupees_f64(&self) -> f64 {
+        self.paise as f64 / 100.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.paise == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.paise < 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.paise > 0
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.paise.abs(), self.currency)
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.paise
+            .checked_add(other.paise)
+            .map(|paise| Self::new(paise, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.paise
+            .checked_sub(other.paise)
+            .map(|paise| Self::new(paise, self.currency))
+    }
+
+    /// Subtract, flooring the result at zero
+    pub fn saturating_sub(self, other: Money) -> Money {
+        Self::new((self.paise - other.paise).max(0), self.currency)
+    }
+
+    /// `rate_percent` percent of this amount, rounded half-to-even to the paisa
+    pub fn percentage(&self, rate_percent: f64) -> Money {
+        let rate = Decimal::from_f64_retain(rate_percent)
+            .map(|r| r.round_dp(6))
+            .unwrap_or(Decimal::ZERO);
+        self.percentage_decimal(rate)
+    }
+
+    /// Like [`Money::percentage`] but with an exact decimal rate
+    pub fn percentage_decimal(&self, rate_percent: Decimal) -> Money {
+        let value = self.to_decimal() * rate_percent / Decimal::ONE_HUNDRED;
+        let rounded = value.round_dp_with_strategy(2, RoundingStrategy::MidpointNearestEven);
+        let paise = (rounded * Decimal::ONE_HUNDRED)
+            .trunc()
+            .try_into()
+            .unwrap_or(i64::MAX);
+        Self::new(paise, self.currency)
+    }
+
+    /// Split into `parts` amounts that differ by at most one paisa and sum to `self`
+    pub fn split(&self, parts: usize) -> Vec<Money> {
+        if parts == 0 {
+            return Vec::new();
+        }
+        let n = parts as i64;
+        let base = self.paise.div_euclid(n);
+        let remainder = self.paise.rem_euclid(n);
+        (0..n)
+            .map(|i| Self::new(base + if i < remainder { 1 } else { 0 }, self.currency))
+            .collect()
+    }
+}
+
+impl Default for Money {
+    fn default() -> Self {
+        Money::ZERO
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.paise < 0 { "-" } else { "" };
+        let abs = self.paise.unsigned_abs();
+        write!(
+            f,
+            "{}{}{}.{:02}",
+            sign,
+            self.currency.symbol(),
+            abs / 100,
+            abs % 100
+        )
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim().trim_start_matches('₹').replace(',', "");
+        let amount = Decimal::from_str(&trimmed)
+            .map_err(|_| MoneyError::InvalidAmount(s.to_string()))?;
+        Money::from_decimal(amount)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        debug_assert_eq!(self.currency, other.currency);
+        Money::new(self.paise + other.paise, self.currency)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        debug_assert_eq!(self.currency, other.currency);
+        Money::new(self.paise - other.paise, self.currency)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::new(-self.paise, self.currency)
+    }
+}
+
+impl Mul<u32> for Money {
+    type Output = Money;
+
+    fn mul(self, quantity: u32) -> Money {
+        Money::new(self.paise * quantity as i64, self.currency)
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, quantity: i64) -> Money {
+        Money::new(self.paise * quantity, self.currency)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+// Amounts go over the wire as rupee numbers (`123.45`) so existing clients
+// keep working; strings are accepted on input for clients that want to avoid
+// float parsing altogether.
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_rupees_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Integer(i64),
+            Float(f64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Integer(rupees) => rupees
+                .checked_mul(100)
+                .map(Money::from_paise)
+                .ok_or_else(|| serde::de::Error::custom(MoneyError::OutOfRange(rupees.to_string()))),
+            Repr::Float(rupees) => Money::from_rupees_f64(rupees).map_err(serde::de::Error::custom),
+            Repr::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+// Stored as NUMERIC / DECIMAL(10,2) in Postgres
+impl Type<Postgres> for Money {
+    fn type_info() -> PgTypeInfo {
+        <Decimal as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Decimal as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for Money {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <Decimal as Encode<Postgres>>::encode_by_ref(&self.to_decimal(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Money {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let amount = <Decimal as Decode<Postgres>>::decode(value)?;
+        let rounded = amount.round_dp_with_strategy(2, RoundingStrategy::MidpointNearestEven);
+        Ok(Money::from_decimal(rounded)?)
+    }
+}
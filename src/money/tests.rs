@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use crate::india::payments::PaymentGatewayFees;
+    use crate::india::GSTRate;
+    use crate::money::{Currency, Money};
+    use crate::orders::models::OrderItem;
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    fn order_item(unit_paise: i64, quantity: u32) -> OrderItem {
+        let unit_price = Money::from_paise(unit_paise);
+        OrderItem {
+            id: Uuid::new_v4(),
+            menu_item_id: Uuid::new_v4(),
+            name: "Paneer Tikka".to_string(),
+            quantity,
+            unit_price,
+            total_price: unit_price * quantity,
+            customizations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_money_parse_and_display() {
+        let amount: Money = "299.50".parse().unwrap();
+        assert_eq!(amount.paise(), 29950);
+        assert_eq!(amount.currency(), Currency::INR);
+        assert_eq!(amount.to_string(), "₹299.50");
+        assert_eq!("₹1,299".parse::<Money>().unwrap(), Money::from_rupees(1299));
+        assert_eq!(Money::from_paise(-5).to_string(), "-₹0.05");
+
+        assert!("12.345".parse::<Money>().is_err());
+        assert!("abc".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_money_serde_round_trip() {
+        let amount = Money::from_paise(2599);
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "25.99");
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), amount);
+        assert_eq!(serde_json::from_str::<Money>("\"25.99\"").unwrap(), amount);
+        assert_eq!(serde_json::from_str::<Money>("299").unwrap(), Money::from_rupees(299));
+    }
+
+    #[test]
+    fn test_gst_uses_bankers_rounding() {
+        let gst = GSTRate {
+            category: "Restaurant Service".to_string(),
+            rate: 5.0,
+            description: String::new(),
+        };
+        // 5% of ₹0.50 is 2.5 paise, which rounds to the even 2 paise
+        assert_eq!(gst.calculate_gst(Money::from_paise(50)).paise(), 2);
+        // 5% of ₹0.70 is 3.5 paise, which rounds to the even 4 paise
+        assert_eq!(gst.calculate_gst(Money::from_paise(70)).paise(), 4);
+        assert_eq!(gst.calculate_gst(Money::from_rupees(200)), Money::from_rupees(10));
+    }
+
+    #[test]
+    fn test_gateway_fee_includes_gst() {
+        let fees = PaymentGatewayFees::get_standard_fees();
+        let credit_card = fees
+            .iter()
+            .find(|f| matches!(f.payment_method, crate::payments::models::PaymentMethod::CreditCard))
+            .unwrap();
+        // 2% of ₹500 + ₹3 = ₹13, plus 18% GST = ₹15.34
+        assert_eq!(credit_card.calculate_fee(Money::from_rupees(500)).paise(), 1534);
+
+        let upi = fees
+            .iter()
+            .find(|f| matches!(f.payment_method, crate::payments::models::PaymentMethod::UPI))
+            .unwrap();
+        assert!(upi.calculate_fee(Money::from_rupees(500)).is_zero());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_order_total_is_sum_of_line_items(
+            lines in prop::collection::vec((1i64..=500_000, 1u32..=20), 1..15)
+        ) {
+            let items: Vec<OrderItem> = lines
+                .iter()
+                .map(|(paise, quantity)| order_item(*paise, *quantity))
+                .collect();
+            let total: Money = items.iter().map(|item| item.total_price).sum();
+            let expected: i64 = lines.iter().map(|(paise, quantity)| paise * *quantity as i64).sum();
+            prop_assert_eq!(total.paise(), expected);
+        }
+
+        #[test]
+        fn prop_bill_total_equals_sum_of_parts(
+            subtotal in 0i64..=10_000_000,
+            delivery_fee in 0i64..=10_000,
+            rate in prop::sample::select(vec![0.0, 5.0, 12.0, 18.0])
+        ) {
+            let gst = GSTRate {
+                category: "Restaurant Service".to_string(),
+                rate,
+                description: String::new(),
+            };
+            let subtotal = Money::from_paise(subtotal);
+            let delivery_fee = Money::from_paise(delivery_fee);
+            let tax = gst.calculate_gst(subtotal);
+            let total = subtotal + tax + delivery_fee;
+
+            prop_assert_eq!(total - tax - delivery_fee, subtotal);
+            prop_assert_eq!(total.paise(), subtotal.paise() + tax.paise() + delivery_fee.paise());
+        }
+
+        #[test]
+        fn prop_split_preserves_total(paise in -10_000_000i64..=10_000_000, parts in 1usize..=12) {
+            let amount = Money::from_paise(paise);
+            let shares = amount.split(parts);
+            prop_assert_eq!(shares.len(), parts);
+            prop_assert_eq!(shares.iter().sum::<Money>(), amount);
+            let min = shares.iter().map(|m| m.paise()).min().unwrap();
+            let max = shares.iter().map(|m| m.paise()).max().unwrap();
+            prop_assert!(max - min <= 1);
+        }
+
+        #[test]
+        fn prop_decimal_and_json_round_trip(paise in -1_000_000_000i64..=1_000_000_000) {
+            let amount = Money::from_paise(paise);
+            prop_assert_eq!(Money::from_decimal(amount.to_decimal()).unwrap(), amount);
+            let json = serde_json::to_string(&amount).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), amount);
+        }
+
+        #[test]
+        fn prop_percentage_is_within_half_paisa(paise in 0i64..=100_000_000, bps in 0u32..=10_000) {
+            let amount = Money::from_paise(paise);
+            let rate = bps as f64 / 100.0;
+            let exact = paise as i128 * bps as i128; // in 1/10_000ths of a paisa
+            let got = amount.percentage(rate).paise() as i128 * 10_000;
+            prop_assert!((got - exact).abs() <= 5_000);
+        }
+    }
+}
@@ -2,8 +2,13 @@ use crate::auth::models::User;
 use crate::error::Result;
 use crate::notifications::fcm::FCMService;
 use crate::orders::models::{
-    CreateOrderRequest, Order, OrderItem, OrderResponse, OrderStatus, UpdateOrderStatusRequest,
+    CreateOrderRequest, Order, OrderResponse, OrderStatus, UpdateOrderStatusRequest,
 };
+use crate::orders::prep_time::{PrepTimeReport, PrepTimeService};
+use crate::orders::service::OrderService;
+use crate::restaurants::busy::{publish_busy_change, BusyModeService};
+use crate::restaurants::inventory::publish_stock_changes;
+use crate::routes::AppState;
 use axum::{
     extract::{Path, State},
     Extension, Json,
@@ -15,56 +20,25 @@ use uuid::Uuid;
 pub type SharedFCMService = Arc<Mutex<FCMService>>;
 
 pub async fn create_order(
-    State(fcm_service): State<SharedFCMService>,
+    State(state): State<AppState>,
     Extension(user): Extension<User>,
     Json(payload): Json<CreateOrderRequest>,
 ) -> Result<Json<OrderResponse>> {
-
-    // Create order items with pricing (in real app, fetch from database)
-    let mut total_amount = 0.0;
-    let order_items: Vec<OrderItem> = payload
-        .items
-        .into_iter()
-        .map(|item| {
-            let unit_price = 15.99; // Mock price - fetch from menu in real app
-            let total_price = unit_price * item.quantity as f64;
-            total_amount += total_price;
-
-            OrderItem {
-                id: Uuid::new_v4(),
-                menu_item_id: item.menu_item_id,
-                name: format!("Menu Item {}", item.menu_item_id), // Mock name
-                quantity: item.quantity,
-                unit_price,
-                total_price,
-                customizations: item.customizations,
-            }
-        })
-        .collect();
-
-    let now = chrono::Utc::now();
-    let order = Order {
-        id: Uuid::new_v4(),
-        customer_id: user.id,
-        restaurant_id: payload.restaurant_id,
-        delivery_person_id: None,
-        items: order_items,
-        status: OrderStatus::Placed,
-        total_amount,
-        delivery_address: payload.delivery_address,
-        restaurant_address: crate::orders::models::Address {
-            street: "123 Restaurant St".to_string(),
-            city: "Food City".to_string(),
-            state: "FC".to_string(),
-            postal_code: "12345".to_string(),
-            country: "US".to_string(),
-            latitude: Some(40.7128),
-            longitude: Some(-74.0060),
-        },
-        created_at: now,
-        updated_at: now,
-        estimated_delivery_time: Some(now + chrono::Duration::minutes(30)),
-    };
+    let fcm_service = state.fcm_service.clone();
+
+    // Price items from the menu, including any sizes and add-ons, and save the order
+    let service = OrderService::new(state.database.clone());
+    let order = service.create_order(&user, payload).await?;
+
+    // The new order may tip the kitchen into busy mode
+    match BusyModeService::new(state.database.clone())
+        .refresh(order.restaurant_id, chrono::Utc::now())
+        .await
+    {
+        Ok(Some(restaurant)) => publish_busy_change(&state.delivery_websocket_manager, &restaurant).await,
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Busy mode check failed for restaurant {}: {:?}", order.restaurant_id, e),
+    }
 
     // Send notifications (mock tokens - in real app, fetch from database)
     let customer_token = "customer_device_token";
@@ -91,54 +65,22 @@ pub async fn create_order(
 }
 
 pub async fn get_order(
+    State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<Order>> {
-
-    // Mock order retrieval - in real app, fetch from database
-    let now = chrono::Utc::now();
-    let order = Order {
-        id: order_id,
-        customer_id: user.id,
-        restaurant_id: Uuid::new_v4(),
-        delivery_person_id: Some(Uuid::new_v4()),
-        items: vec![],
-        status: OrderStatus::Preparing,
-        total_amount: 25.99,
-        delivery_address: crate::orders::models::Address {
-            street: "456 Customer Ave".to_string(),
-            city: "User City".to_string(),
-            state: "UC".to_string(),
-            postal_code: "67890".to_string(),
-            country: "US".to_string(),
-            latitude: Some(40.7589),
-            longitude: Some(-73.9851),
-        },
-        restaurant_address: crate::orders::models::Address {
-            street: "123 Restaurant St".to_string(),
-            city: "Food City".to_string(),
-            state: "FC".to_string(),
-            postal_code: "12345".to_string(),
-            country: "US".to_string(),
-            latitude: Some(40.7128),
-            longitude: Some(-74.0060),
-        },
-        created_at: now - chrono::Duration::minutes(15),
-        updated_at: now,
-        estimated_delivery_time: Some(now + chrono::Duration::minutes(15)),
-    };
+    let service = OrderService::new(state.database.clone());
+    let order = service.get_order(order_id, &user).await?;
 
     Ok(Json(order))
 }
 
 pub async fn update_order_status(
-    State(fcm_service): State<SharedFCMService>,
+    State(state): State<AppState>,
     Extension(user): Extension<User>,
     Path(order_id): Path<Uuid>,
     Json(payload): Json<UpdateOrderStatusRequest>,
 ) -> Result<Json<OrderResponse>> {
-
-    // In real app, update order in database and verify permissions
     tracing::info!(
         "Updating order {} status to {:?} by user {}",
         order_id,
@@ -146,8 +88,16 @@ pub async fn update_order_status(
         user.id
     );
 
+    let service = OrderService::new(state.database.clone());
+    let (order, stock_changes) = service.update_status(order_id, &user, payload.status).await?;
+
+    // Let menu viewers and the kitchen see confirmed or cancelled stock
+    publish_stock_changes(&state.delivery_websocket_manager, &state.cache, &stock_changes).await;
+
+    let fcm_service = state.fcm_service.clone();
+
     // Send appropriate notifications based on status
-    match payload.status {
+    match order.status {
         OrderStatus::Ready => {
             let customer_token = "customer_device_token";
             let delivery_token = "delivery_device_token";
@@ -165,121 +115,40 @@ pub async fn update_order_status(
         }
         _ => {
             // Handle other status updates with appropriate notifications
-            tracing::info!("Status updated to {:?} for order {}", payload.status, order_id);
+            tracing::info!("Status updated to {:?} for order {}", order.status, order_id);
         }
     }
 
-    // Mock updated order
-    let now = chrono::Utc::now();
-    let order = Order {
-        id: order_id,
-        customer_id: user.id,
-        restaurant_id: Uuid::new_v4(),
-        delivery_person_id: Some(Uuid::new_v4()),
-        items: vec![],
-        status: payload.status,
-        total_amount: 25.99,
-        delivery_address: crate::orders::models::Address {
-            street: "456 Customer Ave".to_string(),
-            city: "User City".to_string(),
-            state: "UC".to_string(),
-            postal_code: "67890".to_string(),
-            country: "US".to_string(),
-            latitude: Some(40.7589),
-            longitude: Some(-73.9851),
-        },
-        restaurant_address: crate::orders::models::Address {
-            street: "123 Restaurant St".to_string(),
-            city: "Food City".to_string(),
-            state: "FC".to_string(),
-            postal_code: "12345".to_string(),
-            country: "US".to_string(),
-            latitude: Some(40.7128),
-            longitude: Some(-74.0060),
-        },
-        created_at: now - chrono::Duration::minutes(20),
-        updated_at: now,
-        estimated_delivery_time: Some(now + chrono::Duration::minutes(10)),
-    };
-
     Ok(Json(OrderResponse {
         order,
         message: "Order status updated successfully".to_string(),
     }))
-}pub 
-async fn get_customer_orders(
+}
+
+pub async fn get_customer_orders(
+    State(state): State<AppState>,
     Path(customer_id): Path<Uuid>,
     Extension(user): Extension<User>,
 ) -> Result<Json<Vec<Order>>> {
     // Ensure user can only access their own orders or is authorized
-    if user.id != customer_id {
+    if user.id != customer_id && user.role != "admin" {
         return Err(crate::error::AppError::Unauthorized);
     }
 
-    // Mock customer orders - in real app, fetch from database
-    let now = chrono::Utc::now();
-    let orders = vec![
-        Order {
-            id: Uuid::new_v4(),
-            customer_id,
-            restaurant_id: Uuid::new_v4(),
-            delivery_person_id: Some(Uuid::new_v4()),
-            items: vec![],
-            status: OrderStatus::Delivered,
-            total_amount: 25.99,
-            delivery_address: crate::orders::models::Address {
-                street: "456 Customer Ave".to_string(),
-                city: "User City".to_string(),
-                state: "UC".to_string(),
-                postal_code: "67890".to_string(),
-                country: "US".to_string(),
-                latitude: Some(40.7589),
-                longitude: Some(-73.9851),
-            },
-            restaurant_address: crate::orders::models::Address {
-                street: "123 Restaurant St".to_string(),
-                city: "Food City".to_string(),
-                state: "FC".to_string(),
-                postal_code: "12345".to_string(),
-                country: "US".to_string(),
-                latitude: Some(40.7128),
-                longitude: Some(-74.0060),
-            },
-            created_at: now - chrono::Duration::hours(2),
-            updated_at: now - chrono::Duration::hours(1),
-            estimated_delivery_time: Some(now - chrono::Duration::hours(1)),
-        },
-        Order {
-            id: Uuid::new_v4(),
-            customer_id,
-            restaurant_id: Uuid::new_v4(),
-            delivery_person_id: None,
-            items: vec![],
-            status: OrderStatus::Preparing,
-            total_amount: 18.50,
-            delivery_address: crate::orders::models::Address {
-                street: "456 Customer Ave".to_string(),
-                city: "User City".to_string(),
-                state: "UC".to_string(),
-                postal_code: "67890".to_string(),
-                country: "US".to_string(),
-                latitude: Some(40.7589),
-                longitude: Some(-73.9851),
-            },
-            restaurant_address: crate::orders::models::Address {
-                street: "789 Food Plaza".to_string(),
-                city: "Food City".to_string(),
-                state: "FC".to_string(),
-                postal_code: "12345".to_string(),
-                country: "US".to_string(),
-                latitude: Some(40.7505),
-                longitude: Some(-73.9934),
-            },
-            created_at: now - chrono::Duration::minutes(30),
-            updated_at: now - chrono::Duration::minutes(15),
-            estimated_delivery_time: Some(now + chrono::Duration::minutes(20)),
-        },
-    ];
+    let service = OrderService::new(state.database.clone());
+    let orders = service.get_customer_orders(customer_id).await?;
 
     Ok(Json(orders))
-}
\ No newline at end of file
+}
+
+pub async fn get_prep_time_report(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<PrepTimeReport>> {
+    let service = PrepTimeService::new(state.database.clone());
+    
+    let report = service.report(restaurant_id, user.id).await?;
+    
+    Ok(Json(report))
+}
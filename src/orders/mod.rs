@@ -1,5 +1,12 @@
 pub mod models;
 pub mod handlers;
+pub mod prep_time;
+pub mod service;
 
 pub use models::*;
-pub use handlers::*;
\ No newline at end of file
+pub use handlers::*;
+pub use prep_time::*;
+pub use service::*;
+
+#[cfg(test)]
+mod tests;
@@ -1,3 +1,7 @@
+use crate::error::AppError;
+use crate::money::Money;
+use crate::promotions::pricing::OrderBill;
+use crate::restaurants::modifiers::{AppliedModifier, ModifierSelection};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -9,12 +13,21 @@ pub struct Order {
     pub delivery_person_id: Option<Uuid>,
     pub items: Vec<OrderItem>,
     pub status: OrderStatus,
-    pub total_amount: f64,
+    pub total_amount: Money,
+    /// How `total_amount` was reached; absent on orders placed before promotions
+    #[serde(default)]
+    pub bill: Option<OrderBill>,
+    /// Published menu version the order was priced against
+    #[serde(default)]
+    pub menu_version_id: Option<Uuid>,
     pub delivery_address: Address,
     pub restaurant_address: Address,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub estimated_delivery_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the kitchen is expected to finish; riders are sent to match
+    #[serde(default)]
+    pub estimated_ready_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,12 +36,13 @@ pub struct OrderItem {
     pub menu_item_id: Uuid,
     pub name: String,
     pub quantity: u32,
-    pub unit_price: f64,
-    pub total_price: f64,
-    pub customizations: Option<serde_json::Value>,
+    pub unit_price: Money,
+    pub total_price: Money,
+    #[serde(default)]
+    pub customizations: Vec<AppliedModifier>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
     Placed,
     Confirmed,
@@ -40,6 +54,71 @@ pub enum OrderStatus {
     Cancelled,
 }
 
+impl OrderStatus {
+    /// Value stored in `orders.status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Placed => "placed",
+            OrderStatus::Confirmed => "confirmed",
+            OrderStatus::Preparing => "preparing",
+            OrderStatus::Ready => "ready",
+            OrderStatus::PickedUp => "picked_up",
+            OrderStatus::OnTheWay => "out_for_delivery",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Allowed moves in the order lifecycle. Orders can be cancelled until
+    /// they leave the restaurant.
+    pub fn can_transition_to(&self, next: &OrderStatus) -> bool {
+        matches!(
+            (self, next),
+            (OrderStatus::Placed, OrderStatus::Confirmed)
+                | (OrderStatus::Confirmed, OrderStatus::Preparing)
+                | (OrderStatus::Preparing, OrderStatus::Ready)
+                | (OrderStatus::Ready, OrderStatus::PickedUp)
+                | (OrderStatus::PickedUp, OrderStatus::OnTheWay)
+                | (OrderStatus::PickedUp, OrderStatus::Delivered)
+                | (OrderStatus::OnTheWay, OrderStatus::Delivered)
+                | (OrderStatus::Placed, OrderStatus::Cancelled)
+                | (OrderStatus::Confirmed, OrderStatus::Cancelled)
+                | (OrderStatus::Preparing, OrderStatus::Cancelled)
+                | (OrderStatus::Ready, OrderStatus::Cancelled)
+        )
+    }
+
+    pub fn transition_to(&self, next: OrderStatus) -> Result<OrderStatus, AppError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(AppError::InvalidStatusTransition(format!(
+                "order cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::str::FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "placed" => Ok(OrderStatus::Placed),
+            "confirmed" => Ok(OrderStatus::Confirmed),
+            "preparing" => Ok(OrderStatus::Preparing),
+            "ready" => Ok(OrderStatus::Ready),
+            "picked_up" => Ok(OrderStatus::PickedUp),
+            "out_for_delivery" => Ok(OrderStatus::OnTheWay),
+            "delivered" => Ok(OrderStatus::Delivered),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            _ => Err(format!("Invalid order status: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Address {
     pub street: String,
@@ -56,13 +135,20 @@ pub struct CreateOrderRequest {
     pub restaurant_id: Uuid,
     pub items: Vec<CreateOrderItem>,
     pub delivery_address: Address,
+    /// Platform coupon to apply on top of the restaurant's own offers
+    #[serde(default)]
+    pub coupon_code: Option<String>,
+    /// Order even though items clash with the customer's dietary profile
+    #[serde(default)]
+    pub acknowledge_dietary_conflicts: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateOrderItem {
     pub menu_item_id: Uuid,
     pub quantity: u32,
-    pub customizations: Option<serde_json::Value>,
+    #[serde(default)]
+    pub customizations: Vec<ModifierSelection>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,522 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::india::config::ISTConfig;
+use crate::orders::models::OrderItem;
+use crate::restaurants::busy::{active_busy_mode, BusyMode};
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A restaurant needs this many timed orders before its learned model is used
+pub const MIN_TRAINING_SAMPLES: usize = 20;
+/// How far back order history is used for training
+pub const TRAINING_WINDOW_DAYS: i64 = 28;
+/// Prep time assumed for an item that has since left the menu
+pub const DEFAULT_ITEM_PREP_MINUTES: i32 = 15;
+/// Time a rider needs to reach the restaurant once dispatched
+pub const RIDER_APPROACH_MINUTES: i64 = 10;
+/// Time to hand the order over at the door
+pub const HANDOVER_MINUTES: i64 = 5;
+/// Average rider speed in Indian city traffic
+pub const RIDER_SPEED_KMPH: f64 = 20.0;
+
+const MIN_PREP_MINUTES: f64 = 3.0;
+const MAX_PREP_MINUTES: f64 = 120.0;
+/// Confirmed-to-ready times outside this range are kitchens forgetting to
+/// mark orders ready, not real prep times
+const MAX_SAMPLE_MINUTES: f64 = 180.0;
+/// Weight of the static prep times against the history; roughly the number
+/// of orders it takes to move away from them
+const PRIOR_STRENGTH: f64 = 10.0;
+
+/// Lunch and dinner rushes, in IST
+const PEAK_HOURS: [(u32, u32, u32, u32); 2] = [(12, 0, 15, 0), (19, 0, 22, 30)];
+
+pub fn is_peak_hour(at: DateTime<Utc>) -> bool {
+    let local = at.with_timezone(&ISTConfig::default().offset()).time();
+    PEAK_HOURS.iter().any(|&(start_h, start_m, end_h, end_m)| {
+        let start = NaiveTime::from_hms_opt(start_h, start_m, 0).unwrap();
+        let end = NaiveTime::from_hms_opt(end_h, end_m, 0).unwrap();
+        local >= start && local < end
+    })
+}
+
+/// Minutes for a rider to cover the distance and hand the order over
+pub fn ride_minutes(distance_km: f64) -> i64 {
+    (distance_km / RIDER_SPEED_KMPH * 60.0).ceil() as i64 + HANDOVER_MINUTES
+}
+
+/// What the kitchen is asked to make and how busy it is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrepFeatures {
+    /// Owner-set prep time of the slowest item in the order
+    pub slowest_item_minutes: f64,
+    /// Units beyond the first
+    pub extra_items: f64,
+    /// Orders confirmed but not yet ready when this one was confirmed
+    pub kitchen_load: f64,
+    pub is_peak: bool,
+}
+
+impl PrepFeatures {
+    pub fn new(items: &[OrderItem], prep_minutes: &HashMap<Uuid, i32>, kitchen_load: i64, at: DateTime<Utc>) -> Self {
+        let slowest = items
+            .iter()
+            .map(|item| prep_minutes.get(&item.menu_item_id).copied().unwrap_or(DEFAULT_ITEM_PREP_MINUTES))
+            .max()
+            .unwrap_or(DEFAULT_ITEM_PREP_MINUTES);
+        let units: u32 = items.iter().map(|item| item.quantity).sum();
+
+        Self {
+            slowest_item_minutes: slowest as f64,
+            extra_items: units.saturating_sub(1) as f64,
+            kitchen_load: kitchen_load as f64,
+            is_peak: is_peak_hour(at),
+        }
+    }
+
+    fn vector(&self) -> [f64; 5] {
+        [
+            1.0,
+            self.slowest_item_minutes,
+            self.extra_items,
+            self.kitchen_load,
+            if self.is_peak { 1.0 } else { 0.0 },
+        ]
+    }
+}
+
+/// Minutes added by each feature
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PrepTimeWeights {
+    pub base_minutes: f64,
+    /// Multiplier on the slowest item's owner-set prep time
+    pub slowest_item_factor: f64,
+    pub per_extra_item_minutes: f64,
+    pub per_order_in_kitchen_minutes: f64,
+    pub peak_hour_minutes: f64,
+}
+
+impl PrepTimeWeights {
+    /// Trust the owner: the order takes as long as its slowest item
+    pub const STATIC: Self = Self {
+        base_minutes: 0.0,
+        slowest_item_factor: 1.0,
+        per_extra_item_minutes: 0.0,
+        per_order_in_kitchen_minutes: 0.0,
+        peak_hour_minutes: 0.0,
+    };
+
+    fn from_vector(w: [f64; 5]) -> Self {
+        Self {
+            base_minutes: w[0],
+            slowest_item_factor: w[1],
+            per_extra_item_minutes: w[2],
+            per_order_in_kitchen_minutes: w[3],
+            peak_hour_minutes: w[4],
+        }
+    }
+
+    fn vector(&self) -> [f64; 5] {
+        [
+            self.base_minutes,
+            self.slowest_item_factor,
+            self.per_extra_item_minutes,
+            self.per_order_in_kitchen_minutes,
+            self.peak_hour_minutes,
+        ]
+    }
+
+    pub fn predict(&self, features: &PrepFeatures) -> f64 {
+        let minutes: f64 = self.vector().iter().zip(features.vector()).map(|(w, x)| w * x).sum();
+        minutes.clamp(MIN_PREP_MINUTES, MAX_PREP_MINUTES)
+    }
+}
+
+/// One past order: what was asked of the kitchen and how long it took
+#[derive(Debug, Clone, Copy)]
+pub struct PrepSample {
+    pub features: PrepFeatures,
+    pub minutes: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrepTimeModel {
+    pub restaurant_id: Uuid,
+    pub weights: PrepTimeWeights,
+    pub sample_count: i32,
+    /// Average miss on the orders it was trained on
+    pub mean_absolute_error_minutes: f64,
+    pub trained_at: DateTime<Utc>,
+}
+
+/// Ridge regression pulled towards the static weights, so a restaurant with
+/// little history predicts close to its owner-set prep times. `None` when
+/// there are too few usable samples.
+pub fn fit_prep_time(samples: &[PrepSample]) -> Option<(PrepTimeWeights, f64)> {
+    let samples: Vec<&PrepSample> = samples
+        .iter()
+        .filter(|sample| sample.minutes > 0.0 && sample.minutes <= MAX_SAMPLE_MINUTES)
+        .collect();
+    if samples.len() < MIN_TRAINING_SAMPLES {
+        return None;
+    }
+
+    let prior = PrepTimeWeights::STATIC.vector();
+    let mut a = [[0.0; 5]; 5];
+    let mut b = [0.0; 5];
+    for sample in &samples {
+        let x = sample.features.vector();
+        for i in 0..5 {
+            for j in 0..5 {
+                a[i][j] += x[i] * x[j];
+            }
+            b[i] += x[i] * sample.minutes;
+        }
+    }
+    for i in 0..5 {
+        a[i][i] += PRIOR_STRENGTH;
+        b[i] += PRIOR_STRENGTH * prior[i];
+    }
+
+    let weights = PrepTimeWeights::from_vector(solve(a, b)?);
+    let error = samples
+        .iter()
+        .map(|sample| (weights.predict(&sample.features) - sample.minutes).abs())
+        .sum::<f64>()
+        / samples.len() as f64;
+
+    Some((weights, error))
+}
+
+/// Gaussian elimination with partial pivoting
+fn solve(mut a: [[f64; 5]; 5], mut b: [f64; 5]) -> Option<[f64; 5]> {
+    for col in 0..5 {
+        let pivot = (col..5).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..5 {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot_value) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; 5];
+    for row in (0..5).rev() {
+        let rest: f64 = (row + 1..5).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    x.iter().all(|value| value.is_finite()).then_some(x)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrepTimeSource {
+    /// Learned from the restaurant's order history
+    Learned,
+    /// Owner-set item prep times; not enough history yet
+    Static,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrepTimeEstimate {
+    pub minutes: i64,
+    pub ready_at: DateTime<Utc>,
+    /// When to send a rider so they arrive as the food is ready
+    pub dispatch_at: DateTime<Utc>,
+    pub kitchen_load: i64,
+    pub source: PrepTimeSource,
+    /// Added because the restaurant is in busy mode
+    pub busy_extra_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrepTimeReport {
+    pub restaurant_id: Uuid,
+    pub model: Option<PrepTimeModel>,
+    pub kitchen_load: i64,
+}
+
+pub struct PrepTimeService {
+    db: Database,
+}
+
+impl PrepTimeService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// How long the kitchen will take over these items if it starts at `start`.
+    /// Reads through `conn` so callers holding a transaction can estimate on it.
+    pub async fn estimate(
+        conn: &mut PgConnection,
+        restaurant_id: Uuid,
+        items: &[OrderItem],
+        start: DateTime<Utc>,
+    ) -> Result<PrepTimeEstimate> {
+        let item_ids: Vec<Uuid> = items.iter().map(|item| item.menu_item_id).collect();
+        let prep_minutes = Self::item_prep_minutes(&mut *conn, restaurant_id, Some(&item_ids)).await?;
+        let kitchen_load = Self::kitchen_load(&mut *conn, restaurant_id).await?;
+        let model = Self::model(&mut *conn, restaurant_id).await?;
+
+        let features = PrepFeatures::new(items, &prep_minutes, kitchen_load, start);
+        let (weights, source) = match &model {
+            Some(model) => (model.weights, PrepTimeSource::Learned),
+            None => (PrepTimeWeights::STATIC, PrepTimeSource::Static),
+        };
+        let busy_mode = sqlx::query_scalar::<_, Option<BusyMode>>("SELECT busy_mode FROM restaurants WHERE id = $1")
+            .bind(restaurant_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .flatten();
+        let busy_extra_minutes = active_busy_mode(&busy_mode, start)
+            .map_or(0, |mode| mode.extra_prep_minutes as i64);
+        let minutes = weights.predict(&features).round() as i64 + busy_extra_minutes;
+        let ready_at = start + Duration::minutes(minutes);
+
+        Ok(PrepTimeEstimate {
+            minutes,
+            ready_at,
+            dispatch_at: (ready_at - Duration::minutes(RIDER_APPROACH_MINUTES)).max(start),
+            kitchen_load,
+            source,
+            busy_extra_minutes,
+        })
+    }
+
+    /// Orders the kitchen has accepted and not yet finished
+    pub async fn kitchen_load(conn: &mut PgConnection, restaurant_id: Uuid) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM orders WHERE restaurant_id = $1 AND status IN ('confirmed', 'preparing')"
+        )
+        .bind(restaurant_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn model(conn: &mut PgConnection, restaurant_id: Uuid) -> Result<Option<PrepTimeModel>> {
+        let row = sqlx::query(
+            "SELECT * FROM prep_time_models WHERE restaurant_id = $1"
+        )
+        .bind(restaurant_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let weights: Vec<f64> = row.get("coefficients");
+        let weights: [f64; 5] = weights
+            .try_into()
+            .map_err(|_| AppError::DatabaseError("Invalid prep time coefficients".to_string()))?;
+
+        Ok(Some(PrepTimeModel {
+            restaurant_id,
+            weights: PrepTimeWeights::from_vector(weights),
+            sample_count: row.get("sample_count"),
+            mean_absolute_error_minutes: row.get("mean_absolute_error_minutes"),
+            trained_at: row.get("trained_at"),
+        }))
+    }
+
+    pub async fn report(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<PrepTimeReport> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ViewOrders)
+            .await?;
+
+        let mut conn = self.db.pool().acquire().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(PrepTimeReport {
+            restaurant_id,
+            model: Self::model(&mut conn, restaurant_id).await?,
+            kitchen_load: Self::kitchen_load(&mut conn, restaurant_id).await?,
+        })
+    }
+
+    /// Learn a restaurant's prep times from its confirmed-to-ready history.
+    /// Without enough history any previous model is dropped, so estimates fall
+    /// back to the owner-set times.
+    pub async fn train(&self, restaurant_id: Uuid, now: DateTime<Utc>) -> Result<Option<PrepTimeModel>> {
+        let rows = sqlx::query(
+            r#"
+            WITH timings AS (
+                SELECT o.id, o.items,
+                       MIN(h.timestamp) FILTER (WHERE h.status = 'confirmed') AS confirmed_at,
+                       MIN(h.timestamp) FILTER (WHERE h.status = 'ready') AS ready_at
+                FROM orders o
+                JOIN order_status_history h ON h.order_id = o.id
+                WHERE o.restaurant_id = $1 AND o.created_at >= $2
+                GROUP BY o.id
+            )
+            SELECT t.items, t.confirmed_at, t.ready_at,
+                   (
+                       SELECT COUNT(*) FROM timings other
+                       WHERE other.id <> t.id
+                         AND other.confirmed_at <= t.confirmed_at
+                         AND other.ready_at > t.confirmed_at
+                   ) AS kitchen_load
+            FROM timings t
+            WHERE t.confirmed_at IS NOT NULL AND t.ready_at > t.confirmed_at
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(now - Duration::days(TRAINING_WINDOW_DAYS))
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut conn = self.db.pool().acquire().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let prep_minutes = Self::item_prep_minutes(&mut conn, restaurant_id, None).await?;
+        let mut samples = Vec::with_capacity(rows.len());
+        for row in &rows {
+            // Orders with unreadable items are left out rather than failing training
+            let Ok(items) = serde_json::from_value::<Vec<OrderItem>>(row.get("items")) else {
+                continue;
+            };
+            let confirmed_at: DateTime<Utc> = row.get("confirmed_at");
+            let ready_at: DateTime<Utc> = row.get("ready_at");
+            samples.push(PrepSample {
+                features: PrepFeatures::new(&items, &prep_minutes, row.get("kitchen_load"), confirmed_at),
+                minutes: (ready_at - confirmed_at).num_seconds() as f64 / 60.0,
+            });
+        }
+
+        let Some((weights, error)) = fit_prep_time(&samples) else {
+            sqlx::query("DELETE FROM prep_time_models WHERE restaurant_id = $1")
+                .bind(restaurant_id)
+                .execute(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            return Ok(None);
+        };
+
+        let model = PrepTimeModel {
+            restaurant_id,
+            weights,
+            sample_count: samples.len() as i32,
+            mean_absolute_error_minutes: error,
+            trained_at: now,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO prep_time_models (restaurant_id, coefficients, sample_count, mean_absolute_error_minutes, trained_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (restaurant_id) DO UPDATE
+            SET coefficients = EXCLUDED.coefficients,
+                sample_count = EXCLUDED.sample_count,
+                mean_absolute_error_minutes = EXCLUDED.mean_absolute_error_minutes,
+                trained_at = EXCLUDED.trained_at
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(model.weights.vector().to_vec())
+        .bind(model.sample_count)
+        .bind(model.mean_absolute_error_minutes)
+        .bind(model.trained_at)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(Some(model))
+    }
+
+    /// Retrain every restaurant that has marked an order ready within the
+    /// training window, or still has a model. Returns how many have a model.
+    pub async fn train_all(&self, now: DateTime<Utc>) -> Result<usize> {
+        let restaurant_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT o.restaurant_id
+            FROM orders o
+            JOIN order_status_history h ON h.order_id = o.id
+            WHERE h.status = 'ready' AND h.timestamp >= $1
+            UNION
+            SELECT restaurant_id FROM prep_time_models
+            "#,
+        )
+        .bind(now - Duration::days(TRAINING_WINDOW_DAYS))
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut trained = 0;
+        for restaurant_id in restaurant_ids {
+            match self.train(restaurant_id, now).await {
+                Ok(Some(_)) => trained += 1,
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Prep time training failed for restaurant {}: {:?}", restaurant_id, e),
+            }
+        }
+        Ok(trained)
+    }
+
+    /// Owner-set prep times, for the given items or the whole menu
+    async fn item_prep_minutes(
+        conn: &mut PgConnection,
+        restaurant_id: Uuid,
+        item_ids: Option<&[Uuid]>,
+    ) -> Result<HashMap<Uuid, i32>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, preparation_time_minutes FROM menu_items
+            WHERE restaurant_id = $1 AND ($2::uuid[] IS NULL OR id = ANY($2))
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(item_ids)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get("preparation_time_minutes")))
+            .collect())
+    }
+}
+
+/// Periodically relearns every restaurant's prep times
+pub struct PrepTimeTrainer {
+    service: PrepTimeService,
+    interval: std::time::Duration,
+}
+
+impl PrepTimeTrainer {
+    pub fn new(db: Database, interval: std::time::Duration) -> Self {
+        Self {
+            service: PrepTimeService::new(db),
+            interval,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(trained) => tracing::info!("Trained prep time models for {} restaurants", trained),
+                    Err(e) => tracing::error!("Prep time training failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        self.service.train_all(Utc::now()).await
+    }
+}
@@ -0,0 +1,493 @@
+use crate::auth::models::User;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::india::config::IndiaConfig;
+use crate::money::Money;
+use crate::orders::models::{Address, CreateOrderItem, CreateOrderRequest, Order, OrderItem, OrderStatus};
+use crate::orders::prep_time::{ride_minutes, PrepTimeService};
+use crate::promotions::pricing::{compute_bill, BillLine};
+use crate::promotions::service::PromotionService;
+use crate::restaurants::availability::is_orderable_at;
+use crate::restaurants::busy::BusyModeService;
+use crate::restaurants::dietary::{describe_warnings, DietaryProfileService};
+use crate::restaurants::inventory::{InventoryService, StockChange};
+use crate::restaurants::menu_versions::MenuVersionService;
+use crate::restaurants::models::MenuItem;
+use crate::restaurants::modifiers::price_selections;
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::serviceability::{delivery_fee_for_distance, GeoPoint};
+use crate::restaurants::staff::{Permission, StaffRole};
+use chrono::Utc;
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+fn order_from_row(row: &PgRow) -> Result<Order> {
+    let status: String = row.get("status");
+    let json_error = |e: serde_json::Error| AppError::DatabaseError(format!("Invalid order data: {}", e));
+
+    Ok(Order {
+        id: row.get("id"),
+        customer_id: row.get("customer_id"),
+        restaurant_id: row.get("restaurant_id"),
+        delivery_person_id: row.get("delivery_person_id"),
+        items: serde_json::from_value(row.get("items")).map_err(json_error)?,
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        total_amount: row.get("total_amount"),
+        bill: row
+            .get::<Option<serde_json::Value>, _>("bill")
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(json_error)?,
+        menu_version_id: row.get("menu_version_id"),
+        delivery_address: serde_json::from_value(row.get("delivery_address")).map_err(json_error)?,
+        restaurant_address: serde_json::from_value(row.get("restaurant_address")).map_err(json_error)?,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        estimated_delivery_time: row.get("estimated_delivery_time"),
+        estimated_ready_at: row.get("estimated_ready_at"),
+    })
+}
+
+fn staff_role_from_row(row: &PgRow) -> Result<Option<StaffRole>> {
+    row.get::<Option<String>, _>("staff_role")
+        .map(|role| role.parse().map_err(AppError::DatabaseError))
+        .transpose()
+}
+
+pub struct OrderService {
+    db: Database,
+}
+
+impl OrderService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Price the requested items from the restaurant's current menu, validating
+    /// each item's customizations against its modifier groups. Also returns
+    /// the lines as promotions see them, with each item's category, and the
+    /// published menu version the prices came from.
+    pub async fn price_items(
+        &self,
+        restaurant_id: Uuid,
+        items: Vec<CreateOrderItem>,
+    ) -> Result<(Vec<OrderItem>, Vec<BillLine>, Option<Uuid>)> {
+        if items.is_empty() {
+            return Err(AppError::ValidationError("Order must contain at least one item".to_string()));
+        }
+
+        // Publishing rewrites the live menu and the published version together,
+        // so one snapshot sees the prices and the version they belong to
+        let mut snapshot = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *snapshot)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let menu_version_id = MenuVersionService::published_version_id(&mut *snapshot, restaurant_id).await?;
+        let item_ids: Vec<Uuid> = items.iter().map(|item| item.menu_item_id).collect();
+        let menu_items = sqlx::query_as::<_, MenuItem>(
+            "SELECT * FROM menu_items WHERE restaurant_id = $1 AND id = ANY($2)"
+        )
+        .bind(restaurant_id)
+        .bind(&item_ids)
+        .fetch_all(&mut *snapshot)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let mut modifier_groups = RestaurantService::load_modifier_groups(&mut snapshot, &item_ids).await?;
+
+        snapshot.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let category_availability = RestaurantService::new(self.db.clone())
+            .get_category_availability(restaurant_id)
+            .await?;
+        let now = Utc::now();
+
+        let mut order_items = Vec::with_capacity(items.len());
+        let mut bill_lines = Vec::with_capacity(items.len());
+
+        for item in items {
+            if item.quantity == 0 {
+                return Err(AppError::ValidationError("Item quantity must be at least 1".to_string()));
+            }
+
+            let menu_item = menu_items
+                .iter()
+                .find(|menu_item| menu_item.id == item.menu_item_id)
+                .ok_or_else(|| AppError::ValidationError(format!(
+                    "Menu item {} is not on this restaurant's menu",
+                    item.menu_item_id
+                )))?;
+            if !menu_item.is_available {
+                return Err(AppError::ValidationError(format!("{} is currently unavailable", menu_item.name)));
+            }
+            if !is_orderable_at(
+                menu_item.availability.as_ref(),
+                category_availability.get(&menu_item.category),
+                now,
+            ) {
+                return Err(AppError::ValidationError(format!(
+                    "{} is not available at this time",
+                    menu_item.name
+                )));
+            }
+
+            let groups = modifier_groups.entry(menu_item.id).or_default();
+            let priced = price_selections(menu_item.price, groups, &item.customizations)?;
+            let total_price = priced.unit_price * item.quantity;
+            bill_lines.push(BillLine {
+                menu_item_id: menu_item.id,
+                category: menu_item.category.clone(),
+                quantity: item.quantity,
+                unit_price: priced.unit_price,
+            });
+
+            order_items.push(OrderItem {
+                id: Uuid::new_v4(),
+                menu_item_id: menu_item.id,
+                name: menu_item.name.clone(),
+                quantity: item.quantity,
+                unit_price: priced.unit_price,
+                total_price,
+                customizations: priced.modifiers,
+            });
+        }
+
+        Ok((order_items, bill_lines, menu_version_id))
+    }
+
+    pub async fn create_order(&self, customer: &User, request: CreateOrderRequest) -> Result<Order> {
+        let restaurant = RestaurantService::new(self.db.clone())
+            .get_restaurant(request.restaurant_id)
+            .await?;
+        if !restaurant.is_accepting_orders {
+            return Err(AppError::ValidationError(format!(
+                "{} is not accepting orders right now",
+                restaurant.name
+            )));
+        }
+
+        // Restaurants on the map only deliver inside their area and charge by
+        // distance; the rest keep their flat fee
+        let restaurant_location = restaurant.latitude.zip(restaurant.longitude).map(|(lat, lng)| GeoPoint::new(lat, lng));
+        let customer_location = request
+            .delivery_address
+            .latitude
+            .zip(request.delivery_address.longitude)
+            .map(|(lat, lng)| GeoPoint::new(lat, lng));
+        let distance_km = match (restaurant_location, customer_location) {
+            (Some(from), Some(to)) => {
+                if !restaurant.delivery_area.covers(from, to) {
+                    return Err(AppError::ValidationError(format!(
+                        "{} does not deliver to this address",
+                        restaurant.name
+                    )));
+                }
+                Some(from.distance_km(&to))
+            }
+            (Some(_), None) => {
+                return Err(AppError::ValidationError(
+                    "Delivery address needs a latitude and longitude".to_string(),
+                ));
+            }
+            (None, _) => None,
+        };
+        let india = IndiaConfig::default();
+        let delivery_fee = match distance_km {
+            Some(distance_km) => delivery_fee_for_distance(
+                restaurant.delivery_fee,
+                distance_km,
+                india.delivery_fee_included_km,
+                india.delivery_fee_per_extra_km,
+            ),
+            None => restaurant.delivery_fee,
+        };
+
+        let (items, bill_lines, menu_version_id) = self.price_items(restaurant.id, request.items).await?;
+        if !request.acknowledge_dietary_conflicts {
+            let item_ids: Vec<Uuid> = items.iter().map(|item| item.menu_item_id).collect();
+            let warnings = DietaryProfileService::new(self.db.clone())
+                .check_items(customer.id, restaurant.id, &item_ids)
+                .await?;
+            if !warnings.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "{}. Set acknowledge_dietary_conflicts to order anyway",
+                    describe_warnings(&warnings)
+                )));
+            }
+        }
+        let items_total: Money = items.iter().map(|item| item.total_price).sum();
+        if items_total < restaurant.minimum_order {
+            return Err(AppError::ValidationError(format!(
+                "Minimum order at {} is {}",
+                restaurant.name, restaurant.minimum_order
+            )));
+        }
+
+        let restaurant_address = Address {
+            street: restaurant.address.clone(),
+            city: restaurant.city.clone(),
+            state: restaurant.state.clone(),
+            postal_code: restaurant.postal_code.clone(),
+            country: restaurant.country.clone(),
+            latitude: restaurant.latitude,
+            longitude: restaurant.longitude,
+        };
+
+        let now = Utc::now();
+        let promotion_service = PromotionService::new(self.db.clone());
+        let promotions = promotion_service
+            .active_promotions(&[restaurant.id], now)
+            .await?
+            .remove(&restaurant.id)
+            .unwrap_or_default();
+        let coupon = match request.coupon_code.as_deref().map(str::trim).filter(|code| !code.is_empty()) {
+            Some(code) => Some(promotion_service.find_coupon(code, restaurant.id, now).await?),
+            None => None,
+        };
+        let bill = compute_bill(&bill_lines, &promotions, coupon.as_ref(), delivery_fee, now);
+        // A coupon that did not end up applying is not recorded against the order
+        let applied_coupon = coupon.filter(|_| bill.coupon_discount.is_positive());
+
+        // The kitchen's predicted prep time, then the ride when both ends are
+        // on the map; otherwise the restaurant's advertised delivery time
+        let prep = {
+            let mut conn = self.db.pool().acquire().await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            PrepTimeService::estimate(&mut conn, restaurant.id, &items, now).await?
+        };
+        let estimated_delivery_time = match distance_km {
+            Some(distance_km) => prep.ready_at + chrono::Duration::minutes(ride_minutes(distance_km)),
+            None => (now + chrono::Duration::minutes(restaurant.delivery_time_minutes as i64))
+                .max(prep.ready_at + chrono::Duration::minutes(ride_minutes(0.0))),
+        };
+
+        let order_id = Uuid::new_v4();
+        let order_number = format!(
+            "ORD-{}-{}",
+            now.format("%Y%m%d"),
+            &order_id.simple().to_string()[..8].to_uppercase()
+        );
+        let to_json = |value: serde_json::Result<serde_json::Value>| {
+            value.map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode order: {}", e)))
+        };
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        // Checked under the restaurant's row lock, so a busy-mode cap holds
+        // even when orders arrive at the same moment
+        BusyModeService::check_capacity(&mut tx, restaurant.id, now).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO orders (
+                id, order_number, customer_id, restaurant_id, status, items,
+                subtotal, delivery_fee, discount_amount, total_amount, bill, coupon_id,
+                menu_version_id, delivery_address, restaurant_address, estimated_delivery_time,
+                estimated_ready_at, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $18)
+            RETURNING *
+            "#,
+        )
+        .bind(order_id)
+        .bind(&order_number)
+        .bind(customer.id)
+        .bind(restaurant.id)
+        .bind(OrderStatus::Placed.as_str())
+        .bind(to_json(serde_json::to_value(&items))?)
+        .bind(bill.items_total)
+        .bind(bill.delivery_fee)
+        .bind(bill.discount_total())
+        .bind(bill.total)
+        .bind(to_json(serde_json::to_value(&bill))?)
+        .bind(applied_coupon.as_ref().map(|coupon| coupon.id))
+        .bind(menu_version_id)
+        .bind(to_json(serde_json::to_value(&request.delivery_address))?)
+        .bind(to_json(serde_json::to_value(&restaurant_address))?)
+        .bind(estimated_delivery_time)
+        .bind(prep.ready_at)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::record_status(&mut tx, order_id, OrderStatus::Placed, customer.id).await?;
+        if let Some(coupon) = &applied_coupon {
+            PromotionService::record_coupon_use(&mut tx, coupon.id, customer.id, order_id, bill.coupon_discount).await?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        order_from_row(&row)
+    }
+
+    /// Fetch an order visible to the user: its customer, the restaurant owner,
+    /// the assigned rider or an admin
+    pub async fn get_order(&self, order_id: Uuid, user: &User) -> Result<Order> {
+        let row = sqlx::query(
+            r#"
+            SELECT o.*, CASE WHEN r.owner_id = $2 THEN 'owner' ELSE s.role END AS staff_role
+            FROM orders o
+            JOIN restaurants r ON r.id = o.restaurant_id
+            LEFT JOIN restaurant_staff s
+                ON s.restaurant_id = r.id AND s.user_id = $2 AND s.status = 'active'
+            WHERE o.id = $1
+            "#,
+        )
+        .bind(order_id)
+        .bind(user.id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        let order = order_from_row(&row)?;
+        let staff_role = staff_role_from_row(&row)?;
+        let allowed = user.role == "admin"
+            || order.customer_id == user.id
+            || staff_role.is_some_and(|role| role.has_permission(Permission::ViewOrders))
+            || order.delivery_person_id == Some(user.id);
+        if !allowed {
+            return Err(AppError::NotFound("Order not found".to_string()));
+        }
+
+        Ok(order)
+    }
+
+    pub async fn get_customer_orders(&self, customer_id: Uuid) -> Result<Vec<Order>> {
+        let rows = sqlx::query(
+            "SELECT * FROM orders WHERE customer_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(customer_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(order_from_row).collect()
+    }
+
+    /// Move an order along its lifecycle. Confirming reserves stock for the
+    /// order's items and cancelling returns it, in the same transaction as the
+    /// status change; the stock changes are returned for broadcasting.
+    pub async fn update_status(&self, order_id: Uuid, user: &User, next: OrderStatus) -> Result<(Order, Vec<StockChange>)> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT o.*, CASE WHEN r.owner_id = $2 THEN 'owner' ELSE s.role END AS staff_role
+            FROM orders o
+            JOIN restaurants r ON r.id = o.restaurant_id
+            LEFT JOIN restaurant_staff s
+                ON s.restaurant_id = r.id AND s.user_id = $2 AND s.status = 'active'
+            WHERE o.id = $1
+            FOR UPDATE OF o
+            "#,
+        )
+        .bind(order_id)
+        .bind(user.id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        let order = order_from_row(&row)?;
+        let staff_role = staff_role_from_row(&row)?;
+
+        // Restaurant staff act through their role at this restaurant,
+        // whatever their account type
+        let staff_allowed = staff_role
+            .zip(Permission::for_order_status(next))
+            .is_some_and(|(role, permission)| role.has_permission(permission));
+        let allowed = staff_allowed || match user.role.as_str() {
+            "admin" => true,
+            "delivery_person" => {
+                order.delivery_person_id == Some(user.id)
+                    && matches!(next, OrderStatus::PickedUp | OrderStatus::OnTheWay | OrderStatus::Delivered)
+            }
+            _ => {
+                order.customer_id == user.id
+                    && next == OrderStatus::Cancelled
+                    && order.status == OrderStatus::Placed
+            }
+        };
+        if !allowed {
+            return Err(AppError::Forbidden(format!(
+                "Not allowed to mark this order as {}",
+                next.as_str()
+            )));
+        }
+
+        let status = order.status.transition_to(next)?;
+        let stock_changes = match status {
+            OrderStatus::Confirmed => InventoryService::reserve_for_order(&mut tx, order.id, &order.items).await?,
+            OrderStatus::Cancelled => InventoryService::release_for_order(&mut tx, order.id).await?,
+            _ => Vec::new(),
+        };
+        // The kitchen starts now, so predict again with its current load and
+        // move the delivery estimate by the same amount. This reads on the
+        // transaction rather than waiting on a second pool connection.
+        let ready_at = match status {
+            OrderStatus::Confirmed => Some(
+                PrepTimeService::estimate(&mut tx, order.restaurant_id, &order.items, Utc::now())
+                    .await?
+                    .ready_at,
+            ),
+            _ => None,
+        };
+
+        let row = sqlx::query(
+            r#"
+            UPDATE orders
+            SET status = $1,
+                actual_delivery_time = CASE WHEN $1 = 'delivered' THEN NOW() ELSE actual_delivery_time END,
+                estimated_delivery_time = CASE
+                    WHEN $3::timestamptz IS NOT NULL AND estimated_ready_at IS NOT NULL
+                        THEN estimated_delivery_time + ($3 - estimated_ready_at)
+                    ELSE estimated_delivery_time
+                END,
+                estimated_ready_at = COALESCE($3, estimated_ready_at),
+                updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(order.id)
+        .bind(ready_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::record_status(&mut tx, order.id, status, user.id).await?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok((order_from_row(&row)?, stock_changes))
+    }
+
+    async fn record_status(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        order_id: Uuid,
+        status: OrderStatus,
+        changed_by: Uuid,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO order_status_history (order_id, status, changed_by) VALUES ($1, $2, $3)"
+        )
+        .bind(order_id)
+        .bind(status.as_str())
+        .bind(changed_by)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::AppError;
+    use crate::money::Money;
+    use crate::orders::models::{OrderItem, OrderStatus};
+    use crate::orders::prep_time::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    /// 2024-01-15 at the given IST time
+    fn ist(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 15, h, m, 0).unwrap() - chrono::Duration::minutes(330)
+    }
+
+    fn order_item(menu_item_id: Uuid, quantity: u32) -> OrderItem {
+        OrderItem {
+            id: Uuid::new_v4(),
+            menu_item_id,
+            name: "Dish".to_string(),
+            quantity,
+            unit_price: Money::from_rupees(100),
+            total_price: Money::from_rupees(100) * quantity as i64,
+            customizations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_order_status_happy_path() {
+        let path = [
+            OrderStatus::Placed,
+            OrderStatus::Confirmed,
+            OrderStatus::Preparing,
+            OrderStatus::Ready,
+            OrderStatus::PickedUp,
+            OrderStatus::OnTheWay,
+            OrderStatus::Delivered,
+        ];
+
+        for pair in path.windows(2) {
+            assert_eq!(pair[0].transition_to(pair[1]).unwrap(), pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_order_cancellation_only_before_pickup() {
+        assert!(OrderStatus::Placed.can_transition_to(&OrderStatus::Cancelled));
+        assert!(OrderStatus::Ready.can_transition_to(&OrderStatus::Cancelled));
+        assert!(!OrderStatus::PickedUp.can_transition_to(&OrderStatus::Cancelled));
+        assert!(!OrderStatus::Delivered.can_transition_to(&OrderStatus::Cancelled));
+        assert!(matches!(
+            OrderStatus::Cancelled.transition_to(OrderStatus::Confirmed),
+            Err(AppError::InvalidStatusTransition(_))
+        ));
+    }
+
+    #[test]
+    fn test_order_status_round_trips_through_database_value() {
+        for status in [OrderStatus::Placed, OrderStatus::OnTheWay, OrderStatus::Cancelled] {
+            assert_eq!(status.as_str().parse::<OrderStatus>().unwrap(), status);
+        }
+        assert_eq!(OrderStatus::OnTheWay.as_str(), "out_for_delivery");
+        assert!("shipped".parse::<OrderStatus>().is_err());
+    }
+
+    #[test]
+    fn test_prep_features_from_order() {
+        let biryani = Uuid::new_v4();
+        let raita = Uuid::new_v4();
+        let prep_minutes = HashMap::from([(biryani, 25), (raita, 5)]);
+
+        let items = [order_item(biryani, 2), order_item(raita, 1)];
+        let features = PrepFeatures::new(&items, &prep_minutes, 3, ist(13, 30));
+        assert_eq!(features.slowest_item_minutes, 25.0);
+        assert_eq!(features.extra_items, 2.0);
+        assert_eq!(features.kitchen_load, 3.0);
+        assert!(features.is_peak);
+
+        // An item no longer on the menu falls back to the default
+        let features = PrepFeatures::new(&[order_item(Uuid::new_v4(), 1)], &prep_minutes, 0, ist(16, 0));
+        assert_eq!(features.slowest_item_minutes, DEFAULT_ITEM_PREP_MINUTES as f64);
+        assert_eq!(features.extra_items, 0.0);
+        assert!(!features.is_peak);
+
+        assert!(is_peak_hour(ist(22, 29)));
+        assert!(!is_peak_hour(ist(22, 30)));
+        assert_eq!(ride_minutes(5.0), 15 + HANDOVER_MINUTES);
+    }
+
+    #[test]
+    fn test_prep_time_model_learns_from_history() {
+        let truth = PrepTimeWeights {
+            base_minutes: 2.0,
+            slowest_item_factor: 1.2,
+            per_extra_item_minutes: 1.5,
+            per_order_in_kitchen_minutes: 2.0,
+            peak_hour_minutes: 4.0,
+        };
+        let mut samples = Vec::new();
+        for slowest in [10.0, 15.0, 20.0, 30.0] {
+            for extra_items in 0..4 {
+                for kitchen_load in 0..5 {
+                    for is_peak in [false, true] {
+                        let features = PrepFeatures {
+                            slowest_item_minutes: slowest,
+                            extra_items: extra_items as f64,
+                            kitchen_load: kitchen_load as f64,
+                            is_peak,
+                        };
+                        samples.push(PrepSample { features, minutes: truth.predict(&features) });
+                    }
+                }
+            }
+        }
+        // A kitchen that forgot to mark an order ready is not a real prep time
+        samples.push(PrepSample { features: samples[0].features, minutes: 600.0 });
+
+        let (weights, error) = fit_prep_time(&samples).unwrap();
+        assert!((weights.slowest_item_factor - 1.2).abs() < 0.05, "{:?}", weights);
+        assert!((weights.per_extra_item_minutes - 1.5).abs() < 0.1, "{:?}", weights);
+        assert!((weights.per_order_in_kitchen_minutes - 2.0).abs() < 0.1, "{:?}", weights);
+        // The pull towards the static weights shows most on the flat terms
+        assert!((weights.peak_hour_minutes - 4.0).abs() < 1.0, "{:?}", weights);
+        assert!(error < 1.0);
+
+        let busy_dinner = PrepFeatures {
+            slowest_item_minutes: 20.0,
+            extra_items: 2.0,
+            kitchen_load: 4.0,
+            is_peak: true,
+        };
+        assert!((weights.predict(&busy_dinner) - truth.predict(&busy_dinner)).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_prep_time_model_needs_enough_history() {
+        let features = PrepFeatures {
+            slowest_item_minutes: 20.0,
+            extra_items: 0.0,
+            kitchen_load: 0.0,
+            is_peak: false,
+        };
+        let samples = vec![PrepSample { features, minutes: 35.0 }; MIN_TRAINING_SAMPLES - 1];
+        assert!(fit_prep_time(&samples).is_none());
+
+        // Until then, an order takes as long as its slowest item
+        assert_eq!(PrepTimeWeights::STATIC.predict(&features), 20.0);
+        assert_eq!(
+            PrepTimeWeights::STATIC.predict(&PrepFeatures { slowest_item_minutes: 0.0, ..features }),
+            3.0
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::config::Config;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Status of a payment as reported by the payment gateway
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GatewayPaymentStatus {
+    Pending,
+    Succeeded,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum GatewayError {
+    /// Timeouts, 5xx and rate limiting; worth retrying later
+    #[error("Transient gateway error: {0}")]
+    Transient(String),
+    /// The gateway will never give a different answer for this request
+    #[error("Gateway rejected request: {0}")]
+    Permanent(String),
+}
+
+#[async_trait]
+pub trait PaymentGateway: Send + Sync {
+    async fn fetch_status(&self, transaction_id: &str) -> Result<GatewayPaymentStatus, GatewayError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct GatewayStatusResponse {
+    status: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// REST payment gateway client (Razorpay/Cashfree style status API)
+pub struct HttpPaymentGateway {
+    client: Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl HttpPaymentGateway {
+    pub fn new(config: &Config) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            base_url: config.payment_gateway_base_url.trim_end_matches('/').to_string(),
+            api_key: config.payment_gateway_api_key.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentGateway for HttpPaymentGateway {
+    async fn fetch_status(&self, transaction_id: &str) -> Result<GatewayPaymentStatus, GatewayError> {
+        let response = self
+            .client
+            .get(format!("{}/v1/payments/{}", self.base_url, transaction_id))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .map_err(|e| GatewayError::Transient(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(GatewayError::Transient(format!("gateway returned {}", status)));
+        }
+        if !status.is_success() {
+            return Err(GatewayError::Permanent(format!("gateway returned {}", status)));
+        }
+
+        let body: GatewayStatusResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Transient(e.to_string()))?;
+
+        Ok(parse_gateway_status(&body.status, body.error_description))
+    }
+}
+
+pub(crate) fn parse_gateway_status(status: &str, error: Option<String>) -> GatewayPaymentStatus {
+    match status.to_lowercase().as_str() {
+        "captured" | "succeeded" | "success" | "paid" => GatewayPaymentStatus::Succeeded,
+        "failed" | "declined" | "cancelled" | "expired" => {
+            GatewayPaymentStatus::Failed(error.unwrap_or_else(|| status.to_string()))
+        }
+        _ => GatewayPaymentStatus::Pending,
+    }
+}
@@ -1,5 +1,14 @@
 pub mod models;
 pub mod handlers;
+pub mod service;
+pub mod gateway;
+pub mod reconciler;
 
 pub use models::*;
-pub use handlers::*;
\ No newline at end of file
+pub use handlers::*;
+pub use service::*;
+pub use gateway::*;
+pub use reconciler::*;
+
+#[cfg(test)]
+mod tests;
@@ -1,3 +1,5 @@
+use crate::error::AppError;
+use crate::money::{Currency, Money};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -6,8 +8,8 @@ pub struct Payment {
     pub id: Uuid,
     pub order_id: Uuid,
     pub customer_id: Uuid,
-    pub amount: f64,
-    pub currency: String,
+    pub amount: Money,
+    pub currency: Currency,
     pub status: PaymentStatus,
     pub payment_method: PaymentMethod,
     pub transaction_id: Option<String>,
@@ -15,7 +17,7 @@ pub struct Payment {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PaymentStatus {
     Pending,
     Processing,
@@ -25,7 +27,7 @@ pub enum PaymentStatus {
     Cancelled,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PaymentMethod {
     // Traditional methods
     CreditCard,
@@ -57,12 +59,140 @@ pub enum PaymentMethod {
     DigitalWallet,
 }
 
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Processing => "processing",
+            PaymentStatus::Completed => "completed",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Refunded => "refunded",
+            PaymentStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl PaymentStatus {
+    /// Whether the payment is still waiting on the gateway
+    pub fn is_live(&self) -> bool {
+        matches!(self, PaymentStatus::Pending | PaymentStatus::Processing)
+    }
+
+    /// Allowed moves in the payment lifecycle:
+    /// pending -> processing -> completed -> refunded, with failed and
+    /// cancelled reachable from either live state.
+    pub fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        matches!(
+            (self, next),
+            (PaymentStatus::Pending, PaymentStatus::Processing)
+                | (PaymentStatus::Pending, PaymentStatus::Completed)
+                | (PaymentStatus::Pending, PaymentStatus::Failed)
+                | (PaymentStatus::Pending, PaymentStatus::Cancelled)
+                | (PaymentStatus::Processing, PaymentStatus::Completed)
+                | (PaymentStatus::Processing, PaymentStatus::Failed)
+                | (PaymentStatus::Processing, PaymentStatus::Cancelled)
+                | (PaymentStatus::Completed, PaymentStatus::Refunded)
+        )
+    }
+
+    pub fn transition_to(&self, next: PaymentStatus) -> Result<PaymentStatus, AppError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(AppError::InvalidStatusTransition(format!(
+                "payment cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::str::FromStr for PaymentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(PaymentStatus::Pending),
+            "processing" => Ok(PaymentStatus::Processing),
+            "completed" => Ok(PaymentStatus::Completed),
+            "failed" => Ok(PaymentStatus::Failed),
+            "refunded" => Ok(PaymentStatus::Refunded),
+            "cancelled" => Ok(PaymentStatus::Cancelled),
+            _ => Err(format!("Invalid payment status: {}", s)),
+        }
+    }
+}
+
+impl PaymentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMethod::CreditCard => "credit_card",
+            PaymentMethod::DebitCard => "debit_card",
+            PaymentMethod::Cash => "cash",
+            PaymentMethod::UPI => "upi",
+            PaymentMethod::Paytm => "paytm",
+            PaymentMethod::PhonePe => "phonepe",
+            PaymentMethod::GooglePay => "googlepay",
+            PaymentMethod::AmazonPay => "amazonpay",
+            PaymentMethod::MobiKwik => "mobikwik",
+            PaymentMethod::FreeCharge => "freecharge",
+            PaymentMethod::NetBanking => "netbanking",
+            PaymentMethod::IMPS => "imps",
+            PaymentMethod::NEFT => "neft",
+            PaymentMethod::RTGS => "rtgs",
+            PaymentMethod::Simpl => "simpl",
+            PaymentMethod::LazyPay => "lazypay",
+            PaymentMethod::ZestMoney => "zestmoney",
+            PaymentMethod::BankTransfer => "bank_transfer",
+            PaymentMethod::DigitalWallet => "digital_wallet",
+        }
+    }
+
+    /// Cash is collected at the door and never touches a payment rail
+    pub fn is_prepaid(&self) -> bool {
+        !matches!(self, PaymentMethod::Cash)
+    }
+}
+
+impl std::str::FromStr for PaymentMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "credit_card" => Ok(PaymentMethod::CreditCard),
+            "debit_card" => Ok(PaymentMethod::DebitCard),
+            "cash" => Ok(PaymentMethod::Cash),
+            "upi" => Ok(PaymentMethod::UPI),
+            "paytm" => Ok(PaymentMethod::Paytm),
+            "phonepe" => Ok(PaymentMethod::PhonePe),
+            "googlepay" => Ok(PaymentMethod::GooglePay),
+            "amazonpay" => Ok(PaymentMethod::AmazonPay),
+            "mobikwik" => Ok(PaymentMethod::MobiKwik),
+            "freecharge" => Ok(PaymentMethod::FreeCharge),
+            "netbanking" => Ok(PaymentMethod::NetBanking),
+            "imps" => Ok(PaymentMethod::IMPS),
+            "neft" => Ok(PaymentMethod::NEFT),
+            "rtgs" => Ok(PaymentMethod::RTGS),
+            "simpl" => Ok(PaymentMethod::Simpl),
+            "lazypay" => Ok(PaymentMethod::LazyPay),
+            "zestmoney" => Ok(PaymentMethod::ZestMoney),
+            "bank_transfer" => Ok(PaymentMethod::BankTransfer),
+            "digital_wallet" => Ok(PaymentMethod::DigitalWallet),
+            _ => Err(format!("Invalid payment method: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreatePaymentRequest {
     pub order_id: Uuid,
     pub payment_method: PaymentMethod,
-    pub amount: f64,
-    pub currency: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetryPaymentRequest {
+    pub payment_method: PaymentMethod,
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,262 @@
+use crate::config::Config;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::payments::gateway::{GatewayError, GatewayPaymentStatus, PaymentGateway};
+use crate::payments::models::PaymentStatus;
+use crate::payments::service::PaymentService;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Payments examined per reconciliation pass
+const RECONCILE_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct ReconcilerConfig {
+    pub interval: std::time::Duration,
+    /// Live payments older than this are expired and their orders released
+    pub payment_expiry: Duration,
+    pub max_attempts: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ReconcilerConfig {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            interval: std::time::Duration::from_secs(config.payment_reconcile_interval_seconds),
+            payment_expiry: Duration::minutes(config.payment_expiry_minutes),
+            max_attempts: config.payment_max_reconcile_attempts,
+            base_backoff: Duration::seconds(config.payment_reconcile_interval_seconds as i64),
+            max_backoff: Duration::minutes(10),
+        }
+    }
+
+    /// Exponential backoff after `attempts` consecutive transient failures
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let exponent = attempts.clamp(0, 16) as u32;
+        let delay = self.base_backoff * 2i32.pow(exponent);
+        delay.min(self.max_backoff)
+    }
+}
+
+struct LivePayment {
+    id: Uuid,
+    transaction_id: Option<String>,
+    attempts: i32,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ReconcileSummary {
+    pub checked: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub expired: usize,
+    pub retry_scheduled: usize,
+    pub orders_cancelled: usize,
+}
+
+/// Background job that drives live payments to a final state by polling the
+/// gateway, and cancels orders whose payment was abandoned.
+pub struct PaymentReconciler {
+    db: Database,
+    gateway: Arc<dyn PaymentGateway>,
+    config: ReconcilerConfig,
+}
+
+impl PaymentReconciler {
+    pub fn new(db: Database, gateway: Arc<dyn PaymentGateway>, config: ReconcilerConfig) -> Self {
+        Self { db, gateway, config }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(summary) if summary.checked > 0 || summary.orders_cancelled > 0 => {
+                        tracing::info!("Payment reconciliation: {:?}", summary);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Payment reconciliation failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<ReconcileSummary> {
+        let now = Utc::now();
+        let mut summary = ReconcileSummary::default();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, transaction_id, reconcile_attempts, created_at
+            FROM payments
+            WHERE status IN ('pending', 'processing')
+              AND payment_method <> 'cash'
+              AND (next_reconcile_at IS NULL OR next_reconcile_at <= $1)
+            ORDER BY created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(now)
+        .bind(RECONCILE_BATCH_SIZE)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for row in rows {
+            let payment = LivePayment {
+                id: row.get("id"),
+                transaction_id: row.get("transaction_id"),
+                attempts: row.get("reconcile_attempts"),
+                created_at: row.get("created_at"),
+            };
+
+            summary.checked += 1;
+            if let Err(e) = self.reconcile_payment(&payment, now, &mut summary).await {
+                // A concurrent update is expected now and then; the next pass will see the new state
+                tracing::warn!("Failed to reconcile payment {}: {:?}", payment.id, e);
+            }
+        }
+
+        summary.orders_cancelled = self.cancel_abandoned_orders(now).await?;
+
+        Ok(summary)
+    }
+
+    async fn reconcile_payment(
+        &self,
+        payment: &LivePayment,
+        now: DateTime<Utc>,
+        summary: &mut ReconcileSummary,
+    ) -> Result<()> {
+        let service = PaymentService::new(self.db.clone());
+        let payment_id = payment.id;
+        let attempts = payment.attempts;
+        let expired = now - payment.created_at > self.config.payment_expiry;
+
+        let Some(transaction_id) = payment.transaction_id.as_deref() else {
+            if expired {
+                service
+                    .transition_payment(payment_id, PaymentStatus::Cancelled, Some("expired"))
+                    .await?;
+                summary.expired += 1;
+            }
+            return Ok(());
+        };
+
+        match self.gateway.fetch_status(transaction_id).await {
+            Ok(GatewayPaymentStatus::Succeeded) => {
+                service
+                    .transition_payment(payment_id, PaymentStatus::Completed, None)
+                    .await?;
+                summary.completed += 1;
+            }
+            Ok(GatewayPaymentStatus::Failed(reason)) => {
+                service
+                    .transition_payment(payment_id, PaymentStatus::Failed, Some(&reason))
+                    .await?;
+                summary.failed += 1;
+            }
+            Ok(GatewayPaymentStatus::Pending) if expired => {
+                service
+                    .transition_payment(payment_id, PaymentStatus::Cancelled, Some("expired"))
+                    .await?;
+                summary.expired += 1;
+            }
+            Ok(GatewayPaymentStatus::Pending) => {
+                self.schedule_next_check(payment_id, now + self.config.base_backoff, attempts)
+                    .await?;
+            }
+            Err(GatewayError::Permanent(reason)) => {
+                service
+                    .transition_payment(payment_id, PaymentStatus::Failed, Some(&reason))
+                    .await?;
+                summary.failed += 1;
+            }
+            Err(GatewayError::Transient(reason)) if expired || attempts + 1 >= self.config.max_attempts => {
+                tracing::warn!("Giving up on payment {} after {} attempts: {}", payment_id, attempts + 1, reason);
+                service
+                    .transition_payment(payment_id, PaymentStatus::Cancelled, Some("expired"))
+                    .await?;
+                summary.expired += 1;
+            }
+            Err(GatewayError::Transient(_)) => {
+                self.schedule_next_check(payment_id, now + self.config.backoff(attempts), attempts + 1)
+                    .await?;
+                summary.retry_scheduled += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn schedule_next_check(&self, payment_id: Uuid, at: DateTime<Utc>, attempts: i32) -> Result<()> {
+        sqlx::query(
+            "UPDATE payments SET next_reconcile_at = $1, reconcile_attempts = $2 WHERE id = $3 AND status IN ('pending', 'processing')"
+        )
+        .bind(at)
+        .bind(attempts)
+        .bind(payment_id)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Cancel placed orders whose last payment failed or expired more than the
+    /// expiry window ago without the customer retrying
+    async fn cancel_abandoned_orders(&self, now: DateTime<Utc>) -> Result<usize> {
+        let mut tx = self
+            .db
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let cancelled: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE orders o
+            SET status = 'cancelled'
+            WHERE o.status = 'placed'
+              AND EXISTS (SELECT 1 FROM payments p WHERE p.order_id = o.id)
+              AND NOT EXISTS (
+                  SELECT 1 FROM payments p
+                  WHERE p.order_id = o.id
+                    AND (p.status IN ('pending', 'processing', 'completed') OR p.updated_at > $1)
+              )
+            RETURNING o.id
+            "#,
+        )
+        .bind(now - self.config.payment_expiry)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for order_id in &cancelled {
+            sqlx::query(
+                "INSERT INTO order_status_history (order_id, status, notes) VALUES ($1, 'cancelled', 'Payment abandoned')"
+            )
+            .bind(order_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for order_id in &cancelled {
+            tracing::info!("Cancelled order {} after payment was abandoned", order_id);
+        }
+
+        Ok(cancelled.len())
+    }
+}
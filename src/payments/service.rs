@@ -0,0 +1,280 @@
+use crate::auth::models::User;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::india::config::ISTConfig;
+use crate::india::payments::{BankingNetwork, RBICompliance};
+use crate::money::{Currency, Money};
+use crate::payments::models::*;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+/// How recently the user must have signed in to satisfy step-up authentication
+const STEP_UP_AUTH_MAX_AGE_MINUTES: i64 = 5;
+
+pub struct PaymentService {
+    db: Database,
+    compliance: RBICompliance,
+}
+
+impl PaymentService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            compliance: RBICompliance::default(),
+        }
+    }
+
+    pub async fn create_payment(&self, user: &User, request: CreatePaymentRequest) -> Result<Payment> {
+        self.insert_payment(user, request, None).await
+    }
+
+    /// Start a new payment for the same order after a failed or expired
+    /// attempt, optionally switching to a different payment method
+    pub async fn retry_payment(&self, user: &User, payment_id: Uuid, request: RetryPaymentRequest) -> Result<Payment> {
+        let previous = self.get_payment(payment_id, user).await?;
+        if !matches!(previous.status, PaymentStatus::Failed | PaymentStatus::Cancelled) {
+            return Err(AppError::InvalidStatusTransition(format!(
+                "only failed or cancelled payments can be retried, this one is {}",
+                previous.status.as_str()
+            )));
+        }
+
+        let request = CreatePaymentRequest {
+            order_id: previous.order_id,
+            payment_method: request.payment_method,
+        };
+
+        self.insert_payment(user, request, Some(previous.id)).await
+    }
+
+    /// Move a payment to `next`, validating the transition against the state
+    /// machine. The update is conditional on the status we read, so a
+    /// concurrent writer makes this return `InvalidStatusTransition`.
+    pub async fn transition_payment(&self, payment_id: Uuid, next: PaymentStatus, reason: Option<&str>) -> Result<Payment> {
+        let current: String = sqlx::query_scalar("SELECT status FROM payments WHERE id = $1")
+            .bind(payment_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))?;
+        let current: PaymentStatus = current.parse().map_err(AppError::DatabaseError)?;
+        let next = current.transition_to(next)?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE payments
+            SET status = $1,
+                failure_reason = COALESCE($2, failure_reason),
+                completed_at = CASE WHEN $1 = 'completed' THEN NOW() ELSE completed_at END,
+                next_reconcile_at = NULL
+            WHERE id = $3 AND status = $4
+            RETURNING id, order_id, customer_id, amount, currency, payment_method, status,
+                      transaction_id, created_at, updated_at
+            "#,
+        )
+        .bind(next.as_str())
+        .bind(reason)
+        .bind(payment_id)
+        .bind(current.as_str())
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| {
+            AppError::InvalidStatusTransition("payment status changed concurrently".to_string())
+        })?;
+
+        payment_from_row(&row)
+    }
+
+    /// Charge the order's total. The customer row is locked for the whole
+    /// check-and-insert, so concurrent payments by one customer are checked
+    /// against the daily limit one at a time.
+    async fn insert_payment(&self, user: &User, request: CreatePaymentRequest, retry_of: Option<Uuid>) -> Result<Payment> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let kyc_verified = sqlx::query_scalar::<_, bool>(
+            "SELECT kyc_verified FROM users WHERE id = $1 FOR UPDATE"
+        )
+        .bind(user.id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .unwrap_or(false);
+
+        let order = sqlx::query("SELECT customer_id, status, total_amount FROM orders WHERE id = $1 FOR UPDATE")
+            .bind(request.order_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+        if order.get::<Uuid, _>("customer_id") != user.id {
+            return Err(AppError::Forbidden("You can only pay for your own orders".to_string()));
+        }
+        if order.get::<String, _>("status") == "cancelled" {
+            return Err(AppError::BadRequest("Order has been cancelled".to_string()));
+        }
+        let amount: Money = order.get("total_amount");
+        if !amount.is_positive() {
+            return Err(AppError::ValidationError("Payment amount must be positive".to_string()));
+        }
+
+        let has_active_payment = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM payments WHERE order_id = $1 AND status IN ('pending', 'processing', 'completed'))"
+        )
+        .bind(request.order_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if has_active_payment {
+            return Err(AppError::BadRequest(
+                "Order already has an active or completed payment".to_string(),
+            ));
+        }
+
+        let spent_today = if request.payment_method.is_prepaid() {
+            spent_today(&mut tx, user.id).await?
+        } else {
+            Money::ZERO
+        };
+        self.check_compliance(user, &request.payment_method, amount, spent_today, kyc_verified)?;
+
+        let payment_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO payments (
+                id, order_id, customer_id, amount, currency, payment_method, status,
+                transaction_id, retry_of, next_reconcile_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, order_id, customer_id, amount, currency, payment_method, status,
+                      transaction_id, created_at, updated_at
+            "#,
+        )
+        .bind(payment_id)
+        .bind(request.order_id)
+        .bind(user.id)
+        .bind(amount)
+        .bind(amount.currency().code())
+        .bind(request.payment_method.as_str())
+        .bind(PaymentStatus::Processing.as_str())
+        .bind(format!("txn_{}", Uuid::new_v4()))
+        .bind(retry_of)
+        .bind(now)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        payment_from_row(&row)
+    }
+
+    pub async fn get_payment(&self, payment_id: Uuid, user: &User) -> Result<Payment> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, order_id, customer_id, amount, currency, payment_method, status,
+                   transaction_id, created_at, updated_at
+            FROM payments WHERE id = $1
+            "#,
+        )
+        .bind(payment_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))?;
+
+        let payment = payment_from_row(&row)?;
+        if payment.customer_id != user.id && user.role != "admin" {
+            return Err(AppError::Forbidden("You can only view your own payments".to_string()));
+        }
+
+        Ok(payment)
+    }
+
+    /// Apply banking network limits and RBI rules to a new payment
+    fn check_compliance(
+        &self,
+        user: &User,
+        method: &PaymentMethod,
+        amount: Money,
+        spent_today: Money,
+        kyc_verified: bool,
+    ) -> Result<()> {
+        if let Some(network) = BankingNetwork::for_payment_method(method) {
+            network.validate_amount(amount)?;
+        }
+
+        if !method.is_prepaid() {
+            return Ok(());
+        }
+
+        let step_up_verified = user
+            .auth_time
+            .map(|t| Utc::now() - t <= Duration::minutes(STEP_UP_AUTH_MAX_AGE_MINUTES))
+            .unwrap_or(false);
+
+        self.compliance
+            .validate_payment(amount, spent_today, kyc_verified, step_up_verified)?;
+
+        Ok(())
+    }
+}
+
+/// Total of the customer's live and settled prepaid payments since midnight IST
+async fn spent_today(conn: &mut PgConnection, customer_id: Uuid) -> Result<Money> {
+    let total: Option<Money> = sqlx::query_scalar(
+        r#"
+        SELECT SUM(amount) FROM payments
+        WHERE customer_id = $1
+          AND created_at >= $2
+          AND status IN ('pending', 'processing', 'completed')
+          AND payment_method <> 'cash'
+        "#,
+    )
+    .bind(customer_id)
+    .bind(ist_day_start(Utc::now()))
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    Ok(total.unwrap_or(Money::ZERO))
+}
+
+/// Start of the current calendar day in IST, expressed in UTC
+pub(crate) fn ist_day_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let ist = ISTConfig::default().offset();
+    now.with_timezone(&ist)
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(ist).single())
+        .map(|midnight| midnight.with_timezone(&Utc))
+        .unwrap_or(now)
+}
+
+pub(crate) fn payment_from_row(row: &PgRow) -> Result<Payment> {
+    let currency: String = row.get("currency");
+    let currency: Currency = currency.parse()?;
+    let amount: Money = row.get("amount");
+    let status: String = row.get("status");
+    let method: String = row.get("payment_method");
+
+    Ok(Payment {
+        id: row.get("id"),
+        order_id: row.get("order_id"),
+        customer_id: row.get("customer_id"),
+        amount: Money::new(amount.paise(), currency),
+        currency,
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        payment_method: method.parse().map_err(AppError::DatabaseError)?,
+        transaction_id: row.get("transaction_id"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
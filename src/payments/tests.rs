@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::AppError;
+    use crate::india::payments::{BankingNetwork, PaymentComplianceError, RBICompliance};
+    use crate::money::Money;
+    use crate::payments::models::*;
+    use crate::payments::gateway::{parse_gateway_status, GatewayPaymentStatus};
+    use crate::payments::reconciler::ReconcilerConfig;
+    use crate::payments::service::ist_day_start;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_network_limits_per_method() {
+        let upi = BankingNetwork::for_payment_method(&PaymentMethod::UPI).unwrap();
+        assert!(upi.validate_amount(Money::from_rupees(500)).is_ok());
+        assert!(matches!(
+            upi.validate_amount(Money::from_paise(50)),
+            Err(PaymentComplianceError::BelowNetworkMinimum { network: "UPI", .. })
+        ));
+        assert!(matches!(
+            upi.validate_amount(Money::from_rupees(100_001)),
+            Err(PaymentComplianceError::AboveNetworkMaximum { network: "UPI", .. })
+        ));
+
+        let rtgs = BankingNetwork::for_payment_method(&PaymentMethod::RTGS).unwrap();
+        assert_eq!(
+            rtgs.validate_amount(Money::from_rupees(1_000)).unwrap_err().code(),
+            "PAYMENT_BELOW_METHOD_MINIMUM"
+        );
+
+        assert!(BankingNetwork::for_payment_method(&PaymentMethod::CreditCard).is_none());
+    }
+
+    #[test]
+    fn test_rbi_rules_in_order() {
+        let rbi = RBICompliance::default();
+
+        assert!(rbi
+            .validate_payment(Money::from_rupees(499), Money::ZERO, false, false)
+            .is_ok());
+
+        let err = rbi
+            .validate_payment(Money::from_rupees(20_000), Money::from_rupees(90_000), true, true)
+            .unwrap_err();
+        assert_eq!(err.code(), "PAYMENT_DAILY_LIMIT_EXCEEDED");
+        assert_eq!(
+            err,
+            PaymentComplianceError::DailyLimitExceeded {
+                limit: Money::from_rupees(100_000),
+                remaining: Money::from_rupees(10_000),
+            }
+        );
+
+        let err = rbi
+            .validate_payment(Money::from_rupees(60_000), Money::ZERO, false, true)
+            .unwrap_err();
+        assert_eq!(err.code(), "PAYMENT_KYC_REQUIRED");
+
+        let err = rbi
+            .validate_payment(Money::from_rupees(15_000), Money::ZERO, true, false)
+            .unwrap_err();
+        assert_eq!(err.code(), "PAYMENT_STEP_UP_AUTH_REQUIRED");
+
+        assert!(rbi
+            .validate_payment(Money::from_rupees(60_000), Money::ZERO, true, true)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_compliance_error_exposes_code() {
+        let err: AppError = PaymentComplianceError::KycRequired {
+            threshold: Money::from_rupees(50_000),
+        }
+        .into();
+        assert_eq!(err.code(), Some("PAYMENT_KYC_REQUIRED"));
+        assert_eq!(AppError::Unauthorized.code(), None);
+    }
+
+    #[test]
+    fn test_ist_day_start() {
+        // 20:00 UTC on Jan 1 is 01:30 IST on Jan 2, so the IST day began at 18:30 UTC
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 20, 0, 0).unwrap();
+        assert_eq!(ist_day_start(now), Utc.with_ymd_and_hms(2024, 1, 1, 18, 30, 0).unwrap());
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        assert_eq!(ist_day_start(now), Utc.with_ymd_and_hms(2023, 12, 31, 18, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_payment_enum_round_trip() {
+        for status in [
+            PaymentStatus::Pending,
+            PaymentStatus::Processing,
+            PaymentStatus::Completed,
+            PaymentStatus::Failed,
+            PaymentStatus::Refunded,
+            PaymentStatus::Cancelled,
+        ] {
+            assert_eq!(status.as_str().parse::<PaymentStatus>().unwrap(), status);
+        }
+        assert_eq!("upi".parse::<PaymentMethod>().unwrap(), PaymentMethod::UPI);
+        assert!("cheque".parse::<PaymentMethod>().is_err());
+    }
+
+    #[test]
+    fn test_payment_status_state_machine() {
+        assert!(PaymentStatus::Pending.can_transition_to(&PaymentStatus::Processing));
+        assert!(PaymentStatus::Processing.can_transition_to(&PaymentStatus::Completed));
+        assert!(PaymentStatus::Processing.can_transition_to(&PaymentStatus::Cancelled));
+        assert!(PaymentStatus::Completed.can_transition_to(&PaymentStatus::Refunded));
+
+        assert!(!PaymentStatus::Completed.can_transition_to(&PaymentStatus::Failed));
+        assert!(!PaymentStatus::Failed.can_transition_to(&PaymentStatus::Completed));
+        assert!(!PaymentStatus::Cancelled.can_transition_to(&PaymentStatus::Processing));
+        assert!(!PaymentStatus::Refunded.can_transition_to(&PaymentStatus::Completed));
+
+        assert!(matches!(
+            PaymentStatus::Failed.transition_to(PaymentStatus::Completed),
+            Err(AppError::InvalidStatusTransition(_))
+        ));
+        assert_eq!(
+            PaymentStatus::Pending.transition_to(PaymentStatus::Failed).unwrap(),
+            PaymentStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_reconciler_backoff_is_exponential_and_capped() {
+        let config = ReconcilerConfig {
+            interval: std::time::Duration::from_secs(30),
+            payment_expiry: Duration::minutes(15),
+            max_attempts: 8,
+            base_backoff: Duration::seconds(30),
+            max_backoff: Duration::minutes(10),
+        };
+        assert_eq!(config.backoff(0), Duration::seconds(30));
+        assert_eq!(config.backoff(1), Duration::seconds(60));
+        assert_eq!(config.backoff(3), Duration::seconds(240));
+        assert_eq!(config.backoff(10), Duration::minutes(10));
+    }
+
+    #[test]
+    fn test_parse_gateway_status() {
+        assert_eq!(parse_gateway_status("captured", None), GatewayPaymentStatus::Succeeded);
+        assert_eq!(parse_gateway_status("created", None), GatewayPaymentStatus::Pending);
+        assert_eq!(
+            parse_gateway_status("failed", Some("insufficient funds".to_string())),
+            GatewayPaymentStatus::Failed("insufficient funds".to_string())
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use uuid::Uuid;
+
+use crate::auth::models::User;
+use crate::error::Result;
+use crate::promotions::{
+    models::*,
+    service::PromotionService,
+};
+use crate::routes::AppState;
+
+pub async fn list_promotions(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<Vec<Promotion>>> {
+    let promotion_service = PromotionService::new(state.database.clone());
+
+    let promotions = promotion_service.list_promotions(restaurant_id, user.id).await?;
+
+    Ok(Json(promotions))
+}
+
+pub async fn create_promotion(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<PromotionRequest>,
+) -> Result<Json<Promotion>> {
+    let promotion_service = PromotionService::new(state.database.clone());
+
+    let promotion = promotion_service
+        .create_promotion(restaurant_id, user.id, request)
+        .await?;
+
+    Ok(Json(promotion))
+}
+
+pub async fn update_promotion(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, promotion_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<PromotionRequest>,
+) -> Result<Json<Promotion>> {
+    let promotion_service = PromotionService::new(state.database.clone());
+
+    let promotion = promotion_service
+        .update_promotion(restaurant_id, promotion_id, user.id, request)
+        .await?;
+
+    Ok(Json(promotion))
+}
+
+pub async fn delete_promotion(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, promotion_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    let promotion_service = PromotionService::new(state.database.clone());
+
+    promotion_service
+        .delete_promotion(restaurant_id, promotion_id, user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,12 @@
+pub mod handlers;
+pub mod models;
+pub mod pricing;
+pub mod service;
+
+pub use handlers::*;
+pub use models::*;
+pub use pricing::*;
+pub use service::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,268 @@
+use crate::error::AppError;
+use crate::money::Money;
+use crate::restaurants::availability::MenuAvailability;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+/// What a promotion gives the customer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromotionKind {
+    /// Percentage off the eligible items, optionally capped
+    PercentageOff {
+        percent: f64,
+        #[serde(default)]
+        max_discount: Option<Money>,
+    },
+    /// Fixed amount off the eligible items
+    FlatOff { amount: Money },
+    /// Every second unit of an eligible item is free
+    BuyOneGetOne,
+    /// Delivery fee waived
+    FreeDelivery,
+}
+
+impl PromotionKind {
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self {
+            PromotionKind::PercentageOff { percent, max_discount } => {
+                if !(*percent > 0.0 && *percent <= 100.0) {
+                    return Err(AppError::ValidationError(
+                        "Discount percent must be between 0 and 100".to_string(),
+                    ));
+                }
+                if max_discount.is_some_and(|max| !max.is_positive()) {
+                    return Err(AppError::ValidationError("max_discount must be positive".to_string()));
+                }
+            }
+            PromotionKind::FlatOff { amount } => {
+                if !amount.is_positive() {
+                    return Err(AppError::ValidationError("Discount amount must be positive".to_string()));
+                }
+            }
+            PromotionKind::BuyOneGetOne | PromotionKind::FreeDelivery => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the promotion changes item prices, as opposed to the order
+    /// as a whole; only these are badged on menu items
+    pub fn is_item_level(&self) -> bool {
+        matches!(self, PromotionKind::PercentageOff { .. } | PromotionKind::BuyOneGetOne)
+    }
+}
+
+/// Items a promotion applies to. Both lists empty means the whole menu.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromotionEligibility {
+    #[serde(default)]
+    pub item_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+impl PromotionEligibility {
+    pub fn is_whole_menu(&self) -> bool {
+        self.item_ids.is_empty() && self.categories.is_empty()
+    }
+
+    pub fn covers(&self, menu_item_id: Uuid, category: &str) -> bool {
+        self.is_whole_menu()
+            || self.item_ids.contains(&menu_item_id)
+            || self.categories.iter().any(|c| c.eq_ignore_ascii_case(category))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Promotion {
+    pub id: Uuid,
+    pub restaurant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: PromotionKind,
+    pub eligibility: PromotionEligibility,
+    /// Items total the order must reach, e.g. free delivery above ₹199
+    pub min_order: Option<Money>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Recurring days and hours, for happy hours
+    pub availability: Option<MenuAvailability>,
+    /// Whether a platform coupon can be used on the same order
+    pub stackable_with_coupons: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Promotion {
+    /// Running at `at`: switched on, within its dates and inside its hours
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.is_active
+            && self.starts_at <= at
+            && self.ends_at.is_none_or(|ends_at| at < ends_at)
+            && self
+                .availability
+                .as_ref()
+                .is_none_or(|availability| availability.is_available_at(at))
+    }
+
+    pub fn badge(&self) -> PromotionBadge {
+        let offer = match &self.kind {
+            PromotionKind::PercentageOff { percent, .. } => format!("{}% OFF", percent),
+            PromotionKind::FlatOff { amount } => format!("{} OFF", short_amount(*amount)),
+            PromotionKind::BuyOneGetOne => "Buy 1 Get 1".to_string(),
+            PromotionKind::FreeDelivery => "Free delivery".to_string(),
+        };
+        let label = match self.min_order {
+            Some(min_order) if min_order.is_positive() => format!("{} above {}", offer, short_amount(min_order)),
+            _ => offer,
+        };
+
+        PromotionBadge {
+            promotion_id: self.id,
+            label,
+            ends_at: self.ends_at,
+        }
+    }
+}
+
+/// "₹199" rather than "₹199.00" for whole-rupee amounts
+fn short_amount(amount: Money) -> String {
+    if amount.paise() % 100 == 0 {
+        format!("₹{}", amount.paise() / 100)
+    } else {
+        amount.to_string()
+    }
+}
+
+pub(crate) fn promotion_from_row(row: &PgRow) -> Result<Promotion, AppError> {
+    let json_error = |e: serde_json::Error| AppError::DatabaseError(format!("Invalid promotion data: {}", e));
+
+    Ok(Promotion {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        kind: serde_json::from_value(row.get("kind")).map_err(json_error)?,
+        eligibility: serde_json::from_value(row.get("eligibility")).map_err(json_error)?,
+        min_order: row.get("min_order"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        availability: row.get("availability"),
+        stackable_with_coupons: row.get("stackable_with_coupons"),
+        is_active: row.get("is_active"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// Short offer label shown on restaurant cards and menu items
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromotionBadge {
+    pub promotion_id: Uuid,
+    pub label: String,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Used for both creating and replacing a promotion
+#[derive(Debug, Deserialize)]
+pub struct PromotionRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: PromotionKind,
+    #[serde(default)]
+    pub eligibility: PromotionEligibility,
+    pub min_order: Option<Money>,
+    /// Defaults to now
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub availability: Option<MenuAvailability>,
+    #[serde(default = "default_true")]
+    pub stackable_with_coupons: bool,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+impl PromotionRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::ValidationError("Promotion name is required".to_string()));
+        }
+        self.kind.validate()?;
+        if self.min_order.is_some_and(|min_order| min_order.is_negative()) {
+            return Err(AppError::ValidationError("min_order cannot be negative".to_string()));
+        }
+        if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at) {
+            if ends_at <= starts_at {
+                return Err(AppError::ValidationError("ends_at must be after starts_at".to_string()));
+            }
+        }
+        if let Some(availability) = &self.availability {
+            availability.validate()?;
+        }
+        if matches!(self.kind, PromotionKind::FreeDelivery) && !self.eligibility.is_whole_menu() {
+            return Err(AppError::ValidationError(
+                "Free delivery applies to the whole order, not selected items".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A platform coupon from the `coupons` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coupon {
+    pub id: Uuid,
+    pub code: String,
+    pub discount: CouponDiscount,
+    pub minimum_order_amount: Money,
+    pub maximum_discount_amount: Option<Money>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CouponDiscount {
+    Percentage(f64),
+    FixedAmount(Money),
+}
+
+impl Coupon {
+    /// Discount on an items total, or `None` below the coupon's minimum
+    pub fn discount_for(&self, amount: Money) -> Option<Money> {
+        if amount < self.minimum_order_amount {
+            return None;
+        }
+        let discount = match self.discount {
+            CouponDiscount::Percentage(percent) => amount.percentage(percent),
+            CouponDiscount::FixedAmount(value) => value,
+        };
+        let discount = match self.maximum_discount_amount {
+            Some(max) => discount.min(max),
+            None => discount,
+        };
+        Some(discount.min(amount))
+    }
+}
+
+pub(crate) fn coupon_from_row(row: &PgRow) -> Result<Coupon, AppError> {
+    let discount_type: String = row.get("discount_type");
+    let discount_value: Money = row.get("discount_value");
+    let discount = match discount_type.as_str() {
+        "percentage" => CouponDiscount::Percentage(discount_value.to_rupees_f64()),
+        "fixed_amount" => CouponDiscount::FixedAmount(discount_value),
+        other => return Err(AppError::DatabaseError(format!("Invalid coupon discount type: {}", other))),
+    };
+
+    Ok(Coupon {
+        id: row.get("id"),
+        code: row.get("code"),
+        discount,
+        minimum_order_amount: row.get::<Option<Money>, _>("minimum_order_amount").unwrap_or_default(),
+        maximum_discount_amount: row.get("maximum_discount_amount"),
+    })
+}
@@ -0,0 +1,205 @@
+use crate::money::Money;
+use crate::promotions::models::{Coupon, Promotion, PromotionKind};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A priced order line, as promotions see it
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillLine {
+    pub menu_item_id: Uuid,
+    pub category: String,
+    pub quantity: u32,
+    pub unit_price: Money,
+}
+
+impl BillLine {
+    fn total(&self) -> Money {
+        self.unit_price * self.quantity
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedPromotion {
+    pub promotion_id: Uuid,
+    pub name: String,
+    pub discount: Money,
+}
+
+/// Breakdown of what the customer pays, stored with the order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBill {
+    pub items_total: Money,
+    pub applied_promotions: Vec<AppliedPromotion>,
+    /// Item discounts from promotions; a waived delivery fee is not included
+    pub promotion_discount: Money,
+    pub coupon_code: Option<String>,
+    pub coupon_discount: Money,
+    /// Why a supplied coupon was not applied
+    pub coupon_note: Option<String>,
+    pub delivery_fee: Money,
+    pub total: Money,
+}
+
+impl OrderBill {
+    pub fn discount_total(&self) -> Money {
+        self.promotion_discount + self.coupon_discount
+    }
+}
+
+/// What the running promotions take off an order
+struct PromotionSavings {
+    applied: Vec<AppliedPromotion>,
+    item_discount: Money,
+    free_delivery: bool,
+}
+
+/// Apply the restaurant's promotions running at `at`. Buy-one-get-one
+/// frees every second unit of each eligible line; of the percentage and
+/// flat offers only the one worth most to the customer is used, on what is
+/// left after the free units.
+fn promotion_savings(lines: &[BillLine], promotions: &[Promotion], delivery_fee: Money, at: DateTime<Utc>) -> PromotionSavings {
+    let items_total: Money = lines.iter().map(BillLine::total).sum();
+    let running: Vec<&Promotion> = promotions
+        .iter()
+        .filter(|promotion| promotion.is_active_at(at))
+        .filter(|promotion| promotion.min_order.is_none_or(|min_order| items_total >= min_order))
+        .collect();
+
+    let mut applied = Vec::new();
+
+    // Free units per line; a line only ever gets one buy-one-get-one
+    let mut free_value = vec![Money::ZERO; lines.len()];
+    for promotion in running.iter().filter(|p| p.kind == PromotionKind::BuyOneGetOne) {
+        let mut discount = Money::ZERO;
+        for (line, free) in lines.iter().zip(free_value.iter_mut()) {
+            if free.is_zero() && promotion.eligibility.covers(line.menu_item_id, &line.category) {
+                *free = line.unit_price * (line.quantity / 2);
+                discount += *free;
+            }
+        }
+        if discount.is_positive() {
+            applied.push(AppliedPromotion {
+                promotion_id: promotion.id,
+                name: promotion.name.clone(),
+                discount,
+            });
+        }
+    }
+    let bogo_discount: Money = free_value.iter().sum();
+
+    let best_offer = running
+        .iter()
+        .filter_map(|promotion| {
+            let base: Money = lines
+                .iter()
+                .zip(&free_value)
+                .filter(|(line, _)| promotion.eligibility.covers(line.menu_item_id, &line.category))
+                .map(|(line, free)| line.total() - *free)
+                .sum();
+            let discount = match &promotion.kind {
+                PromotionKind::PercentageOff { percent, max_discount } => {
+                    let discount = base.percentage(*percent);
+                    max_discount.map_or(discount, |max| discount.min(max))
+                }
+                PromotionKind::FlatOff { amount } => (*amount).min(base),
+                PromotionKind::BuyOneGetOne | PromotionKind::FreeDelivery => return None,
+            };
+            discount.is_positive().then_some((promotion, discount))
+        })
+        .max_by_key(|(_, discount)| *discount);
+
+    let mut item_discount = bogo_discount;
+    if let Some((promotion, discount)) = best_offer {
+        item_discount += discount;
+        applied.push(AppliedPromotion {
+            promotion_id: promotion.id,
+            name: promotion.name.clone(),
+            discount,
+        });
+    }
+
+    let free_delivery = running.iter().find(|p| p.kind == PromotionKind::FreeDelivery);
+    if let Some(promotion) = free_delivery {
+        applied.push(AppliedPromotion {
+            promotion_id: promotion.id,
+            name: promotion.name.clone(),
+            discount: delivery_fee,
+        });
+    }
+
+    PromotionSavings {
+        applied,
+        item_discount,
+        free_delivery: free_delivery.is_some(),
+    }
+}
+
+/// Price an order with the restaurant's running promotions and an optional
+/// platform coupon. The coupon applies to the items total after promotion
+/// discounts. When any applied promotion does not stack with coupons, the
+/// customer gets whichever of the two saves more.
+pub fn compute_bill(
+    lines: &[BillLine],
+    promotions: &[Promotion],
+    coupon: Option<&Coupon>,
+    delivery_fee: Money,
+    at: DateTime<Utc>,
+) -> OrderBill {
+    let items_total: Money = lines.iter().map(BillLine::total).sum();
+    let savings = promotion_savings(lines, promotions, delivery_fee, at);
+
+    let mut bill = OrderBill {
+        items_total,
+        applied_promotions: savings.applied,
+        promotion_discount: savings.item_discount,
+        coupon_code: coupon.map(|coupon| coupon.code.clone()),
+        coupon_discount: Money::ZERO,
+        coupon_note: None,
+        delivery_fee: if savings.free_delivery { Money::ZERO } else { delivery_fee },
+        total: Money::ZERO,
+    };
+
+    if let Some(coupon) = coupon {
+        let exclusive = bill.applied_promotions.iter().find(|applied| {
+            promotions
+                .iter()
+                .any(|p| p.id == applied.promotion_id && !p.stackable_with_coupons)
+        });
+
+        match exclusive {
+            None => match coupon.discount_for(items_total - bill.promotion_discount) {
+                Some(discount) => bill.coupon_discount = discount,
+                None => bill.coupon_note = Some(minimum_note(coupon)),
+            },
+            Some(exclusive) => {
+                let promotion_savings = bill.promotion_discount + (delivery_fee - bill.delivery_fee);
+                match coupon.discount_for(items_total) {
+                    Some(discount) if discount > promotion_savings => {
+                        bill.applied_promotions.clear();
+                        bill.promotion_discount = Money::ZERO;
+                        bill.delivery_fee = delivery_fee;
+                        bill.coupon_discount = discount;
+                    }
+                    Some(_) => {
+                        bill.coupon_note = Some(format!(
+                            "Not combined with {}, which saves you more",
+                            exclusive.name
+                        ));
+                    }
+                    None => bill.coupon_note = Some(minimum_note(coupon)),
+                }
+            }
+        }
+    }
+
+    bill.total = items_total - bill.promotion_discount - bill.coupon_discount + bill.delivery_fee;
+    bill
+}
+
+fn minimum_note(coupon: &Coupon) -> String {
+    format!(
+        "{} needs an order of at least {}",
+        coupon.code, coupon.minimum_order_amount
+    )
+}
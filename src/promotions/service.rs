@@ -0,0 +1,299 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::money::Money;
+use crate::promotions::models::{coupon_from_row, promotion_from_row, Coupon, Promotion, PromotionRequest};
+use crate::restaurants::models::{MenuResponse, RestaurantResponse};
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub struct PromotionService {
+    db: Database,
+}
+
+impl PromotionService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// All of a restaurant's promotions, including scheduled and expired ones
+    pub async fn list_promotions(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Vec<Promotion>> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManagePromotions)
+            .await?;
+
+        let rows = sqlx::query(
+            "SELECT * FROM restaurant_promotions WHERE restaurant_id = $1 ORDER BY starts_at DESC, created_at DESC"
+        )
+        .bind(restaurant_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(promotion_from_row).collect()
+    }
+
+    pub async fn create_promotion(&self, restaurant_id: Uuid, user_id: Uuid, request: PromotionRequest) -> Result<Promotion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManagePromotions)
+            .await?;
+        request.validate()?;
+        self.verify_eligible_items(restaurant_id, &request).await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO restaurant_promotions (
+                restaurant_id, name, description, kind, eligibility, min_order,
+                starts_at, ends_at, availability, stackable_with_coupons, is_active
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(request.name.trim())
+        .bind(&request.description)
+        .bind(sqlx::types::Json(&request.kind))
+        .bind(sqlx::types::Json(&request.eligibility))
+        .bind(request.min_order)
+        .bind(request.starts_at.unwrap_or_else(Utc::now))
+        .bind(request.ends_at)
+        .bind(request.availability.as_ref().filter(|a| !a.is_unrestricted()))
+        .bind(request.stackable_with_coupons)
+        .bind(request.is_active)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        promotion_from_row(&row)
+    }
+
+    /// Replace a promotion wholesale with `request`
+    pub async fn update_promotion(
+        &self,
+        restaurant_id: Uuid,
+        promotion_id: Uuid,
+        user_id: Uuid,
+        request: PromotionRequest,
+    ) -> Result<Promotion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManagePromotions)
+            .await?;
+        request.validate()?;
+        self.verify_eligible_items(restaurant_id, &request).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE restaurant_promotions
+            SET name = $3, description = $4, kind = $5, eligibility = $6, min_order = $7,
+                starts_at = COALESCE($8, starts_at), ends_at = $9, availability = $10,
+                stackable_with_coupons = $11, is_active = $12, updated_at = NOW()
+            WHERE id = $1 AND restaurant_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(promotion_id)
+        .bind(restaurant_id)
+        .bind(request.name.trim())
+        .bind(&request.description)
+        .bind(sqlx::types::Json(&request.kind))
+        .bind(sqlx::types::Json(&request.eligibility))
+        .bind(request.min_order)
+        .bind(request.starts_at)
+        .bind(request.ends_at)
+        .bind(request.availability.as_ref().filter(|a| !a.is_unrestricted()))
+        .bind(request.stackable_with_coupons)
+        .bind(request.is_active)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Promotion not found".to_string()))?;
+
+        promotion_from_row(&row)
+    }
+
+    pub async fn delete_promotion(&self, restaurant_id: Uuid, promotion_id: Uuid, user_id: Uuid) -> Result<()> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManagePromotions)
+            .await?;
+
+        let result = sqlx::query("DELETE FROM restaurant_promotions WHERE id = $1 AND restaurant_id = $2")
+            .bind(promotion_id)
+            .bind(restaurant_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Promotion not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Items named in a promotion's eligibility must be on this restaurant's menu
+    async fn verify_eligible_items(&self, restaurant_id: Uuid, request: &PromotionRequest) -> Result<()> {
+        if request.eligibility.item_ids.is_empty() {
+            return Ok(());
+        }
+
+        let found = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM menu_items WHERE restaurant_id = $1 AND id = ANY($2)"
+        )
+        .bind(restaurant_id)
+        .bind(&request.eligibility.item_ids)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut unique = request.eligibility.item_ids.clone();
+        unique.sort();
+        unique.dedup();
+        if found != unique.len() as i64 {
+            return Err(AppError::ValidationError(
+                "Promotion names items that are not on this restaurant's menu".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Promotions running at `at` for each of `restaurant_ids`
+    pub async fn active_promotions(&self, restaurant_ids: &[Uuid], at: DateTime<Utc>) -> Result<HashMap<Uuid, Vec<Promotion>>> {
+        if restaurant_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Dates and the on/off switch are filtered here; recurring hours in Rust
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM restaurant_promotions
+            WHERE restaurant_id = ANY($1) AND is_active = true
+              AND starts_at <= $2 AND (ends_at IS NULL OR ends_at > $2)
+            ORDER BY created_at
+            "#,
+        )
+        .bind(restaurant_ids)
+        .bind(at)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut promotions: HashMap<Uuid, Vec<Promotion>> = HashMap::new();
+        for row in &rows {
+            let promotion = promotion_from_row(row)?;
+            if promotion.is_active_at(at) {
+                promotions.entry(promotion.restaurant_id).or_default().push(promotion);
+            }
+        }
+        Ok(promotions)
+    }
+
+    /// Fill in the offer badges on restaurant cards
+    pub async fn attach_restaurant_badges<'a>(
+        &self,
+        restaurants: impl IntoIterator<Item = &'a mut RestaurantResponse>,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let restaurants: Vec<&mut RestaurantResponse> = restaurants.into_iter().collect();
+        let ids: Vec<Uuid> = restaurants.iter().map(|restaurant| restaurant.id).collect();
+        let mut promotions = self.active_promotions(&ids, at).await?;
+
+        for restaurant in restaurants {
+            restaurant.promotions = promotions
+                .remove(&restaurant.id)
+                .unwrap_or_default()
+                .iter()
+                .map(Promotion::badge)
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Badge the menu items covered by a restaurant's running promotions
+    pub async fn attach_menu_badges(&self, menu: &mut MenuResponse, at: DateTime<Utc>) -> Result<()> {
+        let promotions = self
+            .active_promotions(&[menu.restaurant_id], at)
+            .await?
+            .remove(&menu.restaurant_id)
+            .unwrap_or_default();
+        apply_menu_promotions(menu, &promotions);
+        Ok(())
+    }
+
+    /// A platform coupon usable on an order from `restaurant_id` at `at`
+    pub async fn find_coupon(&self, code: &str, restaurant_id: Uuid, at: DateTime<Utc>) -> Result<Coupon> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM coupons
+            WHERE UPPER(code) = UPPER($1) AND is_active = true
+              AND valid_from <= $2 AND valid_until > $2
+              AND (usage_limit IS NULL OR COALESCE(used_count, 0) < usage_limit)
+              AND (applicable_restaurants IS NULL OR cardinality(applicable_restaurants) = 0
+                   OR $3 = ANY(applicable_restaurants))
+            "#,
+        )
+        .bind(code.trim())
+        .bind(at)
+        .bind(restaurant_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::ValidationError(format!("Coupon {} is not valid for this order", code.trim())))?;
+
+        coupon_from_row(&row)
+    }
+
+    /// Record a coupon against an order, in the order's transaction
+    pub async fn record_coupon_use(
+        tx: &mut Transaction<'_, Postgres>,
+        coupon_id: Uuid,
+        user_id: Uuid,
+        order_id: Uuid,
+        discount: Money,
+    ) -> Result<()> {
+        // Guarded on the limit so that concurrent orders cannot overrun it
+        let claimed = sqlx::query(
+            r#"
+            UPDATE coupons SET used_count = COALESCE(used_count, 0) + 1
+            WHERE id = $1 AND (usage_limit IS NULL OR COALESCE(used_count, 0) < usage_limit)
+            "#,
+        )
+        .bind(coupon_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if claimed.rows_affected() == 0 {
+            return Err(AppError::ValidationError("Coupon usage limit reached".to_string()));
+        }
+
+        sqlx::query(
+            "INSERT INTO coupon_usage (coupon_id, user_id, order_id, discount_amount) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(coupon_id)
+        .bind(user_id)
+        .bind(order_id)
+        .bind(discount)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Put badges for item-level promotions on the menu items they cover.
+/// `promotions` should already be narrowed to those running now.
+pub fn apply_menu_promotions(menu: &mut MenuResponse, promotions: &[Promotion]) {
+    for category in &mut menu.categories {
+        for item in &mut category.items {
+            item.promotions = promotions
+                .iter()
+                .filter(|promotion| promotion.kind.is_item_level())
+                .filter(|promotion| promotion.eligibility.covers(item.id, &item.category))
+                .map(Promotion::badge)
+                .collect();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::promotions::models::*;
+    use crate::promotions::pricing::*;
+    use crate::promotions::service::apply_menu_promotions;
+    use crate::restaurants::availability::MenuAvailability;
+    use crate::restaurants::models::{MenuCategory, MenuItemResponse, MenuResponse};
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use uuid::Uuid;
+
+    /// January 2024 in IST; the 1st is a Monday
+    fn ist(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(5 * 3600 + 30 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn promotion(kind: PromotionKind) -> Promotion {
+        Promotion {
+            id: Uuid::new_v4(),
+            restaurant_id: Uuid::nil(),
+            name: "Offer".to_string(),
+            description: None,
+            kind,
+            eligibility: PromotionEligibility::default(),
+            min_order: None,
+            starts_at: ist(1, 0, 0),
+            ends_at: None,
+            availability: None,
+            stackable_with_coupons: true,
+            is_active: true,
+            created_at: ist(1, 0, 0),
+            updated_at: ist(1, 0, 0),
+        }
+    }
+
+    fn percentage_off(percent: f64) -> PromotionKind {
+        PromotionKind::PercentageOff { percent, max_discount: None }
+    }
+
+    fn line(category: &str, quantity: u32, rupees: i64) -> BillLine {
+        BillLine {
+            menu_item_id: Uuid::new_v4(),
+            category: category.to_string(),
+            quantity,
+            unit_price: Money::from_rupees(rupees),
+        }
+    }
+
+    fn coupon(discount: CouponDiscount, minimum_rupees: i64) -> Coupon {
+        Coupon {
+            id: Uuid::new_v4(),
+            code: "SAVE50".to_string(),
+            discount,
+            minimum_order_amount: Money::from_rupees(minimum_rupees),
+            maximum_discount_amount: None,
+        }
+    }
+
+    #[test]
+    fn test_happy_hour_only_applies_inside_its_window() {
+        let mut happy_hour = promotion(percentage_off(20.0));
+        happy_hour.availability = Some(
+            serde_json::from_value(serde_json::json!({
+                "windows": [{ "open": "15:00", "close": "17:00" }]
+            }))
+            .unwrap(),
+        );
+        let lines = [line("Mains", 2, 250)];
+        let fee = Money::from_rupees(40);
+
+        let during = compute_bill(&lines, std::slice::from_ref(&happy_hour), None, fee, ist(2, 16, 0));
+        assert_eq!(during.promotion_discount, Money::from_rupees(100));
+        assert_eq!(during.total, Money::from_rupees(440));
+
+        let after = compute_bill(&lines, std::slice::from_ref(&happy_hour), None, fee, ist(2, 17, 0));
+        assert!(after.applied_promotions.is_empty());
+        assert_eq!(after.total, Money::from_rupees(540));
+
+        // Switched off or past its end date, it never applies
+        happy_hour.ends_at = Some(ist(2, 0, 0));
+        assert!(!happy_hour.is_active_at(ist(2, 16, 0)));
+    }
+
+    #[test]
+    fn test_buy_one_get_one_frees_every_second_unit() {
+        let mut bogo = promotion(PromotionKind::BuyOneGetOne);
+        let pizza = line("Pizza", 3, 300);
+        let drink = line("Drinks", 2, 60);
+        bogo.eligibility.item_ids = vec![pizza.menu_item_id];
+
+        let bill = compute_bill(&[pizza, drink], &[bogo], None, Money::ZERO, ist(2, 12, 0));
+        assert_eq!(bill.items_total, Money::from_rupees(1020));
+        assert_eq!(bill.promotion_discount, Money::from_rupees(300));
+        assert_eq!(bill.total, Money::from_rupees(720));
+    }
+
+    #[test]
+    fn test_best_single_offer_applies_after_free_units() {
+        let mut bogo = promotion(PromotionKind::BuyOneGetOne);
+        bogo.eligibility.categories = vec!["pizza".to_string()];
+        let ten_percent = promotion(percentage_off(10.0));
+        let flat = promotion(PromotionKind::FlatOff { amount: Money::from_rupees(50) });
+
+        // ₹400 of paid items after the free pizza: 10% is ₹40, so the flat ₹50 wins
+        let lines = [line("Pizza", 2, 300), line("Sides", 1, 100)];
+        let bill = compute_bill(&lines, &[bogo, ten_percent, flat.clone()], None, Money::ZERO, ist(2, 12, 0));
+        assert_eq!(bill.applied_promotions.len(), 2);
+        assert_eq!(bill.applied_promotions[1].promotion_id, flat.id);
+        assert_eq!(bill.promotion_discount, Money::from_rupees(350));
+    }
+
+    #[test]
+    fn test_free_delivery_above_minimum() {
+        let mut free_delivery = promotion(PromotionKind::FreeDelivery);
+        free_delivery.min_order = Some(Money::from_rupees(199));
+        assert_eq!(free_delivery.badge().label, "Free delivery above ₹199");
+        let fee = Money::from_rupees(30);
+
+        let small = compute_bill(&[line("Mains", 1, 150)], std::slice::from_ref(&free_delivery), None, fee, ist(2, 12, 0));
+        assert_eq!(small.delivery_fee, fee);
+        assert_eq!(small.total, Money::from_rupees(180));
+
+        let large = compute_bill(&[line("Mains", 2, 150)], std::slice::from_ref(&free_delivery), None, fee, ist(2, 12, 0));
+        assert_eq!(large.delivery_fee, Money::ZERO);
+        assert_eq!(large.promotion_discount, Money::ZERO);
+        assert_eq!(large.total, Money::from_rupees(300));
+    }
+
+    #[test]
+    fn test_coupon_stacks_on_discounted_total() {
+        let promo = promotion(percentage_off(20.0));
+        let coupon = coupon(CouponDiscount::Percentage(10.0), 0);
+
+        let bill = compute_bill(&[line("Mains", 1, 500)], &[promo], Some(&coupon), Money::ZERO, ist(2, 12, 0));
+        assert_eq!(bill.promotion_discount, Money::from_rupees(100));
+        assert_eq!(bill.coupon_discount, Money::from_rupees(40));
+        assert_eq!(bill.total, Money::from_rupees(360));
+        assert!(bill.coupon_note.is_none());
+    }
+
+    #[test]
+    fn test_non_stackable_promotion_gives_the_better_of_the_two() {
+        let mut promo = promotion(percentage_off(20.0));
+        promo.stackable_with_coupons = false;
+        let lines = [line("Mains", 1, 500)];
+
+        // ₹100 off beats a ₹50 coupon; the coupon is reported as not applied
+        let small_coupon = coupon(CouponDiscount::FixedAmount(Money::from_rupees(50)), 0);
+        let bill = compute_bill(&lines, std::slice::from_ref(&promo), Some(&small_coupon), Money::ZERO, ist(2, 12, 0));
+        assert_eq!(bill.promotion_discount, Money::from_rupees(100));
+        assert_eq!(bill.coupon_discount, Money::ZERO);
+        assert!(bill.coupon_note.is_some());
+
+        // A ₹150 coupon beats the promotion, which is then dropped
+        let big_coupon = coupon(CouponDiscount::FixedAmount(Money::from_rupees(150)), 0);
+        let bill = compute_bill(&lines, &[promo], Some(&big_coupon), Money::ZERO, ist(2, 12, 0));
+        assert!(bill.applied_promotions.is_empty());
+        assert_eq!(bill.coupon_discount, Money::from_rupees(150));
+        assert_eq!(bill.total, Money::from_rupees(350));
+    }
+
+    #[test]
+    fn test_coupon_minimum_and_cap() {
+        let mut capped = coupon(CouponDiscount::Percentage(50.0), 200);
+        capped.maximum_discount_amount = Some(Money::from_rupees(75));
+        assert_eq!(capped.discount_for(Money::from_rupees(199)), None);
+        assert_eq!(capped.discount_for(Money::from_rupees(400)), Some(Money::from_rupees(75)));
+
+        // A flat coupon never takes the items below zero
+        let flat = coupon(CouponDiscount::FixedAmount(Money::from_rupees(100)), 0);
+        let bill = compute_bill(&[line("Drinks", 1, 60)], &[], Some(&flat), Money::from_rupees(20), ist(2, 12, 0));
+        assert_eq!(bill.coupon_discount, Money::from_rupees(60));
+        assert_eq!(bill.total, Money::from_rupees(20));
+    }
+
+    #[test]
+    fn test_promotion_request_validation() {
+        let request: PromotionRequest = serde_json::from_value(serde_json::json!({
+            "name": "Happy hour",
+            "kind": { "type": "percentage_off", "percent": 20.0, "max_discount": "100.00" }
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+        assert!(request.stackable_with_coupons && request.is_active);
+
+        let invalid = [
+            serde_json::json!({ "name": "", "kind": { "type": "buy_one_get_one" } }),
+            serde_json::json!({ "name": "Too much", "kind": { "type": "percentage_off", "percent": 120.0 } }),
+            serde_json::json!({ "name": "Nothing", "kind": { "type": "flat_off", "amount": "0.00" } }),
+            serde_json::json!({
+                "name": "Pizza delivery",
+                "kind": { "type": "free_delivery" },
+                "eligibility": { "categories": ["Pizza"] }
+            }),
+            serde_json::json!({
+                "name": "Backwards",
+                "kind": { "type": "buy_one_get_one" },
+                "starts_at": "2024-02-02T00:00:00Z",
+                "ends_at": "2024-02-01T00:00:00Z"
+            }),
+        ];
+        for json in invalid {
+            let request: PromotionRequest = serde_json::from_value(json.clone()).unwrap();
+            assert!(request.validate().is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_menu_badges_only_on_covered_items() {
+        let item = |name: &str, category: &str| -> MenuItemResponse {
+            serde_json::from_value(serde_json::json!({
+                "id": Uuid::new_v4(),
+                "restaurant_id": Uuid::nil(),
+                "name": name,
+                "description": null,
+                "category": category,
+                "price": "199.00",
+                "image_url": null,
+                "is_vegetarian": true,
+                "is_vegan": false,
+                "is_gluten_free": false,
+                "spice_level": 1,
+                "ingredients": null,
+                "allergens": null,
+                "is_available": true,
+                "preparation_time_minutes": 15,
+                "calories": null,
+                "external_sku": null,
+                "stock_quantity": null,
+                "daily_stock": null,
+                "modifier_groups": [],
+                "created_at": ist(1, 0, 0),
+                "updated_at": ist(1, 0, 0)
+            }))
+            .unwrap()
+        };
+        let mut menu = MenuResponse {
+            restaurant_id: Uuid::nil(),
+            categories: vec![
+                MenuCategory {
+                    name: "Pizza".to_string(),
+                    has_customizations: false,
+                    availability: None,
+                    items: vec![item("Margherita", "Pizza")],
+                },
+                MenuCategory {
+                    name: "Drinks".to_string(),
+                    has_customizations: false,
+                    availability: Some(MenuAvailability::default()),
+                    items: vec![item("Lassi", "Drinks")],
+                },
+            ],
+        };
+
+        let mut bogo = promotion(PromotionKind::BuyOneGetOne);
+        bogo.eligibility.categories = vec!["Pizza".to_string()];
+        // Order-level offers are shown on the restaurant, not on items
+        let free_delivery = promotion(PromotionKind::FreeDelivery);
+        apply_menu_promotions(&mut menu, &[bogo, free_delivery]);
+
+        let pizza = &menu.categories[0].items[0];
+        assert_eq!(pizza.promotions.len(), 1);
+        assert_eq!(pizza.promotions[0].label, "Buy 1 Get 1");
+        assert!(menu.categories[1].items[0].promotions.is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::error::{AppError, Result};
+use crate::india::config::ISTConfig;
+use crate::restaurants::models::MenuResponse;
+use crate::restaurants::schedule::Shift;
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// When a menu item or category can be ordered, in IST. `days` limits it to
+/// some days of the week and `windows` to some times of day; leaving either
+/// empty means no limit on that axis. A window that crosses midnight belongs
+/// to the day it starts on, like restaurant shifts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MenuAvailability {
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    #[serde(default)]
+    pub windows: Vec<Shift>,
+}
+
+impl MenuAvailability {
+    /// No day or time limits, i.e. always available
+    pub fn is_unrestricted(&self) -> bool {
+        self.days.is_empty() && self.windows.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        if let Some(day) = self.days.iter().find(|day| !seen.insert(**day)) {
+            return Err(AppError::ValidationError(format!(
+                "{} is listed twice in the availability days",
+                day
+            )));
+        }
+        Ok(())
+    }
+
+    fn on_day(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    pub fn is_available_at(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&ISTConfig::default().offset()).naive_local();
+        let today = local.date().weekday();
+        let yesterday = (local.date() - Duration::days(1)).weekday();
+        let time = local.time();
+
+        if self.windows.is_empty() {
+            return self.on_day(today);
+        }
+
+        self.windows.iter().any(|window| {
+            if window.crosses_midnight() {
+                (time >= window.open && self.on_day(today)) || (time < window.close && self.on_day(yesterday))
+            } else {
+                window.open <= time && time < window.close && self.on_day(today)
+            }
+        })
+    }
+}
+
+/// Whether an item with its own and its category's availability can be
+/// ordered at `at`; both have to allow it
+pub fn is_orderable_at(
+    item: Option<&MenuAvailability>,
+    category: Option<&MenuAvailability>,
+    at: DateTime<Utc>,
+) -> bool {
+    [item, category]
+        .into_iter()
+        .flatten()
+        .all(|availability| availability.is_available_at(at))
+}
+
+/// Mark items outside their availability window at `at` as unavailable, or
+/// drop them (and categories left empty) when `hide_unavailable` is set.
+/// The cached menu is time-independent; this runs on every read.
+pub fn apply_menu_availability(menu: &mut MenuResponse, at: DateTime<Utc>, hide_unavailable: bool) {
+    for category in &mut menu.categories {
+        for item in &mut category.items {
+            if !is_orderable_at(item.availability.as_ref(), category.availability.as_ref(), at) {
+                item.is_available = false;
+            }
+        }
+        if hide_unavailable {
+            category.items.retain(|item| item.is_available);
+        }
+    }
+    if hide_unavailable {
+        menu.categories.retain(|category| !category.items.is_empty());
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for MenuAvailability {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for MenuAvailability {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<&Self> as sqlx::Encode<sqlx::Postgres>>::encode(sqlx::types::Json(self), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for MenuAvailability {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        // Malformed JSON leaves the item unrestricted rather than failing the menu
+        let raw = <serde_json::Value as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetCategoryAvailabilityRequest {
+    /// `null` or an empty object removes the category's limits
+    pub availability: Option<MenuAvailability>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MenuQuery {
+    /// Leave out items that cannot be ordered right now instead of marking them
+    #[serde(default)]
+    pub hide_unavailable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MenuPreviewQuery {
+    /// Time to preview the menu at; defaults to now
+    pub at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub hide_unavailable: bool,
+}
@@ -0,0 +1,404 @@
+use crate::database::Database;
+use crate::delivery::websocket::DeliveryWebSocketManager;
+use crate::error::{AppError, Result};
+use crate::restaurants::models::Restaurant;
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+const MAX_EXTRA_PREP_MINUTES: i32 = 90;
+const MAX_OPEN_ORDERS: i32 = 200;
+const MIN_BUSY_MINUTES: i32 = 5;
+const MAX_BUSY_MINUTES: i32 = 240;
+
+fn default_extra_prep_minutes() -> i32 {
+    15
+}
+
+fn default_busy_minutes() -> i32 {
+    30
+}
+
+/// Open orders: placed and not yet handed to a rider
+const OPEN_ORDER_STATUSES: &str = "('placed', 'confirmed', 'preparing')";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusySource {
+    /// Turned on by staff
+    Manual,
+    /// Turned on because open orders reached the restaurant's threshold
+    Auto,
+}
+
+/// A spell of busy mode. It lapses at `expires_at` without anyone having to
+/// turn it off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusyMode {
+    pub source: BusySource,
+    /// Added to every prep time estimate while busy
+    pub extra_prep_minutes: i32,
+    /// New orders are refused while this many are open; 0 pauses ordering
+    pub max_open_orders: Option<i32>,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl BusyMode {
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.expires_at > at
+    }
+
+    /// Whether no new orders are taken at all
+    pub fn pauses_orders(&self) -> bool {
+        self.max_open_orders == Some(0)
+    }
+
+    /// Whether one more order fits alongside `open_orders`
+    pub fn admits(&self, open_orders: i64) -> bool {
+        self.max_open_orders.is_none_or(|cap| open_orders < cap as i64)
+    }
+}
+
+/// The spell in force at `at`, if any
+pub fn active_busy_mode(mode: &Option<BusyMode>, at: DateTime<Utc>) -> Option<&BusyMode> {
+    mode.as_ref().filter(|mode| mode.is_active_at(at))
+}
+
+fn validate_limits(extra_prep_minutes: i32, max_open_orders: Option<i32>, duration_minutes: i32) -> Result<()> {
+    if !(0..=MAX_EXTRA_PREP_MINUTES).contains(&extra_prep_minutes) {
+        return Err(AppError::ValidationError(format!(
+            "Extra prep time must be between 0 and {} minutes",
+            MAX_EXTRA_PREP_MINUTES
+        )));
+    }
+    if max_open_orders.is_some_and(|cap| !(0..=MAX_OPEN_ORDERS).contains(&cap)) {
+        return Err(AppError::ValidationError(format!(
+            "Order cap must be between 0 and {}",
+            MAX_OPEN_ORDERS
+        )));
+    }
+    if !(MIN_BUSY_MINUTES..=MAX_BUSY_MINUTES).contains(&duration_minutes) {
+        return Err(AppError::ValidationError(format!(
+            "Busy mode lasts between {} and {} minutes",
+            MIN_BUSY_MINUTES, MAX_BUSY_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// When busy mode turns itself on, and what it does then
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusySettings {
+    /// Open orders at which busy mode turns on by itself; `None` leaves it to staff
+    #[serde(default)]
+    pub auto_threshold: Option<i32>,
+    #[serde(default = "default_extra_prep_minutes")]
+    pub extra_prep_minutes: i32,
+    #[serde(default)]
+    pub max_open_orders: Option<i32>,
+    #[serde(default = "default_busy_minutes")]
+    pub duration_minutes: i32,
+}
+
+impl Default for BusySettings {
+    fn default() -> Self {
+        Self {
+            auto_threshold: None,
+            extra_prep_minutes: default_extra_prep_minutes(),
+            max_open_orders: None,
+            duration_minutes: default_busy_minutes(),
+        }
+    }
+}
+
+impl BusySettings {
+    pub fn validate(&self) -> Result<()> {
+        if self.auto_threshold.is_some_and(|threshold| !(1..=MAX_OPEN_ORDERS).contains(&threshold)) {
+            return Err(AppError::ValidationError(format!(
+                "Busy threshold must be between 1 and {} open orders",
+                MAX_OPEN_ORDERS
+            )));
+        }
+        validate_limits(self.extra_prep_minutes, self.max_open_orders, self.duration_minutes)
+    }
+
+    fn auto_mode(&self, now: DateTime<Utc>) -> BusyMode {
+        BusyMode {
+            source: BusySource::Auto,
+            extra_prep_minutes: self.extra_prep_minutes,
+            max_open_orders: self.max_open_orders,
+            started_at: now,
+            expires_at: now + Duration::minutes(self.duration_minutes as i64),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartBusyModeRequest {
+    #[serde(default = "default_extra_prep_minutes")]
+    pub extra_prep_minutes: i32,
+    pub max_open_orders: Option<i32>,
+    #[serde(default = "default_busy_minutes")]
+    pub duration_minutes: i32,
+}
+
+/// What the monitor should do with a restaurant's busy mode on this pass
+#[derive(Debug, Clone, PartialEq)]
+pub enum BusyAction {
+    Keep,
+    Start(BusyMode),
+    /// The spell lapsed and the kitchen has caught up
+    End,
+}
+
+/// A running spell is left alone, so staff who turned busy mode on or off
+/// by hand are not overridden until it lapses. Once it has, busy mode comes
+/// back on by itself while open orders are still at the threshold.
+pub fn busy_action(settings: &BusySettings, current: Option<&BusyMode>, open_orders: i64, now: DateTime<Utc>) -> BusyAction {
+    if current.is_some_and(|mode| mode.is_active_at(now)) {
+        return BusyAction::Keep;
+    }
+    if settings.auto_threshold.is_some_and(|threshold| open_orders >= threshold as i64) {
+        return BusyAction::Start(settings.auto_mode(now));
+    }
+    match current {
+        Some(_) => BusyAction::End,
+        None => BusyAction::Keep,
+    }
+}
+
+pub struct BusyModeService {
+    db: Database,
+}
+
+impl BusyModeService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn start(&self, restaurant_id: Uuid, user_id: Uuid, request: StartBusyModeRequest) -> Result<Restaurant> {
+        validate_limits(request.extra_prep_minutes, request.max_open_orders, request.duration_minutes)?;
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageAvailability)
+            .await?;
+
+        let now = Utc::now();
+        let mode = BusyMode {
+            source: BusySource::Manual,
+            extra_prep_minutes: request.extra_prep_minutes,
+            max_open_orders: request.max_open_orders,
+            started_at: now,
+            expires_at: now + Duration::minutes(request.duration_minutes as i64),
+        };
+        self.set_busy_mode(restaurant_id, Some(&mode)).await
+    }
+
+    pub async fn end(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Restaurant> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageAvailability)
+            .await?;
+
+        self.set_busy_mode(restaurant_id, None).await
+    }
+
+    pub async fn open_orders(executor: impl sqlx::PgExecutor<'_>, restaurant_id: Uuid) -> Result<i64> {
+        sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM orders WHERE restaurant_id = $1 AND status IN {}",
+            OPEN_ORDER_STATUSES
+        ))
+        .bind(restaurant_id)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse a new order while busy mode caps or pauses ordering. Locks the
+    /// restaurant row so concurrent orders are counted one at a time; run it
+    /// in the transaction that inserts the order.
+    pub async fn check_capacity(conn: &mut PgConnection, restaurant_id: Uuid, now: DateTime<Utc>) -> Result<()> {
+        let row = sqlx::query("SELECT name, busy_mode FROM restaurants WHERE id = $1 FOR UPDATE")
+            .bind(restaurant_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
+        let busy_mode: Option<BusyMode> = row.get("busy_mode");
+
+        let Some(mode) = active_busy_mode(&busy_mode, now) else {
+            return Ok(());
+        };
+        if mode.max_open_orders.is_none() {
+            return Ok(());
+        }
+        if !mode.pauses_orders() && mode.admits(Self::open_orders(&mut *conn, restaurant_id).await?) {
+            return Ok(());
+        }
+
+        let name: String = row.get("name");
+        let minutes = (mode.expires_at - now).num_minutes().max(1);
+        Err(AppError::ValidationError(format!(
+            "{} is too busy for new orders. Try again in {} minutes",
+            name, minutes
+        )))
+    }
+
+    /// Start or end automatic busy mode for one restaurant as its open
+    /// orders call for. Returns the restaurant when its busy state changed.
+    pub async fn refresh(&self, restaurant_id: Uuid, now: DateTime<Utc>) -> Result<Option<Restaurant>> {
+        let restaurant = RestaurantService::new(self.db.clone())
+            .find_restaurant(restaurant_id)
+            .await?;
+        let open_orders = match restaurant.busy_settings.auto_threshold {
+            Some(_) => Self::open_orders(self.db.pool(), restaurant_id).await?,
+            None => 0,
+        };
+
+        match busy_action(&restaurant.busy_settings, restaurant.busy_mode.as_ref(), open_orders, now) {
+            BusyAction::Keep => Ok(None),
+            BusyAction::Start(mode) => self.set_busy_mode(restaurant_id, Some(&mode)).await.map(Some),
+            BusyAction::End => self.set_busy_mode(restaurant_id, None).await.map(Some),
+        }
+    }
+
+    /// `refresh` every restaurant that is busy or can become busy by itself
+    pub async fn refresh_all(&self, now: DateTime<Utc>) -> Result<Vec<Restaurant>> {
+        let restaurant_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM restaurants
+            WHERE deleted_at IS NULL
+              AND (busy_mode IS NOT NULL OR busy_settings->>'auto_threshold' IS NOT NULL)
+            "#,
+        )
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut changed = Vec::new();
+        for restaurant_id in restaurant_ids {
+            match self.refresh(restaurant_id, now).await {
+                Ok(Some(restaurant)) => changed.push(restaurant),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Busy mode check failed for restaurant {}: {:?}", restaurant_id, e),
+            }
+        }
+        Ok(changed)
+    }
+
+    async fn set_busy_mode(&self, restaurant_id: Uuid, mode: Option<&BusyMode>) -> Result<Restaurant> {
+        sqlx::query_as::<_, Restaurant>(
+            "UPDATE restaurants SET busy_mode = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL RETURNING *"
+        )
+        .bind(mode)
+        .bind(restaurant_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))
+    }
+}
+
+/// Tell the kitchen and anyone viewing the restaurant that it became busy or
+/// calmed down
+pub async fn publish_busy_change(websocket_manager: &DeliveryWebSocketManager, restaurant: &Restaurant) {
+    let busy = active_busy_mode(&restaurant.busy_mode, Utc::now()).cloned();
+    let is_accepting_orders = restaurant.is_accepting_orders && !busy.as_ref().is_some_and(BusyMode::pauses_orders);
+    // No subscribers is not an error worth reporting
+    let _ = websocket_manager
+        .broadcast_busy_update(restaurant.id, busy, is_accepting_orders)
+        .await;
+}
+
+/// Background job that starts automatic busy mode and lets spells lapse
+pub struct BusyModeMonitor {
+    service: BusyModeService,
+    websocket_manager: DeliveryWebSocketManager,
+    interval: std::time::Duration,
+}
+
+impl BusyModeMonitor {
+    pub fn new(db: Database, websocket_manager: DeliveryWebSocketManager, interval: std::time::Duration) -> Self {
+        Self {
+            service: BusyModeService::new(db),
+            websocket_manager,
+            interval,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(changed) => tracing::info!("Busy mode changed for {} restaurants", changed),
+                    Err(e) => tracing::error!("Busy mode check failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        let changed = self.service.refresh_all(Utc::now()).await?;
+        for restaurant in &changed {
+            publish_busy_change(&self.websocket_manager, restaurant).await;
+        }
+        Ok(changed.len())
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for BusyMode {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for BusyMode {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<&Self> as sqlx::Encode<sqlx::Postgres>>::encode(sqlx::types::Json(self), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for BusyMode {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        Ok(<sqlx::types::Json<Self> as sqlx::Decode<sqlx::Postgres>>::decode(value)?.0)
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for BusySettings {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for BusySettings {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<&Self> as sqlx::Encode<sqlx::Postgres>>::encode(sqlx::types::Json(self), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for BusySettings {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        // Malformed settings mean "defaults" rather than a failed query
+        let raw = <serde_json::Value as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
+}
@@ -0,0 +1,501 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::restaurants::menu_import::MenuImportItem;
+use crate::restaurants::models::{MenuItem, MenuItemResponse, MenuResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Most allergens a profile can list
+const MAX_AVOIDED_ALLERGENS: usize = 20;
+
+/// Onion, garlic and root vegetables, which Jain food leaves out, with
+/// their common Hindi names
+const JAIN_EXCLUDED_INGREDIENTS: &[&str] = &[
+    "onion", "garlic", "potato", "carrot", "beetroot", "beet", "radish", "ginger", "turnip", "yam",
+    "pyaz", "pyaaz", "kanda", "lehsun", "lahsun", "aloo", "alu", "gajar", "mooli", "adrak", "arbi",
+    "shakarkandi", "jimikand",
+];
+
+const EGG_INGREDIENTS: &[&str] = &["egg", "anda", "omelette", "omelet"];
+
+/// Diets a customer can follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Diet {
+    Vegetarian,
+    Vegan,
+    /// Vegetarian without egg, onion, garlic or root vegetables
+    Jain,
+    /// No egg; meat and fish are still fine
+    EggFree,
+    GlutenFree,
+}
+
+impl Diet {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Diet::Vegetarian => "vegetarian",
+            Diet::Vegan => "vegan",
+            Diet::Jain => "jain",
+            Diet::EggFree => "egg_free",
+            Diet::GlutenFree => "gluten_free",
+        }
+    }
+
+    /// Whether a dish is suitable for the diet
+    pub fn allows(&self, dish: &DishFacts) -> bool {
+        match self {
+            Diet::Vegetarian => dish.is_vegetarian,
+            Diet::Vegan => dish.is_vegan,
+            Diet::Jain => {
+                dish.is_pure_veg()
+                    && !dish
+                        .ingredients
+                        .iter()
+                        .any(|ingredient| mentions_any(ingredient, JAIN_EXCLUDED_INGREDIENTS))
+            }
+            Diet::EggFree => !dish.contains_egg(),
+            Diet::GlutenFree => dish.is_gluten_free,
+        }
+    }
+}
+
+impl std::str::FromStr for Diet {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "vegetarian" => Ok(Diet::Vegetarian),
+            "vegan" => Ok(Diet::Vegan),
+            "jain" => Ok(Diet::Jain),
+            "egg_free" => Ok(Diet::EggFree),
+            "gluten_free" => Ok(Diet::GlutenFree),
+            _ => Err(format!("Invalid diet: {}", s)),
+        }
+    }
+}
+
+/// Whether any word of `text` is one of `terms`, allowing plurals, so
+/// "Spring onions" mentions onion but "eggless" does not mention egg
+fn mentions_any(text: &str, terms: &[&str]) -> bool {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .any(|word| {
+            terms.iter().any(|term| {
+                word == *term
+                    || word.strip_suffix('s').is_some_and(|stem| stem == *term || stem.strip_suffix('e') == Some(*term))
+            })
+        })
+}
+
+/// One name per allergen, so "Peanuts", "groundnut" and "peanut" all match
+pub fn canonical_allergen(name: &str) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    match name.as_str() {
+        "milk" | "lactose" | "dairy" => "dairy".to_string(),
+        "peanut" | "peanuts" | "groundnut" | "groundnuts" => "peanut".to_string(),
+        "nut" | "nuts" | "tree nut" | "tree nuts" => "tree nut".to_string(),
+        "egg" | "eggs" => "egg".to_string(),
+        "wheat" | "gluten" => "gluten".to_string(),
+        "soy" | "soya" | "soybean" | "soybeans" => "soy".to_string(),
+        "sesame" | "til" => "sesame".to_string(),
+        "shellfish" | "prawn" | "prawns" | "shrimp" | "crab" => "shellfish".to_string(),
+        _ => match name.strip_suffix('s') {
+            Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => name,
+        },
+    }
+}
+
+/// The dietary details of a dish, borrowed from whichever model carries them
+#[derive(Debug, Clone, Copy)]
+pub struct DishFacts<'a> {
+    pub is_vegetarian: bool,
+    pub is_vegan: bool,
+    pub is_gluten_free: bool,
+    pub spice_level: i32,
+    pub ingredients: &'a [String],
+    pub allergens: &'a [String],
+}
+
+impl DishFacts<'_> {
+    pub fn contains_egg(&self) -> bool {
+        self.allergens.iter().any(|allergen| canonical_allergen(allergen) == "egg")
+            || self
+                .ingredients
+                .iter()
+                .any(|ingredient| mentions_any(ingredient, EGG_INGREDIENTS))
+    }
+
+    /// Vegetarian in the Indian sense, which leaves out egg
+    pub fn is_pure_veg(&self) -> bool {
+        self.is_vegetarian && !self.contains_egg()
+    }
+}
+
+impl<'a> From<&'a MenuItem> for DishFacts<'a> {
+    fn from(item: &'a MenuItem) -> Self {
+        Self {
+            is_vegetarian: item.is_vegetarian,
+            is_vegan: item.is_vegan,
+            is_gluten_free: item.is_gluten_free,
+            spice_level: item.spice_level,
+            ingredients: item.ingredients.as_deref().unwrap_or_default(),
+            allergens: item.allergens.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+impl<'a> From<&'a MenuItemResponse> for DishFacts<'a> {
+    fn from(item: &'a MenuItemResponse) -> Self {
+        Self {
+            is_vegetarian: item.is_vegetarian,
+            is_vegan: item.is_vegan,
+            is_gluten_free: item.is_gluten_free,
+            spice_level: item.spice_level,
+            ingredients: item.ingredients.as_deref().unwrap_or_default(),
+            allergens: item.allergens.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+impl<'a> From<&'a MenuImportItem> for DishFacts<'a> {
+    fn from(item: &'a MenuImportItem) -> Self {
+        Self {
+            is_vegetarian: item.is_vegetarian,
+            is_vegan: item.is_vegan,
+            is_gluten_free: item.is_gluten_free,
+            spice_level: item.spice_level,
+            ingredients: &item.ingredients,
+            allergens: &item.allergens,
+        }
+    }
+}
+
+/// Why a dish does not suit a dietary profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DietaryConflict {
+    Diet { diet: Diet },
+    Allergen { allergen: String },
+    Spice { spice_level: i32 },
+}
+
+impl std::fmt::Display for DietaryConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DietaryConflict::Diet { diet } => write!(f, "is not {}", diet.as_str().replace('_', "-")),
+            DietaryConflict::Allergen { allergen } => write!(f, "contains {}", allergen),
+            DietaryConflict::Spice { spice_level } => write!(f, "has spice level {}", spice_level),
+        }
+    }
+}
+
+/// What a customer eats. Saved per user and applied to menus, search and
+/// checkout.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DietaryProfile {
+    #[serde(default)]
+    pub diets: Vec<Diet>,
+    #[serde(default)]
+    pub avoid_allergens: Vec<String>,
+    /// Hottest dish wanted, on the menu's 0-5 scale
+    #[serde(default)]
+    pub max_spice_level: Option<i32>,
+    /// Only show restaurants certified pure veg
+    #[serde(default)]
+    pub pure_veg_only: bool,
+}
+
+impl DietaryProfile {
+    pub fn is_empty(&self) -> bool {
+        self.diets.is_empty() && self.avoid_allergens.is_empty() && self.max_spice_level.is_none() && !self.pure_veg_only
+    }
+
+    /// Validate the profile and put it in canonical form: diets sorted and
+    /// allergens under their canonical names, without duplicates
+    pub fn normalized(mut self) -> Result<Self> {
+        if let Some(level) = self.max_spice_level {
+            if !(0..=5).contains(&level) {
+                return Err(AppError::ValidationError("max_spice_level must be between 0 and 5".to_string()));
+            }
+        }
+
+        self.diets.sort();
+        self.diets.dedup();
+
+        let mut allergens: Vec<String> = self
+            .avoid_allergens
+            .iter()
+            .map(|allergen| canonical_allergen(allergen))
+            .filter(|allergen| !allergen.is_empty())
+            .collect();
+        allergens.sort();
+        allergens.dedup();
+        if allergens.len() > MAX_AVOIDED_ALLERGENS {
+            return Err(AppError::ValidationError(format!(
+                "At most {} allergens can be avoided",
+                MAX_AVOIDED_ALLERGENS
+            )));
+        }
+        self.avoid_allergens = allergens;
+
+        Ok(self)
+    }
+
+    /// Every way the dish breaks the profile; empty when it suits
+    pub fn conflicts(&self, dish: &DishFacts) -> Vec<DietaryConflict> {
+        let mut conflicts: Vec<DietaryConflict> = self
+            .diets
+            .iter()
+            .filter(|diet| !diet.allows(dish))
+            .map(|diet| DietaryConflict::Diet { diet: *diet })
+            .collect();
+
+        let dish_allergens: Vec<String> = dish.allergens.iter().map(|allergen| canonical_allergen(allergen)).collect();
+        for allergen in &self.avoid_allergens {
+            let listed = dish_allergens.contains(allergen)
+                || (allergen == "egg" && dish.contains_egg());
+            if listed {
+                conflicts.push(DietaryConflict::Allergen { allergen: allergen.clone() });
+            }
+        }
+
+        if let Some(max) = self.max_spice_level {
+            if dish.spice_level > max {
+                conflicts.push(DietaryConflict::Spice { spice_level: dish.spice_level });
+            }
+        }
+        conflicts
+    }
+}
+
+/// A dietary profile as applied to one read, and whether conflicting dishes
+/// are dropped or only flagged
+#[derive(Debug, Clone)]
+pub struct DietaryFilter {
+    pub profile: DietaryProfile,
+    pub hide_conflicts: bool,
+}
+
+/// Flag each item that conflicts with the profile, or drop it (and
+/// categories left empty) when `hide_conflicts` is set. Like availability,
+/// this runs on every read of the cached menu.
+pub fn apply_dietary_filter(menu: &mut MenuResponse, filter: &DietaryFilter) {
+    for category in &mut menu.categories {
+        for item in &mut category.items {
+            item.dietary_conflicts = filter.profile.conflicts(&DishFacts::from(&*item));
+        }
+        if filter.hide_conflicts {
+            category.items.retain(|item| item.dietary_conflicts.is_empty());
+        }
+    }
+    if filter.hide_conflicts {
+        menu.categories.retain(|category| !category.items.is_empty());
+    }
+}
+
+/// Reject dishes a pure veg kitchen cannot list: anything non-vegetarian or
+/// with egg
+pub fn check_pure_veg<'a>(dishes: impl IntoIterator<Item = (&'a str, DishFacts<'a>)>) -> Result<()> {
+    for (name, dish) in dishes {
+        if !dish.is_pure_veg() {
+            return Err(AppError::ValidationError(format!(
+                "{} is not vegetarian or contains egg, which a pure veg restaurant cannot serve",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Dietary overrides on menu and search reads. Any of the profile fields
+/// replaces the saved profile for that read.
+#[derive(Debug, Default, Deserialize)]
+pub struct DietaryQuery {
+    /// Comma-separated, e.g. `jain,gluten_free`
+    pub diets: Option<String>,
+    /// Comma-separated allergen names
+    pub avoid_allergens: Option<String>,
+    pub max_spice_level: Option<i32>,
+    pub pure_veg_only: Option<bool>,
+    /// Drop conflicting dishes instead of flagging them
+    #[serde(default)]
+    pub hide_conflicts: bool,
+    /// Skip the saved profile, e.g. when ordering for someone else
+    #[serde(default)]
+    pub ignore_profile: bool,
+}
+
+impl DietaryQuery {
+    /// The profile spelled out in the query, if any
+    pub fn explicit_profile(&self) -> Result<Option<DietaryProfile>> {
+        if self.diets.is_none()
+            && self.avoid_allergens.is_none()
+            && self.max_spice_level.is_none()
+            && self.pure_veg_only.is_none()
+        {
+            return Ok(None);
+        }
+
+        let list = |value: &Option<String>| -> Vec<String> {
+            value
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let diets = list(&self.diets)
+            .iter()
+            .map(|diet| diet.parse().map_err(AppError::ValidationError))
+            .collect::<Result<Vec<Diet>>>()?;
+
+        DietaryProfile {
+            diets,
+            avoid_allergens: list(&self.avoid_allergens),
+            max_spice_level: self.max_spice_level,
+            pure_veg_only: self.pure_veg_only.unwrap_or(false),
+        }
+        .normalized()
+        .map(Some)
+    }
+}
+
+/// A cart item that conflicts with the customer's profile
+#[derive(Debug, Clone, Serialize)]
+pub struct DietaryWarning {
+    pub menu_item_id: Uuid,
+    pub name: String,
+    pub conflicts: Vec<DietaryConflict>,
+}
+
+/// "Paneer Tikka contains dairy; Aloo Gobi is not jain"
+pub fn describe_warnings(warnings: &[DietaryWarning]) -> String {
+    warnings
+        .iter()
+        .map(|warning| {
+            let reasons: Vec<String> = warning.conflicts.iter().map(ToString::to_string).collect();
+            format!("{} {}", warning.name, reasons.join(" and "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub struct DietaryProfileService {
+    db: Database,
+}
+
+impl DietaryProfileService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// The user's saved profile; empty when they have not set one
+    pub async fn get_profile(&self, user_id: Uuid) -> Result<DietaryProfile> {
+        let row = sqlx::query("SELECT * FROM dietary_profiles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let Some(row) = row else {
+            return Ok(DietaryProfile::default());
+        };
+        let diets = row
+            .get::<Vec<String>, _>("diets")
+            .iter()
+            .map(|diet| diet.parse().map_err(AppError::DatabaseError))
+            .collect::<Result<Vec<Diet>>>()?;
+
+        Ok(DietaryProfile {
+            diets,
+            avoid_allergens: row.get("avoid_allergens"),
+            max_spice_level: row.get("max_spice_level"),
+            pure_veg_only: row.get("pure_veg_only"),
+        })
+    }
+
+    pub async fn save_profile(&self, user_id: Uuid, profile: DietaryProfile) -> Result<DietaryProfile> {
+        let profile = profile.normalized()?;
+        let diets: Vec<&str> = profile.diets.iter().map(Diet::as_str).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO dietary_profiles (user_id, diets, avoid_allergens, max_spice_level, pure_veg_only)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE SET
+                diets = EXCLUDED.diets,
+                avoid_allergens = EXCLUDED.avoid_allergens,
+                max_spice_level = EXCLUDED.max_spice_level,
+                pure_veg_only = EXCLUDED.pure_veg_only
+            "#,
+        )
+        .bind(user_id)
+        .bind(&diets)
+        .bind(&profile.avoid_allergens)
+        .bind(profile.max_spice_level)
+        .bind(profile.pure_veg_only)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(profile)
+    }
+
+    pub async fn delete_profile(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM dietary_profiles WHERE user_id = $1")
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// The filter for a menu or search read: the query's own profile if it
+    /// gives one, otherwise the signed-in user's saved profile. `None` when
+    /// neither says anything.
+    pub async fn resolve(&self, user_id: Option<Uuid>, query: &DietaryQuery) -> Result<Option<DietaryFilter>> {
+        let profile = match (query.explicit_profile()?, user_id) {
+            (Some(profile), _) => profile,
+            (None, Some(user_id)) if !query.ignore_profile => self.get_profile(user_id).await?,
+            (None, _) => return Ok(None),
+        };
+
+        Ok((!profile.is_empty()).then_some(DietaryFilter {
+            profile,
+            hide_conflicts: query.hide_conflicts,
+        }))
+    }
+
+    /// Cart items that conflict with the customer's saved profile
+    pub async fn check_items(&self, user_id: Uuid, restaurant_id: Uuid, item_ids: &[Uuid]) -> Result<Vec<DietaryWarning>> {
+        let profile = self.get_profile(user_id).await?;
+        if profile.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let items = sqlx::query_as::<_, MenuItem>("SELECT * FROM menu_items WHERE restaurant_id = $1 AND id = ANY($2)")
+            .bind(restaurant_id)
+            .bind(item_ids)
+            .fetch_all(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                let conflicts = profile.conflicts(&DishFacts::from(item));
+                (!conflicts.is_empty()).then(|| DietaryWarning {
+                    menu_item_id: item.id,
+                    name: item.name.clone(),
+                    conflicts,
+                })
+            })
+            .collect())
+    }
+}
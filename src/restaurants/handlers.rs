@@ -1,24 +1,51 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use serde::Deserialize;
+use chrono::Utc;
 use uuid::Uuid;
 
 use crate::auth::models::User;
 use axum::Extension;
-use crate::error::Result;
+use crate::cache::CacheKey;
+use crate::error::{AppError, Result};
+use crate::promotions::service::PromotionService;
 use crate::restaurants::{
+    availability::{
+        apply_menu_availability, MenuAvailability, MenuPreviewQuery, MenuQuery,
+        SetCategoryAvailabilityRequest,
+    },
+    busy::{publish_busy_change, BusyModeService, StartBusyModeRequest},
+    dietary::{apply_dietary_filter, DietaryProfile, DietaryProfileService, DietaryQuery},
+    inventory::{publish_stock_changes, InventoryService, StockChange, UpdateStockRequest},
+    menu_import::{write_menu_csv, MenuFile, MenuFileFormat, MenuImportResponse},
+    menu_versions::{
+        CreateMenuVersionRequest, MenuVersion, MenuVersionDiff, MenuVersionDiffQuery,
+        MenuVersionService, MenuVersionStatus, MenuVersionSummary, PublishMenuVersionRequest,
+        UpdateMenuVersionRequest,
+    },
+    onboarding::{
+        KycDocument, OnboardingQueueQuery, OnboardingQueueResponse, OnboardingService,
+        OnboardingState, ReviewOnboardingRequest, UploadKycDocumentRequest,
+    },
     models::*,
+    modifiers::{CreateModifierGroupRequest, ModifierGroup},
     service::RestaurantService,
+    serviceability::{NearbyQuery, NearbyRestaurantsResponse},
+    staff::{InviteStaffRequest, Permission, StaffMember, StaffService},
 };
 use crate::routes::AppState;
+use crate::search::{SearchFilters, SearchService};
 
 #[derive(Debug, Deserialize)]
 pub struct RestaurantListQuery {
     pub city: Option<String>,
     pub cuisine_type: Option<String>,
+    /// Defaults to true; pass `false` to include restaurants that are currently closed
+    pub open_now: Option<bool>,
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
@@ -27,10 +54,23 @@ pub struct RestaurantListQuery {
 pub struct RestaurantSearchQuery {
     pub q: String,
     pub city: Option<String>,
+    pub open_now: Option<bool>,
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MenuImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MenuExportQuery {
+    #[serde(default)]
+    pub format: MenuFileFormat,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OrderStatusQuery {
     pub status: Option<String>,
@@ -65,8 +105,12 @@ pub async fn get_restaurant(
     let restaurant = restaurant_service
         .get_restaurant(restaurant_id)
         .await?;
+    let mut response = RestaurantResponse::from(restaurant);
+    PromotionService::new(state.database.clone())
+        .attach_restaurant_badges([&mut response], Utc::now())
+        .await?;
     
-    Ok(Json(RestaurantResponse::from(restaurant)))
+    Ok(Json(response))
 }
 
 pub async fn update_restaurant(
@@ -108,7 +152,7 @@ pub async fn list_restaurants(
     let per_page = params.per_page.unwrap_or(20).min(100); // Max 100 per page
     
     let response = restaurant_service
-        .list_restaurants(params.city, params.cuisine_type, page, per_page)
+        .list_restaurants(params.city, params.cuisine_type, params.open_now.unwrap_or(true), page, per_page)
         .await?;
     
     Ok(Json(response))
@@ -118,13 +162,40 @@ pub async fn search_restaurants(
     State(state): State<AppState>,
     Query(params): Query<RestaurantSearchQuery>,
 ) -> Result<Json<RestaurantListResponse>> {
+    let search_service = SearchService::new(state.database.clone());
+    
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100); // Max 100 per page
+    let filters = SearchFilters {
+        city: params.city,
+        open_now: params.open_now,
+        ..SearchFilters::default()
+    };
+    
+    let response = search_service
+        .search(&params.q, &filters, None, page, per_page)
+        .await?;
+    
+    Ok(Json(RestaurantListResponse {
+        restaurants: response.results.into_iter().map(|hit| hit.restaurant).collect(),
+        total: response.total,
+        page,
+        per_page,
+    }))
+}
+
+/// Restaurants that deliver to the given location
+pub async fn list_nearby_restaurants(
+    State(state): State<AppState>,
+    Query(params): Query<NearbyQuery>,
+) -> Result<Json<NearbyRestaurantsResponse>> {
     let restaurant_service = RestaurantService::new(state.database.clone());
     
-    let page = params.page.unwrap_or(1);
-    let per_page = params.per_page.unwrap_or(20).min(100); // Max 100 per page
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100); // Max 100 per page
     
     let response = restaurant_service
-        .search_restaurants(params.q, params.city, page, per_page)
+        .list_nearby(&params, page, per_page)
         .await?;
     
     Ok(Json(response))
@@ -142,23 +213,81 @@ pub async fn create_menu_item(
     let menu_item = restaurant_service
         .create_menu_item(restaurant_id, user.id, request)
         .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
     
     Ok(Json(MenuItemResponse::from(menu_item)))
 }
 
+/// Public, but signed-in customers get items checked against their saved
+/// dietary profile
 pub async fn get_menu(
     State(state): State<AppState>,
+    user: Option<Extension<User>>,
+    Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<MenuQuery>,
+    Query(dietary): Query<DietaryQuery>,
+) -> Result<Json<MenuResponse>> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    let filter = DietaryProfileService::new(state.database.clone())
+        .resolve(user.map(|Extension(user)| user.id), &dietary)
+        .await?;
+    let mut menu = state
+        .cache
+        .get_or_compute(&CacheKey::RestaurantMenu(restaurant_id), || {
+            restaurant_service.get_menu(restaurant_id)
+        })
+        .await?;
+    let now = Utc::now();
+    apply_menu_availability(&mut menu, now, params.hide_unavailable);
+    if let Some(filter) = &filter {
+        apply_dietary_filter(&mut menu, filter);
+    }
+    PromotionService::new(state.database.clone())
+        .attach_menu_badges(&mut menu, now)
+        .await?;
+    
+    Ok(Json(menu))
+}
+
+/// The menu as customers would see it at a chosen time
+pub async fn preview_menu(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
     Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<MenuPreviewQuery>,
 ) -> Result<Json<MenuResponse>> {
     let restaurant_service = RestaurantService::new(state.database.clone());
     
-    let menu = restaurant_service
-        .get_menu(restaurant_id)
+    restaurant_service
+        .authorize(restaurant_id, user.id, Permission::EditMenu)
+        .await?;
+    let mut menu = restaurant_service.get_menu(restaurant_id).await?;
+    let at = params.at.unwrap_or_else(Utc::now);
+    apply_menu_availability(&mut menu, at, params.hide_unavailable);
+    PromotionService::new(state.database.clone())
+        .attach_menu_badges(&mut menu, at)
         .await?;
     
     Ok(Json(menu))
 }
 
+pub async fn set_category_availability(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, category)): Path<(Uuid, String)>,
+    Json(request): Json<SetCategoryAvailabilityRequest>,
+) -> Result<Json<Option<MenuAvailability>>> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    let availability = restaurant_service
+        .set_category_availability(restaurant_id, &category, user.id, request.availability)
+        .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
+    
+    Ok(Json(availability))
+}
+
 pub async fn update_menu_item(
     State(state): State<AppState>,
     Extension(user): Extension<User>,
@@ -170,6 +299,7 @@ pub async fn update_menu_item(
     let menu_item = restaurant_service
         .update_menu_item(restaurant_id, item_id, user.id, request)
         .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
     
     Ok(Json(MenuItemResponse::from(menu_item)))
 }
@@ -184,10 +314,273 @@ pub async fn delete_menu_item(
     restaurant_service
         .delete_menu_item(restaurant_id, item_id, user.id)
         .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_modifier_group(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<CreateModifierGroupRequest>,
+) -> Result<Json<ModifierGroup>> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    let group = restaurant_service
+        .create_modifier_group(restaurant_id, item_id, user.id, request)
+        .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
+    
+    Ok(Json(group))
+}
+
+pub async fn update_modifier_group(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, item_id, group_id)): Path<(Uuid, Uuid, Uuid)>,
+    Json(request): Json<CreateModifierGroupRequest>,
+) -> Result<Json<ModifierGroup>> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    let group = restaurant_service
+        .update_modifier_group(restaurant_id, item_id, group_id, user.id, request)
+        .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
+    
+    Ok(Json(group))
+}
+
+pub async fn delete_modifier_group(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, item_id, group_id)): Path<(Uuid, Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    restaurant_service
+        .delete_modifier_group(restaurant_id, item_id, group_id, user.id)
+        .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
     
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Set an item's stock count and daily restock level
+pub async fn update_menu_item_stock(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateStockRequest>,
+) -> Result<Json<StockChange>> {
+    let inventory_service = InventoryService::new(state.database.clone());
+    
+    let change = inventory_service
+        .set_stock(restaurant_id, item_id, user.id, request)
+        .await?;
+    publish_stock_changes(&state.delivery_websocket_manager, &state.cache, std::slice::from_ref(&change)).await;
+    
+    Ok(Json(change))
+}
+
+/// Bulk upsert menu items from CSV (`Content-Type: text/csv`) or JSON
+pub async fn import_menu(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<MenuImportQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<MenuImportResponse>)> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    let format = MenuFileFormat::from_content_type(
+        headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()),
+    );
+
+    let result = restaurant_service
+        .import_menu(restaurant_id, user.id, format, &body, params.dry_run)
+        .await?;
+
+    if result.applied {
+        invalidate_menu_cache(&state, restaurant_id).await;
+    }
+
+    let status = if result.dry_run || result.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+
+    Ok((status, Json(result)))
+}
+
+pub async fn export_menu(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<MenuExportQuery>,
+) -> Result<Response> {
+    let restaurant_service = RestaurantService::new(state.database.clone());
+    
+    let items = restaurant_service
+        .export_menu(restaurant_id, user.id)
+        .await?;
+
+    Ok(match params.format {
+        MenuFileFormat::Json => Json(MenuFile { items }).into_response(),
+        MenuFileFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"menu-{}.csv\"", restaurant_id),
+                ),
+            ],
+            write_menu_csv(&items)?,
+        )
+            .into_response(),
+    })
+}
+
+// Menu Versions
+pub async fn list_menu_versions(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<Vec<MenuVersionSummary>>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let versions = version_service.list_versions(restaurant_id, user.id).await?;
+    
+    Ok(Json(versions))
+}
+
+pub async fn create_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<CreateMenuVersionRequest>,
+) -> Result<(StatusCode, Json<MenuVersion>)> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .create_draft(restaurant_id, user.id, request)
+        .await?;
+    
+    Ok((StatusCode::CREATED, Json(version)))
+}
+
+pub async fn get_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<MenuVersion>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .get_version(restaurant_id, version_id, user.id)
+        .await?;
+    
+    Ok(Json(version))
+}
+
+pub async fn update_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateMenuVersionRequest>,
+) -> Result<Json<MenuVersion>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .update_draft(restaurant_id, version_id, user.id, request)
+        .await?;
+    
+    Ok(Json(version))
+}
+
+pub async fn discard_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    version_service
+        .discard_draft(restaurant_id, version_id, user.id)
+        .await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn publish_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<PublishMenuVersionRequest>,
+) -> Result<Json<MenuVersion>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .publish(restaurant_id, version_id, user.id, request)
+        .await?;
+    if version.summary.status == MenuVersionStatus::Published {
+        invalidate_menu_cache(&state, restaurant_id).await;
+    }
+    
+    Ok(Json(version))
+}
+
+pub async fn unschedule_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<MenuVersion>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .unschedule(restaurant_id, version_id, user.id)
+        .await?;
+    
+    Ok(Json(version))
+}
+
+pub async fn rollback_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<MenuVersion>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let version = version_service
+        .rollback(restaurant_id, version_id, user.id)
+        .await?;
+    invalidate_menu_cache(&state, restaurant_id).await;
+    
+    Ok(Json(version))
+}
+
+pub async fn diff_menu_version(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, version_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<MenuVersionDiffQuery>,
+) -> Result<Json<MenuVersionDiff>> {
+    let version_service = MenuVersionService::new(state.database.clone());
+    
+    let diff = version_service
+        .diff(restaurant_id, version_id, user.id, params.against)
+        .await?;
+    
+    Ok(Json(diff))
+}
+
+async fn invalidate_menu_cache(state: &AppState, restaurant_id: Uuid) {
+    if let Err(e) = state.cache.delete(&CacheKey::RestaurantMenu(restaurant_id)).await {
+        tracing::warn!("Failed to invalidate menu cache for restaurant {}: {:?}", restaurant_id, e);
+    }
+}
+
 // Restaurant Operations
 pub async fn get_restaurant_orders(
     State(state): State<AppState>,
@@ -219,6 +612,191 @@ pub async fn update_restaurant_status(
     Ok(Json(RestaurantResponse::from(restaurant)))
 }
 
+pub async fn start_busy_mode(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<StartBusyModeRequest>,
+) -> Result<Json<RestaurantResponse>> {
+    let busy_service = BusyModeService::new(state.database.clone());
+    
+    let restaurant = busy_service.start(restaurant_id, user.id, request).await?;
+    publish_busy_change(&state.delivery_websocket_manager, &restaurant).await;
+    
+    Ok(Json(RestaurantResponse::from(restaurant)))
+}
+
+pub async fn end_busy_mode(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<RestaurantResponse>> {
+    let busy_service = BusyModeService::new(state.database.clone());
+    
+    let restaurant = busy_service.end(restaurant_id, user.id).await?;
+    publish_busy_change(&state.delivery_websocket_manager, &restaurant).await;
+    
+    Ok(Json(RestaurantResponse::from(restaurant)))
+}
+
+// Staff Management
+pub async fn list_staff(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<Vec<StaffMember>>> {
+    let staff_service = StaffService::new(state.database.clone());
+    
+    let staff = staff_service.list_staff(restaurant_id, user.id).await?;
+    
+    Ok(Json(staff))
+}
+
+pub async fn invite_staff(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<InviteStaffRequest>,
+) -> Result<Json<StaffMember>> {
+    let staff_service = StaffService::new(state.database.clone());
+    
+    let member = staff_service
+        .invite_staff(restaurant_id, &user, request)
+        .await?;
+    
+    Ok(Json(member))
+}
+
+pub async fn accept_staff_invitation(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<StaffMember>> {
+    let staff_service = StaffService::new(state.database.clone());
+    
+    let member = staff_service
+        .accept_invitation(restaurant_id, user.id)
+        .await?;
+    
+    Ok(Json(member))
+}
+
+pub async fn remove_staff(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((restaurant_id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    let staff_service = StaffService::new(state.database.clone());
+    
+    staff_service
+        .remove_staff(restaurant_id, member_id, user.id)
+        .await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Restaurants the current user works at, and pending invitations
+pub async fn get_staff_memberships(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<Vec<StaffMember>>> {
+    let staff_service = StaffService::new(state.database.clone());
+    
+    let memberships = staff_service.get_memberships(user.id).await?;
+    
+    Ok(Json(memberships))
+}
+
+// Onboarding and KYC
+pub async fn get_onboarding(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<OnboardingState>> {
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let onboarding = onboarding_service.get_onboarding(restaurant_id, user.id).await?;
+    
+    Ok(Json(onboarding))
+}
+
+pub async fn upload_kyc_document(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<UploadKycDocumentRequest>,
+) -> Result<Json<KycDocument>> {
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let document = onboarding_service
+        .upload_document(restaurant_id, user.id, request)
+        .await?;
+    
+    Ok(Json(document))
+}
+
+pub async fn submit_onboarding(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<OnboardingState>> {
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let onboarding = onboarding_service.submit(restaurant_id, user.id).await?;
+    
+    Ok(Json(onboarding))
+}
+
+pub async fn go_live(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+) -> Result<Json<OnboardingState>> {
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let onboarding = onboarding_service.go_live(restaurant_id, user.id).await?;
+    
+    Ok(Json(onboarding))
+}
+
+pub async fn get_onboarding_queue(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(params): Query<OnboardingQueueQuery>,
+) -> Result<Json<OnboardingQueueResponse>> {
+    if user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
+    
+    let queue = onboarding_service.review_queue(params.status, page, per_page).await?;
+    
+    Ok(Json(queue))
+}
+
+pub async fn review_onboarding(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(restaurant_id): Path<Uuid>,
+    Json(request): Json<ReviewOnboardingRequest>,
+) -> Result<Json<OnboardingState>> {
+    if user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let onboarding_service = OnboardingService::new(state.database.clone());
+    
+    let onboarding = onboarding_service
+        .review(restaurant_id, &user, request)
+        .await?;
+    
+    Ok(Json(onboarding))
+}
+
 // India-specific restaurant endpoints
 pub async fn get_restaurants_by_city(
     State(state): State<AppState>,
@@ -231,7 +809,7 @@ pub async fn get_restaurants_by_city(
     let per_page = params.per_page.unwrap_or(20).min(100);
     
     let response = restaurant_service
-        .list_restaurants(Some(city), params.cuisine_type, page, per_page)
+        .list_restaurants(Some(city), params.cuisine_type, params.open_now.unwrap_or(true), page, per_page)
         .await?;
     
     Ok(Json(response))
@@ -248,8 +826,43 @@ pub async fn get_restaurants_by_cuisine(
     let per_page = params.per_page.unwrap_or(20).min(100);
     
     let response = restaurant_service
-        .list_restaurants(params.city, Some(cuisine_type), page, per_page)
+        .list_restaurants(params.city, Some(cuisine_type), params.open_now.unwrap_or(true), page, per_page)
         .await?;
     
     Ok(Json(response))
-}
\ No newline at end of file
+}
+
+// Dietary Profiles
+pub async fn get_dietary_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<DietaryProfile>> {
+    let profile_service = DietaryProfileService::new(state.database.clone());
+    
+    let profile = profile_service.get_profile(user.id).await?;
+    
+    Ok(Json(profile))
+}
+
+pub async fn update_dietary_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(request): Json<DietaryProfile>,
+) -> Result<Json<DietaryProfile>> {
+    let profile_service = DietaryProfileService::new(state.database.clone());
+    
+    let profile = profile_service.save_profile(user.id, request).await?;
+    
+    Ok(Json(profile))
+}
+
+pub async fn delete_dietary_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    let profile_service = DietaryProfileService::new(state.database.clone());
+    
+    profile_service.delete_profile(user.id).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
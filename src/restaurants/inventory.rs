@@ -0,0 +1,345 @@
+use crate::cache::{CacheKey, CacheService};
+use crate::database::Database;
+use crate::delivery::websocket::DeliveryWebSocketManager;
+use crate::error::{AppError, Result};
+use crate::india::config::ISTConfig;
+use crate::orders::models::OrderItem;
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// New stock level of a menu item, pushed to menu viewers and the kitchen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StockChange {
+    pub restaurant_id: Uuid,
+    pub menu_item_id: Uuid,
+    /// `None` when the item's stock is not tracked
+    pub stock_quantity: Option<i32>,
+    pub is_available: bool,
+}
+
+fn stock_change_from_row(row: &PgRow) -> StockChange {
+    StockChange {
+        restaurant_id: row.get("restaurant_id"),
+        menu_item_id: row.get("id"),
+        stock_quantity: row.get("stock_quantity"),
+        is_available: row.get("is_available"),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateStockRequest {
+    /// Portions left right now; `null` stops tracking stock for the item
+    pub stock_quantity: Option<i32>,
+    /// Level the item is reset to at the daily restock; `null` disables it
+    pub daily_stock: Option<i32>,
+}
+
+impl UpdateStockRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.stock_quantity.is_some_and(|quantity| quantity < 0) {
+            return Err(AppError::ValidationError("stock_quantity cannot be negative".to_string()));
+        }
+        if self.daily_stock.is_some_and(|quantity| quantity < 1) {
+            return Err(AppError::ValidationError("daily_stock must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Most recent daily restock time (IST) at or before `now`
+pub fn last_restock_boundary(now: DateTime<Utc>, restock_time: NaiveTime) -> DateTime<Utc> {
+    let ist = ISTConfig::default().offset();
+    let today = now.with_timezone(&ist).date_naive();
+    let boundary = |date: chrono::NaiveDate| {
+        ist.from_local_datetime(&date.and_time(restock_time))
+            .single()
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or(now)
+    };
+
+    let todays = boundary(today);
+    if todays <= now {
+        todays
+    } else {
+        boundary(today - Duration::days(1))
+    }
+}
+
+/// Sum quantities per menu item; ordered so row locks are always taken in the same order
+fn quantities_by_item(items: &[OrderItem]) -> BTreeMap<Uuid, i32> {
+    let mut quantities = BTreeMap::new();
+    for item in items {
+        *quantities.entry(item.menu_item_id).or_insert(0) += item.quantity as i32;
+    }
+    quantities
+}
+
+/// Per-item stock counts. Items with `stock_quantity = NULL` are not tracked
+/// and are never sold out automatically.
+pub struct InventoryService {
+    db: Database,
+}
+
+impl InventoryService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Set an item's stock by hand. Stock above zero brings back an item that
+    /// sold out automatically; zero marks it sold out.
+    pub async fn set_stock(&self, restaurant_id: Uuid, item_id: Uuid, user_id: Uuid, request: UpdateStockRequest) -> Result<StockChange> {
+        request.validate()?;
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageStock)
+            .await?;
+        RestaurantService::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE menu_items
+            SET stock_quantity = $1,
+                daily_stock = $2,
+                is_available = CASE
+                    WHEN $1 = 0 THEN FALSE
+                    ELSE is_available OR sold_out_automatically
+                END,
+                sold_out_automatically = CASE
+                    WHEN $1 = 0 THEN is_available OR sold_out_automatically
+                    ELSE FALSE
+                END,
+                last_restocked_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $3 AND restaurant_id = $4
+            RETURNING restaurant_id, id, stock_quantity, is_available
+            "#,
+        )
+        .bind(request.stock_quantity)
+        .bind(request.daily_stock)
+        .bind(item_id)
+        .bind(restaurant_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Menu item not found".to_string()))?;
+
+        Ok(stock_change_from_row(&row))
+    }
+
+    /// Take stock for a confirmed order. Fails without changing anything if
+    /// any tracked item is short; items reaching zero are sold out.
+    pub async fn reserve_for_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        order_id: Uuid,
+        items: &[OrderItem],
+    ) -> Result<Vec<StockChange>> {
+        let quantities = quantities_by_item(items);
+        let item_ids: Vec<Uuid> = quantities.keys().copied().collect();
+
+        let rows = sqlx::query(
+            "SELECT id, name, stock_quantity FROM menu_items WHERE id = ANY($1) AND stock_quantity IS NOT NULL ORDER BY id FOR UPDATE"
+        )
+        .bind(&item_ids)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for row in &rows {
+            let item_id: Uuid = row.get("id");
+            let in_stock: i32 = row.get("stock_quantity");
+            if in_stock < quantities[&item_id] {
+                let name: String = row.get("name");
+                return Err(AppError::ValidationError(format!(
+                    "Only {} left of {}",
+                    in_stock, name
+                )));
+            }
+        }
+
+        let mut changes = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let item_id: Uuid = row.get("id");
+            let quantity = quantities[&item_id];
+
+            let updated = sqlx::query(
+                r#"
+                UPDATE menu_items
+                SET stock_quantity = stock_quantity - $1,
+                    sold_out_automatically = CASE
+                        WHEN stock_quantity - $1 <= 0 AND is_available THEN TRUE
+                        ELSE sold_out_automatically
+                    END,
+                    is_available = CASE WHEN stock_quantity - $1 <= 0 THEN FALSE ELSE is_available END,
+                    updated_at = NOW()
+                WHERE id = $2
+                RETURNING restaurant_id, id, stock_quantity, is_available
+                "#,
+            )
+            .bind(quantity)
+            .bind(item_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            sqlx::query(
+                "INSERT INTO menu_stock_reservations (order_id, menu_item_id, quantity) VALUES ($1, $2, $3)"
+            )
+            .bind(order_id)
+            .bind(item_id)
+            .bind(quantity)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            changes.push(stock_change_from_row(&updated));
+        }
+
+        Ok(changes)
+    }
+
+    /// Return a cancelled order's stock. Reservations taken before the item's
+    /// last restock are dropped, since the restock already reset the count.
+    pub async fn release_for_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        order_id: Uuid,
+    ) -> Result<Vec<StockChange>> {
+        let reservations = sqlx::query(
+            r#"
+            UPDATE menu_stock_reservations
+            SET released_at = NOW()
+            WHERE order_id = $1 AND released_at IS NULL
+            RETURNING menu_item_id, quantity, created_at
+            "#,
+        )
+        .bind(order_id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut changes = Vec::with_capacity(reservations.len());
+        for reservation in &reservations {
+            let reserved_at: DateTime<Utc> = reservation.get("created_at");
+            let updated = sqlx::query(
+                r#"
+                UPDATE menu_items
+                SET stock_quantity = stock_quantity + $1,
+                    is_available = is_available OR sold_out_automatically,
+                    sold_out_automatically = FALSE,
+                    updated_at = NOW()
+                WHERE id = $2
+                  AND stock_quantity IS NOT NULL
+                  AND (last_restocked_at IS NULL OR last_restocked_at <= $3)
+                RETURNING restaurant_id, id, stock_quantity, is_available
+                "#,
+            )
+            .bind(reservation.get::<i32, _>("quantity"))
+            .bind(reservation.get::<Uuid, _>("menu_item_id"))
+            .bind(reserved_at)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            if let Some(row) = updated {
+                changes.push(stock_change_from_row(&row));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Reset every item with a daily stock level that has not been restocked
+    /// since the most recent restock time
+    pub async fn run_daily_restock(&self, now: DateTime<Utc>, restock_time: NaiveTime) -> Result<Vec<StockChange>> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE menu_items
+            SET stock_quantity = daily_stock,
+                is_available = is_available OR sold_out_automatically,
+                sold_out_automatically = FALSE,
+                last_restocked_at = $1,
+                updated_at = $1
+            WHERE daily_stock IS NOT NULL
+              AND (last_restocked_at IS NULL OR last_restocked_at < $2)
+            RETURNING restaurant_id, id, stock_quantity, is_available
+            "#,
+        )
+        .bind(now)
+        .bind(last_restock_boundary(now, restock_time))
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.iter().map(stock_change_from_row).collect())
+    }
+}
+
+/// Push stock changes to connected clients and drop the cached menus they affect
+pub async fn publish_stock_changes(
+    websocket_manager: &DeliveryWebSocketManager,
+    cache: &CacheService,
+    changes: &[StockChange],
+) {
+    let mut restaurants = HashSet::new();
+    for change in changes {
+        restaurants.insert(change.restaurant_id);
+        // No subscribers is not an error worth reporting
+        let _ = websocket_manager.broadcast_stock_update(change.clone()).await;
+    }
+
+    for restaurant_id in restaurants {
+        if let Err(e) = cache.delete(&CacheKey::RestaurantMenu(restaurant_id)).await {
+            tracing::warn!("Failed to invalidate menu cache for restaurant {}: {:?}", restaurant_id, e);
+        }
+    }
+}
+
+/// Background job that applies the daily restock
+pub struct InventoryRestocker {
+    service: InventoryService,
+    websocket_manager: DeliveryWebSocketManager,
+    cache: CacheService,
+    interval: std::time::Duration,
+    restock_time: NaiveTime,
+}
+
+impl InventoryRestocker {
+    pub fn new(
+        db: Database,
+        websocket_manager: DeliveryWebSocketManager,
+        cache: CacheService,
+        interval: std::time::Duration,
+        restock_time: NaiveTime,
+    ) -> Self {
+        Self {
+            service: InventoryService::new(db),
+            websocket_manager,
+            cache,
+            interval,
+            restock_time,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(restocked) => tracing::info!("Daily restock applied to {} menu items", restocked),
+                    Err(e) => tracing::error!("Daily restock failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        let changes = self.service.run_daily_restock(Utc::now(), self.restock_time).await?;
+        publish_stock_changes(&self.websocket_manager, &self.cache, &changes).await;
+        Ok(changes.len())
+    }
+}
@@ -0,0 +1,367 @@
+use crate::error::{AppError, Result};
+use crate::money::Money;
+use crate::restaurants::models::MenuItemResponse;
+use crate::restaurants::modifiers::CreateModifierGroupRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Separator for list columns (allergens, ingredients) in CSV files
+const LIST_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuFileFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl MenuFileFormat {
+    /// Pick the format from a request's Content-Type, defaulting to JSON
+    pub fn from_content_type(content_type: Option<&str>) -> Self {
+        match content_type {
+            Some(value) if value.contains("csv") => MenuFileFormat::Csv,
+            _ => MenuFileFormat::Json,
+        }
+    }
+}
+
+/// One dish in a bulk menu file. The SKU is the restaurant's own stable key
+/// for the dish; re-importing the same SKU updates the item in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuImportItem {
+    pub sku: String,
+    pub category: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub price: Money,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub is_vegetarian: bool,
+    #[serde(default)]
+    pub is_vegan: bool,
+    #[serde(default)]
+    pub is_gluten_free: bool,
+    #[serde(default)]
+    pub spice_level: i32,
+    #[serde(default)]
+    pub ingredients: Vec<String>,
+    #[serde(default)]
+    pub allergens: Vec<String>,
+    #[serde(default = "default_available")]
+    pub is_available: bool,
+    #[serde(default = "default_preparation_time")]
+    pub preparation_time_minutes: i32,
+    #[serde(default)]
+    pub calories: Option<i32>,
+    /// Replaces the item's modifier groups; an empty list removes them
+    #[serde(default)]
+    pub modifiers: Vec<CreateModifierGroupRequest>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+fn default_preparation_time() -> i32 {
+    15
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuFile {
+    pub items: Vec<MenuImportItem>,
+}
+
+/// Flat CSV layout; modifier groups are embedded as a JSON array
+#[derive(Debug, Serialize, Deserialize)]
+struct MenuCsvRow {
+    sku: String,
+    category: String,
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    price: Money,
+    #[serde(default)]
+    image_url: Option<String>,
+    #[serde(default)]
+    is_vegetarian: Option<bool>,
+    #[serde(default)]
+    is_vegan: Option<bool>,
+    #[serde(default)]
+    is_gluten_free: Option<bool>,
+    #[serde(default)]
+    spice_level: Option<i32>,
+    #[serde(default)]
+    ingredients: Option<String>,
+    #[serde(default)]
+    allergens: Option<String>,
+    #[serde(default)]
+    is_available: Option<bool>,
+    #[serde(default)]
+    preparation_time_minutes: Option<i32>,
+    #[serde(default)]
+    calories: Option<i32>,
+    #[serde(default)]
+    modifiers: Option<String>,
+}
+
+impl MenuCsvRow {
+    fn into_item(self) -> std::result::Result<MenuImportItem, String> {
+        let modifiers = match self.modifiers.as_deref().map(str::trim) {
+            None | Some("") => Vec::new(),
+            Some(raw) => serde_json::from_str(raw).map_err(|e| format!("modifiers: {}", e))?,
+        };
+
+        Ok(MenuImportItem {
+            sku: self.sku,
+            category: self.category,
+            name: self.name,
+            description: self.description.filter(|d| !d.is_empty()),
+            price: self.price,
+            image_url: self.image_url.filter(|url| !url.is_empty()),
+            is_vegetarian: self.is_vegetarian.unwrap_or(false),
+            is_vegan: self.is_vegan.unwrap_or(false),
+            is_gluten_free: self.is_gluten_free.unwrap_or(false),
+            spice_level: self.spice_level.unwrap_or(0),
+            ingredients: split_list(self.ingredients.as_deref()),
+            allergens: split_list(self.allergens.as_deref()),
+            is_available: self.is_available.unwrap_or(true),
+            preparation_time_minutes: self.preparation_time_minutes.unwrap_or_else(default_preparation_time),
+            calories: self.calories,
+            modifiers,
+        })
+    }
+
+    fn from_item(item: &MenuImportItem) -> Result<Self> {
+        let modifiers = if item.modifiers.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&item.modifiers).map_err(export_error)?)
+        };
+
+        Ok(Self {
+            sku: item.sku.clone(),
+            category: item.category.clone(),
+            name: item.name.clone(),
+            description: item.description.clone(),
+            price: item.price,
+            image_url: item.image_url.clone(),
+            is_vegetarian: Some(item.is_vegetarian),
+            is_vegan: Some(item.is_vegan),
+            is_gluten_free: Some(item.is_gluten_free),
+            spice_level: Some(item.spice_level),
+            ingredients: join_list(&item.ingredients),
+            allergens: join_list(&item.allergens),
+            is_available: Some(item.is_available),
+            preparation_time_minutes: Some(item.preparation_time_minutes),
+            calories: item.calories,
+            modifiers,
+        })
+    }
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(LIST_SEPARATOR)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn join_list(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(&LIST_SEPARATOR.to_string()))
+    }
+}
+
+/// Problems with one row of an import file. `row` counts data rows from 1,
+/// not including the CSV header.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub sku: Option<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuImportResponse {
+    pub dry_run: bool,
+    /// False for dry runs and for imports rejected because of row errors
+    pub applied: bool,
+    pub total_rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Rows of an uploaded file, each tagged with its row number
+#[derive(Debug, Default)]
+pub struct ParsedMenuFile {
+    pub items: Vec<(usize, MenuImportItem)>,
+    /// Rows that could not be read at all
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Parse an uploaded menu file. Rows that fail to parse are reported as
+/// errors rather than aborting the whole file.
+pub fn parse_menu_file(format: MenuFileFormat, body: &[u8]) -> Result<ParsedMenuFile> {
+    match format {
+        MenuFileFormat::Json => {
+            let file: MenuFile = serde_json::from_slice(body)
+                .map_err(|e| AppError::ValidationError(format!("Invalid menu JSON: {}", e)))?;
+            Ok(ParsedMenuFile {
+                items: file.items.into_iter().enumerate().map(|(i, item)| (i + 1, item)).collect(),
+                errors: Vec::new(),
+            })
+        }
+        MenuFileFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body);
+            let mut items = Vec::new();
+            let mut errors = Vec::new();
+
+            for (index, record) in reader.deserialize::<MenuCsvRow>().enumerate() {
+                let row = index + 1;
+                match record.map_err(|e| e.to_string()).and_then(MenuCsvRow::into_item) {
+                    Ok(item) => items.push((row, item)),
+                    Err(message) => errors.push(ImportRowError {
+                        row,
+                        sku: None,
+                        errors: vec![message],
+                    }),
+                }
+            }
+
+            Ok(ParsedMenuFile { items, errors })
+        }
+    }
+}
+
+/// Check every parsed row, collecting all problems per row
+pub fn validate_import(items: &[(usize, MenuImportItem)]) -> Vec<ImportRowError> {
+    let mut seen_skus = HashSet::new();
+    let mut row_errors = Vec::new();
+
+    for (row, item) in items {
+        let mut errors = Vec::new();
+
+        if item.sku.trim().is_empty() {
+            errors.push("sku is required".to_string());
+        } else if !seen_skus.insert(item.sku.trim().to_string()) {
+            errors.push(format!("sku '{}' appears more than once", item.sku));
+        }
+        if item.name.trim().is_empty() {
+            errors.push("name is required".to_string());
+        }
+        if item.category.trim().is_empty() {
+            errors.push("category is required".to_string());
+        }
+        if !item.price.is_positive() {
+            errors.push("price must be greater than zero".to_string());
+        }
+        if !(0..=5).contains(&item.spice_level) {
+            errors.push("spice_level must be between 0 and 5".to_string());
+        }
+        if item.preparation_time_minutes <= 0 {
+            errors.push("preparation_time_minutes must be positive".to_string());
+        }
+        if item.calories.is_some_and(|calories| calories < 0) {
+            errors.push("calories cannot be negative".to_string());
+        }
+        if item.is_vegan && !item.is_vegetarian {
+            errors.push("vegan items must also be marked vegetarian".to_string());
+        }
+
+        let mut group_names = HashSet::new();
+        for group in &item.modifiers {
+            if !group_names.insert(group.name.trim().to_lowercase()) {
+                errors.push(format!("modifier group '{}' appears more than once", group.name));
+            }
+            if let Err(e) = group.validate() {
+                errors.push(match e {
+                    AppError::ValidationError(message) => message,
+                    other => other.to_string(),
+                });
+            }
+        }
+
+        if !errors.is_empty() {
+            row_errors.push(ImportRowError {
+                row: *row,
+                sku: Some(item.sku.clone()).filter(|sku| !sku.is_empty()),
+                errors,
+            });
+        }
+    }
+
+    row_errors
+}
+
+/// Turn a menu item back into an import row. Items created before SKUs
+/// existed export their id, which a later import matches back to the item.
+pub fn export_item(item: &MenuItemResponse) -> MenuImportItem {
+    MenuImportItem {
+        sku: item.external_sku.clone().unwrap_or_else(|| item.id.to_string()),
+        category: item.category.clone(),
+        name: item.name.clone(),
+        description: item.description.clone(),
+        price: item.price,
+        image_url: item.image_url.clone(),
+        is_vegetarian: item.is_vegetarian,
+        is_vegan: item.is_vegan,
+        is_gluten_free: item.is_gluten_free,
+        spice_level: item.spice_level,
+        ingredients: item.ingredients.clone().unwrap_or_default(),
+        allergens: item.allergens.clone().unwrap_or_default(),
+        is_available: item.is_available,
+        preparation_time_minutes: item.preparation_time_minutes,
+        calories: item.calories,
+        modifiers: item.modifier_groups.iter().map(CreateModifierGroupRequest::from).collect(),
+    }
+}
+
+pub fn write_menu_csv(items: &[MenuImportItem]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for item in items {
+        writer
+            .serialize(MenuCsvRow::from_item(item)?)
+            .map_err(export_error)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(export_error)
+}
+
+fn export_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Failed to write menu export: {}", e))
+}
+
+/// Id of a legacy item named by its exported id instead of a SKU
+pub(crate) fn legacy_item_id(sku: &str) -> Option<Uuid> {
+    Uuid::parse_str(sku).ok()
+}
+
+/// For each row, the id of the existing item it updates: the item with the
+/// same SKU, or a legacy item named by its id
+pub(crate) fn match_menu_items(existing: &[(Uuid, Option<String>)], items: &[MenuImportItem]) -> Vec<Option<Uuid>> {
+    items
+        .iter()
+        .map(|item| {
+            let sku = item.sku.trim();
+            existing
+                .iter()
+                .find(|(_, external_sku)| external_sku.as_deref() == Some(sku))
+                .or_else(|| {
+                    let legacy_id = legacy_item_id(sku)?;
+                    existing.iter().find(|(id, external_sku)| *id == legacy_id && external_sku.is_none())
+                })
+                .map(|(id, _)| *id)
+        })
+        .collect()
+}
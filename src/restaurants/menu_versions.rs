@@ -0,0 +1,756 @@
+use crate::cache::{CacheKey, CacheService};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::restaurants::menu_import::{match_menu_items, validate_import, ImportRowError, MenuImportItem};
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Lifecycle of a menu version. Only the published version is what
+/// customers see; drafts are edited without touching the live menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuVersionStatus {
+    Draft,
+    /// Waiting for `publish_at`; still editable
+    Scheduled,
+    Published,
+    /// Replaced by a later version; kept for rollback
+    Archived,
+}
+
+impl MenuVersionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MenuVersionStatus::Draft => "draft",
+            MenuVersionStatus::Scheduled => "scheduled",
+            MenuVersionStatus::Published => "published",
+            MenuVersionStatus::Archived => "archived",
+        }
+    }
+
+    /// Whether the version's items can still be changed
+    pub fn is_editable(&self) -> bool {
+        matches!(self, MenuVersionStatus::Draft | MenuVersionStatus::Scheduled)
+    }
+
+    pub fn can_transition_to(&self, next: &MenuVersionStatus) -> bool {
+        matches!(
+            (self, next),
+            (MenuVersionStatus::Draft, MenuVersionStatus::Scheduled)
+                | (MenuVersionStatus::Draft, MenuVersionStatus::Published)
+                | (MenuVersionStatus::Scheduled, MenuVersionStatus::Scheduled)
+                | (MenuVersionStatus::Scheduled, MenuVersionStatus::Draft)
+                | (MenuVersionStatus::Scheduled, MenuVersionStatus::Published)
+                | (MenuVersionStatus::Published, MenuVersionStatus::Archived)
+        )
+    }
+
+    pub fn transition_to(&self, next: MenuVersionStatus) -> Result<MenuVersionStatus> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(AppError::InvalidStatusTransition(format!(
+                "menu version cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::str::FromStr for MenuVersionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(MenuVersionStatus::Draft),
+            "scheduled" => Ok(MenuVersionStatus::Scheduled),
+            "published" => Ok(MenuVersionStatus::Published),
+            "archived" => Ok(MenuVersionStatus::Archived),
+            _ => Err(format!("Invalid menu version status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuVersionSummary {
+    pub id: Uuid,
+    pub restaurant_id: Uuid,
+    pub version_number: i32,
+    pub status: MenuVersionStatus,
+    pub note: Option<String>,
+    /// Version this one was copied from
+    pub based_on: Option<Uuid>,
+    pub item_count: i32,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_by: Uuid,
+    /// Who published or scheduled the version
+    pub published_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuVersion {
+    #[serde(flatten)]
+    pub summary: MenuVersionSummary,
+    pub items: Vec<MenuImportItem>,
+}
+
+const SUMMARY_COLUMNS: &str = "id, restaurant_id, version_number, status, note, based_on, publish_at, \
+     published_at, created_by, published_by, created_at, updated_at, jsonb_array_length(items) AS item_count";
+
+fn summary_from_row(row: &PgRow) -> Result<MenuVersionSummary> {
+    let status: String = row.get("status");
+
+    Ok(MenuVersionSummary {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        version_number: row.get("version_number"),
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        note: row.get("note"),
+        based_on: row.get("based_on"),
+        item_count: row.get("item_count"),
+        publish_at: row.get("publish_at"),
+        published_at: row.get("published_at"),
+        created_by: row.get("created_by"),
+        published_by: row.get("published_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+fn menu_version_from_row(row: &PgRow) -> Result<MenuVersion> {
+    Ok(MenuVersion {
+        summary: summary_from_row(row)?,
+        items: serde_json::from_value(row.get("items"))
+            .map_err(|e| AppError::DatabaseError(format!("Invalid menu version items: {}", e)))?,
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateMenuVersionRequest {
+    /// Start from an earlier version instead of the live menu
+    #[serde(default)]
+    pub from_version: Option<Uuid>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Replace a draft's contents. Items use the bulk import format.
+#[derive(Debug, Deserialize)]
+pub struct UpdateMenuVersionRequest {
+    #[serde(default)]
+    pub items: Option<Vec<MenuImportItem>>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PublishMenuVersionRequest {
+    /// Publish later instead of now; a time in the past publishes now
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MenuVersionDiffQuery {
+    /// Version to compare against; defaults to the live menu
+    pub against: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuItemDiff {
+    pub sku: String,
+    pub name: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Dishes added, removed and changed between two menus, matched by SKU
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MenuDiff {
+    pub added: Vec<MenuImportItem>,
+    pub removed: Vec<MenuImportItem>,
+    pub changed: Vec<MenuItemDiff>,
+}
+
+impl MenuDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuVersionDiff {
+    /// `None` when comparing against the live menu
+    pub from: Option<MenuVersionSummary>,
+    pub to: MenuVersionSummary,
+    #[serde(flatten)]
+    pub diff: MenuDiff,
+}
+
+/// Compare two menus. Field changes are reported per top-level field of the
+/// import format, so a changed modifier group shows up as `modifiers`.
+pub fn diff_menu_items(from: &[MenuImportItem], to: &[MenuImportItem]) -> MenuDiff {
+    let before: HashMap<&str, &MenuImportItem> = from.iter().map(|item| (item.sku.trim(), item)).collect();
+    let after: HashMap<&str, &MenuImportItem> = to.iter().map(|item| (item.sku.trim(), item)).collect();
+    let mut diff = MenuDiff::default();
+
+    for item in to {
+        let Some(old) = before.get(item.sku.trim()) else {
+            diff.added.push(item.clone());
+            continue;
+        };
+
+        let old_fields = serde_json::to_value(old).unwrap_or_default();
+        let new_fields = serde_json::to_value(item).unwrap_or_default();
+        let changes: Vec<FieldChange> = new_fields
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(field, _)| field.as_str() != "sku")
+            .filter_map(|(field, value)| {
+                let old_value = old_fields.get(field).cloned().unwrap_or_default();
+                (old_value != *value).then(|| FieldChange {
+                    field: field.clone(),
+                    from: old_value,
+                    to: value.clone(),
+                })
+            })
+            .collect();
+
+        if !changes.is_empty() {
+            diff.changed.push(MenuItemDiff {
+                sku: item.sku.trim().to_string(),
+                name: item.name.clone(),
+                changes,
+            });
+        }
+    }
+
+    diff.removed = from
+        .iter()
+        .filter(|item| !after.contains_key(item.sku.trim()))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+fn describe_row_errors(errors: &[ImportRowError]) -> String {
+    errors
+        .iter()
+        .map(|error| match &error.sku {
+            Some(sku) => format!("item {} ({}): {}", error.row, sku, error.errors.join(", ")),
+            None => format!("item {}: {}", error.row, error.errors.join(", ")),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// A version must be a complete, valid menu before it can go live
+fn validate_menu_version(items: &[MenuImportItem]) -> Result<()> {
+    if items.is_empty() {
+        return Err(AppError::ValidationError("A menu version needs at least one item".to_string()));
+    }
+
+    let rows: Vec<(usize, MenuImportItem)> = items.iter().cloned().enumerate().map(|(i, item)| (i + 1, item)).collect();
+    let errors = validate_import(&rows);
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Menu version has invalid items: {}",
+            describe_row_errors(&errors)
+        )));
+    }
+
+    Ok(())
+}
+
+pub struct MenuVersionService {
+    db: Database,
+}
+
+impl MenuVersionService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn list_versions(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Vec<MenuVersionSummary>> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM menu_versions WHERE restaurant_id = $1 ORDER BY version_number DESC",
+            SUMMARY_COLUMNS
+        ))
+        .bind(restaurant_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(summary_from_row).collect()
+    }
+
+    pub async fn get_version(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid) -> Result<MenuVersion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+        self.load(restaurant_id, version_id).await
+    }
+
+    /// Start a draft from the live menu, or from an earlier version. A
+    /// restaurant has at most one draft or scheduled version at a time.
+    pub async fn create_draft(&self, restaurant_id: Uuid, user_id: Uuid, request: CreateMenuVersionRequest) -> Result<MenuVersion> {
+        let restaurant_service = RestaurantService::new(self.db.clone());
+        restaurant_service
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let pending = sqlx::query_scalar::<_, i32>(
+            "SELECT version_number FROM menu_versions WHERE restaurant_id = $1 AND status IN ('draft', 'scheduled')"
+        )
+        .bind(restaurant_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if let Some(version_number) = pending {
+            return Err(AppError::ValidationError(format!(
+                "Version {} is already being edited; publish or discard it first",
+                version_number
+            )));
+        }
+
+        let items = match request.from_version {
+            Some(version_id) => self.load(restaurant_id, version_id).await?.items,
+            None => restaurant_service.live_menu_items(restaurant_id).await?,
+        };
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO menu_versions (restaurant_id, version_number, status, items, note, based_on, created_by)
+            SELECT $1, COALESCE(MAX(version_number), 0) + 1, 'draft', $2, $3, $4, $5
+            FROM menu_versions WHERE restaurant_id = $1
+            RETURNING {}, items
+            "#,
+            SUMMARY_COLUMNS
+        ))
+        .bind(restaurant_id)
+        .bind(Self::items_json(&items)?)
+        .bind(request.note.as_deref().map(str::trim).filter(|note| !note.is_empty()))
+        .bind(request.from_version)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        menu_version_from_row(&row)
+    }
+
+    /// Replace a draft's items or note. The live menu is untouched until
+    /// the version is published.
+    pub async fn update_draft(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid, request: UpdateMenuVersionRequest) -> Result<MenuVersion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let version = self.load(restaurant_id, version_id).await?;
+        if !version.summary.status.is_editable() {
+            return Err(AppError::ValidationError(format!(
+                "A {} menu version cannot be edited",
+                version.summary.status.as_str()
+            )));
+        }
+        if let Some(items) = &request.items {
+            validate_menu_version(items)?;
+        }
+
+        let items = request.items.as_ref().map(|items| Self::items_json(items)).transpose()?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE menu_versions
+            SET items = COALESCE($3, items), note = NULLIF(COALESCE($4, note), '')
+            WHERE id = $1 AND restaurant_id = $2 AND status IN ('draft', 'scheduled')
+            RETURNING {}, items
+            "#,
+            SUMMARY_COLUMNS
+        ))
+        .bind(version_id)
+        .bind(restaurant_id)
+        .bind(items)
+        .bind(request.note.as_deref().map(str::trim))
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::InvalidStatusTransition("menu version is no longer editable".to_string()))?;
+
+        menu_version_from_row(&row)
+    }
+
+    /// Throw away a draft or scheduled version
+    pub async fn discard_draft(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid) -> Result<()> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let result = sqlx::query(
+            "DELETE FROM menu_versions WHERE id = $1 AND restaurant_id = $2 AND status IN ('draft', 'scheduled')"
+        )
+        .bind(version_id)
+        .bind(restaurant_id)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("No draft menu version found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Publish a version now, or schedule it when `publish_at` is in the future
+    pub async fn publish(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid, request: PublishMenuVersionRequest) -> Result<MenuVersion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let version = self.load(restaurant_id, version_id).await?;
+        validate_menu_version(&version.items)?;
+
+        match request.publish_at.filter(|at| *at > Utc::now()) {
+            Some(publish_at) => {
+                version.summary.status.transition_to(MenuVersionStatus::Scheduled)?;
+                let row = sqlx::query(&format!(
+                    r#"
+                    UPDATE menu_versions
+                    SET status = 'scheduled', publish_at = $3, published_by = $4
+                    WHERE id = $1 AND restaurant_id = $2 AND status IN ('draft', 'scheduled')
+                    RETURNING {}, items
+                    "#,
+                    SUMMARY_COLUMNS
+                ))
+                .bind(version_id)
+                .bind(restaurant_id)
+                .bind(publish_at)
+                .bind(user_id)
+                .fetch_optional(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| AppError::InvalidStatusTransition("menu version is no longer editable".to_string()))?;
+
+                menu_version_from_row(&row)
+            }
+            None => self.publish_now(restaurant_id, version_id, Some(user_id)).await,
+        }
+    }
+
+    /// Move a scheduled version back to draft
+    pub async fn unschedule(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid) -> Result<MenuVersion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let version = self.load(restaurant_id, version_id).await?;
+        version.summary.status.transition_to(MenuVersionStatus::Draft)?;
+        self.revert_to_draft(version_id).await?;
+        self.load(restaurant_id, version_id).await
+    }
+
+    /// Publish a copy of an earlier version as a new version
+    pub async fn rollback(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid) -> Result<MenuVersion> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::lock_restaurant(&mut tx, restaurant_id).await?;
+
+        let target = Self::load_in(&mut tx, restaurant_id, version_id).await?;
+        if target.summary.status != MenuVersionStatus::Archived {
+            return Err(AppError::ValidationError(format!(
+                "Only earlier versions can be rolled back to; version {} is {}",
+                target.summary.version_number,
+                target.summary.status.as_str()
+            )));
+        }
+
+        Self::replace_live_menu(&mut tx, restaurant_id, &target.items).await?;
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO menu_versions (
+                restaurant_id, version_number, status, items, note, based_on,
+                published_at, created_by, published_by
+            )
+            SELECT $1, COALESCE(MAX(version_number), 0) + 1, 'published', $2, $3, $4, NOW(), $5, $5
+            FROM menu_versions WHERE restaurant_id = $1
+            RETURNING {}, items
+            "#,
+            SUMMARY_COLUMNS
+        ))
+        .bind(restaurant_id)
+        .bind(Self::items_json(&target.items)?)
+        .bind(format!("Rollback to version {}", target.summary.version_number))
+        .bind(version_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tracing::info!(
+            "Restaurant {} rolled its menu back to version {}",
+            restaurant_id,
+            target.summary.version_number
+        );
+
+        menu_version_from_row(&row)
+    }
+
+    /// Compare a version with another version, or with the live menu
+    pub async fn diff(&self, restaurant_id: Uuid, version_id: Uuid, user_id: Uuid, against: Option<Uuid>) -> Result<MenuVersionDiff> {
+        let restaurant_service = RestaurantService::new(self.db.clone());
+        restaurant_service
+            .authorize(restaurant_id, user_id, Permission::EditMenu)
+            .await?;
+
+        let to = self.load(restaurant_id, version_id).await?;
+        let (from, from_items) = match against {
+            Some(against) => {
+                let from = self.load(restaurant_id, against).await?;
+                (Some(from.summary), from.items)
+            }
+            None => (None, restaurant_service.live_menu_items(restaurant_id).await?),
+        };
+
+        Ok(MenuVersionDiff {
+            diff: diff_menu_items(&from_items, &to.items),
+            from,
+            to: to.summary,
+        })
+    }
+
+    /// The published version. Read it in the same snapshot as the live menu
+    /// to know which version those items came from.
+    pub async fn published_version_id(executor: impl sqlx::PgExecutor<'_>, restaurant_id: Uuid) -> Result<Option<Uuid>> {
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM menu_versions WHERE restaurant_id = $1 AND status = 'published'"
+        )
+        .bind(restaurant_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// Publish every scheduled version whose time has come. Versions that can
+    /// no longer be published go back to draft so they are not retried forever.
+    /// Returns the restaurants whose menu changed.
+    pub async fn publish_due(&self, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let due = sqlx::query(
+            "SELECT id, restaurant_id FROM menu_versions WHERE status = 'scheduled' AND publish_at <= $1 ORDER BY publish_at"
+        )
+        .bind(now)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut published = Vec::new();
+        for row in due {
+            let version_id: Uuid = row.get("id");
+            let restaurant_id: Uuid = row.get("restaurant_id");
+
+            let result = match self.load(restaurant_id, version_id).await {
+                Ok(version) => match validate_menu_version(&version.items) {
+                    Ok(()) => self.publish_now(restaurant_id, version_id, None).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(_) => published.push(restaurant_id),
+                Err(e) => {
+                    tracing::warn!(
+                        "Scheduled menu version {} for restaurant {} could not be published: {:?}",
+                        version_id,
+                        restaurant_id,
+                        e
+                    );
+                    self.revert_to_draft(version_id).await?;
+                }
+            }
+        }
+
+        Ok(published)
+    }
+
+    async fn publish_now(&self, restaurant_id: Uuid, version_id: Uuid, published_by: Option<Uuid>) -> Result<MenuVersion> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::lock_restaurant(&mut tx, restaurant_id).await?;
+
+        let version = Self::load_in(&mut tx, restaurant_id, version_id).await?;
+        version.summary.status.transition_to(MenuVersionStatus::Published)?;
+
+        Self::replace_live_menu(&mut tx, restaurant_id, &version.items).await?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE menu_versions
+            SET status = 'published', published_at = NOW(), published_by = COALESCE($2, published_by)
+            WHERE id = $1
+            RETURNING {}, items
+            "#,
+            SUMMARY_COLUMNS
+        ))
+        .bind(version_id)
+        .bind(published_by)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tracing::info!(
+            "Published menu version {} for restaurant {}",
+            version.summary.version_number,
+            restaurant_id
+        );
+
+        menu_version_from_row(&row)
+    }
+
+    /// Make the live menu match `items` exactly and archive the version that
+    /// was live. Dishes missing from `items` are deleted, like deleting them
+    /// one by one would.
+    async fn replace_live_menu(tx: &mut Transaction<'_, Postgres>, restaurant_id: Uuid, items: &[MenuImportItem]) -> Result<()> {
+        let existing = RestaurantService::existing_menu_items(&mut **tx, restaurant_id).await?;
+        let matched = match_menu_items(&existing, items);
+        let kept: Vec<Uuid> = matched.iter().flatten().copied().collect();
+
+        sqlx::query("DELETE FROM menu_items WHERE restaurant_id = $1 AND NOT (id = ANY($2))")
+            .bind(restaurant_id)
+            .bind(&kept)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        RestaurantService::write_menu_items(tx, restaurant_id, items, matched).await?;
+
+        sqlx::query("UPDATE menu_versions SET status = 'archived' WHERE restaurant_id = $1 AND status = 'published'")
+            .bind(restaurant_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Serialise publishes and rollbacks for one restaurant
+    async fn lock_restaurant(tx: &mut Transaction<'_, Postgres>, restaurant_id: Uuid) -> Result<()> {
+        sqlx::query_scalar::<_, Uuid>("SELECT id FROM restaurants WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(restaurant_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn revert_to_draft(&self, version_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE menu_versions SET status = 'draft', publish_at = NULL WHERE id = $1 AND status = 'scheduled'")
+            .bind(version_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn load(&self, restaurant_id: Uuid, version_id: Uuid) -> Result<MenuVersion> {
+        let mut conn = self.db.pool().acquire().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::fetch_version(&mut conn, restaurant_id, version_id, "").await
+    }
+
+    async fn load_in(tx: &mut Transaction<'_, Postgres>, restaurant_id: Uuid, version_id: Uuid) -> Result<MenuVersion> {
+        Self::fetch_version(tx, restaurant_id, version_id, "FOR UPDATE").await
+    }
+
+    async fn fetch_version(conn: &mut sqlx::PgConnection, restaurant_id: Uuid, version_id: Uuid, lock: &str) -> Result<MenuVersion> {
+        let row = sqlx::query(&format!(
+            "SELECT {}, items FROM menu_versions WHERE id = $1 AND restaurant_id = $2 {}",
+            SUMMARY_COLUMNS, lock
+        ))
+        .bind(version_id)
+        .bind(restaurant_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Menu version not found".to_string()))?;
+
+        menu_version_from_row(&row)
+    }
+
+    fn items_json(items: &[MenuImportItem]) -> Result<serde_json::Value> {
+        serde_json::to_value(items)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode menu version: {}", e)))
+    }
+}
+
+/// Background job that publishes scheduled menu versions
+pub struct MenuPublishScheduler {
+    service: MenuVersionService,
+    cache: CacheService,
+    interval: std::time::Duration,
+}
+
+impl MenuPublishScheduler {
+    pub fn new(db: Database, cache: CacheService, interval: std::time::Duration) -> Self {
+        Self {
+            service: MenuVersionService::new(db),
+            cache,
+            interval,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(published) => tracing::info!("Published {} scheduled menu versions", published),
+                    Err(e) => tracing::error!("Scheduled menu publishing failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        let restaurants = self.service.publish_due(Utc::now()).await?;
+        for restaurant_id in &restaurants {
+            if let Err(e) = self.cache.delete(&CacheKey::RestaurantMenu(*restaurant_id)).await {
+                tracing::warn!("Failed to invalidate menu cache for restaurant {}: {:?}", restaurant_id, e);
+            }
+        }
+        Ok(restaurants.len())
+    }
+}
@@ -1,7 +1,34 @@
 pub mod models;
 pub mod handlers;
 pub mod service;
+pub mod modifiers;
+pub mod menu_import;
+pub mod schedule;
+pub mod scheduler;
+pub mod inventory;
+pub mod serviceability;
+pub mod staff;
+pub mod availability;
+pub mod onboarding;
+pub mod menu_versions;
+pub mod dietary;
+pub mod busy;
 
 pub use models::*;
 pub use handlers::*;
-pub use service::*;
\ No newline at end of file
+pub use service::*;
+pub use modifiers::*;
+pub use menu_import::*;
+pub use schedule::*;
+pub use scheduler::*;
+pub use inventory::*;
+pub use serviceability::*;
+pub use staff::*;
+pub use availability::*;
+pub use onboarding::*;
+pub use menu_versions::*;
+pub use dietary::*;
+pub use busy::*;
+
+#[cfg(test)]
+mod tests;
@@ -1,3 +1,11 @@
+use crate::money::Money;
+use crate::promotions::models::PromotionBadge;
+use crate::restaurants::availability::MenuAvailability;
+use crate::restaurants::busy::{active_busy_mode, BusyMode, BusySettings};
+use crate::restaurants::dietary::DietaryConflict;
+use crate::restaurants::modifiers::ModifierGroup;
+use crate::restaurants::schedule::{OpenStatus, OpeningHours};
+use crate::restaurants::serviceability::DeliveryArea;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -23,14 +31,20 @@ pub struct Restaurant {
     pub cover_image_url: Option<String>,
     pub rating: f64,
     pub total_reviews: i32,
-    pub delivery_fee: f64,
-    pub minimum_order: f64,
+    pub delivery_fee: Money,
+    pub minimum_order: Money,
     pub delivery_time_minutes: i32,
     pub is_active: bool,
     pub is_accepting_orders: bool,
     pub fssai_license: Option<String>,
     pub gst_number: Option<String>,
-    pub opening_hours: serde_json::Value,
+    pub opening_hours: OpeningHours,
+    pub delivery_area: DeliveryArea,
+    /// Certified pure veg: no meat, fish or egg on the menu
+    pub pure_veg: bool,
+    /// Current busy spell; may have lapsed, see `active_busy_mode`
+    pub busy_mode: Option<BusyMode>,
+    pub busy_settings: BusySettings,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -42,7 +56,7 @@ pub struct MenuItem {
     pub name: String,
     pub description: Option<String>,
     pub category: String,
-    pub price: f64,
+    pub price: Money,
     pub image_url: Option<String>,
     pub is_vegetarian: bool,
     pub is_vegan: bool,
@@ -53,6 +67,14 @@ pub struct MenuItem {
     pub is_available: bool,
     pub preparation_time_minutes: i32,
     pub calories: Option<i32>,
+    /// Restaurant's own identifier for the dish, used by bulk menu import
+    pub external_sku: Option<String>,
+    /// Portions left; `None` when stock is not tracked
+    pub stock_quantity: Option<i32>,
+    /// Level restored by the daily restock
+    pub daily_stock: Option<i32>,
+    /// Days and times the item can be ordered; `None` means always
+    pub availability: Option<MenuAvailability>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -72,12 +94,17 @@ pub struct CreateRestaurantRequest {
     pub longitude: Option<f64>,
     pub image_url: Option<String>,
     pub cover_image_url: Option<String>,
-    pub delivery_fee: f64,
-    pub minimum_order: f64,
+    pub delivery_fee: Money,
+    pub minimum_order: Money,
     pub delivery_time_minutes: i32,
     pub fssai_license: Option<String>,
     pub gst_number: Option<String>,
-    pub opening_hours: serde_json::Value,
+    #[serde(default)]
+    pub opening_hours: OpeningHours,
+    #[serde(default)]
+    pub delivery_area: DeliveryArea,
+    #[serde(default)]
+    pub pure_veg: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,13 +122,17 @@ pub struct UpdateRestaurantRequest {
     pub longitude: Option<f64>,
     pub image_url: Option<String>,
     pub cover_image_url: Option<String>,
-    pub delivery_fee: Option<f64>,
-    pub minimum_order: Option<f64>,
+    pub delivery_fee: Option<Money>,
+    pub minimum_order: Option<Money>,
     pub delivery_time_minutes: Option<i32>,
     pub is_accepting_orders: Option<bool>,
     pub fssai_license: Option<String>,
     pub gst_number: Option<String>,
-    pub opening_hours: Option<serde_json::Value>,
+    pub opening_hours: Option<OpeningHours>,
+    pub delivery_area: Option<DeliveryArea>,
+    /// Turning this on requires every menu item to be vegetarian and egg-free
+    pub pure_veg: Option<bool>,
+    pub busy_settings: Option<BusySettings>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -109,7 +140,7 @@ pub struct CreateMenuItemRequest {
     pub name: String,
     pub description: Option<String>,
     pub category: String,
-    pub price: f64,
+    pub price: Money,
     pub image_url: Option<String>,
     pub is_vegetarian: bool,
     pub is_vegan: bool,
@@ -119,6 +150,8 @@ pub struct CreateMenuItemRequest {
     pub allergens: Option<Vec<String>>,
     pub preparation_time_minutes: i32,
     pub calories: Option<i32>,
+    #[serde(default)]
+    pub availability: Option<MenuAvailability>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,7 +159,7 @@ pub struct UpdateMenuItemRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub category: Option<String>,
-    pub price: Option<f64>,
+    pub price: Option<Money>,
     pub image_url: Option<String>,
     pub is_vegetarian: Option<bool>,
     pub is_vegan: Option<bool>,
@@ -137,6 +170,8 @@ pub struct UpdateMenuItemRequest {
     pub is_available: Option<bool>,
     pub preparation_time_minutes: Option<i32>,
     pub calories: Option<i32>,
+    /// An empty object removes the item's limits
+    pub availability: Option<MenuAvailability>,
 }
 
 #[derive(Debug, Serialize)]
@@ -158,18 +193,35 @@ pub struct RestaurantResponse {
     pub cover_image_url: Option<String>,
     pub rating: f64,
     pub total_reviews: i32,
-    pub delivery_fee: f64,
-    pub minimum_order: f64,
+    pub delivery_fee: Money,
+    pub minimum_order: Money,
     pub delivery_time_minutes: i32,
     pub is_active: bool,
+    /// False while the restaurant is closed or busy mode has paused ordering
     pub is_accepting_orders: bool,
-    pub opening_hours: serde_json::Value,
+    /// Busy mode in force right now
+    pub busy: Option<BusyMode>,
+    pub opening_hours: OpeningHours,
+    /// Open-now / opens-at per the schedule; absent when no hours are set
+    pub open_status: Option<OpenStatus>,
+    pub delivery_area: DeliveryArea,
+    pub pure_veg: bool,
+    /// Offers running right now; filled in by the handlers
+    pub promotions: Vec<PromotionBadge>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl From<Restaurant> for RestaurantResponse {
     fn from(restaurant: Restaurant) -> Self {
+        let open_status = restaurant
+            .opening_hours
+            .is_configured()
+            .then(|| restaurant.opening_hours.status_at(Utc::now()));
+        let busy = active_busy_mode(&restaurant.busy_mode, Utc::now()).cloned();
+        let is_accepting_orders =
+            restaurant.is_accepting_orders && !busy.as_ref().is_some_and(BusyMode::pauses_orders);
+
         Self {
             id: restaurant.id,
             name: restaurant.name,
@@ -192,22 +244,27 @@ impl From<Restaurant> for RestaurantResponse {
             minimum_order: restaurant.minimum_order,
             delivery_time_minutes: restaurant.delivery_time_minutes,
             is_active: restaurant.is_active,
-            is_accepting_orders: restaurant.is_accepting_orders,
+            is_accepting_orders,
+            busy,
             opening_hours: restaurant.opening_hours,
+            open_status,
+            delivery_area: restaurant.delivery_area,
+            pure_veg: restaurant.pure_veg,
+            promotions: Vec::new(),
             created_at: restaurant.created_at,
             updated_at: restaurant.updated_at,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MenuItemResponse {
     pub id: Uuid,
     pub restaurant_id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub category: String,
-    pub price: f64,
+    pub price: Money,
     pub image_url: Option<String>,
     pub is_vegetarian: bool,
     pub is_vegan: bool,
@@ -218,6 +275,19 @@ pub struct MenuItemResponse {
     pub is_available: bool,
     pub preparation_time_minutes: i32,
     pub calories: Option<i32>,
+    pub external_sku: Option<String>,
+    pub stock_quantity: Option<i32>,
+    pub daily_stock: Option<i32>,
+    #[serde(default)]
+    pub availability: Option<MenuAvailability>,
+    /// Sizes and add-ons; filled in by `get_menu`
+    pub modifier_groups: Vec<ModifierGroup>,
+    /// Item-level offers running right now; applied on every menu read
+    #[serde(default)]
+    pub promotions: Vec<PromotionBadge>,
+    /// How the item clashes with the reader's dietary profile
+    #[serde(default)]
+    pub dietary_conflicts: Vec<DietaryConflict>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -241,6 +311,13 @@ impl From<MenuItem> for MenuItemResponse {
             is_available: item.is_available,
             preparation_time_minutes: item.preparation_time_minutes,
             calories: item.calories,
+            external_sku: item.external_sku,
+            stock_quantity: item.stock_quantity,
+            daily_stock: item.daily_stock,
+            availability: item.availability,
+            modifier_groups: Vec::new(),
+            promotions: Vec::new(),
+            dietary_conflicts: Vec::new(),
             created_at: item.created_at,
             updated_at: item.updated_at,
         }
@@ -255,14 +332,19 @@ pub struct RestaurantListResponse {
     pub per_page: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MenuResponse {
     pub restaurant_id: Uuid,
     pub categories: Vec<MenuCategory>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MenuCategory {
     pub name: String,
+    /// True when any item in the category has sizes or add-ons to choose
+    pub has_customizations: bool,
+    /// Limits applying to every item in the category
+    #[serde(default)]
+    pub availability: Option<MenuAvailability>,
     pub items: Vec<MenuItemResponse>,
 }
@@ -0,0 +1,334 @@
+use crate::error::{AppError, Result};
+use crate::money::Money;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Variants pick exactly one option that replaces part of the base price
+/// (half/full plate, regular/large); add-ons are optional extras.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModifierGroupKind {
+    Variant,
+    AddOn,
+}
+
+impl ModifierGroupKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModifierGroupKind::Variant => "variant",
+            ModifierGroupKind::AddOn => "add_on",
+        }
+    }
+}
+
+impl std::str::FromStr for ModifierGroupKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "variant" => Ok(ModifierGroupKind::Variant),
+            "add_on" => Ok(ModifierGroupKind::AddOn),
+            other => Err(AppError::ValidationError(format!("Unknown modifier group kind: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifierOption {
+    pub id: Uuid,
+    pub group_id: Uuid,
+    pub name: String,
+    /// Added to the item's base price; may be negative for cheaper variants
+    pub price_delta: Money,
+    pub is_default: bool,
+    pub is_available: bool,
+    pub display_order: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifierGroup {
+    pub id: Uuid,
+    pub menu_item_id: Uuid,
+    pub name: String,
+    pub kind: ModifierGroupKind,
+    pub min_selections: i32,
+    pub max_selections: i32,
+    pub is_required: bool,
+    pub display_order: i32,
+    pub options: Vec<ModifierOption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateModifierOptionRequest {
+    pub name: String,
+    #[serde(default)]
+    pub price_delta: Money,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default = "default_true")]
+    pub is_available: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateModifierGroupRequest {
+    pub name: String,
+    pub kind: ModifierGroupKind,
+    #[serde(default)]
+    pub min_selections: i32,
+    pub max_selections: Option<i32>,
+    #[serde(default)]
+    pub is_required: bool,
+    #[serde(default)]
+    pub display_order: i32,
+    pub options: Vec<CreateModifierOptionRequest>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl From<&ModifierGroup> for CreateModifierGroupRequest {
+    fn from(group: &ModifierGroup) -> Self {
+        Self {
+            name: group.name.clone(),
+            kind: group.kind,
+            min_selections: group.min_selections,
+            max_selections: Some(group.max_selections),
+            is_required: group.is_required,
+            display_order: group.display_order,
+            options: group
+                .options
+                .iter()
+                .map(|option| CreateModifierOptionRequest {
+                    name: option.name.clone(),
+                    price_delta: option.price_delta,
+                    is_default: option.is_default,
+                    is_available: option.is_available,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CreateModifierGroupRequest {
+    /// Effective (min, max) selections. Variants are always exactly one;
+    /// a required add-on group needs at least one pick.
+    pub fn selection_bounds(&self) -> (i32, i32) {
+        match self.kind {
+            ModifierGroupKind::Variant => (1, 1),
+            ModifierGroupKind::AddOn => {
+                let min = if self.is_required {
+                    self.min_selections.max(1)
+                } else {
+                    self.min_selections
+                };
+                let max = self.max_selections.unwrap_or(self.options.len() as i32);
+                (min, max)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::ValidationError("Modifier group name is required".to_string()));
+        }
+        if self.options.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "Modifier group '{}' needs at least one option",
+                self.name
+            )));
+        }
+
+        let (min, max) = self.selection_bounds();
+        if min < 0 || max < 1 || min > max || max > self.options.len() as i32 {
+            return Err(AppError::ValidationError(format!(
+                "Modifier group '{}' has invalid selection limits ({}..={} of {} options)",
+                self.name,
+                min,
+                max,
+                self.options.len()
+            )));
+        }
+
+        let mut names = HashSet::new();
+        for option in &self.options {
+            if option.name.trim().is_empty() {
+                return Err(AppError::ValidationError("Modifier option name is required".to_string()));
+            }
+            if !names.insert(option.name.trim().to_lowercase()) {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate option '{}' in modifier group '{}'",
+                    option.name, self.name
+                )));
+            }
+            if self.kind == ModifierGroupKind::AddOn && option.price_delta.is_negative() {
+                return Err(AppError::ValidationError(format!(
+                    "Add-on '{}' cannot have a negative price",
+                    option.name
+                )));
+            }
+        }
+
+        let defaults = self.options.iter().filter(|o| o.is_default).count() as i32;
+        if defaults > max {
+            return Err(AppError::ValidationError(format!(
+                "Modifier group '{}' has more default options than it allows",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Options the customer picked from one group, as sent when placing an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifierSelection {
+    pub group_id: Uuid,
+    #[serde(default)]
+    pub option_ids: Vec<Uuid>,
+}
+
+/// A priced option on an order item. Names are copied so the order still
+/// reads correctly after the menu changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedModifier {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub option_id: Uuid,
+    pub option_name: String,
+    pub price_delta: Money,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricedItem {
+    pub unit_price: Money,
+    pub modifiers: Vec<AppliedModifier>,
+}
+
+/// Validate a customer's selections against an item's modifier groups and
+/// work out the unit price. Groups the customer left out fall back to their
+/// default options.
+pub fn price_selections(
+    base_price: Money,
+    groups: &[ModifierGroup],
+    selections: &[ModifierSelection],
+) -> Result<PricedItem> {
+    let mut selected_groups = HashSet::new();
+    for selection in selections {
+        if !groups.iter().any(|g| g.id == selection.group_id) {
+            return Err(AppError::ValidationError(format!(
+                "Modifier group {} does not belong to this item",
+                selection.group_id
+            )));
+        }
+        if !selected_groups.insert(selection.group_id) {
+            return Err(AppError::ValidationError(format!(
+                "Modifier group {} selected more than once",
+                selection.group_id
+            )));
+        }
+    }
+
+    let mut unit_price = base_price;
+    let mut modifiers = Vec::new();
+
+    for group in groups {
+        let chosen: Vec<&ModifierOption> = match selections.iter().find(|s| s.group_id == group.id) {
+            Some(selection) => {
+                let mut seen = HashSet::new();
+                let mut chosen = Vec::with_capacity(selection.option_ids.len());
+                for option_id in &selection.option_ids {
+                    let option = group.options.iter().find(|o| o.id == *option_id).ok_or_else(|| {
+                        AppError::ValidationError(format!(
+                            "Option {} is not part of '{}'",
+                            option_id, group.name
+                        ))
+                    })?;
+                    if !option.is_available {
+                        return Err(AppError::ValidationError(format!(
+                            "'{}' is currently unavailable",
+                            option.name
+                        )));
+                    }
+                    if !seen.insert(option.id) {
+                        return Err(AppError::ValidationError(format!(
+                            "'{}' selected more than once",
+                            option.name
+                        )));
+                    }
+                    chosen.push(option);
+                }
+                chosen
+            }
+            None => group
+                .options
+                .iter()
+                .filter(|o| o.is_default && o.is_available)
+                .collect(),
+        };
+
+        let count = chosen.len() as i32;
+        if count < group.min_selections {
+            return Err(AppError::ValidationError(format!(
+                "Choose at least {} option(s) for '{}'",
+                group.min_selections, group.name
+            )));
+        }
+        if count > group.max_selections {
+            return Err(AppError::ValidationError(format!(
+                "Choose at most {} option(s) for '{}'",
+                group.max_selections, group.name
+            )));
+        }
+
+        for option in chosen {
+            unit_price += option.price_delta;
+            modifiers.push(AppliedModifier {
+                group_id: group.id,
+                group_name: group.name.clone(),
+                option_id: option.id,
+                option_name: option.name.clone(),
+                price_delta: option.price_delta,
+            });
+        }
+    }
+
+    if unit_price.is_negative() {
+        return Err(AppError::ValidationError(
+            "Selected options bring the item price below zero".to_string(),
+        ));
+    }
+
+    Ok(PricedItem { unit_price, modifiers })
+}
+
+pub(crate) fn modifier_group_from_row(row: &PgRow) -> Result<ModifierGroup> {
+    let kind: String = row.get("kind");
+
+    Ok(ModifierGroup {
+        id: row.get("id"),
+        menu_item_id: row.get("menu_item_id"),
+        name: row.get("name"),
+        kind: kind.parse()?,
+        min_selections: row.get("min_selections"),
+        max_selections: row.get("max_selections"),
+        is_required: row.get("is_required"),
+        display_order: row.get("display_order"),
+        options: Vec::new(),
+    })
+}
+
+pub(crate) fn modifier_option_from_row(row: &PgRow) -> ModifierOption {
+    ModifierOption {
+        id: row.get("id"),
+        group_id: row.get("group_id"),
+        name: row.get("name"),
+        price_delta: row.get("price_delta"),
+        is_default: row.get("is_default"),
+        is_available: row.get("is_available"),
+        display_order: row.get("display_order"),
+    }
+}
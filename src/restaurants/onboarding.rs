@@ -0,0 +1,559 @@
+use crate::auth::models::User;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::india::compliance::{validate_fssai_license, validate_gstin};
+use crate::india::config::ComplianceConfig;
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Where a restaurant is in onboarding. It is listed to customers
+/// (`is_active`) only once it has been approved and gone live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStatus {
+    /// Filling in details and uploading documents
+    Draft,
+    /// Waiting for an admin to pick it up
+    DocumentsSubmitted,
+    UnderReview,
+    /// Cleared to go live whenever the owner is ready
+    Approved,
+    Live,
+    /// Sent back with a note; can be corrected and resubmitted
+    Rejected,
+}
+
+impl OnboardingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnboardingStatus::Draft => "draft",
+            OnboardingStatus::DocumentsSubmitted => "documents_submitted",
+            OnboardingStatus::UnderReview => "under_review",
+            OnboardingStatus::Approved => "approved",
+            OnboardingStatus::Live => "live",
+            OnboardingStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Whether KYC details and documents can still be changed
+    pub fn is_editable(&self) -> bool {
+        matches!(self, OnboardingStatus::Draft | OnboardingStatus::Rejected)
+    }
+
+    pub fn can_transition_to(&self, next: &OnboardingStatus) -> bool {
+        matches!(
+            (self, next),
+            (OnboardingStatus::Draft, OnboardingStatus::DocumentsSubmitted)
+                | (OnboardingStatus::Rejected, OnboardingStatus::DocumentsSubmitted)
+                | (OnboardingStatus::DocumentsSubmitted, OnboardingStatus::UnderReview)
+                | (OnboardingStatus::UnderReview, OnboardingStatus::Approved)
+                | (OnboardingStatus::UnderReview, OnboardingStatus::Rejected)
+                | (OnboardingStatus::Approved, OnboardingStatus::Live)
+        )
+    }
+
+    pub fn transition_to(&self, next: OnboardingStatus) -> Result<OnboardingStatus> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(AppError::InvalidStatusTransition(format!(
+                "onboarding cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::str::FromStr for OnboardingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(OnboardingStatus::Draft),
+            "documents_submitted" => Ok(OnboardingStatus::DocumentsSubmitted),
+            "under_review" => Ok(OnboardingStatus::UnderReview),
+            "approved" => Ok(OnboardingStatus::Approved),
+            "live" => Ok(OnboardingStatus::Live),
+            "rejected" => Ok(OnboardingStatus::Rejected),
+            _ => Err(format!("Invalid onboarding status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KycDocumentType {
+    FssaiLicense,
+    GstCertificate,
+    /// PAN card of the business or proprietor
+    PanCard,
+    /// Cancelled cheque or bank statement for payouts
+    BankProof,
+}
+
+impl KycDocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KycDocumentType::FssaiLicense => "fssai_license",
+            KycDocumentType::GstCertificate => "gst_certificate",
+            KycDocumentType::PanCard => "pan_card",
+            KycDocumentType::BankProof => "bank_proof",
+        }
+    }
+
+    /// Documents every restaurant must upload under `config`
+    pub fn required(config: &ComplianceConfig) -> Vec<KycDocumentType> {
+        let mut required = Vec::new();
+        if config.fssai_required {
+            required.push(KycDocumentType::FssaiLicense);
+        }
+        if config.gst_registration_required {
+            required.push(KycDocumentType::GstCertificate);
+        }
+        required.extend([KycDocumentType::PanCard, KycDocumentType::BankProof]);
+        required
+    }
+}
+
+impl std::str::FromStr for KycDocumentType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "fssai_license" => Ok(KycDocumentType::FssaiLicense),
+            "gst_certificate" => Ok(KycDocumentType::GstCertificate),
+            "pan_card" => Ok(KycDocumentType::PanCard),
+            "bank_proof" => Ok(KycDocumentType::BankProof),
+            _ => Err(format!("Invalid KYC document type: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KycDocument {
+    pub id: Uuid,
+    pub restaurant_id: Uuid,
+    pub document_type: KycDocumentType,
+    /// Storage key or URL of the uploaded file
+    pub reference: String,
+    pub uploaded_by: Uuid,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+fn kyc_document_from_row(row: &PgRow) -> Result<KycDocument> {
+    let document_type: String = row.get("document_type");
+
+    Ok(KycDocument {
+        id: row.get("id"),
+        restaurant_id: row.get("restaurant_id"),
+        document_type: document_type.parse().map_err(AppError::DatabaseError)?,
+        reference: row.get("reference"),
+        uploaded_by: row.get("uploaded_by"),
+        uploaded_at: row.get("uploaded_at"),
+    })
+}
+
+/// What still stands between a restaurant and submitting for review:
+/// missing or malformed FSSAI and GST numbers, and missing documents
+pub fn missing_requirements(
+    config: &ComplianceConfig,
+    fssai_license: Option<&str>,
+    gst_number: Option<&str>,
+    state: &str,
+    documents: &[KycDocument],
+) -> Vec<String> {
+    let mut missing = Vec::new();
+
+    match fssai_license {
+        Some(number) => {
+            if let Err(AppError::ValidationError(message)) = validate_fssai_license(number) {
+                missing.push(message);
+            }
+        }
+        None if config.fssai_required => missing.push("FSSAI licence number is required".to_string()),
+        None => {}
+    }
+    match gst_number {
+        Some(gstin) => {
+            if let Err(AppError::ValidationError(message)) = validate_gstin(gstin, state) {
+                missing.push(message);
+            }
+        }
+        None if config.gst_registration_required => missing.push("GSTIN is required".to_string()),
+        None => {}
+    }
+
+    for document_type in KycDocumentType::required(config) {
+        if !documents.iter().any(|document| document.document_type == document_type) {
+            missing.push(format!("{} document has not been uploaded", document_type.as_str()));
+        }
+    }
+    missing
+}
+
+/// Validate FSSAI and GST numbers supplied on a restaurant create or update
+/// and replace them with their normalised form
+pub fn normalize_kyc_fields(fssai_license: &mut Option<String>, gst_number: &mut Option<String>, state: &str) -> Result<()> {
+    if let Some(number) = fssai_license.as_deref() {
+        *fssai_license = Some(validate_fssai_license(number)?);
+    }
+    if let Some(gstin) = gst_number.as_deref() {
+        *gst_number = Some(validate_gstin(gstin, state)?);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    pub restaurant_id: Uuid,
+    pub restaurant_name: String,
+    pub owner_id: Uuid,
+    pub status: OnboardingStatus,
+    pub fssai_license: Option<String>,
+    pub gst_number: Option<String>,
+    pub documents: Vec<KycDocument>,
+    /// Empty once the restaurant can be submitted
+    pub missing: Vec<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    /// Reason given with the last rejection or approval
+    pub review_note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadKycDocumentRequest {
+    pub document_type: KycDocumentType,
+    /// Storage key or URL of the uploaded file
+    pub reference: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingReviewAction {
+    /// Pick up a submitted restaurant
+    StartReview,
+    Approve,
+    /// Send back to the owner; a note is required
+    Reject,
+}
+
+impl OnboardingReviewAction {
+    pub fn target_status(&self) -> OnboardingStatus {
+        match self {
+            OnboardingReviewAction::StartReview => OnboardingStatus::UnderReview,
+            OnboardingReviewAction::Approve => OnboardingStatus::Approved,
+            OnboardingReviewAction::Reject => OnboardingStatus::Rejected,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewOnboardingRequest {
+    pub action: OnboardingReviewAction,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnboardingQueueQuery {
+    /// Defaults to both submitted and under-review restaurants
+    pub status: Option<OnboardingStatus>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnboardingQueueResponse {
+    pub restaurants: Vec<OnboardingState>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+fn status_changed(expected: OnboardingStatus) -> AppError {
+    AppError::InvalidStatusTransition(format!("onboarding is no longer {}", expected.as_str()))
+}
+
+const ONBOARDING_COLUMNS: &str = "id, name, owner_id, state, fssai_license, gst_number, onboarding_status, \
+     onboarding_submitted_at, onboarding_reviewed_at, onboarding_review_note";
+
+pub struct OnboardingService {
+    db: Database,
+    compliance: ComplianceConfig,
+}
+
+impl OnboardingService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            compliance: ComplianceConfig::default(),
+        }
+    }
+
+    pub async fn get_onboarding(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<OnboardingState> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageRestaurant)
+            .await?;
+        self.load(restaurant_id).await
+    }
+
+    /// Attach a document, replacing any earlier upload of the same type
+    pub async fn upload_document(&self, restaurant_id: Uuid, user_id: Uuid, request: UploadKycDocumentRequest) -> Result<KycDocument> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageRestaurant)
+            .await?;
+
+        let reference = request.reference.trim();
+        if reference.is_empty() {
+            return Err(AppError::ValidationError("Document reference is required".to_string()));
+        }
+        let status = self.load(restaurant_id).await?.status;
+        if !status.is_editable() {
+            return Err(AppError::ValidationError(format!(
+                "Documents cannot be changed while onboarding is {}",
+                status.as_str()
+            )));
+        }
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO restaurant_kyc_documents (restaurant_id, document_type, reference, uploaded_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (restaurant_id, document_type)
+            DO UPDATE SET reference = EXCLUDED.reference, uploaded_by = EXCLUDED.uploaded_by, uploaded_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(request.document_type.as_str())
+        .bind(reference)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        kyc_document_from_row(&row)
+    }
+
+    /// Send the restaurant for admin review once nothing is missing
+    pub async fn submit(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<OnboardingState> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageRestaurant)
+            .await?;
+
+        let onboarding = self.load(restaurant_id).await?;
+        let next = onboarding.status.transition_to(OnboardingStatus::DocumentsSubmitted)?;
+        if !onboarding.missing.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "Onboarding is incomplete: {}",
+                onboarding.missing.join("; ")
+            )));
+        }
+
+        self.set_status(restaurant_id, onboarding.status, next, "onboarding_submitted_at = NOW()")
+            .await?;
+        self.load(restaurant_id).await
+    }
+
+    /// Start taking orders after approval
+    pub async fn go_live(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<OnboardingState> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageRestaurant)
+            .await?;
+
+        let current = self.load(restaurant_id).await?.status;
+        let next = current.transition_to(OnboardingStatus::Live)?;
+        self.set_status(restaurant_id, current, next, "is_active = true").await?;
+        self.load(restaurant_id).await
+    }
+
+    /// Admin queue of restaurants waiting for or in review, oldest first
+    pub async fn review_queue(&self, status: Option<OnboardingStatus>, page: i32, per_page: i32) -> Result<OnboardingQueueResponse> {
+        let statuses: Vec<&str> = match status {
+            Some(status) => vec![status.as_str()],
+            None => vec![
+                OnboardingStatus::DocumentsSubmitted.as_str(),
+                OnboardingStatus::UnderReview.as_str(),
+            ],
+        };
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {} FROM restaurants
+            WHERE deleted_at IS NULL AND onboarding_status = ANY($1)
+            ORDER BY onboarding_submitted_at NULLS LAST, created_at
+            LIMIT $2 OFFSET $3
+            "#,
+            ONBOARDING_COLUMNS
+        ))
+        .bind(&statuses)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let total = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM restaurants WHERE deleted_at IS NULL AND onboarding_status = ANY($1)"
+        )
+        .bind(&statuses)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let mut documents = self.documents(&ids).await?;
+        let restaurants = rows
+            .iter()
+            .map(|row| {
+                let id: Uuid = row.get("id");
+                self.state_from_row(row, documents.remove(&id).unwrap_or_default())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(OnboardingQueueResponse {
+            restaurants,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Move a restaurant through review on behalf of an admin
+    pub async fn review(&self, restaurant_id: Uuid, admin: &User, request: ReviewOnboardingRequest) -> Result<OnboardingState> {
+        let note = request
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty());
+        if request.action == OnboardingReviewAction::Reject && note.is_none() {
+            return Err(AppError::ValidationError("A note is required when rejecting".to_string()));
+        }
+
+        let current = self.load(restaurant_id).await?.status;
+        let next = current.transition_to(request.action.target_status())?;
+
+        // Picking a restaurant up records the reviewer; the outcome also
+        // records when and why, replacing the note from any earlier round
+        let result = sqlx::query(
+            r#"
+            UPDATE restaurants
+            SET onboarding_status = $2,
+                onboarding_reviewed_by = $4,
+                onboarding_reviewed_at = CASE WHEN $2 = 'under_review' THEN onboarding_reviewed_at ELSE NOW() END,
+                onboarding_review_note = CASE WHEN $2 = 'under_review' THEN onboarding_review_note ELSE $5 END,
+                updated_at = NOW()
+            WHERE id = $1 AND onboarding_status = $3 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(next.as_str())
+        .bind(current.as_str())
+        .bind(admin.id)
+        .bind(note)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Err(status_changed(current));
+        }
+
+        self.load(restaurant_id).await
+    }
+
+    /// Compare-and-set the status so that concurrent requests cannot both
+    /// win; `extra` is added to the SET clause
+    async fn set_status(&self, restaurant_id: Uuid, current: OnboardingStatus, next: OnboardingStatus, extra: &str) -> Result<()> {
+        let query = format!(
+            r#"
+            UPDATE restaurants SET onboarding_status = $2, {}, updated_at = NOW()
+            WHERE id = $1 AND onboarding_status = $3 AND deleted_at IS NULL
+            "#,
+            extra
+        );
+        let result = sqlx::query(&query)
+            .bind(restaurant_id)
+            .bind(next.as_str())
+            .bind(current.as_str())
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(status_changed(current));
+        }
+        Ok(())
+    }
+
+    async fn load(&self, restaurant_id: Uuid) -> Result<OnboardingState> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM restaurants WHERE id = $1 AND deleted_at IS NULL",
+            ONBOARDING_COLUMNS
+        ))
+        .bind(restaurant_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
+
+        let documents = self.documents(&[restaurant_id]).await?.remove(&restaurant_id).unwrap_or_default();
+        self.state_from_row(&row, documents)
+    }
+
+    async fn documents(&self, restaurant_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<KycDocument>>> {
+        let rows = sqlx::query(
+            "SELECT * FROM restaurant_kyc_documents WHERE restaurant_id = ANY($1) ORDER BY uploaded_at"
+        )
+        .bind(restaurant_ids)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut documents: HashMap<Uuid, Vec<KycDocument>> = HashMap::new();
+        for row in &rows {
+            let document = kyc_document_from_row(row)?;
+            documents.entry(document.restaurant_id).or_default().push(document);
+        }
+        Ok(documents)
+    }
+
+    fn state_from_row(&self, row: &PgRow, documents: Vec<KycDocument>) -> Result<OnboardingState> {
+        let status: String = row.get("onboarding_status");
+        let status: OnboardingStatus = status.parse().map_err(AppError::DatabaseError)?;
+        let fssai_license: Option<String> = row.get("fssai_license");
+        let gst_number: Option<String> = row.get("gst_number");
+        let state: String = row.get("state");
+
+        let missing = if status.is_editable() {
+            missing_requirements(
+                &self.compliance,
+                fssai_license.as_deref(),
+                gst_number.as_deref(),
+                &state,
+                &documents,
+            )
+        } else {
+            Vec::new()
+        };
+
+        Ok(OnboardingState {
+            restaurant_id: row.get("id"),
+            restaurant_name: row.get("name"),
+            owner_id: row.get("owner_id"),
+            status,
+            fssai_license,
+            gst_number,
+            documents,
+            missing,
+            submitted_at: row.get("onboarding_submitted_at"),
+            reviewed_at: row.get("onboarding_reviewed_at"),
+            review_note: row.get("onboarding_review_note"),
+        })
+    }
+}
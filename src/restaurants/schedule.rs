@@ -0,0 +1,255 @@
+use crate::error::{AppError, Result};
+use crate::india::config::ISTConfig;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// How far ahead to look for the next opening before giving up
+const MAX_LOOKAHEAD_DAYS: i64 = 14;
+
+/// A single service window, in IST wall-clock time. A shift whose closing
+/// time is at or before its opening time runs past midnight into the next
+/// day (e.g. 19:00-02:00); equal times mean open around the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shift {
+    #[serde(with = "hhmm")]
+    pub open: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub close: NaiveTime,
+}
+
+impl Shift {
+    pub fn crosses_midnight(&self) -> bool {
+        self.close <= self.open
+    }
+
+    /// The shift as a half-open local interval when it starts on `date`
+    fn interval_on(&self, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        let start = date.and_time(self.open);
+        let end_date = if self.crosses_midnight() {
+            date + Duration::days(1)
+        } else {
+            date
+        };
+        (start, end_date.and_time(self.close))
+    }
+}
+
+/// Replaces the weekly shifts for one calendar date. An override with no
+/// shifts closes the restaurant for the day (holidays, festivals).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateOverride {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub shifts: Vec<Shift>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Weekly opening hours with per-date overrides, stored as JSONB on
+/// `restaurants.opening_hours`. Older rows with a single
+/// `{"open", "close"}` object per day are read as one shift.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningHours {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub monday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub tuesday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub wednesday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub thursday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub friday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub saturday: Vec<Shift>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub sunday: Vec<Shift>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<DateOverride>,
+}
+
+/// Open-now / opens-at summary shown to customers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OpenStatus {
+    pub is_open_now: bool,
+    pub opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+}
+
+impl OpeningHours {
+    pub fn shifts_for_weekday(&self, weekday: Weekday) -> &[Shift] {
+        match weekday {
+            Weekday::Mon => &self.monday,
+            Weekday::Tue => &self.tuesday,
+            Weekday::Wed => &self.wednesday,
+            Weekday::Thu => &self.thursday,
+            Weekday::Fri => &self.friday,
+            Weekday::Sat => &self.saturday,
+            Weekday::Sun => &self.sunday,
+        }
+    }
+
+    /// Shifts starting on `date`, after applying any override for that date
+    pub fn shifts_for_date(&self, date: NaiveDate) -> &[Shift] {
+        match self.overrides.iter().find(|o| o.date == date) {
+            Some(date_override) => &date_override.shifts,
+            None => self.shifts_for_weekday(date.weekday()),
+        }
+    }
+
+    /// A restaurant without any hours configured is treated as unscheduled:
+    /// the owner controls `is_accepting_orders` by hand.
+    pub fn is_configured(&self) -> bool {
+        !self.overrides.is_empty()
+            || [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]
+            .iter()
+            .any(|day| !self.shifts_for_weekday(*day).is_empty())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        for date_override in &self.overrides {
+            if !seen.insert(date_override.date) {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate opening hours override for {}",
+                    date_override.date
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Local (IST) intervals of every shift starting between `from` and `to` inclusive
+    fn intervals_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let mut intervals: Vec<_> = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .flat_map(|date| {
+                self.shifts_for_date(date)
+                    .iter()
+                    .map(move |shift| shift.interval_on(date))
+            })
+            .collect();
+        intervals.sort();
+        intervals
+    }
+
+    pub fn is_open_at(&self, at: DateTime<Utc>) -> bool {
+        self.status_at(at).is_open_now
+    }
+
+    pub fn status_at(&self, at: DateTime<Utc>) -> OpenStatus {
+        let ist = ISTConfig::default().offset();
+        let local = at.with_timezone(&ist).naive_local();
+        let today = local.date();
+
+        // Yesterday's late shifts may still be running
+        let intervals = self.intervals_between(
+            today - Duration::days(1),
+            today + Duration::days(MAX_LOOKAHEAD_DAYS),
+        );
+        let to_utc = |t: NaiveDateTime| ist.from_local_datetime(&t).single().map(|d| d.with_timezone(&Utc));
+
+        let current = intervals.iter().find(|(start, end)| *start <= local && local < *end);
+        match current {
+            Some(&(_, mut end)) => {
+                // Back-to-back or overlapping shifts read as one continuous opening
+                for &(next_start, next_end) in &intervals {
+                    if next_start <= end && next_end > end {
+                        end = next_end;
+                    }
+                }
+                OpenStatus {
+                    is_open_now: true,
+                    opens_at: None,
+                    closes_at: to_utc(end),
+                }
+            }
+            None => OpenStatus {
+                is_open_now: false,
+                opens_at: intervals
+                    .iter()
+                    .map(|(start, _)| *start)
+                    .find(|start| *start > local)
+                    .and_then(to_utc),
+                closes_at: None,
+            },
+        }
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Shift>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Many(Vec<Shift>),
+        One(Shift),
+        Closed {
+            #[allow(dead_code)]
+            closed: bool,
+        },
+    }
+
+    Ok(match Option::<Repr>::deserialize(deserializer)? {
+        Some(Repr::Many(shifts)) => shifts,
+        Some(Repr::One(shift)) => vec![shift],
+        Some(Repr::Closed { .. }) | None => Vec::new(),
+    })
+}
+
+/// `HH:MM` times, also accepting `HH:MM:SS` on input
+mod hhmm {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        // "24:00" is a common way of writing end-of-day closing
+        if raw == "24:00" {
+            return Ok(NaiveTime::MIN);
+        }
+        NaiveTime::parse_from_str(&raw, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(&raw, "%H:%M:%S"))
+            .map_err(|_| serde::de::Error::custom(format!("invalid time '{}', expected HH:MM", raw)))
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for OpeningHours {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for OpeningHours {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<&Self> as sqlx::Encode<sqlx::Postgres>>::encode(sqlx::types::Json(self), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for OpeningHours {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        // Malformed legacy JSON means "no schedule" rather than a failed query
+        let raw = <serde_json::Value as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
+}
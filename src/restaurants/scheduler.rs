@@ -0,0 +1,136 @@
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::restaurants::schedule::OpeningHours;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ScheduleSummary {
+    pub checked: usize,
+    pub opened: usize,
+    pub closed: usize,
+}
+
+/// What the scheduler should do with a restaurant on this pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleAction {
+    /// Nothing changed since the last pass
+    Keep,
+    /// The schedule crossed a boundary; set `is_accepting_orders` to this value
+    Apply(bool),
+    /// Hours were removed; stop tracking and leave the manual toggle alone
+    Forget,
+}
+
+/// Only act on boundaries, so an owner pausing orders mid-shift (or opening
+/// early) is respected until the schedule next opens or closes.
+pub fn schedule_action(hours: &OpeningHours, last_applied: Option<bool>, at: DateTime<Utc>) -> ScheduleAction {
+    if !hours.is_configured() {
+        return match last_applied {
+            Some(_) => ScheduleAction::Forget,
+            None => ScheduleAction::Keep,
+        };
+    }
+
+    let is_open = hours.is_open_at(at);
+    if last_applied == Some(is_open) {
+        ScheduleAction::Keep
+    } else {
+        ScheduleAction::Apply(is_open)
+    }
+}
+
+/// Background job that flips `is_accepting_orders` when a restaurant's
+/// opening hours say it has opened or closed.
+pub struct OpeningHoursScheduler {
+    db: Database,
+    interval: std::time::Duration,
+}
+
+impl OpeningHoursScheduler {
+    pub fn new(db: Database, interval: std::time::Duration) -> Self {
+        Self { db, interval }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(summary) if summary.opened > 0 || summary.closed > 0 => {
+                        tracing::info!("Opening hours update: {:?}", summary);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Opening hours update failed: {:?}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<ScheduleSummary> {
+        let now = Utc::now();
+        let mut summary = ScheduleSummary::default();
+
+        let rows = sqlx::query(
+            "SELECT id, opening_hours, schedule_is_open FROM restaurants WHERE is_active = true"
+        )
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for row in rows {
+            let restaurant_id: Uuid = row.get("id");
+            let hours: OpeningHours = row.get("opening_hours");
+            let last_applied: Option<bool> = row.get("schedule_is_open");
+
+            summary.checked += 1;
+            match schedule_action(&hours, last_applied, now) {
+                ScheduleAction::Keep => {}
+                ScheduleAction::Apply(is_open) => {
+                    self.apply(restaurant_id, is_open, now).await?;
+                    if is_open {
+                        summary.opened += 1;
+                    } else {
+                        summary.closed += 1;
+                    }
+                }
+                ScheduleAction::Forget => {
+                    sqlx::query("UPDATE restaurants SET schedule_is_open = NULL WHERE id = $1")
+                        .bind(restaurant_id)
+                        .execute(self.db.pool())
+                        .await
+                        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn apply(&self, restaurant_id: Uuid, is_open: bool, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE restaurants
+            SET is_accepting_orders = $1, schedule_is_open = $1, updated_at = $2
+            WHERE id = $3 AND schedule_is_open IS DISTINCT FROM $1
+            "#,
+        )
+        .bind(is_open)
+        .bind(now)
+        .bind(restaurant_id)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tracing::info!(
+            "Restaurant {} {} by opening hours",
+            restaurant_id,
+            if is_open { "opened" } else { "closed" }
+        );
+
+        Ok(())
+    }
+}
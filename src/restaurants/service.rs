@@ -1,11 +1,30 @@
 use crate::database::Database;
 use crate::error::{AppError, Result};
+use crate::restaurants::menu_import::{
+    export_item, match_menu_items, parse_menu_file, validate_import, MenuFileFormat, MenuImportItem,
+    MenuImportResponse, ParsedMenuFile,
+};
+use crate::delivery::service::DeliveryService;
+use crate::india::config::IndiaConfig;
+use crate::promotions::service::PromotionService;
+use crate::restaurants::availability::MenuAvailability;
+use crate::restaurants::dietary::{check_pure_veg, DishFacts};
+use crate::restaurants::onboarding::{normalize_kyc_fields, OnboardingStatus};
 use crate::restaurants::models::*;
+use crate::restaurants::staff::{Permission, StaffRole};
+use crate::restaurants::serviceability::{
+    delivery_fee_for_distance, sort_nearby, GeoPoint, NearbyQuery, NearbyRestaurant,
+    NearbyRestaurantsResponse, MAX_DELIVERY_RADIUS_KM,
+};
+use crate::restaurants::modifiers::{
+    modifier_group_from_row, modifier_option_from_row, CreateModifierGroupRequest,
+    CreateModifierOptionRequest, ModifierGroup, ModifierOption,
+};
 use uuid::Uuid;
 use chrono::Utc;
-// Using f64 for decimal values for better PostgreSQL compatibility
+use crate::money::Money;
 use std::collections::HashMap;
-use sqlx::Row;
+use sqlx::{PgConnection, Row};
 
 pub struct RestaurantService {
     db: Database,
@@ -16,7 +35,13 @@ impl RestaurantService {
         Self { db }
     }
 
-    pub async fn create_restaurant(&self, owner_id: Uuid, request: CreateRestaurantRequest) -> Result<Restaurant> {
+    /// Create a restaurant in draft. It is listed once it has been through
+    /// onboarding review and gone live.
+    pub async fn create_restaurant(&self, owner_id: Uuid, mut request: CreateRestaurantRequest) -> Result<Restaurant> {
+        request.opening_hours.validate()?;
+        request.delivery_area.validate()?;
+        normalize_kyc_fields(&mut request.fssai_license, &mut request.gst_number, &request.state)?;
+
         let restaurant_id = Uuid::new_v4();
         let now = Utc::now();
 
@@ -27,10 +52,10 @@ impl RestaurantService {
                 postal_code, country, phone, email, latitude, longitude, image_url, 
                 cover_image_url, rating, total_reviews, delivery_fee, minimum_order, 
                 delivery_time_minutes, is_active, is_accepting_orders, fssai_license, 
-                gst_number, opening_hours, created_at, updated_at
+                gst_number, opening_hours, delivery_area, pure_veg, created_at, updated_at
             ) VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, 
-                $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28
+                $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30
             ) RETURNING *
             "#,
         )
@@ -55,11 +80,13 @@ impl RestaurantService {
         .bind(&request.delivery_fee)
         .bind(&request.minimum_order)
         .bind(request.delivery_time_minutes)
-        .bind(true) // is_active
+        .bind(false) // is_active; set when onboarding goes live
         .bind(true) // is_accepting_orders
         .bind(&request.fssai_license)
         .bind(&request.gst_number)
         .bind(&request.opening_hours)
+        .bind(&request.delivery_area)
+        .bind(request.pure_veg)
         .bind(now)
         .bind(now)
         .fetch_one(self.db.pool())
@@ -82,7 +109,60 @@ impl RestaurantService {
         Ok(restaurant)
     }
 
-    pub async fn update_restaurant(&self, restaurant_id: Uuid, owner_id: Uuid, request: UpdateRestaurantRequest) -> Result<Restaurant> {
+    /// Like `get_restaurant`, but also finds restaurants that are still
+    /// onboarding; for owners and staff rather than customers
+    pub(crate) async fn find_restaurant(&self, restaurant_id: Uuid) -> Result<Restaurant> {
+        sqlx::query_as::<_, Restaurant>("SELECT * FROM restaurants WHERE id = $1 AND deleted_at IS NULL")
+            .bind(restaurant_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))
+    }
+
+    pub async fn update_restaurant(&self, restaurant_id: Uuid, user_id: Uuid, mut request: UpdateRestaurantRequest) -> Result<Restaurant> {
+        if let Some(opening_hours) = &request.opening_hours {
+            opening_hours.validate()?;
+        }
+        if let Some(delivery_area) = &request.delivery_area {
+            delivery_area.validate()?;
+        }
+        if let Some(busy_settings) = &request.busy_settings {
+            busy_settings.validate()?;
+        }
+        self.authorize(restaurant_id, user_id, Permission::ManageRestaurant).await?;
+        if request.pure_veg == Some(true) {
+            let menu = sqlx::query_as::<_, MenuItem>("SELECT * FROM menu_items WHERE restaurant_id = $1")
+                .bind(restaurant_id)
+                .fetch_all(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            check_pure_veg(menu.iter().map(|item| (item.name.as_str(), DishFacts::from(item))))?;
+        }
+
+        // FSSAI and GST details are what admins review, so they are frozen
+        // from submission onwards
+        if request.fssai_license.is_some() || request.gst_number.is_some() {
+            let row = sqlx::query("SELECT state, onboarding_status FROM restaurants WHERE id = $1 AND deleted_at IS NULL")
+                .bind(restaurant_id)
+                .fetch_optional(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
+            let status: OnboardingStatus = row
+                .get::<String, _>("onboarding_status")
+                .parse()
+                .map_err(AppError::DatabaseError)?;
+            if !status.is_editable() {
+                return Err(AppError::ValidationError(format!(
+                    "FSSAI and GST details cannot be changed while onboarding is {}",
+                    status.as_str()
+                )));
+            }
+            let state = request.state.clone().unwrap_or_else(|| row.get("state"));
+            normalize_kyc_fields(&mut request.fssai_license, &mut request.gst_number, &state)?;
+        }
+
         let now = Utc::now();
 
         // Build dynamic update query
@@ -169,6 +249,18 @@ impl RestaurantService {
             query_parts.push(format!("opening_hours = ${}", bind_count));
             bind_count += 1;
         }
+        if request.delivery_area.is_some() {
+            query_parts.push(format!("delivery_area = ${}", bind_count));
+            bind_count += 1;
+        }
+        if request.pure_veg.is_some() {
+            query_parts.push(format!("pure_veg = ${}", bind_count));
+            bind_count += 1;
+        }
+        if request.busy_settings.is_some() {
+            query_parts.push(format!("busy_settings = ${}", bind_count));
+            bind_count += 1;
+        }
 
         if query_parts.is_empty() {
             return Err(AppError::BadRequest("No fields to update".to_string()));
@@ -178,10 +270,9 @@ impl RestaurantService {
         let update_clause = query_parts.join(", ");
 
         let query = format!(
-            "UPDATE restaurants SET {} WHERE id = ${} AND owner_id = ${} AND is_active = true RETURNING *",
+            "UPDATE restaurants SET {} WHERE id = ${} AND deleted_at IS NULL RETURNING *",
             update_clause,
-            bind_count + 1,
-            bind_count + 2
+            bind_count + 1
         );
 
         let mut query_builder = sqlx::query_as::<_, Restaurant>(&query);
@@ -247,41 +338,54 @@ impl RestaurantService {
         if let Some(opening_hours) = &request.opening_hours {
             query_builder = query_builder.bind(opening_hours);
         }
+        if let Some(delivery_area) = &request.delivery_area {
+            query_builder = query_builder.bind(delivery_area);
+        }
+        if let Some(pure_veg) = &request.pure_veg {
+            query_builder = query_builder.bind(pure_veg);
+        }
+        if let Some(busy_settings) = &request.busy_settings {
+            query_builder = query_builder.bind(busy_settings);
+        }
 
         let restaurant = query_builder
             .bind(now)
             .bind(restaurant_id)
-            .bind(owner_id)
             .fetch_optional(self.db.pool())
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Restaurant not found or not owned by user".to_string()))?;
+            .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
 
         Ok(restaurant)
     }
 
-    pub async fn delete_restaurant(&self, restaurant_id: Uuid, owner_id: Uuid) -> Result<()> {
+    pub async fn delete_restaurant(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<()> {
+        self.authorize(restaurant_id, user_id, Permission::DeleteRestaurant).await?;
+
         let result = sqlx::query(
-            "UPDATE restaurants SET is_active = false, updated_at = $1 WHERE id = $2 AND owner_id = $3 AND is_active = true"
+            "UPDATE restaurants SET is_active = false, deleted_at = $1, updated_at = $1 WHERE id = $2 AND deleted_at IS NULL"
         )
         .bind(Utc::now())
         .bind(restaurant_id)
-        .bind(owner_id)
         .execute(self.db.pool())
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("Restaurant not found or not owned by user".to_string()));
+            return Err(AppError::NotFound("Restaurant not found".to_string()));
         }
 
         Ok(())
     }
 
-    pub async fn list_restaurants(&self, city: Option<String>, cuisine_type: Option<String>, page: i32, per_page: i32) -> Result<RestaurantListResponse> {
+    pub async fn list_restaurants(&self, city: Option<String>, cuisine_type: Option<String>, open_now: bool, page: i32, per_page: i32) -> Result<RestaurantListResponse> {
         let offset = (page - 1) * per_page;
         
-        let mut where_conditions = vec!["is_active = true", "is_accepting_orders = true"];
+        let mut where_conditions = vec!["is_active = true"];
+        if open_now {
+            // Kept in step with the opening hours by the OpeningHoursScheduler
+            where_conditions.push("is_accepting_orders = true");
+        }
         let mut bind_count = 1;
         let city_condition;
         let cuisine_condition;
@@ -335,10 +439,13 @@ impl RestaurantService {
             .await
             .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let restaurant_responses: Vec<RestaurantResponse> = restaurants
+        let mut restaurant_responses: Vec<RestaurantResponse> = restaurants
             .into_iter()
             .map(RestaurantResponse::from)
             .collect();
+        PromotionService::new(self.db.clone())
+            .attach_restaurant_badges(&mut restaurant_responses, Utc::now())
+            .await?;
 
         Ok(RestaurantListResponse {
             restaurants: restaurant_responses,
@@ -348,68 +455,81 @@ impl RestaurantService {
         })
     }
 
-    pub async fn search_restaurants(&self, query: String, city: Option<String>, page: i32, per_page: i32) -> Result<RestaurantListResponse> {
-        let offset = (page - 1) * per_page;
-        let search_term = format!("%{}%", query.to_lowercase());
-        
-        let mut where_conditions = vec![
-            "is_active = true",
-            "is_accepting_orders = true",
-            "(LOWER(name) LIKE $1 OR LOWER(description) LIKE $1 OR LOWER(cuisine_type) LIKE $1)"
-        ];
-        let mut bind_count = 2;
-        let city_condition;
-
-        if city.is_some() {
-            city_condition = format!("LOWER(city) = LOWER(${})", bind_count);
-            where_conditions.push(&city_condition);
-            bind_count += 1;
-        }
-
-        let where_clause = where_conditions.join(" AND ");
-        
-        let sql_query = format!(
-            "SELECT * FROM restaurants WHERE {} ORDER BY rating DESC, total_reviews DESC LIMIT ${} OFFSET ${}",
-            where_clause,
-            bind_count,
-            bind_count + 1
-        );
-
-        let count_query = format!(
-            "SELECT COUNT(*) FROM restaurants WHERE {}",
-            where_clause
-        );
-
-        let mut query_builder = sqlx::query_as::<_, Restaurant>(&sql_query);
-        let mut count_query_builder = sqlx::query_scalar::<_, i64>(&count_query);
-
-        query_builder = query_builder.bind(&search_term);
-        count_query_builder = count_query_builder.bind(&search_term);
-
-        if let Some(ref city_val) = city {
-            query_builder = query_builder.bind(city_val);
-            count_query_builder = count_query_builder.bind(city_val);
+    /// Restaurants that deliver to the customer's location, with distance,
+    /// delivery estimate and the fee for that distance
+    pub async fn list_nearby(&self, query: &NearbyQuery, page: i32, per_page: i32) -> Result<NearbyRestaurantsResponse> {
+        let customer = GeoPoint::new(query.lat, query.lng);
+        if !customer.is_valid() {
+            return Err(AppError::ValidationError("Invalid customer location".to_string()));
         }
 
-        let restaurants = query_builder
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(self.db.pool())
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        // Bounding box around the customer; exact coverage is checked below
+        let lat_delta = MAX_DELIVERY_RADIUS_KM / 111.0;
+        let lng_delta = MAX_DELIVERY_RADIUS_KM / (111.0 * customer.latitude.to_radians().cos().max(0.01));
 
-        let total = count_query_builder
-            .fetch_one(self.db.pool())
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        let restaurants = sqlx::query_as::<_, Restaurant>(
+            r#"
+            SELECT * FROM restaurants
+            WHERE is_active = true
+              AND latitude IS NOT NULL AND longitude IS NOT NULL
+              AND latitude BETWEEN $1 AND $2
+              AND longitude BETWEEN $3 AND $4
+              AND ($5 = false OR is_accepting_orders = true)
+              AND ($6::text IS NULL OR LOWER(cuisine_type) = LOWER($6))
+            "#,
+        )
+        .bind(customer.latitude - lat_delta)
+        .bind(customer.latitude + lat_delta)
+        .bind(customer.longitude - lng_delta)
+        .bind(customer.longitude + lng_delta)
+        .bind(query.open_now.unwrap_or(true))
+        .bind(&query.cuisine)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
-        let restaurant_responses: Vec<RestaurantResponse> = restaurants
-            .into_iter()
-            .map(RestaurantResponse::from)
-            .collect();
+        let india = IndiaConfig::default();
+        let delivery_service = DeliveryService::new(self.db.clone());
+        let mut nearby = Vec::new();
+
+        for restaurant in restaurants {
+            let (Some(latitude), Some(longitude)) = (restaurant.latitude, restaurant.longitude) else {
+                continue;
+            };
+            let location = GeoPoint::new(latitude, longitude);
+            if !restaurant.delivery_area.covers(location, customer) {
+                continue;
+            }
 
-        Ok(RestaurantListResponse {
-            restaurants: restaurant_responses,
+            let distance_km = location.distance_km(&customer);
+            let estimate = delivery_service
+                .calculate_delivery_time_estimate(latitude, longitude, customer.latitude, customer.longitude, &restaurant.city)
+                .await?;
+            let delivery_fee = delivery_fee_for_distance(
+                restaurant.delivery_fee,
+                distance_km,
+                india.delivery_fee_included_km,
+                india.delivery_fee_per_extra_km,
+            );
+
+            nearby.push(NearbyRestaurant {
+                restaurant: RestaurantResponse::from(restaurant),
+                distance_km,
+                estimated_delivery_minutes: estimate.total_estimated_minutes,
+                delivery_fee,
+            });
+        }
+
+        sort_nearby(&mut nearby, query.sort);
+        let total = nearby.len() as i64;
+        let offset = ((page - 1) * per_page) as usize;
+        let mut restaurants: Vec<NearbyRestaurant> = nearby.into_iter().skip(offset).take(per_page as usize).collect();
+        PromotionService::new(self.db.clone())
+            .attach_restaurant_badges(restaurants.iter_mut().map(|nearby| &mut nearby.restaurant), Utc::now())
+            .await?;
+
+        Ok(NearbyRestaurantsResponse {
+            restaurants,
             total,
             page,
             per_page,
@@ -417,9 +537,26 @@ impl RestaurantService {
     }
 
     // Menu Management Methods
-    pub async fn create_menu_item(&self, restaurant_id: Uuid, owner_id: Uuid, request: CreateMenuItemRequest) -> Result<MenuItem> {
-        // First verify the restaurant belongs to the owner
-        self.verify_restaurant_ownership(restaurant_id, owner_id).await?;
+    pub async fn create_menu_item(&self, restaurant_id: Uuid, user_id: Uuid, request: CreateMenuItemRequest) -> Result<MenuItem> {
+        // First verify the user may do this at the restaurant
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+        if let Some(availability) = &request.availability {
+            availability.validate()?;
+        }
+        if Self::is_pure_veg(self.db.pool(), restaurant_id).await? {
+            check_pure_veg([(
+                request.name.as_str(),
+                DishFacts {
+                    is_vegetarian: request.is_vegetarian,
+                    is_vegan: request.is_vegan,
+                    is_gluten_free: request.is_gluten_free,
+                    spice_level: request.spice_level,
+                    ingredients: request.ingredients.as_deref().unwrap_or_default(),
+                    allergens: request.allergens.as_deref().unwrap_or_default(),
+                },
+            )])?;
+        }
 
         let item_id = Uuid::new_v4();
         let now = Utc::now();
@@ -429,9 +566,10 @@ impl RestaurantService {
             INSERT INTO menu_items (
                 id, restaurant_id, name, description, category, price, image_url,
                 is_vegetarian, is_vegan, is_gluten_free, spice_level, ingredients,
-                allergens, is_available, preparation_time_minutes, calories, created_at, updated_at
+                allergens, is_available, preparation_time_minutes, calories, availability,
+                created_at, updated_at
             ) VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19
             ) RETURNING *
             "#,
         )
@@ -451,6 +589,7 @@ impl RestaurantService {
         .bind(true) // is_available by default
         .bind(request.preparation_time_minutes)
         .bind(&request.calories)
+        .bind(request.availability.as_ref().filter(|availability| !availability.is_unrestricted()))
         .bind(now)
         .bind(now)
         .fetch_one(self.db.pool())
@@ -469,18 +608,29 @@ impl RestaurantService {
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?;
 
+        let item_ids: Vec<Uuid> = menu_items.iter().map(|item| item.id).collect();
+        let mut modifier_groups = self.get_modifier_groups(&item_ids).await?;
+        let mut category_availability = self.get_category_availability(restaurant_id).await?;
+
         // Group items by category
         let mut categories_map: HashMap<String, Vec<MenuItemResponse>> = HashMap::new();
         
         for item in menu_items {
             let category = item.category.clone();
-            let item_response = MenuItemResponse::from(item);
+            let item_id = item.id;
+            let mut item_response = MenuItemResponse::from(item);
+            item_response.modifier_groups = modifier_groups.remove(&item_id).unwrap_or_default();
             categories_map.entry(category).or_insert_with(Vec::new).push(item_response);
         }
 
         let categories: Vec<MenuCategory> = categories_map
             .into_iter()
-            .map(|(name, items)| MenuCategory { name, items })
+            .map(|(name, items)| MenuCategory {
+                has_customizations: items.iter().any(|item| !item.modifier_groups.is_empty()),
+                availability: category_availability.remove(&name),
+                name,
+                items,
+            })
             .collect();
 
         Ok(MenuResponse {
@@ -489,9 +639,103 @@ impl RestaurantService {
         })
     }
 
-    pub async fn update_menu_item(&self, restaurant_id: Uuid, item_id: Uuid, owner_id: Uuid, request: UpdateMenuItemRequest) -> Result<MenuItem> {
-        // First verify the restaurant belongs to the owner
-        self.verify_restaurant_ownership(restaurant_id, owner_id).await?;
+    /// Availability limits set on whole categories, by category name
+    pub async fn get_category_availability(&self, restaurant_id: Uuid) -> Result<HashMap<String, MenuAvailability>> {
+        let rows = sqlx::query(
+            "SELECT category, availability FROM menu_category_availability WHERE restaurant_id = $1"
+        )
+        .bind(restaurant_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("category"), row.get("availability")))
+            .collect())
+    }
+
+    /// Limit when a whole category can be ordered; `None` lifts the limits
+    pub async fn set_category_availability(
+        &self,
+        restaurant_id: Uuid,
+        category: &str,
+        user_id: Uuid,
+        availability: Option<MenuAvailability>,
+    ) -> Result<Option<MenuAvailability>> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+
+        let availability = availability.filter(|availability| !availability.is_unrestricted());
+        match &availability {
+            Some(availability) => {
+                availability.validate()?;
+
+                let has_items = sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM menu_items WHERE restaurant_id = $1 AND category = $2)"
+                )
+                .bind(restaurant_id)
+                .bind(category)
+                .fetch_one(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+                if !has_items {
+                    return Err(AppError::NotFound(format!("No menu items in category {}", category)));
+                }
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO menu_category_availability (restaurant_id, category, availability)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (restaurant_id, category)
+                    DO UPDATE SET availability = EXCLUDED.availability, updated_at = NOW()
+                    "#,
+                )
+                .bind(restaurant_id)
+                .bind(category)
+                .bind(availability)
+                .execute(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            }
+            None => {
+                sqlx::query("DELETE FROM menu_category_availability WHERE restaurant_id = $1 AND category = $2")
+                    .bind(restaurant_id)
+                    .bind(category)
+                    .execute(self.db.pool())
+                    .await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            }
+        }
+
+        Ok(availability)
+    }
+
+    pub async fn update_menu_item(&self, restaurant_id: Uuid, item_id: Uuid, user_id: Uuid, request: UpdateMenuItemRequest) -> Result<MenuItem> {
+        // First verify the user may do this at the restaurant
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+        if let Some(availability) = &request.availability {
+            availability.validate()?;
+        }
+        let changes_diet = request.is_vegetarian.is_some() || request.ingredients.is_some() || request.allergens.is_some();
+        if changes_diet && Self::is_pure_veg(self.db.pool(), restaurant_id).await? {
+            let current = sqlx::query_as::<_, MenuItem>("SELECT * FROM menu_items WHERE id = $1 AND restaurant_id = $2")
+                .bind(item_id)
+                .bind(restaurant_id)
+                .fetch_optional(self.db.pool())
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| AppError::NotFound("Menu item not found".to_string()))?;
+            check_pure_veg([(
+                request.name.as_deref().unwrap_or(&current.name),
+                DishFacts {
+                    is_vegetarian: request.is_vegetarian.unwrap_or(current.is_vegetarian),
+                    ingredients: request.ingredients.as_deref().or(current.ingredients.as_deref()).unwrap_or_default(),
+                    allergens: request.allergens.as_deref().or(current.allergens.as_deref()).unwrap_or_default(),
+                    ..DishFacts::from(&current)
+                },
+            )])?;
+        }
 
         let now = Utc::now();
 
@@ -555,6 +799,10 @@ impl RestaurantService {
             query_parts.push(format!("calories = ${}", bind_count));
             bind_count += 1;
         }
+        if request.availability.is_some() {
+            query_parts.push(format!("availability = ${}", bind_count));
+            bind_count += 1;
+        }
 
         if query_parts.is_empty() {
             return Err(AppError::BadRequest("No fields to update".to_string()));
@@ -615,6 +863,10 @@ impl RestaurantService {
         if let Some(calories) = &request.calories {
             query_builder = query_builder.bind(calories);
         }
+        if let Some(availability) = &request.availability {
+            // Stored as NULL once every limit is removed
+            query_builder = query_builder.bind(Some(availability).filter(|availability| !availability.is_unrestricted()));
+        }
 
         let menu_item = query_builder
             .bind(now)
@@ -628,9 +880,10 @@ impl RestaurantService {
         Ok(menu_item)
     }
 
-    pub async fn delete_menu_item(&self, restaurant_id: Uuid, item_id: Uuid, owner_id: Uuid) -> Result<()> {
-        // First verify the restaurant belongs to the owner
-        self.verify_restaurant_ownership(restaurant_id, owner_id).await?;
+    pub async fn delete_menu_item(&self, restaurant_id: Uuid, item_id: Uuid, user_id: Uuid) -> Result<()> {
+        // First verify the user may do this at the restaurant
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
 
         let result = sqlx::query(
             "DELETE FROM menu_items WHERE id = $1 AND restaurant_id = $2"
@@ -648,9 +901,444 @@ impl RestaurantService {
         Ok(())
     }
 
-    pub async fn get_restaurant_orders(&self, restaurant_id: Uuid, owner_id: Uuid, status: Option<String>) -> Result<Vec<serde_json::Value>> {
-        // First verify the restaurant belongs to the owner
-        self.verify_restaurant_ownership(restaurant_id, owner_id).await?;
+    // Bulk Menu Import/Export
+    /// Upsert a whole menu file keyed by SKU. Nothing is written when any row
+    /// has errors or when `dry_run` is set; the counts describe what would happen.
+    pub async fn import_menu(&self, restaurant_id: Uuid, user_id: Uuid, format: MenuFileFormat, body: &[u8], dry_run: bool) -> Result<MenuImportResponse> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+
+        let ParsedMenuFile { items, mut errors } = parse_menu_file(format, body)?;
+        let total_rows = items.len() + errors.len();
+        errors.extend(validate_import(&items));
+        errors.sort_by_key(|error| error.row);
+        let items: Vec<MenuImportItem> = items.into_iter().map(|(_, item)| item).collect();
+
+        let existing = Self::existing_menu_items(self.db.pool(), restaurant_id).await?;
+        let matched = match_menu_items(&existing, &items);
+
+        let updated = matched.iter().filter(|id| id.is_some()).count();
+        let mut response = MenuImportResponse {
+            dry_run,
+            applied: false,
+            total_rows,
+            created: items.len() - updated,
+            updated,
+            errors,
+        };
+
+        if dry_run || !response.errors.is_empty() {
+            return Ok(response);
+        }
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Self::write_menu_items(&mut tx, restaurant_id, &items, matched).await?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tracing::info!(
+            "Imported menu for restaurant {}: {} created, {} updated",
+            restaurant_id,
+            response.created,
+            response.updated
+        );
+
+        response.applied = true;
+        Ok(response)
+    }
+
+    /// Ids and SKUs of every item currently on the restaurant's menu
+    pub(crate) async fn existing_menu_items(
+        executor: impl sqlx::PgExecutor<'_>,
+        restaurant_id: Uuid,
+    ) -> Result<Vec<(Uuid, Option<String>)>> {
+        let rows = sqlx::query("SELECT id, external_sku FROM menu_items WHERE restaurant_id = $1")
+            .bind(restaurant_id)
+            .fetch_all(executor)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get("external_sku")))
+            .collect())
+    }
+
+    /// Once a restaurant has published a menu version, its live menu only
+    /// changes by publishing another version
+    pub(crate) async fn ensure_menu_unversioned(executor: impl sqlx::PgExecutor<'_>, restaurant_id: Uuid) -> Result<()> {
+        let versioned = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM menu_versions WHERE restaurant_id = $1 AND status = 'published')"
+        )
+        .bind(restaurant_id)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if versioned {
+            return Err(AppError::ValidationError(
+                "This menu is managed through menu versions; make the change in a draft version and publish it".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the restaurant is certified pure veg, which its menu has to
+    /// live up to
+    pub(crate) async fn is_pure_veg(executor: impl sqlx::PgExecutor<'_>, restaurant_id: Uuid) -> Result<bool> {
+        let pure_veg = sqlx::query_scalar::<_, bool>("SELECT pure_veg FROM restaurants WHERE id = $1")
+            .bind(restaurant_id)
+            .fetch_optional(executor)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(pure_veg.unwrap_or(false))
+    }
+
+    /// Write validated menu rows, updating the matched item in place or
+    /// creating a new one. Modifier groups are replaced wholesale.
+    pub(crate) async fn write_menu_items(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        restaurant_id: Uuid,
+        items: &[MenuImportItem],
+        matched: Vec<Option<Uuid>>,
+    ) -> Result<()> {
+        if Self::is_pure_veg(&mut **tx, restaurant_id).await? {
+            check_pure_veg(items.iter().map(|item| (item.name.as_str(), DishFacts::from(item))))?;
+        }
+
+        for (item, existing_id) in items.iter().zip(matched) {
+            let item_id = match existing_id {
+                Some(item_id) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE menu_items SET
+                            external_sku = $1, name = $2, description = $3, category = $4, price = $5,
+                            image_url = $6, is_vegetarian = $7, is_vegan = $8, is_gluten_free = $9,
+                            spice_level = $10, ingredients = $11, allergens = $12, is_available = $13,
+                            preparation_time_minutes = $14, calories = $15, updated_at = NOW()
+                        WHERE id = $16 AND restaurant_id = $17
+                        "#,
+                    )
+                    .bind(item.sku.trim())
+                    .bind(item.name.trim())
+                    .bind(&item.description)
+                    .bind(item.category.trim())
+                    .bind(item.price)
+                    .bind(&item.image_url)
+                    .bind(item.is_vegetarian)
+                    .bind(item.is_vegan)
+                    .bind(item.is_gluten_free)
+                    .bind(item.spice_level)
+                    .bind(serde_json::to_value(&item.ingredients).unwrap_or(serde_json::Value::Null))
+                    .bind(serde_json::to_value(&item.allergens).unwrap_or(serde_json::Value::Null))
+                    .bind(item.is_available)
+                    .bind(item.preparation_time_minutes)
+                    .bind(item.calories)
+                    .bind(item_id)
+                    .bind(restaurant_id)
+                    .execute(&mut **tx)
+                    .await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+                    sqlx::query("DELETE FROM menu_modifier_groups WHERE menu_item_id = $1")
+                        .bind(item_id)
+                        .execute(&mut **tx)
+                        .await
+                        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+                    item_id
+                }
+                None => {
+                    let item_id = Uuid::new_v4();
+                    sqlx::query(
+                        r#"
+                        INSERT INTO menu_items (
+                            id, restaurant_id, external_sku, name, description, category, price, image_url,
+                            is_vegetarian, is_vegan, is_gluten_free, spice_level, ingredients, allergens,
+                            is_available, preparation_time_minutes, calories
+                        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                        "#,
+                    )
+                    .bind(item_id)
+                    .bind(restaurant_id)
+                    .bind(item.sku.trim())
+                    .bind(item.name.trim())
+                    .bind(&item.description)
+                    .bind(item.category.trim())
+                    .bind(item.price)
+                    .bind(&item.image_url)
+                    .bind(item.is_vegetarian)
+                    .bind(item.is_vegan)
+                    .bind(item.is_gluten_free)
+                    .bind(item.spice_level)
+                    .bind(serde_json::to_value(&item.ingredients).unwrap_or(serde_json::Value::Null))
+                    .bind(serde_json::to_value(&item.allergens).unwrap_or(serde_json::Value::Null))
+                    .bind(item.is_available)
+                    .bind(item.preparation_time_minutes)
+                    .bind(item.calories)
+                    .execute(&mut **tx)
+                    .await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+                    item_id
+                }
+            };
+
+            for group in &item.modifiers {
+                Self::insert_modifier_group(tx, item_id, group).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn export_menu(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Vec<MenuImportItem>> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        self.live_menu_items(restaurant_id).await
+    }
+
+    /// The live menu in the bulk import format, ordered by category and name
+    pub(crate) async fn live_menu_items(&self, restaurant_id: Uuid) -> Result<Vec<MenuImportItem>> {
+        let menu = self.get_menu(restaurant_id).await?;
+        let mut items: Vec<MenuImportItem> = menu
+            .categories
+            .iter()
+            .flat_map(|category| category.items.iter().map(export_item))
+            .collect();
+        items.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(items)
+    }
+
+    // Modifier Group Methods
+    /// Modifier groups with their options for each of the given menu items
+    pub async fn get_modifier_groups(&self, menu_item_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<ModifierGroup>>> {
+        if menu_item_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut conn = self.db.pool().acquire().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Self::load_modifier_groups(&mut conn, menu_item_ids).await
+    }
+
+    pub(crate) async fn load_modifier_groups(
+        conn: &mut PgConnection,
+        menu_item_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<ModifierGroup>>> {
+
+        let group_rows = sqlx::query(
+            "SELECT * FROM menu_modifier_groups WHERE menu_item_id = ANY($1) ORDER BY display_order, name"
+        )
+        .bind(menu_item_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut groups = group_rows
+            .iter()
+            .map(modifier_group_from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        let group_ids: Vec<Uuid> = groups.iter().map(|group| group.id).collect();
+        let option_rows = sqlx::query(
+            "SELECT * FROM menu_modifier_options WHERE group_id = ANY($1) ORDER BY display_order, name"
+        )
+        .bind(&group_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        for option in option_rows.iter().map(modifier_option_from_row) {
+            if let Some(group) = groups.iter_mut().find(|group| group.id == option.group_id) {
+                group.options.push(option);
+            }
+        }
+
+        let mut by_item: HashMap<Uuid, Vec<ModifierGroup>> = HashMap::new();
+        for group in groups {
+            by_item.entry(group.menu_item_id).or_default().push(group);
+        }
+
+        Ok(by_item)
+    }
+
+    pub async fn create_modifier_group(&self, restaurant_id: Uuid, item_id: Uuid, user_id: Uuid, request: CreateModifierGroupRequest) -> Result<ModifierGroup> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+        self.verify_menu_item(restaurant_id, item_id).await?;
+        request.validate()?;
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let group = Self::insert_modifier_group(&mut tx, item_id, &request).await?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(group)
+    }
+
+    /// Replace a modifier group's settings and its full option list
+    pub async fn update_modifier_group(&self, restaurant_id: Uuid, item_id: Uuid, group_id: Uuid, user_id: Uuid, request: CreateModifierGroupRequest) -> Result<ModifierGroup> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+        self.verify_menu_item(restaurant_id, item_id).await?;
+        request.validate()?;
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let (min_selections, max_selections) = request.selection_bounds();
+        let row = sqlx::query(
+            r#"
+            UPDATE menu_modifier_groups
+            SET name = $1, kind = $2, min_selections = $3, max_selections = $4,
+                is_required = $5, display_order = $6, updated_at = NOW()
+            WHERE id = $7 AND menu_item_id = $8
+            RETURNING *
+            "#,
+        )
+        .bind(request.name.trim())
+        .bind(request.kind.as_str())
+        .bind(min_selections)
+        .bind(max_selections)
+        .bind(min_selections > 0)
+        .bind(request.display_order)
+        .bind(group_id)
+        .bind(item_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Modifier group not found".to_string()))?;
+
+        sqlx::query("DELETE FROM menu_modifier_options WHERE group_id = $1")
+            .bind(group_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut group = modifier_group_from_row(&row)?;
+        group.options = Self::insert_modifier_options(&mut tx, group_id, &request.options).await?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(group)
+    }
+
+    pub async fn delete_modifier_group(&self, restaurant_id: Uuid, item_id: Uuid, group_id: Uuid, user_id: Uuid) -> Result<()> {
+        self.authorize(restaurant_id, user_id, Permission::EditMenu).await?;
+        Self::ensure_menu_unversioned(self.db.pool(), restaurant_id).await?;
+        self.verify_menu_item(restaurant_id, item_id).await?;
+
+        let result = sqlx::query(
+            "DELETE FROM menu_modifier_groups WHERE id = $1 AND menu_item_id = $2"
+        )
+        .bind(group_id)
+        .bind(item_id)
+        .execute(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Modifier group not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn insert_modifier_group(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        item_id: Uuid,
+        request: &CreateModifierGroupRequest,
+    ) -> Result<ModifierGroup> {
+        let group_id = Uuid::new_v4();
+        let (min_selections, max_selections) = request.selection_bounds();
+        let row = sqlx::query(
+            r#"
+            INSERT INTO menu_modifier_groups (
+                id, menu_item_id, name, kind, min_selections, max_selections, is_required, display_order
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(group_id)
+        .bind(item_id)
+        .bind(request.name.trim())
+        .bind(request.kind.as_str())
+        .bind(min_selections)
+        .bind(max_selections)
+        .bind(min_selections > 0)
+        .bind(request.display_order)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut group = modifier_group_from_row(&row)?;
+        group.options = Self::insert_modifier_options(tx, group_id, &request.options).await?;
+
+        Ok(group)
+    }
+
+    async fn insert_modifier_options(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        group_id: Uuid,
+        options: &[CreateModifierOptionRequest],
+    ) -> Result<Vec<ModifierOption>> {
+        let mut inserted = Vec::with_capacity(options.len());
+        for (position, option) in options.iter().enumerate() {
+            let row = sqlx::query(
+                r#"
+                INSERT INTO menu_modifier_options (
+                    id, group_id, name, price_delta, is_default, is_available, display_order
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(group_id)
+            .bind(option.name.trim())
+            .bind(option.price_delta)
+            .bind(option.is_default)
+            .bind(option.is_available)
+            .bind(position as i32)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+            inserted.push(modifier_option_from_row(&row));
+        }
+
+        Ok(inserted)
+    }
+
+    async fn verify_menu_item(&self, restaurant_id: Uuid, item_id: Uuid) -> Result<()> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM menu_items WHERE id = $1 AND restaurant_id = $2)"
+        )
+        .bind(item_id)
+        .bind(restaurant_id)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if !exists {
+            return Err(AppError::NotFound("Menu item not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_restaurant_orders(&self, restaurant_id: Uuid, user_id: Uuid, status: Option<String>) -> Result<Vec<serde_json::Value>> {
+        // First verify the user may do this at the restaurant
+        self.authorize(restaurant_id, user_id, Permission::ViewOrders).await?;
 
         let mut query = "SELECT * FROM orders WHERE restaurant_id = $1".to_string();
         let mut bind_count = 2;
@@ -686,7 +1374,7 @@ impl RestaurantService {
                     "customer_id": row.get::<Uuid, _>("customer_id"),
                     "restaurant_id": row.get::<Uuid, _>("restaurant_id"),
                     "status": row.get::<String, _>("status"),
-                    "total_amount": row.get::<f64, _>("total_amount"),
+                    "total_amount": row.get::<Money, _>("total_amount"),
                     "created_at": row.get::<chrono::DateTime<chrono::Utc>, _>("created_at"),
                     "updated_at": row.get::<chrono::DateTime<chrono::Utc>, _>("updated_at")
                 })
@@ -696,37 +1384,55 @@ impl RestaurantService {
         Ok(orders)
     }
 
-    pub async fn update_restaurant_status(&self, restaurant_id: Uuid, owner_id: Uuid, is_accepting_orders: bool) -> Result<Restaurant> {
+    pub async fn update_restaurant_status(&self, restaurant_id: Uuid, user_id: Uuid, is_accepting_orders: bool) -> Result<Restaurant> {
+        self.authorize(restaurant_id, user_id, Permission::ManageAvailability).await?;
+
         let restaurant = sqlx::query_as::<_, Restaurant>(
-            "UPDATE restaurants SET is_accepting_orders = $1, updated_at = $2 WHERE id = $3 AND owner_id = $4 AND is_active = true RETURNING *"
+            "UPDATE restaurants SET is_accepting_orders = $1, updated_at = $2 WHERE id = $3 AND deleted_at IS NULL RETURNING *"
         )
         .bind(is_accepting_orders)
         .bind(Utc::now())
         .bind(restaurant_id)
-        .bind(owner_id)
         .fetch_optional(self.db.pool())
         .await
         .map_err(|e| AppError::DatabaseError(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Restaurant not found or not owned by user".to_string()))?;
+        .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
 
         Ok(restaurant)
     }
 
-    // Helper method to verify restaurant ownership
-    async fn verify_restaurant_ownership(&self, restaurant_id: Uuid, owner_id: Uuid) -> Result<()> {
-        let exists = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(SELECT 1 FROM restaurants WHERE id = $1 AND owner_id = $2 AND is_active = true)"
+    /// The user's role at the restaurant: owner, an active staff member, or
+    /// `None` for anyone else. Errors if the restaurant does not exist.
+    pub(crate) async fn staff_role(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Option<StaffRole>> {
+        let role = sqlx::query_scalar::<_, Option<String>>(
+            r#"
+            SELECT CASE WHEN r.owner_id = $2 THEN 'owner' ELSE s.role END
+            FROM restaurants r
+            LEFT JOIN restaurant_staff s
+                ON s.restaurant_id = r.id AND s.user_id = $2 AND s.status = 'active'
+            WHERE r.id = $1 AND r.deleted_at IS NULL
+            "#,
         )
         .bind(restaurant_id)
-        .bind(owner_id)
-        .fetch_one(self.db.pool())
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
         .await
-        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?;
 
-        if !exists {
-            return Err(AppError::Forbidden("Restaurant not found or not owned by user".to_string()));
-        }
+        role.map(|role| role.parse().map_err(AppError::DatabaseError))
+            .transpose()
+    }
 
-        Ok(())
+    /// Check the user's role at the restaurant grants `permission`
+    pub(crate) async fn authorize(&self, restaurant_id: Uuid, user_id: Uuid, permission: Permission) -> Result<StaffRole> {
+        match self.staff_role(restaurant_id, user_id).await? {
+            Some(role) if role.has_permission(permission) => Ok(role),
+            Some(role) => Err(AppError::Forbidden(format!(
+                "The {} role is not allowed to do this",
+                role.as_str()
+            ))),
+            None => Err(AppError::Forbidden("Not a member of this restaurant's staff".to_string())),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,225 @@
+use crate::error::{AppError, Result};
+use crate::money::Money;
+use crate::restaurants::models::RestaurantResponse;
+use crate::search::ranking::{distance_score, haversine_km, rating_score};
+use serde::{Deserialize, Serialize};
+
+/// Delivery radius for restaurants that have not configured one
+pub const DEFAULT_DELIVERY_RADIUS_KM: f64 = 5.0;
+
+/// No restaurant delivers further than this, whatever its area says
+pub const MAX_DELIVERY_RADIUS_KM: f64 = 25.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self { latitude, longitude }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.latitude) && (-180.0..=180.0).contains(&self.longitude)
+    }
+
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        haversine_km(self.latitude, self.longitude, other.latitude, other.longitude)
+    }
+}
+
+/// Where a restaurant delivers: a circle around it, or a drawn polygon
+/// for areas bounded by rivers, highways and the like
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeliveryArea {
+    Radius { radius_km: f64 },
+    Polygon { points: Vec<GeoPoint> },
+}
+
+impl Default for DeliveryArea {
+    fn default() -> Self {
+        DeliveryArea::Radius {
+            radius_km: DEFAULT_DELIVERY_RADIUS_KM,
+        }
+    }
+}
+
+impl DeliveryArea {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            DeliveryArea::Radius { radius_km } => {
+                if !(*radius_km > 0.0 && *radius_km <= MAX_DELIVERY_RADIUS_KM) {
+                    return Err(AppError::ValidationError(format!(
+                        "Delivery radius must be between 0 and {} km",
+                        MAX_DELIVERY_RADIUS_KM
+                    )));
+                }
+            }
+            DeliveryArea::Polygon { points } => {
+                if points.len() < 3 {
+                    return Err(AppError::ValidationError(
+                        "Delivery polygon needs at least three points".to_string(),
+                    ));
+                }
+                if points.iter().any(|point| !point.is_valid()) {
+                    return Err(AppError::ValidationError(
+                        "Delivery polygon has an invalid coordinate".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a restaurant at `restaurant` delivers to `customer`
+    pub fn covers(&self, restaurant: GeoPoint, customer: GeoPoint) -> bool {
+        if restaurant.distance_km(&customer) > MAX_DELIVERY_RADIUS_KM {
+            return false;
+        }
+
+        match self {
+            DeliveryArea::Radius { radius_km } => restaurant.distance_km(&customer) <= *radius_km,
+            DeliveryArea::Polygon { points } => polygon_contains(points, customer),
+        }
+    }
+}
+
+/// Ray casting point-in-polygon test. Delivery areas are a few kilometres
+/// across, so treating coordinates as planar is accurate enough.
+fn polygon_contains(points: &[GeoPoint], point: GeoPoint) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.latitude > point.latitude) != (b.latitude > point.latitude) {
+            let crossing = (b.longitude - a.longitude) * (point.latitude - a.latitude)
+                / (b.latitude - a.latitude)
+                + a.longitude;
+            if point.longitude < crossing {
+                inside = !inside;
+            }
+        }
+        j = i;
+    }
+
+    inside
+}
+
+impl sqlx::Type<sqlx::Postgres> for DeliveryArea {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<Self> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for DeliveryArea {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<&Self> as sqlx::Encode<sqlx::Postgres>>::encode(sqlx::types::Json(self), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for DeliveryArea {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        // Fall back to the default radius rather than hiding the restaurant
+        let raw = <serde_json::Value as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
+}
+
+/// Delivery fee for a given distance: the restaurant's fee covers the
+/// first few kilometres, then each started kilometre costs extra
+pub fn delivery_fee_for_distance(base_fee: Money, distance_km: f64, included_km: f64, per_extra_km: Money) -> Money {
+    let extra_km = (distance_km - included_km).max(0.0).ceil() as u32;
+    base_fee + per_extra_km * extra_km
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NearbySort {
+    /// Blend of distance, rating and delivery time
+    #[default]
+    Relevance,
+    Distance,
+    Rating,
+    DeliveryTime,
+    DeliveryFee,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NearbyQuery {
+    pub lat: f64,
+    pub lng: f64,
+    #[serde(default)]
+    pub sort: NearbySort,
+    pub cuisine: Option<String>,
+    /// Defaults to true, like the restaurant listing
+    pub open_now: Option<bool>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NearbyRestaurant {
+    pub restaurant: RestaurantResponse,
+    pub distance_km: f64,
+    pub estimated_delivery_minutes: i32,
+    /// Fee for delivering to the customer's location
+    pub delivery_fee: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NearbyRestaurantsResponse {
+    pub restaurants: Vec<NearbyRestaurant>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+/// Relevance for the default ordering: close, well rated and quick to arrive
+pub fn nearby_relevance(distance_km: f64, rating: f64, total_reviews: i32, estimated_minutes: i32) -> f64 {
+    let speed = 1.0 / (1.0 + estimated_minutes.max(0) as f64 / 30.0);
+    0.45 * distance_score(distance_km) + 0.35 * rating_score(rating, total_reviews) + 0.2 * speed
+}
+
+pub fn sort_nearby(restaurants: &mut [NearbyRestaurant], sort: NearbySort) {
+    match sort {
+        NearbySort::Relevance => restaurants.sort_by(|a, b| {
+            let score = |r: &NearbyRestaurant| {
+                nearby_relevance(
+                    r.distance_km,
+                    r.restaurant.rating,
+                    r.restaurant.total_reviews,
+                    r.estimated_delivery_minutes,
+                )
+            };
+            score(b).total_cmp(&score(a))
+        }),
+        NearbySort::Distance => restaurants.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km)),
+        NearbySort::Rating => restaurants.sort_by(|a, b| {
+            b.restaurant
+                .rating
+                .total_cmp(&a.restaurant.rating)
+                .then(a.distance_km.total_cmp(&b.distance_km))
+        }),
+        NearbySort::DeliveryTime => restaurants.sort_by(|a, b| {
+            a.estimated_delivery_minutes
+                .cmp(&b.estimated_delivery_minutes)
+                .then(a.distance_km.total_cmp(&b.distance_km))
+        }),
+        NearbySort::DeliveryFee => restaurants.sort_by(|a, b| {
+            a.delivery_fee
+                .cmp(&b.delivery_fee)
+                .then(a.distance_km.total_cmp(&b.distance_km))
+        }),
+    }
+}
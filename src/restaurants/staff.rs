@@ -0,0 +1,323 @@
+use crate::auth::models::User;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::orders::models::OrderStatus;
+use crate::restaurants::service::RestaurantService;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+/// What a member of a restaurant's team is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Edit the restaurant profile, hours and delivery area
+    ManageRestaurant,
+    DeleteRestaurant,
+    /// Invite and remove staff
+    ManageStaff,
+    /// Menu items, prices, modifiers and bulk import/export
+    EditMenu,
+    ManageStock,
+    /// Pause or resume taking orders
+    ManageAvailability,
+    ViewOrders,
+    /// Confirm or cancel incoming orders
+    AcceptOrders,
+    /// Mark orders as preparing and ready
+    PrepareOrders,
+    ReplyToReviews,
+    /// Create and schedule the restaurant's own offers
+    ManagePromotions,
+}
+
+impl Permission {
+    /// Permission a restaurant needs to move one of its orders to `status`,
+    /// or `None` when the restaurant never sets that status
+    pub fn for_order_status(status: OrderStatus) -> Option<Permission> {
+        match status {
+            OrderStatus::Confirmed | OrderStatus::Cancelled => Some(Permission::AcceptOrders),
+            OrderStatus::Preparing | OrderStatus::Ready => Some(Permission::PrepareOrders),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaffRole {
+    /// The restaurant's `owner_id`; never stored as a membership
+    Owner,
+    Manager,
+    /// Front of house: takes and cancels orders
+    Cashier,
+    /// Prepares orders and marks dishes sold out
+    Kitchen,
+}
+
+impl StaffRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaffRole::Owner => "owner",
+            StaffRole::Manager => "manager",
+            StaffRole::Cashier => "cashier",
+            StaffRole::Kitchen => "kitchen",
+        }
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            StaffRole::Owner => &[
+                ManageRestaurant,
+                DeleteRestaurant,
+                ManageStaff,
+                EditMenu,
+                ManageStock,
+                ManageAvailability,
+                ViewOrders,
+                AcceptOrders,
+                PrepareOrders,
+                ReplyToReviews,
+                ManagePromotions,
+            ],
+            StaffRole::Manager => &[
+                ManageRestaurant,
+                EditMenu,
+                ManageStock,
+                ManageAvailability,
+                ViewOrders,
+                AcceptOrders,
+                PrepareOrders,
+                ReplyToReviews,
+                ManagePromotions,
+            ],
+            StaffRole::Cashier => &[ViewOrders, AcceptOrders],
+            StaffRole::Kitchen => &[ManageStock, ViewOrders, PrepareOrders],
+        }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+impl std::str::FromStr for StaffRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(StaffRole::Owner),
+            "manager" => Ok(StaffRole::Manager),
+            "cashier" => Ok(StaffRole::Cashier),
+            "kitchen" => Ok(StaffRole::Kitchen),
+            _ => Err(format!("Invalid staff role: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MembershipStatus {
+    /// Waiting for the invited user to accept
+    Invited,
+    Active,
+}
+
+impl MembershipStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MembershipStatus::Invited => "invited",
+            MembershipStatus::Active => "active",
+        }
+    }
+}
+
+impl std::str::FromStr for MembershipStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "invited" => Ok(MembershipStatus::Invited),
+            "active" => Ok(MembershipStatus::Active),
+            _ => Err(format!("Invalid membership status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaffMember {
+    pub restaurant_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: StaffRole,
+    pub status: MembershipStatus,
+    pub invited_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+fn staff_member_from_row(row: &PgRow) -> Result<StaffMember> {
+    let role: String = row.get("role");
+    let status: String = row.get("status");
+
+    Ok(StaffMember {
+        restaurant_id: row.get("restaurant_id"),
+        user_id: row.get("user_id"),
+        email: row.get("email"),
+        role: role.parse().map_err(AppError::DatabaseError)?,
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        invited_by: row.get("invited_by"),
+        created_at: row.get("created_at"),
+        accepted_at: row.get("accepted_at"),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteStaffRequest {
+    /// Email the invitee signed up with
+    pub email: String,
+    pub role: StaffRole,
+}
+
+pub struct StaffService {
+    db: Database,
+}
+
+impl StaffService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn list_staff(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<Vec<StaffMember>> {
+        RestaurantService::new(self.db.clone())
+            .authorize(restaurant_id, user_id, Permission::ManageStaff)
+            .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT s.*, u.email
+            FROM restaurant_staff s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.restaurant_id = $1
+            ORDER BY s.created_at
+            "#,
+        )
+        .bind(restaurant_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(staff_member_from_row).collect()
+    }
+
+    /// Invite an existing user to the restaurant's team. Inviting someone who
+    /// is already a member changes their role instead.
+    pub async fn invite_staff(&self, restaurant_id: Uuid, owner: &User, request: InviteStaffRequest) -> Result<StaffMember> {
+        if request.role == StaffRole::Owner {
+            return Err(AppError::ValidationError("A restaurant has exactly one owner".to_string()));
+        }
+
+        let restaurant = RestaurantService::new(self.db.clone());
+        restaurant.authorize(restaurant_id, owner.id, Permission::ManageStaff).await?;
+        let owner_id = restaurant.find_restaurant(restaurant_id).await?.owner_id;
+
+        let invitee_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE LOWER(email) = LOWER($1)")
+            .bind(request.email.trim())
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("No user is registered with that email".to_string()))?;
+        if invitee_id == owner_id {
+            return Err(AppError::ValidationError("The owner cannot be invited as staff".to_string()));
+        }
+
+        let row = sqlx::query(
+            r#"
+            WITH member AS (
+                INSERT INTO restaurant_staff (restaurant_id, user_id, role, status, invited_by)
+                VALUES ($1, $2, $3, 'invited', $4)
+                ON CONFLICT (restaurant_id, user_id) DO UPDATE SET role = EXCLUDED.role
+                RETURNING *
+            )
+            SELECT member.*, u.email FROM member JOIN users u ON u.id = member.user_id
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(invitee_id)
+        .bind(request.role.as_str())
+        .bind(owner.id)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        staff_member_from_row(&row)
+    }
+
+    pub async fn accept_invitation(&self, restaurant_id: Uuid, user_id: Uuid) -> Result<StaffMember> {
+        let row = sqlx::query(
+            r#"
+            WITH member AS (
+                UPDATE restaurant_staff
+                SET status = 'active', accepted_at = NOW()
+                WHERE restaurant_id = $1 AND user_id = $2 AND status = 'invited'
+                RETURNING *
+            )
+            SELECT member.*, u.email FROM member JOIN users u ON u.id = member.user_id
+            "#,
+        )
+        .bind(restaurant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Invitation not found".to_string()))?;
+
+        staff_member_from_row(&row)
+    }
+
+    /// Remove a member, or withdraw an invitation. Members can also remove
+    /// themselves, which is how an invitation is declined.
+    pub async fn remove_staff(&self, restaurant_id: Uuid, member_id: Uuid, user_id: Uuid) -> Result<()> {
+        if member_id != user_id {
+            RestaurantService::new(self.db.clone())
+                .authorize(restaurant_id, user_id, Permission::ManageStaff)
+                .await?;
+        }
+
+        let result = sqlx::query("DELETE FROM restaurant_staff WHERE restaurant_id = $1 AND user_id = $2")
+            .bind(restaurant_id)
+            .bind(member_id)
+            .execute(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Staff member not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// The user's own memberships, including pending invitations
+    pub async fn get_memberships(&self, user_id: Uuid) -> Result<Vec<StaffMember>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.*, u.email
+            FROM restaurant_staff s
+            JOIN users u ON u.id = s.user_id
+            JOIN restaurants r ON r.id = s.restaurant_id
+            WHERE s.user_id = $1 AND r.deleted_at IS NULL
+            ORDER BY s.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(staff_member_from_row).collect()
+    }
+}
@@ -0,0 +1,855 @@
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::restaurants::availability::*;
+    use crate::restaurants::busy::*;
+    use crate::restaurants::dietary::*;
+    use crate::restaurants::inventory::*;
+    use crate::restaurants::menu_import::*;
+    use crate::restaurants::menu_versions::*;
+    use crate::restaurants::modifiers::*;
+    use crate::restaurants::onboarding::*;
+    use crate::restaurants::schedule::*;
+    use crate::restaurants::serviceability::*;
+    use crate::restaurants::staff::*;
+    use crate::orders::models::OrderStatus;
+    use crate::error::AppError;
+    use crate::india::compliance::{validate_fssai_license, validate_gstin};
+    use crate::india::config::ComplianceConfig;
+    use crate::restaurants::scheduler::{schedule_action, ScheduleAction};
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+    use uuid::Uuid;
+
+    /// IST wall-clock time as UTC; 2024-01-01 is a Monday
+    fn ist(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(5 * 3600 + 30 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn hours(json: serde_json::Value) -> OpeningHours {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_split_shifts() {
+        let schedule = hours(serde_json::json!({
+            "monday": [
+                { "open": "11:00", "close": "15:00" },
+                { "open": "18:30", "close": "23:00" }
+            ]
+        }));
+
+        assert!(schedule.is_open_at(ist(1, 12, 0)));
+        assert!(!schedule.is_open_at(ist(1, 16, 0)));
+        assert!(schedule.is_open_at(ist(1, 19, 0)));
+
+        let status = schedule.status_at(ist(1, 16, 0));
+        assert!(!status.is_open_now);
+        assert_eq!(status.opens_at, Some(ist(1, 18, 30)));
+        assert_eq!(status.closes_at, None);
+    }
+
+    #[test]
+    fn test_overnight_shift_runs_into_next_day() {
+        let schedule = hours(serde_json::json!({
+            "friday": [{ "open": "19:00", "close": "02:00" }]
+        }));
+
+        // Friday 2024-01-05 into Saturday morning
+        assert!(schedule.is_open_at(ist(5, 23, 0)));
+        assert!(schedule.is_open_at(ist(6, 1, 30)));
+        assert!(!schedule.is_open_at(ist(6, 2, 0)));
+        assert_eq!(schedule.status_at(ist(5, 23, 0)).closes_at, Some(ist(6, 2, 0)));
+    }
+
+    #[test]
+    fn test_back_to_back_shifts_close_at_the_later_end() {
+        let schedule = hours(serde_json::json!({
+            "monday": [{ "open": "18:00", "close": "24:00" }],
+            "tuesday": [{ "open": "00:00", "close": "01:00" }]
+        }));
+
+        assert_eq!(schedule.status_at(ist(1, 20, 0)).closes_at, Some(ist(2, 1, 0)));
+    }
+
+    #[test]
+    fn test_holiday_override_closes_the_day() {
+        let schedule = hours(serde_json::json!({
+            "monday": [{ "open": "09:00", "close": "21:00" }],
+            "tuesday": [{ "open": "09:00", "close": "21:00" }],
+            "overrides": [{ "date": "2024-01-01", "shifts": [], "reason": "New Year" }]
+        }));
+
+        let status = schedule.status_at(ist(1, 12, 0));
+        assert!(!status.is_open_now);
+        assert_eq!(status.opens_at, Some(ist(2, 9, 0)));
+    }
+
+    #[test]
+    fn test_override_replaces_weekly_shifts() {
+        let schedule = hours(serde_json::json!({
+            "monday": [{ "open": "09:00", "close": "21:00" }],
+            "overrides": [{ "date": "2024-01-01", "shifts": [{ "open": "12:00", "close": "14:00" }] }]
+        }));
+
+        assert!(!schedule.is_open_at(ist(1, 10, 0)));
+        assert!(schedule.is_open_at(ist(1, 13, 0)));
+        assert_eq!(
+            schedule.shifts_for_date(NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_legacy_format_is_read_as_single_shift() {
+        let schedule = hours(serde_json::json!({
+            "monday": { "open": "10:00", "close": "22:00" },
+            "tuesday": { "closed": true },
+            "wednesday": null
+        }));
+
+        assert_eq!(schedule.monday.len(), 1);
+        assert!(schedule.tuesday.is_empty());
+        assert!(schedule.wednesday.is_empty());
+        assert!(schedule.is_open_at(ist(1, 10, 0)));
+
+        assert!(!OpeningHours::default().is_configured());
+        assert!(serde_json::from_value::<OpeningHours>(serde_json::json!({
+            "monday": [{ "open": "25:00", "close": "10:00" }]
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_duplicate_override_dates_are_rejected() {
+        let schedule = hours(serde_json::json!({
+            "overrides": [
+                { "date": "2024-01-26", "shifts": [] },
+                { "date": "2024-01-26", "shifts": [] }
+            ]
+        }));
+
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn test_scheduler_acts_only_on_boundaries() {
+        let schedule = hours(serde_json::json!({
+            "monday": [{ "open": "09:00", "close": "21:00" }]
+        }));
+
+        assert_eq!(schedule_action(&schedule, None, ist(1, 10, 0)), ScheduleAction::Apply(true));
+        // Already applied; a manual pause since then is left alone
+        assert_eq!(schedule_action(&schedule, Some(true), ist(1, 12, 0)), ScheduleAction::Keep);
+        assert_eq!(schedule_action(&schedule, Some(true), ist(1, 21, 0)), ScheduleAction::Apply(false));
+        assert_eq!(
+            schedule_action(&OpeningHours::default(), Some(false), ist(1, 12, 0)),
+            ScheduleAction::Forget
+        );
+    }
+
+    fn option(group_id: Uuid, name: &str, delta_rupees: i64, is_default: bool) -> ModifierOption {
+        ModifierOption {
+            id: Uuid::new_v4(),
+            group_id,
+            name: name.to_string(),
+            price_delta: Money::from_rupees(delta_rupees),
+            is_default,
+            is_available: true,
+            display_order: 0,
+        }
+    }
+
+    /// Half/full plate variant plus up to two add-ons
+    fn thali_groups() -> Vec<ModifierGroup> {
+        let size_id = Uuid::new_v4();
+        let extras_id = Uuid::new_v4();
+        vec![
+            ModifierGroup {
+                id: size_id,
+                menu_item_id: Uuid::nil(),
+                name: "Portion".to_string(),
+                kind: ModifierGroupKind::Variant,
+                min_selections: 1,
+                max_selections: 1,
+                is_required: true,
+                display_order: 0,
+                options: vec![
+                    option(size_id, "Half plate", -80, false),
+                    option(size_id, "Full plate", 0, true),
+                ],
+            },
+            ModifierGroup {
+                id: extras_id,
+                menu_item_id: Uuid::nil(),
+                name: "Extras".to_string(),
+                kind: ModifierGroupKind::AddOn,
+                min_selections: 0,
+                max_selections: 2,
+                is_required: false,
+                display_order: 1,
+                options: vec![
+                    option(extras_id, "Extra cheese", 30, false),
+                    option(extras_id, "Extra roti", 15, false),
+                    option(extras_id, "Raita", 25, false),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_defaults_apply_when_group_not_selected() {
+        let groups = thali_groups();
+        let priced = price_selections(Money::from_rupees(200), &groups, &[]).unwrap();
+
+        assert_eq!(priced.unit_price, Money::from_rupees(200));
+        assert_eq!(priced.modifiers.len(), 1);
+        assert_eq!(priced.modifiers[0].option_name, "Full plate");
+    }
+
+    #[test]
+    fn test_variant_and_add_ons_are_priced() {
+        let groups = thali_groups();
+        let selections = vec![
+            ModifierSelection {
+                group_id: groups[0].id,
+                option_ids: vec![groups[0].options[0].id],
+            },
+            ModifierSelection {
+                group_id: groups[1].id,
+                option_ids: vec![groups[1].options[0].id, groups[1].options[1].id],
+            },
+        ];
+
+        let priced = price_selections(Money::from_rupees(200), &groups, &selections).unwrap();
+        assert_eq!(priced.unit_price, Money::from_rupees(200 - 80 + 30 + 15));
+        assert_eq!(priced.modifiers.len(), 3);
+    }
+
+    #[test]
+    fn test_selection_limits_are_enforced() {
+        let groups = thali_groups();
+        let too_many = vec![ModifierSelection {
+            group_id: groups[1].id,
+            option_ids: groups[1].options.iter().map(|o| o.id).collect(),
+        }];
+        assert!(price_selections(Money::from_rupees(200), &groups, &too_many).is_err());
+
+        let no_portion = vec![ModifierSelection {
+            group_id: groups[0].id,
+            option_ids: vec![],
+        }];
+        assert!(price_selections(Money::from_rupees(200), &groups, &no_portion).is_err());
+
+        let foreign_option = vec![ModifierSelection {
+            group_id: groups[0].id,
+            option_ids: vec![groups[1].options[0].id],
+        }];
+        assert!(price_selections(Money::from_rupees(200), &groups, &foreign_option).is_err());
+
+        let unknown_group = vec![ModifierSelection {
+            group_id: Uuid::new_v4(),
+            option_ids: vec![],
+        }];
+        assert!(price_selections(Money::from_rupees(200), &groups, &unknown_group).is_err());
+    }
+
+    #[test]
+    fn test_unavailable_option_and_negative_price_are_rejected() {
+        let mut groups = thali_groups();
+        groups[1].options[2].is_available = false;
+        let raita = vec![ModifierSelection {
+            group_id: groups[1].id,
+            option_ids: vec![groups[1].options[2].id],
+        }];
+        assert!(price_selections(Money::from_rupees(200), &groups, &raita).is_err());
+
+        let half = vec![ModifierSelection {
+            group_id: groups[0].id,
+            option_ids: vec![groups[0].options[0].id],
+        }];
+        assert!(price_selections(Money::from_rupees(50), &groups, &half).is_err());
+    }
+
+    #[test]
+    fn test_modifier_group_request_validation() {
+        let request: CreateModifierGroupRequest = serde_json::from_value(serde_json::json!({
+            "name": "Toppings",
+            "kind": "add_on",
+            "is_required": true,
+            "options": [
+                { "name": "Olives", "price_delta": 20 },
+                { "name": "Jalapenos", "price_delta": 25 }
+            ]
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+        assert_eq!(request.selection_bounds(), (1, 2));
+
+        let negative_add_on: CreateModifierGroupRequest = serde_json::from_value(serde_json::json!({
+            "name": "Toppings",
+            "kind": "add_on",
+            "options": [{ "name": "Olives", "price_delta": -5 }]
+        }))
+        .unwrap();
+        assert!(negative_add_on.validate().is_err());
+
+        let two_default_sizes: CreateModifierGroupRequest = serde_json::from_value(serde_json::json!({
+            "name": "Size",
+            "kind": "variant",
+            "options": [
+                { "name": "Regular", "is_default": true },
+                { "name": "Large", "price_delta": 60, "is_default": true }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(two_default_sizes.selection_bounds(), (1, 1));
+        assert!(two_default_sizes.validate().is_err());
+    }
+
+    const MENU_CSV: &str = "\
+sku,category,name,description,price,is_vegetarian,is_vegan,allergens,spice_level,modifiers
+PBM-1,Mains,Paneer Butter Masala,Rich and creamy,249.00,true,false,dairy;nuts,2,\"[{\"\"name\"\":\"\"Portion\"\",\"\"kind\"\":\"\"variant\"\",\"\"options\"\":[{\"\"name\"\":\"\"Half\"\",\"\"price_delta\"\":-100},{\"\"name\"\":\"\"Full\"\",\"\"is_default\"\":true}]}]\"
+DAL-1,Mains,Dal Tadka,,179,true,true,,1,
+";
+
+    #[test]
+    fn test_csv_menu_parses_lists_and_modifiers() {
+        let ParsedMenuFile { items, errors } = parse_menu_file(MenuFileFormat::Csv, MENU_CSV.as_bytes()).unwrap();
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(items.len(), 2);
+        let (row, paneer) = &items[0];
+        assert_eq!(*row, 1);
+        assert_eq!(paneer.price, Money::from_rupees(249));
+        assert_eq!(paneer.allergens, vec!["dairy", "nuts"]);
+        assert_eq!(paneer.modifiers.len(), 1);
+        assert_eq!(paneer.modifiers[0].options[0].price_delta, Money::from_rupees(-100));
+        assert!(items[1].1.description.is_none());
+        assert!(validate_import(&items).is_empty());
+    }
+
+    #[test]
+    fn test_import_validation_reports_every_problem_per_row() {
+        let csv = "sku,category,name,price,spice_level,is_vegetarian,is_vegan\n\
+                   A1,Mains,Thali,0,9,false,true\n\
+                   A1,Mains,Thali again,100,0,,\n\
+                   B2,Mains,Broken,not-a-price,0,,\n";
+        let ParsedMenuFile { items, errors: parse_errors } = parse_menu_file(MenuFileFormat::Csv, csv.as_bytes()).unwrap();
+
+        assert_eq!(parse_errors.len(), 1);
+        assert_eq!(parse_errors[0].row, 3);
+
+        let errors = validate_import(&items);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row, 1);
+        assert_eq!(errors[0].errors.len(), 3);
+        assert_eq!(errors[1].row, 2);
+        assert!(errors[1].errors[0].contains("more than once"));
+    }
+
+    #[test]
+    fn test_menu_csv_export_round_trips() {
+        let items: Vec<MenuImportItem> = parse_menu_file(MenuFileFormat::Csv, MENU_CSV.as_bytes())
+            .unwrap()
+            .items
+            .into_iter().map(|(_, item)| item).collect();
+
+        let exported = write_menu_csv(&items).unwrap();
+        let ParsedMenuFile { items: reparsed, errors } = parse_menu_file(MenuFileFormat::Csv, &exported).unwrap();
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(reparsed.len(), 2);
+        assert_eq!(reparsed[0].1.sku, "PBM-1");
+        assert_eq!(reparsed[0].1.allergens, items[0].allergens);
+        assert_eq!(reparsed[0].1.modifiers.len(), 1);
+        assert_eq!(reparsed[1].1.price, Money::from_rupees(179));
+    }
+
+    #[test]
+    fn test_menu_json_import_and_format_detection() {
+        let body = serde_json::json!({
+            "items": [{ "sku": "X", "category": "Drinks", "name": "Lassi", "price": 80 }]
+        });
+        let items = parse_menu_file(MenuFileFormat::Json, body.to_string().as_bytes()).unwrap().items;
+
+        assert_eq!(items[0].1.preparation_time_minutes, 15);
+        assert!(items[0].1.is_available);
+        assert!(parse_menu_file(MenuFileFormat::Json, b"not json").is_err());
+        assert_eq!(MenuFileFormat::from_content_type(Some("text/csv")), MenuFileFormat::Csv);
+        assert_eq!(MenuFileFormat::from_content_type(None), MenuFileFormat::Json);
+    }
+
+    #[test]
+    fn test_last_restock_boundary_uses_ist() {
+        let five_am = NaiveTime::from_hms_opt(5, 0, 0).unwrap();
+
+        // After the restock time: today's boundary
+        assert_eq!(last_restock_boundary(ist(2, 9, 30), five_am), ist(2, 5, 0));
+        // Exactly at the restock time counts as today
+        assert_eq!(last_restock_boundary(ist(2, 5, 0), five_am), ist(2, 5, 0));
+        // Before the restock time: yesterday's boundary
+        assert_eq!(last_restock_boundary(ist(2, 4, 59), five_am), ist(1, 5, 0));
+    }
+
+    #[test]
+    fn test_update_stock_request_validation() {
+        let request = |stock_quantity, daily_stock| UpdateStockRequest { stock_quantity, daily_stock };
+
+        assert!(request(Some(0), Some(20)).validate().is_ok());
+        assert!(request(None, None).validate().is_ok());
+        assert!(request(Some(-1), None).validate().is_err());
+        assert!(request(Some(5), Some(0)).validate().is_err());
+    }
+
+    #[test]
+    fn test_delivery_radius_coverage() {
+        let restaurant = GeoPoint::new(12.9716, 77.5946);
+        let two_km_north = GeoPoint::new(12.9896, 77.5946);
+        let eight_km_north = GeoPoint::new(13.0436, 77.5946);
+
+        let area = DeliveryArea::default();
+        assert!(area.covers(restaurant, two_km_north));
+        assert!(!area.covers(restaurant, eight_km_north));
+        assert!(DeliveryArea::Radius { radius_km: 10.0 }.covers(restaurant, eight_km_north));
+    }
+
+    #[test]
+    fn test_delivery_polygon_coverage() {
+        // Square of roughly 4 km around the restaurant, open to the east only
+        let restaurant = GeoPoint::new(19.0760, 72.8777);
+        let area = DeliveryArea::Polygon {
+            points: vec![
+                GeoPoint::new(19.0580, 72.8700),
+                GeoPoint::new(19.0580, 72.9100),
+                GeoPoint::new(19.0940, 72.9100),
+                GeoPoint::new(19.0940, 72.8700),
+            ],
+        };
+
+        assert!(area.covers(restaurant, GeoPoint::new(19.0760, 72.9000)));
+        // Closer, but west of the boundary
+        assert!(!area.covers(restaurant, GeoPoint::new(19.0760, 72.8650)));
+        assert!(area.validate().is_ok());
+    }
+
+    #[test]
+    fn test_delivery_area_validation() {
+        assert!(DeliveryArea::Radius { radius_km: 0.0 }.validate().is_err());
+        assert!(DeliveryArea::Radius { radius_km: MAX_DELIVERY_RADIUS_KM + 1.0 }.validate().is_err());
+        let triangle_missing_point = DeliveryArea::Polygon {
+            points: vec![GeoPoint::new(19.0, 72.8), GeoPoint::new(19.1, 72.9)],
+        };
+        assert!(triangle_missing_point.validate().is_err());
+
+        // Stored as tagged JSON
+        let parsed: DeliveryArea = serde_json::from_value(serde_json::json!({"type": "radius", "radius_km": 7.5})).unwrap();
+        assert_eq!(parsed, DeliveryArea::Radius { radius_km: 7.5 });
+    }
+
+    #[test]
+    fn test_delivery_fee_for_distance() {
+        let base = Money::from_rupees(29);
+        let per_km = Money::from_rupees(6);
+
+        assert_eq!(delivery_fee_for_distance(base, 2.0, 3.0, per_km), base);
+        assert_eq!(delivery_fee_for_distance(base, 3.0, 3.0, per_km), base);
+        // Each started kilometre beyond the included distance is charged
+        assert_eq!(delivery_fee_for_distance(base, 3.2, 3.0, per_km), Money::from_rupees(35));
+        assert_eq!(delivery_fee_for_distance(base, 5.5, 3.0, per_km), Money::from_rupees(47));
+    }
+
+    #[test]
+    fn test_nearby_relevance_balances_distance_and_rating() {
+        // A well rated restaurant slightly further away beats a poorly rated one
+        let well_rated = nearby_relevance(1.0, 4.6, 800, 30);
+        let poorly_rated = nearby_relevance(0.8, 3.2, 300, 30);
+        assert!(well_rated > poorly_rated);
+        // Distance still matters more than a handful of perfect reviews
+        assert!(nearby_relevance(0.5, 4.0, 200, 30) > nearby_relevance(6.0, 5.0, 3, 30));
+        // All else equal, faster delivery ranks higher
+        assert!(nearby_relevance(2.0, 4.0, 100, 25) > nearby_relevance(2.0, 4.0, 100, 60));
+    }
+
+    #[test]
+    fn test_kitchen_can_mark_ready_but_not_edit_prices() {
+        let ready = Permission::for_order_status(OrderStatus::Ready).unwrap();
+        assert!(StaffRole::Kitchen.has_permission(ready));
+        assert!(StaffRole::Kitchen.has_permission(Permission::ManageStock));
+        assert!(!StaffRole::Kitchen.has_permission(Permission::EditMenu));
+
+        // Cashiers take and cancel orders but do not cook them
+        let confirm = Permission::for_order_status(OrderStatus::Confirmed).unwrap();
+        assert!(StaffRole::Cashier.has_permission(confirm));
+        assert!(!StaffRole::Cashier.has_permission(ready));
+        assert_eq!(Permission::for_order_status(OrderStatus::Delivered), None);
+    }
+
+    #[test]
+    fn test_only_owner_manages_staff_and_deletes() {
+        for role in [StaffRole::Manager, StaffRole::Cashier, StaffRole::Kitchen] {
+            assert!(!role.has_permission(Permission::ManageStaff), "{}", role.as_str());
+            assert!(!role.has_permission(Permission::DeleteRestaurant), "{}", role.as_str());
+            assert!(role.has_permission(Permission::ViewOrders), "{}", role.as_str());
+        }
+        assert!(StaffRole::Manager.has_permission(Permission::EditMenu));
+
+        // The owner holds every permission any other role has
+        for role in [StaffRole::Manager, StaffRole::Cashier, StaffRole::Kitchen] {
+            for permission in role.permissions() {
+                assert!(StaffRole::Owner.has_permission(*permission));
+            }
+        }
+        assert_eq!("kitchen".parse::<StaffRole>().unwrap(), StaffRole::Kitchen);
+        assert!("chef".parse::<StaffRole>().is_err());
+    }
+
+    fn window(open: &str, close: &str) -> Shift {
+        serde_json::from_value(serde_json::json!({ "open": open, "close": close })).unwrap()
+    }
+
+    #[test]
+    fn test_breakfast_window_hides_items_at_dinner() {
+        let breakfast = MenuAvailability {
+            days: Vec::new(),
+            windows: vec![window("07:00", "11:30")],
+        };
+        assert!(breakfast.is_available_at(ist(1, 7, 0)));
+        assert!(breakfast.is_available_at(ist(3, 11, 29)));
+        assert!(!breakfast.is_available_at(ist(1, 11, 30)));
+        assert!(!breakfast.is_available_at(ist(1, 20, 0)));
+    }
+
+    #[test]
+    fn test_availability_days_and_late_night_windows() {
+        // Weekend brunch only
+        let brunch: MenuAvailability = serde_json::from_value(serde_json::json!({
+            "days": ["Sat", "Sun"],
+            "windows": [{ "open": "10:00", "close": "15:00" }]
+        }))
+        .unwrap();
+        assert!(brunch.is_available_at(ist(6, 12, 0)));
+        assert!(!brunch.is_available_at(ist(5, 12, 0)));
+
+        // A Friday late-night window still runs in the early hours of Saturday
+        let late_night = MenuAvailability {
+            days: vec![chrono::Weekday::Fri],
+            windows: vec![window("22:00", "02:00")],
+        };
+        assert!(late_night.is_available_at(ist(5, 23, 0)));
+        assert!(late_night.is_available_at(ist(6, 1, 30)));
+        assert!(!late_night.is_available_at(ist(6, 23, 0)));
+
+        // Days without windows cover the whole day
+        let weekdays_only = MenuAvailability {
+            days: vec![chrono::Weekday::Mon],
+            windows: Vec::new(),
+        };
+        assert!(weekdays_only.is_available_at(ist(1, 23, 59)));
+        assert!(!weekdays_only.is_available_at(ist(2, 0, 0)));
+
+        let duplicate = MenuAvailability {
+            days: vec![chrono::Weekday::Mon, chrono::Weekday::Mon],
+            windows: Vec::new(),
+        };
+        assert!(duplicate.validate().is_err());
+        assert!(MenuAvailability::default().is_unrestricted());
+    }
+
+    #[test]
+    fn test_category_and_item_windows_both_apply() {
+        let lunch = MenuAvailability {
+            days: Vec::new(),
+            windows: vec![window("12:00", "16:00")],
+        };
+        let weekdays = MenuAvailability {
+            days: vec![chrono::Weekday::Mon, chrono::Weekday::Tue],
+            windows: Vec::new(),
+        };
+
+        assert!(is_orderable_at(Some(&lunch), Some(&weekdays), ist(1, 13, 0)));
+        assert!(!is_orderable_at(Some(&lunch), Some(&weekdays), ist(3, 13, 0)));
+        assert!(!is_orderable_at(Some(&lunch), None, ist(1, 19, 0)));
+        assert!(is_orderable_at(None, None, ist(1, 3, 0)));
+    }
+
+    #[test]
+    fn test_onboarding_runs_from_draft_to_live() {
+        let mut status = OnboardingStatus::Draft;
+        for next in [
+            OnboardingStatus::DocumentsSubmitted,
+            OnboardingStatus::UnderReview,
+            OnboardingStatus::Approved,
+            OnboardingStatus::Live,
+        ] {
+            status = status.transition_to(next).unwrap();
+        }
+        assert_eq!(status, OnboardingStatus::Live);
+
+        // Nothing skips review, and a rejection goes back to the owner
+        assert!(matches!(
+            OnboardingStatus::Draft.transition_to(OnboardingStatus::Live),
+            Err(AppError::InvalidStatusTransition(_))
+        ));
+        assert!(!OnboardingStatus::DocumentsSubmitted.can_transition_to(&OnboardingStatus::Approved));
+        assert!(OnboardingStatus::UnderReview.can_transition_to(&OnboardingStatus::Rejected));
+        assert!(OnboardingStatus::Rejected.can_transition_to(&OnboardingStatus::DocumentsSubmitted));
+        assert!(OnboardingStatus::Rejected.is_editable());
+        assert!(!OnboardingStatus::UnderReview.is_editable());
+        assert_eq!(
+            "documents_submitted".parse::<OnboardingStatus>().unwrap(),
+            OnboardingStatus::DocumentsSubmitted
+        );
+    }
+
+    #[test]
+    fn test_gstin_checksum_and_state() {
+        assert_eq!(validate_gstin("27aapfu0939f1zv", "Maharashtra").unwrap(), "27AAPFU0939F1ZV");
+        assert_eq!(validate_gstin("29AAGCB7383J1Z4", "KA").unwrap(), "29AAGCB7383J1Z4");
+
+        // Wrong check character, layout, and state
+        assert!(validate_gstin("27AAPFU0939F1ZW", "Maharashtra").is_err());
+        assert!(validate_gstin("27AAPFU0939F1Z", "Maharashtra").is_err());
+        assert!(validate_gstin("27AAPF10939F1ZV", "Maharashtra").is_err());
+        assert!(validate_gstin("27AAPFU0939F1ZV", "Karnataka").is_err());
+        assert!(validate_gstin("27AAPFU0939F1ZV", "Atlantis").is_err());
+    }
+
+    #[test]
+    fn test_fssai_licence_format() {
+        assert_eq!(validate_fssai_license("1001 2345 6789 01").unwrap(), "10012345678901");
+        assert!(validate_fssai_license("20012345678901").is_ok());
+        assert!(validate_fssai_license("30012345678901").is_err());
+        assert!(validate_fssai_license("1001234567890").is_err());
+        assert!(validate_fssai_license("1001234567890A").is_err());
+    }
+
+    #[test]
+    fn test_missing_requirements_follow_compliance_config() {
+        let config = ComplianceConfig::default();
+        let document = |document_type| KycDocument {
+            id: Uuid::new_v4(),
+            restaurant_id: Uuid::nil(),
+            document_type,
+            reference: "kyc/file.pdf".to_string(),
+            uploaded_by: Uuid::nil(),
+            uploaded_at: Utc::now(),
+        };
+
+        let missing = missing_requirements(&config, None, None, "Maharashtra", &[]);
+        assert_eq!(missing.len(), 6);
+
+        let documents: Vec<KycDocument> = KycDocumentType::required(&config).into_iter().map(document).collect();
+        let ready = missing_requirements(
+            &config,
+            Some("10012345678901"),
+            Some("27AAPFU0939F1ZV"),
+            "Maharashtra",
+            &documents,
+        );
+        assert!(ready.is_empty(), "{:?}", ready);
+
+        // Without GST registration the GSTIN and certificate are optional
+        let config = ComplianceConfig {
+            gst_registration_required: false,
+            ..ComplianceConfig::default()
+        };
+        assert!(!KycDocumentType::required(&config).contains(&KycDocumentType::GstCertificate));
+        let documents: Vec<KycDocument> = KycDocumentType::required(&config).into_iter().map(document).collect();
+        assert!(missing_requirements(&config, Some("10012345678901"), None, "Delhi", &documents).is_empty());
+    }
+
+    #[test]
+    fn test_menu_version_diff_matches_by_sku() {
+        let before: Vec<MenuImportItem> = parse_menu_file(MenuFileFormat::Csv, MENU_CSV.as_bytes())
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect();
+        let mut after = before.clone();
+        after[0].price = Money::from_rupees(269);
+        after[0].allergens.push("gluten".to_string());
+        after.remove(1);
+        after.push(MenuImportItem {
+            sku: "LASSI-1".to_string(),
+            category: "Drinks".to_string(),
+            ..before[1].clone()
+        });
+
+        let diff = diff_menu_items(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].sku, "LASSI-1");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].sku, "DAL-1");
+        assert_eq!(diff.changed.len(), 1);
+        let fields: Vec<&str> = diff.changed[0].changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(fields, ["allergens", "price"]);
+
+        assert!(diff_menu_items(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_menu_version_lifecycle() {
+        use MenuVersionStatus::*;
+
+        assert!(Draft.can_transition_to(&Scheduled));
+        assert!(Draft.can_transition_to(&Published));
+        // Rescheduling and cancelling a schedule
+        assert!(Scheduled.can_transition_to(&Scheduled));
+        assert!(Scheduled.can_transition_to(&Draft));
+        assert!(Published.can_transition_to(&Archived));
+
+        // Old versions are restored by copying them, never republished in place
+        assert!(matches!(Archived.transition_to(Published), Err(AppError::InvalidStatusTransition(_))));
+        assert!(Published.transition_to(Draft).is_err());
+        assert!(Draft.is_editable() && Scheduled.is_editable());
+        assert!(!Published.is_editable() && !Archived.is_editable());
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn dish<'a>(is_vegetarian: bool, ingredients: &'a [String], allergens: &'a [String]) -> DishFacts<'a> {
+        DishFacts {
+            is_vegetarian,
+            is_vegan: false,
+            is_gluten_free: false,
+            spice_level: 2,
+            ingredients,
+            allergens,
+        }
+    }
+
+    #[test]
+    fn test_dietary_conflicts() {
+        let paneer = strings(&["Paneer", "Tomato", "Cashew paste"]);
+        let aloo_gobi = strings(&["Potatoes", "Cauliflower", "Ginger-garlic paste"]);
+        let egg_curry = strings(&["Boiled eggs", "Onion"]);
+        let eggless_cake = strings(&["Flour", "Eggless custard"]);
+        let dairy = strings(&["Milk", "Tree nuts"]);
+        let none: Vec<String> = Vec::new();
+
+        let jain = DietaryProfile {
+            diets: vec![Diet::Jain],
+            ..Default::default()
+        };
+        assert!(jain.conflicts(&dish(true, &paneer, &dairy)).is_empty());
+        assert_eq!(jain.conflicts(&dish(true, &aloo_gobi, &none)), [DietaryConflict::Diet { diet: Diet::Jain }]);
+        // Egg is not vegetarian for Jain food even when the menu says so
+        assert!(!jain.conflicts(&dish(true, &egg_curry, &none)).is_empty());
+
+        let egg_free = DietaryProfile {
+            diets: vec![Diet::EggFree],
+            ..Default::default()
+        };
+        assert!(egg_free.conflicts(&dish(true, &eggless_cake, &none)).is_empty());
+        assert!(!egg_free.conflicts(&dish(false, &egg_curry, &none)).is_empty());
+
+        let allergic = DietaryProfile {
+            avoid_allergens: strings(&["Dairy", "peanuts"]),
+            max_spice_level: Some(1),
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(allergic.avoid_allergens, ["dairy", "peanut"]);
+        assert_eq!(
+            allergic.conflicts(&dish(true, &paneer, &dairy)),
+            [
+                DietaryConflict::Allergen { allergen: "dairy".to_string() },
+                DietaryConflict::Spice { spice_level: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dietary_query_and_pure_veg() {
+        let query = DietaryQuery {
+            diets: Some("jain, egg_free".to_string()),
+            avoid_allergens: Some("Groundnuts,".to_string()),
+            ..Default::default()
+        };
+        let profile = query.explicit_profile().unwrap().unwrap();
+        assert_eq!(profile.diets, [Diet::Jain, Diet::EggFree]);
+        assert_eq!(profile.avoid_allergens, ["peanut"]);
+
+        assert!(DietaryQuery::default().explicit_profile().unwrap().is_none());
+        let bad = DietaryQuery {
+            diets: Some("keto".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(bad.explicit_profile(), Err(AppError::ValidationError(_))));
+        let too_hot = DietaryQuery {
+            max_spice_level: Some(9),
+            ..Default::default()
+        };
+        assert!(too_hot.explicit_profile().is_err());
+
+        let dal = strings(&["Toor dal", "Ghee"]);
+        let omelette = strings(&["Egg", "Chilli"]);
+        let none: Vec<String> = Vec::new();
+        assert!(check_pure_veg([("Dal Tadka", dish(true, &dal, &none))]).is_ok());
+        assert!(check_pure_veg([("Masala Omelette", dish(true, &omelette, &none))]).is_err());
+        assert!(check_pure_veg([("Chicken Tikka", dish(false, &none, &none))]).is_err());
+    }
+
+    #[test]
+    fn test_busy_mode_turns_on_and_lapses() {
+        let settings: BusySettings = serde_json::from_value(serde_json::json!({ "auto_threshold": 10 })).unwrap();
+        assert_eq!(settings.extra_prep_minutes, 15);
+        assert_eq!(settings.duration_minutes, 30);
+        let rush = ist(15, 13, 0);
+
+        assert_eq!(busy_action(&settings, None, 9, rush), BusyAction::Keep);
+        let BusyAction::Start(mode) = busy_action(&settings, None, 10, rush) else {
+            panic!("busy mode should start at the threshold");
+        };
+        assert_eq!(mode.source, BusySource::Auto);
+        assert_eq!(mode.expires_at, ist(15, 13, 30));
+
+        // A running spell is left alone, even once the kitchen catches up
+        assert_eq!(busy_action(&settings, Some(&mode), 2, ist(15, 13, 20)), BusyAction::Keep);
+        // Once it lapses it renews while the kitchen is still full, else ends
+        assert!(matches!(busy_action(&settings, Some(&mode), 12, ist(15, 13, 30)), BusyAction::Start(_)));
+        assert_eq!(busy_action(&settings, Some(&mode), 2, ist(15, 13, 30)), BusyAction::End);
+
+        // Without a threshold only staff turn it on
+        let manual = BusySettings::default();
+        assert_eq!(busy_action(&manual, None, 100, rush), BusyAction::Keep);
+        assert_eq!(busy_action(&manual, Some(&mode), 100, ist(15, 14, 0)), BusyAction::End);
+
+        assert!(active_busy_mode(&Some(mode.clone()), ist(15, 13, 29)).is_some());
+        assert!(active_busy_mode(&Some(mode), ist(15, 13, 30)).is_none());
+    }
+
+    #[test]
+    fn test_busy_mode_caps_orders() {
+        let mut mode = BusyMode {
+            source: BusySource::Manual,
+            extra_prep_minutes: 10,
+            max_open_orders: Some(8),
+            started_at: ist(15, 19, 0),
+            expires_at: ist(15, 20, 0),
+        };
+        assert!(mode.admits(7));
+        assert!(!mode.admits(8));
+        assert!(!mode.pauses_orders());
+
+        mode.max_open_orders = Some(0);
+        assert!(mode.pauses_orders());
+        mode.max_open_orders = None;
+        assert!(mode.admits(500));
+
+        assert!(BusySettings { auto_threshold: Some(0), ..BusySettings::default() }.validate().is_err());
+        assert!(BusySettings { extra_prep_minutes: 120, ..BusySettings::default() }.validate().is_err());
+        assert!(BusySettings { duration_minutes: 1, ..BusySettings::default() }.validate().is_err());
+        assert!(BusySettings { auto_threshold: Some(12), max_open_orders: Some(20), ..BusySettings::default() }
+            .validate()
+            .is_ok());
+    }
+}
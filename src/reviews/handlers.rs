@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    Extension,
+};
+use uuid::Uuid;
+
+use crate::auth::models::User;
+use crate::error::{AppError, Result};
+use crate::reviews::{
+    models::*,
+    service::ReviewService,
+};
+use crate::routes::AppState;
+
+pub async fn create_review(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(request): Json<CreateReviewRequest>,
+) -> Result<Json<Review>> {
+    let review_service = ReviewService::new(state.database.clone());
+
+    let review = review_service.create_review(&user, request).await?;
+
+    Ok(Json(review))
+}
+
+pub async fn get_restaurant_reviews(
+    State(state): State<AppState>,
+    Path(restaurant_id): Path<Uuid>,
+    Query(params): Query<ReviewListQuery>,
+) -> Result<Json<ReviewListResponse>> {
+    list_reviews(state, RevieweeType::Restaurant, restaurant_id, params).await
+}
+
+pub async fn get_delivery_person_reviews(
+    State(state): State<AppState>,
+    Path(delivery_person_id): Path<Uuid>,
+    Query(params): Query<ReviewListQuery>,
+) -> Result<Json<ReviewListResponse>> {
+    list_reviews(state, RevieweeType::DeliveryPerson, delivery_person_id, params).await
+}
+
+async fn list_reviews(
+    state: AppState,
+    reviewee_type: RevieweeType,
+    id: Uuid,
+    params: ReviewListQuery,
+) -> Result<Json<ReviewListResponse>> {
+    let review_service = ReviewService::new(state.database.clone());
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100); // Max 100 per page
+
+    let response = review_service
+        .list_reviews(reviewee_type, id, page, per_page)
+        .await?;
+
+    Ok(Json(response))
+}
+
+pub async fn reply_to_review(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(review_id): Path<Uuid>,
+    Json(request): Json<ReplyToReviewRequest>,
+) -> Result<Json<Review>> {
+    let review_service = ReviewService::new(state.database.clone());
+
+    let review = review_service
+        .reply_to_review(review_id, &user, &request.reply)
+        .await?;
+
+    Ok(Json(review))
+}
+
+pub async fn report_review(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(review_id): Path<Uuid>,
+    Json(request): Json<ReportReviewRequest>,
+) -> Result<Json<Review>> {
+    let review_service = ReviewService::new(state.database.clone());
+
+    let review = review_service
+        .report_review(review_id, &user, &request.reason)
+        .await?;
+
+    Ok(Json(review))
+}
+
+// Admin moderation
+pub async fn get_moderation_queue(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Query(params): Query<ReviewListQuery>,
+) -> Result<Json<ModerationQueueResponse>> {
+    if user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let review_service = ReviewService::new(state.database.clone());
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
+
+    let queue = review_service.moderation_queue(page, per_page).await?;
+
+    Ok(Json(queue))
+}
+
+pub async fn moderate_review(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(review_id): Path<Uuid>,
+    Json(request): Json<ModerateReviewRequest>,
+) -> Result<Json<Review>> {
+    if user.role != "admin" {
+        return Err(AppError::Forbidden("Admin access required".to_string()));
+    }
+
+    let review_service = ReviewService::new(state.database.clone());
+
+    let review = review_service
+        .moderate_review(review_id, &user, request.action, request.note.as_deref())
+        .await?;
+
+    Ok(Json(review))
+}
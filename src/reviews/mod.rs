@@ -0,0 +1,10 @@
+pub mod handlers;
+pub mod models;
+pub mod service;
+
+pub use handlers::*;
+pub use models::*;
+pub use service::*;
+
+#[cfg(test)]
+mod tests;
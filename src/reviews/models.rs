@@ -0,0 +1,247 @@
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+/// Smoothed ratings start from this average and move towards the real
+/// one as reviews come in
+pub const PRIOR_MEAN_RATING: f64 = 3.8;
+/// How many reviews' worth of weight the prior carries
+pub const PRIOR_WEIGHT: f64 = 5.0;
+
+/// Bayesian average of `count` ratings summing to `sum`, rounded to the
+/// two decimals stored on restaurants and delivery persons
+pub fn smoothed_rating(sum: i64, count: i64) -> f64 {
+    let sum = sum.max(0) as f64;
+    let count = count.max(0) as f64;
+    let rating = (sum + PRIOR_MEAN_RATING * PRIOR_WEIGHT) / (count + PRIOR_WEIGHT);
+    (rating * 100.0).round() / 100.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevieweeType {
+    Restaurant,
+    DeliveryPerson,
+}
+
+impl RevieweeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevieweeType::Restaurant => "restaurant",
+            RevieweeType::DeliveryPerson => "delivery_person",
+        }
+    }
+}
+
+impl std::str::FromStr for RevieweeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "restaurant" => Ok(RevieweeType::Restaurant),
+            "delivery_person" => Ok(RevieweeType::DeliveryPerson),
+            _ => Err(format!("Invalid reviewee type: {}", s)),
+        }
+    }
+}
+
+/// Reviews stay public while reported; only removal hides them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Published,
+    /// Waiting in the moderation queue
+    Reported,
+    Removed,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Published => "published",
+            ReviewStatus::Reported => "reported",
+            ReviewStatus::Removed => "removed",
+        }
+    }
+
+    /// Whether the review counts towards aggregate ratings
+    pub fn is_counted(&self) -> bool {
+        !matches!(self, ReviewStatus::Removed)
+    }
+
+    pub fn can_transition_to(&self, next: &ReviewStatus) -> bool {
+        matches!(
+            (self, next),
+            (ReviewStatus::Published, ReviewStatus::Reported)
+                | (ReviewStatus::Reported, ReviewStatus::Published)
+                | (ReviewStatus::Reported, ReviewStatus::Removed)
+                | (ReviewStatus::Published, ReviewStatus::Removed)
+        )
+    }
+
+    pub fn transition_to(&self, next: ReviewStatus) -> Result<ReviewStatus, AppError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(AppError::InvalidStatusTransition(format!(
+                "review cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::str::FromStr for ReviewStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "published" => Ok(ReviewStatus::Published),
+            "reported" => Ok(ReviewStatus::Reported),
+            "removed" => Ok(ReviewStatus::Removed),
+            _ => Err(format!("Invalid review status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub reviewer_id: Uuid,
+    /// Restaurant id, or the delivery person's user id
+    pub reviewee_id: Uuid,
+    pub reviewee_type: RevieweeType,
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub reply: Option<String>,
+    pub replied_at: Option<DateTime<Utc>>,
+    pub status: ReviewStatus,
+    pub report_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub(crate) fn review_from_row(row: &PgRow) -> Result<Review, AppError> {
+    let reviewee_type: String = row.get("reviewee_type");
+    let status: String = row.get("status");
+
+    Ok(Review {
+        id: row.get("id"),
+        order_id: row.get("order_id"),
+        reviewer_id: row.get("reviewer_id"),
+        reviewee_id: row.get("reviewee_id"),
+        reviewee_type: reviewee_type.parse().map_err(AppError::DatabaseError)?,
+        rating: row.get("rating"),
+        comment: row.get("comment"),
+        reply: row.get("reply"),
+        replied_at: row.get("replied_at"),
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        report_count: row.get("report_count"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReviewRequest {
+    pub order_id: Uuid,
+    pub reviewee_type: RevieweeType,
+    pub rating: i32,
+    pub comment: Option<String>,
+}
+
+impl CreateReviewRequest {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(1..=5).contains(&self.rating) {
+            return Err(AppError::ValidationError("Rating must be between 1 and 5".to_string()));
+        }
+        if self.comment.as_ref().is_some_and(|comment| comment.chars().count() > MAX_COMMENT_LENGTH) {
+            return Err(AppError::ValidationError(format!(
+                "Review comment cannot exceed {} characters",
+                MAX_COMMENT_LENGTH
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyToReviewRequest {
+    pub reply: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportReviewRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Back to published; existing reports are dismissed
+    Keep,
+    /// Hide the review and drop it from the aggregate rating
+    Remove,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerateReviewRequest {
+    pub action: ModerationAction,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewListQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+/// Average and star histogram over a reviewee's visible reviews
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RatingSummary {
+    /// Smoothed rating, as stored on the restaurant or delivery person
+    pub rating: f64,
+    pub total_reviews: i64,
+    /// Review counts for 1 to 5 stars, in that order
+    pub histogram: [i64; 5],
+}
+
+impl RatingSummary {
+    pub fn from_histogram(histogram: [i64; 5]) -> Self {
+        let total_reviews = histogram.iter().sum();
+        let sum = histogram
+            .iter()
+            .enumerate()
+            .map(|(index, count)| (index as i64 + 1) * count)
+            .sum();
+
+        Self {
+            rating: smoothed_rating(sum, total_reviews),
+            total_reviews,
+            histogram,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewListResponse {
+    pub summary: RatingSummary,
+    pub reviews: Vec<Review>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModerationQueueResponse {
+    pub reviews: Vec<Review>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
@@ -0,0 +1,417 @@
+use crate::auth::models::User;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::restaurants::service::RestaurantService;
+use crate::restaurants::staff::Permission;
+use crate::reviews::models::{
+    review_from_row, CreateReviewRequest, ModerationAction, ModerationQueueResponse, RatingSummary,
+    Review, ReviewListResponse, ReviewStatus, RevieweeType, MAX_COMMENT_LENGTH, PRIOR_MEAN_RATING,
+    PRIOR_WEIGHT,
+};
+use sqlx::Row;
+use uuid::Uuid;
+
+pub struct ReviewService {
+    db: Database,
+}
+
+impl ReviewService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Review a delivered order on behalf of its customer. The reviewee is
+    /// taken from the order: its restaurant, or the rider who delivered it.
+    pub async fn create_review(&self, customer: &User, request: CreateReviewRequest) -> Result<Review> {
+        request.validate()?;
+
+        let order = sqlx::query(
+            "SELECT customer_id, restaurant_id, delivery_person_id, status FROM orders WHERE id = $1"
+        )
+        .bind(request.order_id)
+        .fetch_optional(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        let customer_id: Uuid = order.get("customer_id");
+        if customer_id != customer.id {
+            return Err(AppError::NotFound("Order not found".to_string()));
+        }
+        let status: String = order.get("status");
+        if status != "delivered" {
+            return Err(AppError::ValidationError("Only delivered orders can be reviewed".to_string()));
+        }
+
+        let reviewee_id: Uuid = match request.reviewee_type {
+            RevieweeType::Restaurant => order.get("restaurant_id"),
+            RevieweeType::DeliveryPerson => order
+                .get::<Option<Uuid>, _>("delivery_person_id")
+                .ok_or_else(|| AppError::ValidationError("This order had no delivery person".to_string()))?,
+        };
+
+        let comment = request
+            .comment
+            .as_deref()
+            .map(str::trim)
+            .filter(|comment| !comment.is_empty());
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO reviews (order_id, reviewer_id, reviewee_id, reviewee_type, rating, comment)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (order_id, reviewee_type) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(request.order_id)
+        .bind(customer.id)
+        .bind(reviewee_id)
+        .bind(request.reviewee_type.as_str())
+        .bind(request.rating)
+        .bind(comment)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| AppError::ValidationError(format!(
+            "This order's {} has already been reviewed",
+            request.reviewee_type.as_str().replace('_', " ")
+        )))?;
+
+        Self::apply_rating(&mut tx, request.reviewee_type, reviewee_id, request.rating as i64, 1).await?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    /// Visible reviews for a restaurant, or for a delivery person by their
+    /// delivery profile id, newest first
+    pub async fn list_reviews(
+        &self,
+        reviewee_type: RevieweeType,
+        id: Uuid,
+        page: i32,
+        per_page: i32,
+    ) -> Result<ReviewListResponse> {
+        let reviewee_id = match reviewee_type {
+            RevieweeType::Restaurant => {
+                sqlx::query_scalar::<_, Uuid>("SELECT id FROM restaurants WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(self.db.pool())
+                    .await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                    .ok_or_else(|| AppError::NotFound("Restaurant not found".to_string()))?
+            }
+            RevieweeType::DeliveryPerson => {
+                sqlx::query_scalar::<_, Uuid>("SELECT user_id FROM delivery_persons WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(self.db.pool())
+                    .await
+                    .map_err(|e| AppError::DatabaseError(e.to_string()))?
+                    .ok_or_else(|| AppError::NotFound("Delivery person not found".to_string()))?
+            }
+        };
+
+        let counts = sqlx::query(
+            r#"
+            SELECT rating, COUNT(*) AS reviews
+            FROM reviews
+            WHERE reviewee_type = $1 AND reviewee_id = $2 AND status <> 'removed'
+            GROUP BY rating
+            "#,
+        )
+        .bind(reviewee_type.as_str())
+        .bind(reviewee_id)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut histogram = [0i64; 5];
+        for row in &counts {
+            let rating: i32 = row.get("rating");
+            if let Some(slot) = histogram.get_mut((rating - 1) as usize) {
+                *slot = row.get("reviews");
+            }
+        }
+        let summary = RatingSummary::from_histogram(histogram);
+
+        let offset = (page - 1) * per_page;
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM reviews
+            WHERE reviewee_type = $1 AND reviewee_id = $2 AND status <> 'removed'
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(reviewee_type.as_str())
+        .bind(reviewee_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let reviews = rows.iter().map(review_from_row).collect::<Result<Vec<_>>>()?;
+
+        Ok(ReviewListResponse {
+            total: summary.total_reviews,
+            summary,
+            reviews,
+            page,
+            per_page,
+        })
+    }
+
+    /// Set or replace the restaurant's public reply to a review
+    pub async fn reply_to_review(&self, review_id: Uuid, user: &User, reply: &str) -> Result<Review> {
+        let reply = reply.trim();
+        if reply.is_empty() {
+            return Err(AppError::ValidationError("Reply cannot be empty".to_string()));
+        }
+        if reply.chars().count() > MAX_COMMENT_LENGTH {
+            return Err(AppError::ValidationError(format!(
+                "Reply cannot exceed {} characters",
+                MAX_COMMENT_LENGTH
+            )));
+        }
+
+        let review = self.get_visible_review(review_id).await?;
+        if review.reviewee_type != RevieweeType::Restaurant {
+            return Err(AppError::ValidationError("Only restaurant reviews can be replied to".to_string()));
+        }
+
+        if user.role != "admin" {
+            RestaurantService::new(self.db.clone())
+                .authorize(review.reviewee_id, user.id, Permission::ReplyToReviews)
+                .await?;
+        }
+
+        let row = sqlx::query(
+            r#"
+            UPDATE reviews
+            SET reply = $1, replied_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(reply)
+        .bind(review_id)
+        .fetch_one(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    /// Flag a review for moderation. Each user can report a review once;
+    /// the review stays visible until a moderator removes it.
+    pub async fn report_review(&self, review_id: Uuid, reporter: &User, reason: &str) -> Result<Review> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::ValidationError("A reason is required to report a review".to_string()));
+        }
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let review = Self::lock_review(&mut tx, review_id).await?;
+        if review.status == ReviewStatus::Removed {
+            return Err(AppError::NotFound("Review not found".to_string()));
+        }
+        if review.reviewer_id == reporter.id {
+            return Err(AppError::ValidationError("You cannot report your own review".to_string()));
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO review_reports (review_id, reporter_id, reason)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (review_id, reporter_id) DO NOTHING
+            "#,
+        )
+        .bind(review_id)
+        .bind(reporter.id)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(AppError::ValidationError("You have already reported this review".to_string()));
+        }
+
+        let status = match review.status {
+            ReviewStatus::Reported => ReviewStatus::Reported,
+            status => status.transition_to(ReviewStatus::Reported)?,
+        };
+
+        let row = sqlx::query(
+            r#"
+            UPDATE reviews
+            SET status = $1, report_count = report_count + 1
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(review_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    /// Reported reviews, most reported first
+    pub async fn moderation_queue(&self, page: i32, per_page: i32) -> Result<ModerationQueueResponse> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reviews WHERE status = 'reported'")
+            .fetch_one(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let offset = (page - 1) * per_page;
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM reviews
+            WHERE status = 'reported'
+            ORDER BY report_count DESC, updated_at ASC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(ModerationQueueResponse {
+            reviews: rows.iter().map(review_from_row).collect::<Result<Vec<_>>>()?,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Resolve a reported review. Keeping it dismisses the open reports;
+    /// removing it hides the review and takes it out of the aggregate rating.
+    pub async fn moderate_review(
+        &self,
+        review_id: Uuid,
+        moderator: &User,
+        action: ModerationAction,
+        note: Option<&str>,
+    ) -> Result<Review> {
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let review = Self::lock_review(&mut tx, review_id).await?;
+        let status = match action {
+            ModerationAction::Keep => review.status.transition_to(ReviewStatus::Published)?,
+            ModerationAction::Remove => review.status.transition_to(ReviewStatus::Removed)?,
+        };
+
+        let row = sqlx::query(
+            r#"
+            UPDATE reviews
+            SET status = $1,
+                report_count = 0,
+                moderated_by = $2,
+                moderated_at = NOW(),
+                moderation_note = $3
+            WHERE id = $4
+            RETURNING *
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(moderator.id)
+        .bind(note)
+        .bind(review_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if review.status.is_counted() && !status.is_counted() {
+            Self::apply_rating(&mut tx, review.reviewee_type, review.reviewee_id, -(review.rating as i64), -1).await?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    async fn get_visible_review(&self, review_id: Uuid) -> Result<Review> {
+        let row = sqlx::query("SELECT * FROM reviews WHERE id = $1 AND status <> 'removed'")
+            .bind(review_id)
+            .fetch_optional(self.db.pool())
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Review not found".to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    async fn lock_review(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, review_id: Uuid) -> Result<Review> {
+        let row = sqlx::query("SELECT * FROM reviews WHERE id = $1 FOR UPDATE")
+            .bind(review_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Review not found".to_string()))?;
+
+        review_from_row(&row)
+    }
+
+    /// Add (or with negative deltas, take back) ratings on the reviewee's
+    /// running totals and recompute its smoothed rating in the same statement,
+    /// so concurrent reviews never lose an update
+    async fn apply_rating(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        reviewee_type: RevieweeType,
+        reviewee_id: Uuid,
+        rating_delta: i64,
+        count_delta: i32,
+    ) -> Result<()> {
+        let query = match reviewee_type {
+            RevieweeType::Restaurant => {
+                r#"
+                UPDATE restaurants
+                SET rating_sum = rating_sum + $2,
+                    total_reviews = total_reviews + $3,
+                    rating = ROUND(((rating_sum + $2 + $4 * $5) / (total_reviews + $3 + $5))::numeric, 2)
+                WHERE id = $1
+                "#
+            }
+            RevieweeType::DeliveryPerson => {
+                r#"
+                UPDATE delivery_persons
+                SET rating_sum = rating_sum + $2,
+                    total_ratings = total_ratings + $3,
+                    rating = ROUND(((rating_sum + $2 + $4 * $5) / (total_ratings + $3 + $5))::numeric, 2)
+                WHERE user_id = $1
+                "#
+            }
+        };
+
+        sqlx::query(query)
+            .bind(reviewee_id)
+            .bind(rating_delta)
+            .bind(count_delta)
+            .bind(PRIOR_MEAN_RATING)
+            .bind(PRIOR_WEIGHT)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::AppError;
+    use crate::reviews::models::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_smoothed_rating_starts_at_prior() {
+        assert_eq!(smoothed_rating(0, 0), PRIOR_MEAN_RATING);
+
+        // A single five-star review barely moves a new restaurant
+        let one = smoothed_rating(5, 1);
+        assert!(one > PRIOR_MEAN_RATING && one < 4.1, "got {}", one);
+
+        // Hundreds of reviews outweigh the prior
+        let many = smoothed_rating(5 * 400, 400);
+        assert!(many > 4.95, "got {}", many);
+    }
+
+    #[test]
+    fn test_incremental_updates_match_full_recalculation() {
+        let ratings = [5, 4, 1, 3, 5, 2];
+        let (mut sum, mut count) = (0i64, 0i64);
+        for rating in ratings {
+            sum += rating;
+            count += 1;
+        }
+        // Removing a review is the inverse of adding it
+        let (sum_after_removal, count_after_removal) = (sum - 1, count - 1);
+
+        let mut histogram = [0i64; 5];
+        for rating in ratings.iter().filter(|&&rating| rating != 1) {
+            histogram[*rating as usize - 1] += 1;
+        }
+        let summary = RatingSummary::from_histogram(histogram);
+
+        assert_eq!(summary.total_reviews, count_after_removal);
+        assert_eq!(summary.rating, smoothed_rating(sum_after_removal, count_after_removal));
+        assert_eq!(summary.histogram, [0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_review_status_transitions() {
+        let published = ReviewStatus::Published;
+        assert_eq!(published.transition_to(ReviewStatus::Reported).unwrap(), ReviewStatus::Reported);
+        assert_eq!(
+            ReviewStatus::Reported.transition_to(ReviewStatus::Published).unwrap(),
+            ReviewStatus::Published
+        );
+        assert!(ReviewStatus::Reported.can_transition_to(&ReviewStatus::Removed));
+
+        // Removal is final
+        assert!(matches!(
+            ReviewStatus::Removed.transition_to(ReviewStatus::Published),
+            Err(AppError::InvalidStatusTransition(_))
+        ));
+        assert!(!ReviewStatus::Removed.is_counted());
+        assert!(ReviewStatus::Reported.is_counted());
+    }
+
+    #[test]
+    fn test_status_and_reviewee_round_trip() {
+        for status in [ReviewStatus::Published, ReviewStatus::Reported, ReviewStatus::Removed] {
+            assert_eq!(status.as_str().parse::<ReviewStatus>().unwrap(), status);
+        }
+        for reviewee in [RevieweeType::Restaurant, RevieweeType::DeliveryPerson] {
+            assert_eq!(reviewee.as_str().parse::<RevieweeType>().unwrap(), reviewee);
+        }
+        assert!("hidden".parse::<ReviewStatus>().is_err());
+    }
+
+    #[test]
+    fn test_create_review_validation() {
+        let request = |rating: i32, comment: Option<String>| CreateReviewRequest {
+            order_id: Uuid::new_v4(),
+            reviewee_type: RevieweeType::Restaurant,
+            rating,
+            comment,
+        };
+
+        assert!(request(5, Some("Great biryani".to_string())).validate().is_ok());
+        assert!(request(0, None).validate().is_err());
+        assert!(request(6, None).validate().is_err());
+        assert!(request(3, Some("a".repeat(MAX_COMMENT_LENGTH + 1))).validate().is_err());
+    }
+}
@@ -3,18 +3,21 @@ use crate::analytics::handlers::{
     get_real_time_metrics, get_restaurant_analytics,
 };
 use crate::analytics::AnalyticsService;
-use crate::auth::middleware::{auth_middleware, SharedFirebaseAuth};
+use crate::auth::middleware::{auth_middleware, optional_auth_middleware, SharedFirebaseAuth};
+use crate::cache::CacheService;
+use crate::media::handlers::{serve_media, upload_media};
+use crate::media::service::MediaStorage;
 use crate::database::Database;
 use crate::delivery::handlers::{
-    assign_order, calculate_delivery_time_estimate, deactivate_delivery_person,
-    get_delivery_analytics, get_delivery_person, get_delivery_person_stats,
+    assign_order, calculate_delivery_time_estimate, deactivate_delivery_person, get_assignment_trail,
+    get_delivery_analytics, get_delivery_assignment, get_delivery_person, get_delivery_person_stats,
     get_india_delivery_zones, get_nearby_delivery_persons, register_delivery_person,
     update_delivery_person, update_delivery_status, update_location, verify_delivery_person,
 };
 use crate::delivery::websocket_handlers::{
     admin_websocket_handler, broadcast_test_message, customer_websocket_handler,
     delivery_person_websocket_handler, delivery_websocket_handler, get_websocket_stats,
-    restaurant_websocket_handler,
+    menu_websocket_handler, restaurant_websocket_handler,
 };
 use crate::delivery::enhanced_handlers::{
     assign_order_enhanced, batch_update_delivery_status, get_delivery_heatmap,
@@ -30,17 +33,36 @@ use crate::india::handlers::{
 };
 use crate::metrics::{health_detailed_handler, metrics_handler, MetricsCollector};
 use crate::orders::handlers::{
-    create_order, get_customer_orders, get_order, update_order_status, SharedFCMService,
+    create_order, get_customer_orders, get_order, get_prep_time_report, update_order_status,
+    SharedFCMService,
 };
-use crate::payments::handlers::{create_payment, get_payment};
+use crate::payments::handlers::{create_payment, get_payment, retry_payment};
 use crate::restaurants::handlers::{
-    create_menu_item, create_restaurant, delete_menu_item, delete_restaurant, get_menu,
+    accept_staff_invitation, create_menu_item, create_modifier_group, create_restaurant,
+    delete_menu_item, get_staff_memberships, invite_staff, list_staff, remove_staff,
+    get_dietary_profile, update_dietary_profile, delete_dietary_profile,
+    delete_modifier_group, delete_restaurant, export_menu, get_menu, import_menu,
     get_restaurant, get_restaurant_orders, get_restaurants_by_city, get_restaurants_by_cuisine,
-    list_restaurants, search_restaurants, update_menu_item, update_restaurant,
-    update_restaurant_status,
+    list_nearby_restaurants, list_restaurants, preview_menu, search_restaurants,
+    set_category_availability, update_menu_item, get_onboarding, upload_kyc_document,
+    submit_onboarding, go_live, get_onboarding_queue, review_onboarding,
+    list_menu_versions, create_menu_version, get_menu_version, update_menu_version,
+    discard_menu_version, publish_menu_version, unschedule_menu_version,
+    rollback_menu_version, diff_menu_version,
+    update_modifier_group, update_menu_item_stock, update_restaurant, update_restaurant_status,
+    start_busy_mode, end_busy_mode,
 };
+use crate::promotions::handlers::{
+    create_promotion, delete_promotion, list_promotions, update_promotion,
+};
+use crate::reviews::handlers::{
+    create_review, get_delivery_person_reviews, get_moderation_queue, get_restaurant_reviews,
+    moderate_review, reply_to_review, report_review,
+};
+use crate::search::handlers::{autocomplete, search};
 use crate::websocket::WebSocketManager;
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
     routing::{get, post, put},
     Router,
@@ -55,6 +77,8 @@ pub struct AppState {
     pub enhanced_delivery_service: std::sync::Arc<EnhancedDeliveryService>,
     pub metrics: MetricsCollector,
     pub analytics_service: AnalyticsService,
+    pub cache: CacheService,
+    pub media: MediaStorage,
 }
 
 pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) -> Router {
@@ -74,21 +98,26 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
             firebase_auth.clone(),
             auth_middleware,
         ))
-        .with_state(app_state.fcm_service.clone());
+        .with_state(app_state.clone());
 
     let payment_routes = Router::new()
         .route("/payments", post(create_payment))
         .route("/payments/:id", get(get_payment))
+        .route("/payments/:id/retry", post(retry_payment))
         .layer(middleware::from_fn_with_state(
             firebase_auth.clone(),
             auth_middleware,
         ))
-        .with_state(app_state.fcm_service.clone());
+        .with_state(app_state.clone());
 
-    // Restaurant routes (mixed public and authenticated)
+    // Restaurant routes (mixed public and authenticated). Public reads are
+    // personalised for signed-in callers.
     let restaurant_public_routes = Router::new()
         .route("/restaurants", get(list_restaurants))
         .route("/restaurants/search", get(search_restaurants))
+        .route("/restaurants/nearby", get(list_nearby_restaurants))
+        .route("/search", get(search))
+        .route("/search/autocomplete", get(autocomplete))
         .route("/restaurants/:id", get(get_restaurant))
         .route("/restaurants/:id/menu", get(get_menu))
         .route("/restaurants/city/:city", get(get_restaurants_by_city))
@@ -96,6 +125,10 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
             "/restaurants/cuisine/:cuisine_type",
             get(get_restaurants_by_cuisine),
         )
+        .layer(middleware::from_fn_with_state(
+            firebase_auth.clone(),
+            optional_auth_middleware,
+        ))
         .with_state(app_state.clone());
 
     let restaurant_auth_routes = Router::new()
@@ -103,13 +136,79 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
         .route("/restaurants/:id", put(update_restaurant))
         .route("/restaurants/:id", axum::routing::delete(delete_restaurant))
         .route("/restaurants/:id/status", put(update_restaurant_status))
+        .route(
+            "/restaurants/:id/busy",
+            put(start_busy_mode).delete(end_busy_mode),
+        )
         .route("/restaurants/:id/orders", get(get_restaurant_orders))
+        .route("/restaurants/:id/prep-time", get(get_prep_time_report))
         .route("/restaurants/:id/menu", post(create_menu_item))
+        .route("/restaurants/:id/menu/import", post(import_menu))
+        .route("/restaurants/:id/menu/export", get(export_menu))
+        .route("/restaurants/:id/menu/preview", get(preview_menu))
+        .route(
+            "/restaurants/:id/menu/versions",
+            get(list_menu_versions).post(create_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/versions/:version_id",
+            get(get_menu_version)
+                .put(update_menu_version)
+                .delete(discard_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/versions/:version_id/publish",
+            post(publish_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/versions/:version_id/unschedule",
+            post(unschedule_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/versions/:version_id/rollback",
+            post(rollback_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/versions/:version_id/diff",
+            get(diff_menu_version),
+        )
+        .route(
+            "/restaurants/:id/menu/categories/:category/availability",
+            put(set_category_availability),
+        )
         .route("/restaurants/:id/menu/:item_id", put(update_menu_item))
         .route(
             "/restaurants/:id/menu/:item_id",
             axum::routing::delete(delete_menu_item),
         )
+        .route(
+            "/restaurants/:id/menu/:item_id/modifiers",
+            post(create_modifier_group),
+        )
+        .route(
+            "/restaurants/:id/menu/:item_id/modifiers/:group_id",
+            put(update_modifier_group).delete(delete_modifier_group),
+        )
+        .route("/restaurants/:id/menu/:item_id/stock", put(update_menu_item_stock))
+        .route("/restaurants/:id/staff", get(list_staff).post(invite_staff))
+        .route("/restaurants/:id/staff/accept", post(accept_staff_invitation))
+        .route(
+            "/restaurants/:id/staff/:user_id",
+            axum::routing::delete(remove_staff),
+        )
+        .route("/staff/memberships", get(get_staff_memberships))
+        .route(
+            "/users/me/dietary-profile",
+            get(get_dietary_profile)
+                .put(update_dietary_profile)
+                .delete(delete_dietary_profile),
+        )
+        .route("/restaurants/:id/onboarding", get(get_onboarding))
+        .route("/restaurants/:id/onboarding/documents", post(upload_kyc_document))
+        .route("/restaurants/:id/onboarding/submit", post(submit_onboarding))
+        .route("/restaurants/:id/onboarding/go-live", post(go_live))
+        .route("/admin/restaurants/onboarding", get(get_onboarding_queue))
+        .route("/admin/restaurants/:id/onboarding", put(review_onboarding))
         .layer(middleware::from_fn_with_state(
             firebase_auth.clone(),
             auth_middleware,
@@ -130,6 +229,8 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
         .route("/delivery/:id/location", put(update_location))
         .route("/delivery/:id/stats", get(get_delivery_person_stats))
         .route("/delivery/assign-order", post(assign_order))
+        .route("/delivery/assignments/:id", get(get_delivery_assignment))
+        .route("/delivery/assignments/:id/trail", get(get_assignment_trail))
         .route("/delivery/assignments/:id/status", put(update_delivery_status))
         .layer(middleware::from_fn_with_state(
             firebase_auth.clone(),
@@ -137,12 +238,62 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
         ))
         .with_state(app_state.clone());
 
+    // Review routes (public listings, authenticated writes)
+    let review_public_routes = Router::new()
+        .route("/restaurants/:id/reviews", get(get_restaurant_reviews))
+        .route("/delivery/:id/reviews", get(get_delivery_person_reviews))
+        .with_state(app_state.clone());
+
+    let review_auth_routes = Router::new()
+        .route("/reviews", post(create_review))
+        .route("/reviews/:id/reply", put(reply_to_review))
+        .route("/reviews/:id/report", post(report_review))
+        .route("/admin/reviews/moderation", get(get_moderation_queue))
+        .route("/admin/reviews/:id/moderation", put(moderate_review))
+        .layer(middleware::from_fn_with_state(
+            firebase_auth.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state.clone());
+
+    // Restaurant-run promotions (authenticated)
+    let promotion_routes = Router::new()
+        .route(
+            "/restaurants/:id/promotions",
+            get(list_promotions).post(create_promotion),
+        )
+        .route(
+            "/restaurants/:id/promotions/:promotion_id",
+            put(update_promotion).delete(delete_promotion),
+        )
+        .layer(middleware::from_fn_with_state(
+            firebase_auth.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state.clone());
+
+    // Image uploads (authenticated) and serving (public)
+    let media_upload_routes = Router::new()
+        .route("/restaurants/:id/media", post(upload_media))
+        // Images have their own size limit instead of axum's 2 MB default
+        .layer(DefaultBodyLimit::max(app_state.media.max_upload_bytes))
+        .layer(middleware::from_fn_with_state(
+            firebase_auth.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state.clone());
+
+    let media_public_routes = Router::new()
+        .route("/media/*key", get(serve_media))
+        .with_state(app_state.clone());
+
     // WebSocket routes (authenticated)
     let websocket_routes = Router::new()
         .route("/ws/delivery", get(delivery_websocket_handler))
         .route("/ws/delivery-person/:id", get(delivery_person_websocket_handler))
         .route("/ws/restaurant/:id", get(restaurant_websocket_handler))
         .route("/ws/customer/:id", get(customer_websocket_handler))
+        .route("/ws/menu/:id", get(menu_websocket_handler))
         .route("/ws/admin", get(admin_websocket_handler))
         .route("/ws/stats", get(get_websocket_stats))
         .route("/ws/test-broadcast", post(broadcast_test_message))
@@ -218,6 +369,11 @@ pub fn create_routes(firebase_auth: SharedFirebaseAuth, app_state: AppState) ->
         .merge(delivery_public_routes)
         .merge(delivery_auth_routes)
         .merge(delivery_admin_routes)
+        .merge(review_public_routes)
+        .merge(review_auth_routes)
+        .merge(promotion_routes)
+        .merge(media_upload_routes)
+        .merge(media_public_routes)
         .merge(websocket_routes)
         .merge(enhanced_delivery_routes)
         .merge(analytics_routes)
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    Extension,
+};
+
+use crate::auth::models::User;
+use crate::error::Result;
+use crate::restaurants::dietary::{DietaryProfileService, DietaryQuery};
+use crate::routes::AppState;
+use crate::search::{
+    models::{AutocompleteQuery, SearchQuery, SearchResponse, Suggestion},
+    service::SearchService,
+};
+
+/// Ranked restaurant and dish search, checked against the signed-in
+/// customer's dietary profile
+pub async fn search(
+    State(state): State<AppState>,
+    user: Option<Extension<User>>,
+    Query(params): Query<SearchQuery>,
+    Query(dietary): Query<DietaryQuery>,
+) -> Result<Json<SearchResponse>> {
+    let search_service = SearchService::new(state.database.clone());
+    
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100); // Max 100 per page
+    let filter = DietaryProfileService::new(state.database.clone())
+        .resolve(user.map(|Extension(user)| user.id), &dietary)
+        .await?;
+    
+    let response = search_service
+        .search(&params.q, &params.filters, filter.as_ref(), page, per_page)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+/// Prefix suggestions for the search box
+pub async fn autocomplete(
+    State(state): State<AppState>,
+    Query(params): Query<AutocompleteQuery>,
+) -> Result<Json<Vec<Suggestion>>> {
+    let search_service = SearchService::new(state.database.clone());
+    
+    let limit = params.limit.unwrap_or(10).clamp(1, 25);
+    let suggestions = search_service
+        .autocomplete(&params.q, params.city.as_deref(), limit)
+        .await?;
+    
+    Ok(Json(suggestions))
+}
@@ -0,0 +1,12 @@
+pub mod handlers;
+pub mod models;
+pub mod ranking;
+pub mod service;
+pub mod text;
+
+pub use handlers::*;
+pub use models::*;
+pub use service::*;
+
+#[cfg(test)]
+mod tests;
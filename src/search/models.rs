@@ -0,0 +1,113 @@
+use crate::money::Money;
+use crate::restaurants::dietary::DietaryConflict;
+use crate::restaurants::models::RestaurantResponse;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Rough cost band from a restaurant's average dish price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceBand {
+    /// Average dish under ₹200
+    Budget,
+    /// ₹200 to ₹500
+    Mid,
+    /// Above ₹500
+    Premium,
+}
+
+impl PriceBand {
+    pub fn from_average_price(average: Money) -> Self {
+        if average < Money::from_rupees(200) {
+            PriceBand::Budget
+        } else if average <= Money::from_rupees(500) {
+            PriceBand::Mid
+        } else {
+            PriceBand::Premium
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchFilters {
+    pub city: Option<String>,
+    pub cuisine: Option<String>,
+    /// Only vegetarian dishes, and restaurants that serve them
+    #[serde(default)]
+    pub veg_only: bool,
+    pub min_rating: Option<f64>,
+    pub price_band: Option<PriceBand>,
+    /// Defaults to true, like the restaurant listing
+    pub open_now: Option<bool>,
+    /// Searcher's position, for distance ranking
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(flatten)]
+    pub filters: SearchFilters,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+/// A dish that matched the query, shown under its restaurant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DishMatch {
+    pub id: Uuid,
+    pub restaurant_id: Uuid,
+    pub name: String,
+    pub category: String,
+    pub price: Money,
+    pub is_vegetarian: bool,
+    pub relevance: f64,
+    /// How the dish clashes with the searcher's dietary profile
+    #[serde(default)]
+    pub dietary_conflicts: Vec<DietaryConflict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub restaurant: RestaurantResponse,
+    /// Blended rank in 0..=1; results are sorted by it
+    pub score: f64,
+    pub distance_km: Option<f64>,
+    pub price_band: Option<PriceBand>,
+    pub matched_dishes: Vec<DishMatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub query: String,
+    /// The query as searched, after transliteration and spelling fixes
+    pub interpreted_query: String,
+    pub results: Vec<SearchHit>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AutocompleteQuery {
+    pub q: String,
+    pub city: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    Restaurant,
+    Dish,
+    Cuisine,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub text: String,
+    pub kind: SuggestionKind,
+    /// Set for restaurant suggestions
+    pub restaurant_id: Option<Uuid>,
+}
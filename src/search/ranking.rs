@@ -0,0 +1,52 @@
+//! Blended search ranking: text relevance first, then rating and distance.
+
+/// Weights when the searcher's location is known
+const TEXT_WEIGHT: f64 = 0.6;
+const RATING_WEIGHT: f64 = 0.25;
+const DISTANCE_WEIGHT: f64 = 0.15;
+
+/// Ratings are pulled towards this average until a restaurant has enough
+/// reviews, so one 5-star review does not outrank hundreds of 4.4s
+const PRIOR_RATING: f64 = 3.5;
+const PRIOR_REVIEWS: f64 = 10.0;
+
+/// Distance at which the distance score halves
+const DISTANCE_HALF_SCORE_KM: f64 = 3.0;
+
+/// Review-count-weighted rating, scaled to 0..=1
+pub fn rating_score(rating: f64, total_reviews: i32) -> f64 {
+    let reviews = total_reviews.max(0) as f64;
+    let weighted = (rating * reviews + PRIOR_RATING * PRIOR_REVIEWS) / (reviews + PRIOR_REVIEWS);
+    (weighted / 5.0).clamp(0.0, 1.0)
+}
+
+/// 1.0 at the searcher's door, 0.5 at three kilometres, tending to zero
+pub fn distance_score(distance_km: f64) -> f64 {
+    1.0 / (1.0 + distance_km.max(0.0) / DISTANCE_HALF_SCORE_KM)
+}
+
+/// Final rank. Without a location the distance weight is shared out
+/// between text and rating in proportion.
+pub fn blend_score(text_relevance: f64, rating_score: f64, distance_km: Option<f64>) -> f64 {
+    let text = text_relevance.clamp(0.0, 1.0);
+    match distance_km {
+        Some(distance) => {
+            TEXT_WEIGHT * text + RATING_WEIGHT * rating_score + DISTANCE_WEIGHT * distance_score(distance)
+        }
+        None => {
+            let total = TEXT_WEIGHT + RATING_WEIGHT;
+            (TEXT_WEIGHT * text + RATING_WEIGHT * rating_score) / total
+        }
+    }
+}
+
+/// Great-circle distance in kilometres
+pub fn haversine_km(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let r = 6371.0;
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+    r * c
+}
@@ -0,0 +1,441 @@
+use crate::database::Database;
+use chrono::Utc;
+use crate::error::{AppError, Result};
+use crate::money::Money;
+use crate::promotions::service::PromotionService;
+use crate::restaurants::dietary::{DietaryFilter, DishFacts};
+use crate::restaurants::models::{Restaurant, RestaurantResponse};
+use crate::search::models::*;
+use crate::search::ranking::{blend_score, haversine_km, rating_score};
+use crate::search::text::{expand_prefix, normalize_query, transliterate_devanagari};
+use sqlx::{FromRow, PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Most candidates fetched from each index before ranking
+const CANDIDATE_LIMIT: i64 = 300;
+
+/// Minimum trigram word similarity for a fuzzy (misspelt) match. Applied as
+/// `pg_trgm.word_similarity_threshold` so the `<%` filters can use the
+/// trigram indexes; `word_similarity()` is only used for scoring.
+const FUZZY_MATCH_THRESHOLD: f64 = 0.4;
+
+/// Dish matches count slightly less than a match on the restaurant itself
+const DISH_RELEVANCE_FACTOR: f64 = 0.9;
+
+/// Dishes listed under each restaurant in results
+const DISHES_PER_RESULT: usize = 5;
+
+/// Text relevance in 0..=1: full-text matches score 0.6 and up by rank,
+/// fuzzy matches score their trigram similarity
+const RESTAURANT_RELEVANCE_SQL: &str = r#"
+    GREATEST(
+        CASE WHEN r.search_document @@ to_tsquery('simple', $1)
+             THEN 0.6 + 0.4 * ts_rank(r.search_document, to_tsquery('simple', $1), 32)::float8
+             ELSE 0 END,
+        word_similarity($2, LOWER(r.name))::float8,
+        word_similarity($2, LOWER(r.cuisine_type))::float8
+    )
+"#;
+
+const DISH_RELEVANCE_SQL: &str = r#"
+    GREATEST(
+        CASE WHEN m.search_document @@ to_tsquery('simple', $1)
+             THEN 0.6 + 0.4 * ts_rank(m.search_document, to_tsquery('simple', $1), 32)::float8
+             ELSE 0 END,
+        word_similarity($2, LOWER(m.name))::float8
+    )
+"#;
+
+/// Set the `<%` threshold for the rest of the transaction
+async fn set_fuzzy_threshold(conn: &mut PgConnection) -> Result<()> {
+    sqlx::query("SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)")
+        .bind(FUZZY_MATCH_THRESHOLD.to_string())
+        .execute(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    Ok(())
+}
+
+struct RestaurantCandidate {
+    restaurant: Restaurant,
+    text_relevance: f64,
+    average_item_price: Option<Money>,
+    is_pure_veg: bool,
+}
+
+pub struct SearchService {
+    db: Database,
+}
+
+impl SearchService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Ranked search over restaurants and their dishes. A restaurant matches
+    /// on its own name, cuisine or description, or through any of its dishes.
+    /// Dishes that clash with `dietary` are flagged, or dropped when it hides
+    /// conflicts.
+    pub async fn search(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        dietary: Option<&DietaryFilter>,
+        page: i32,
+        per_page: i32,
+    ) -> Result<SearchResponse> {
+        let normalized = normalize_query(query);
+        let tsquery = normalized.to_tsquery().ok_or_else(|| {
+            AppError::ValidationError("Search query must contain at least one word".to_string())
+        })?;
+        let similarity_text = normalized.canonical_text();
+
+        let mut dishes = self.find_dishes(&tsquery, &similarity_text, filters, dietary).await?;
+        if dietary.is_some_and(|dietary| dietary.hide_conflicts) {
+            // Before collecting restaurants, so ones matched only through
+            // hidden dishes drop out too
+            dishes.retain(|dish| dish.dietary_conflicts.is_empty());
+        }
+        let dish_restaurants: Vec<Uuid> = dishes
+            .iter()
+            .map(|dish| dish.restaurant_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let candidates = self
+            .find_restaurants(&tsquery, &similarity_text, &dish_restaurants, filters, dietary)
+            .await?;
+
+        let mut results = rank_candidates(candidates, dishes, filters);
+        let total = results.len() as i64;
+        let offset = ((page.max(1) - 1) * per_page) as usize;
+        results = results.into_iter().skip(offset).take(per_page as usize).collect();
+        PromotionService::new(self.db.clone())
+            .attach_restaurant_badges(results.iter_mut().map(|hit| &mut hit.restaurant), Utc::now())
+            .await?;
+
+        Ok(SearchResponse {
+            query: query.to_string(),
+            interpreted_query: similarity_text,
+            results,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    async fn find_dishes(
+        &self,
+        tsquery: &str,
+        similarity_text: &str,
+        filters: &SearchFilters,
+        dietary: Option<&DietaryFilter>,
+    ) -> Result<Vec<DishMatch>> {
+        let sql = format!(
+            r#"
+            SELECT m.id, m.restaurant_id, m.name, m.category, m.price, m.is_vegetarian,
+                   m.is_vegan, m.is_gluten_free, m.spice_level, m.ingredients, m.allergens,
+                   {relevance} AS relevance
+            FROM menu_items m
+            JOIN restaurants r ON r.id = m.restaurant_id
+            WHERE m.is_available = true
+              AND r.is_active = true
+              AND (m.search_document @@ to_tsquery('simple', $1)
+                   OR $2 <% LOWER(m.name))
+              AND ($3 = false OR m.is_vegetarian = true)
+              AND ($4::text IS NULL OR LOWER(r.city) = LOWER($4))
+            ORDER BY relevance DESC
+            LIMIT $5
+            "#,
+            relevance = DISH_RELEVANCE_SQL
+        );
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        set_fuzzy_threshold(&mut tx).await?;
+
+        let rows = sqlx::query(&sql)
+            .bind(tsquery)
+            .bind(similarity_text)
+            .bind(filters.veg_only)
+            .bind(&filters.city)
+            .bind(CANDIDATE_LIMIT)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let list = |column: &str| -> Vec<String> {
+                    row.get::<Option<serde_json::Value>, _>(column)
+                        .and_then(|value| serde_json::from_value(value).ok())
+                        .unwrap_or_default()
+                };
+                let (ingredients, allergens) = (list("ingredients"), list("allergens"));
+                let is_vegetarian = row.get("is_vegetarian");
+                let dietary_conflicts = dietary
+                    .map(|dietary| {
+                        dietary.profile.conflicts(&DishFacts {
+                            is_vegetarian,
+                            is_vegan: row.get("is_vegan"),
+                            is_gluten_free: row.get("is_gluten_free"),
+                            spice_level: row.get("spice_level"),
+                            ingredients: &ingredients,
+                            allergens: &allergens,
+                        })
+                    })
+                    .unwrap_or_default();
+
+                DishMatch {
+                    id: row.get("id"),
+                    restaurant_id: row.get("restaurant_id"),
+                    name: row.get("name"),
+                    category: row.get("category"),
+                    price: row.get("price"),
+                    is_vegetarian,
+                    relevance: row.get("relevance"),
+                    dietary_conflicts,
+                }
+            })
+            .collect())
+    }
+
+    async fn find_restaurants(
+        &self,
+        tsquery: &str,
+        similarity_text: &str,
+        dish_restaurants: &[Uuid],
+        filters: &SearchFilters,
+        dietary: Option<&DietaryFilter>,
+    ) -> Result<Vec<RestaurantCandidate>> {
+        let sql = format!(
+            r#"
+            SELECT r.*,
+                   {relevance} AS text_relevance,
+                   (SELECT ROUND(AVG(price), 2) FROM menu_items
+                    WHERE restaurant_id = r.id AND is_available = true) AS average_item_price,
+                   NOT EXISTS (SELECT 1 FROM menu_items
+                               WHERE restaurant_id = r.id AND is_available = true AND is_vegetarian = false) AS is_pure_veg
+            FROM restaurants r
+            WHERE r.is_active = true
+              AND (r.search_document @@ to_tsquery('simple', $1)
+                   OR $2 <% LOWER(r.name)
+                   OR $2 <% LOWER(r.cuisine_type)
+                   OR r.id = ANY($3))
+              AND ($4 = false OR r.is_accepting_orders = true)
+              AND ($5::text IS NULL OR LOWER(r.city) = LOWER($5))
+              AND ($6::text IS NULL OR LOWER(r.cuisine_type) = LOWER($6))
+              AND ($7::float8 IS NULL OR r.rating >= $7)
+              AND ($8 = false OR r.pure_veg = true)
+            ORDER BY text_relevance DESC, r.rating DESC
+            LIMIT $9
+            "#,
+            relevance = RESTAURANT_RELEVANCE_SQL
+        );
+
+        let mut tx = self.db.pool().begin().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        set_fuzzy_threshold(&mut tx).await?;
+
+        let rows = sqlx::query(&sql)
+            .bind(tsquery)
+            .bind(similarity_text)
+            .bind(dish_restaurants)
+            .bind(filters.open_now.unwrap_or(true))
+            .bind(&filters.city)
+            .bind(&filters.cuisine)
+            .bind(filters.min_rating)
+            .bind(dietary.is_some_and(|dietary| dietary.profile.pure_veg_only))
+            .bind(CANDIDATE_LIMIT)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RestaurantCandidate {
+                    restaurant: Restaurant::from_row(row).map_err(|e| AppError::DatabaseError(e.to_string()))?,
+                    text_relevance: row.get("text_relevance"),
+                    average_item_price: row.get("average_item_price"),
+                    is_pure_veg: row.get("is_pure_veg"),
+                })
+            })
+            .collect()
+    }
+
+    /// Prefix suggestions across restaurant names, dishes and cuisines.
+    /// The last word is completed through known spellings, so "biri" also
+    /// suggests "Chicken Biryani".
+    pub async fn autocomplete(&self, prefix: &str, city: Option<&str>, limit: i64) -> Result<Vec<Suggestion>> {
+        let patterns = prefix_patterns(prefix);
+        if patterns.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT text, kind, restaurant_id FROM (
+                SELECT r.name AS text, 'restaurant' AS kind, r.id AS restaurant_id,
+                       LOWER(r.name) LIKE ANY($1) AS starts_with, r.total_reviews::float8 AS weight
+                FROM restaurants r
+                WHERE r.is_active = true
+                  AND ($3::text IS NULL OR LOWER(r.city) = LOWER($3))
+                  AND (LOWER(r.name) LIKE ANY($1) OR LOWER(r.name) LIKE ANY($2))
+                UNION ALL
+                SELECT MIN(m.name), 'dish', NULL::uuid,
+                       BOOL_OR(LOWER(m.name) LIKE ANY($1)), COUNT(*)::float8
+                FROM menu_items m
+                JOIN restaurants r ON r.id = m.restaurant_id
+                WHERE r.is_active = true AND m.is_available = true
+                  AND ($3::text IS NULL OR LOWER(r.city) = LOWER($3))
+                  AND (LOWER(m.name) LIKE ANY($1) OR LOWER(m.name) LIKE ANY($2))
+                GROUP BY LOWER(m.name)
+                UNION ALL
+                SELECT MIN(r.cuisine_type), 'cuisine', NULL::uuid,
+                       BOOL_OR(LOWER(r.cuisine_type) LIKE ANY($1)), COUNT(*)::float8
+                FROM restaurants r
+                WHERE r.is_active = true
+                  AND ($3::text IS NULL OR LOWER(r.city) = LOWER($3))
+                  AND (LOWER(r.cuisine_type) LIKE ANY($1) OR LOWER(r.cuisine_type) LIKE ANY($2))
+                GROUP BY LOWER(r.cuisine_type)
+            ) suggestions
+            ORDER BY starts_with DESC, weight DESC, text
+            LIMIT $4
+            "#,
+        )
+        .bind(&patterns.starts_with)
+        .bind(&patterns.word_starts_with)
+        .bind(city)
+        .bind(limit)
+        .fetch_all(self.db.pool())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        let mut seen = HashSet::new();
+        let mut suggestions = Vec::with_capacity(rows.len());
+        for row in rows {
+            let text: String = row.get("text");
+            if !seen.insert(text.to_lowercase()) {
+                continue;
+            }
+            let kind = match row.get::<String, _>("kind").as_str() {
+                "restaurant" => SuggestionKind::Restaurant,
+                "cuisine" => SuggestionKind::Cuisine,
+                _ => SuggestionKind::Dish,
+            };
+            suggestions.push(Suggestion {
+                text,
+                kind,
+                restaurant_id: row.get("restaurant_id"),
+            });
+        }
+
+        Ok(suggestions)
+    }
+}
+
+/// LIKE patterns for autocomplete
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PrefixPatterns {
+    /// The whole name starts with the prefix
+    pub starts_with: Vec<String>,
+    /// Any word in the name starts with the prefix
+    pub word_starts_with: Vec<String>,
+}
+
+impl PrefixPatterns {
+    pub fn is_empty(&self) -> bool {
+        self.starts_with.is_empty()
+    }
+}
+
+/// Build autocomplete patterns, completing the last word through known
+/// spellings. Only letters, digits and single spaces survive, so the
+/// patterns never carry LIKE wildcards from user input.
+pub fn prefix_patterns(prefix: &str) -> PrefixPatterns {
+    let text = transliterate_devanagari(prefix).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let Some((last, head)) = words.split_last() else {
+        return PrefixPatterns::default();
+    };
+
+    let mut patterns = PrefixPatterns::default();
+    for completion in expand_prefix(last) {
+        let phrase = head
+            .iter()
+            .copied()
+            .chain(std::iter::once(completion.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        patterns.starts_with.push(format!("{}%", phrase));
+        patterns.word_starts_with.push(format!("% {}%", phrase));
+    }
+    patterns
+}
+
+/// Merge restaurant and dish matches, apply the filters that need both,
+/// and sort by blended score
+fn rank_candidates(candidates: Vec<RestaurantCandidate>, dishes: Vec<DishMatch>, filters: &SearchFilters) -> Vec<SearchHit> {
+    let mut dishes_by_restaurant: HashMap<Uuid, Vec<DishMatch>> = HashMap::new();
+    for dish in dishes {
+        dishes_by_restaurant.entry(dish.restaurant_id).or_default().push(dish);
+    }
+
+    let origin = filters.latitude.zip(filters.longitude);
+    let mut hits: Vec<SearchHit> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let mut matched_dishes = dishes_by_restaurant.remove(&candidate.restaurant.id).unwrap_or_default();
+            if filters.veg_only && !candidate.is_pure_veg && matched_dishes.is_empty() {
+                return None;
+            }
+
+            let price_band = candidate.average_item_price.map(PriceBand::from_average_price);
+            if filters.price_band.is_some() && price_band != filters.price_band {
+                return None;
+            }
+
+            let best_dish = matched_dishes
+                .iter()
+                .map(|dish| dish.relevance * DISH_RELEVANCE_FACTOR)
+                .fold(0.0, f64::max);
+            let text_relevance = candidate.text_relevance.max(best_dish);
+
+            let restaurant = &candidate.restaurant;
+            let distance_km = origin.zip(restaurant.latitude.zip(restaurant.longitude)).map(
+                |((lat, lng), (restaurant_lat, restaurant_lng))| haversine_km(lat, lng, restaurant_lat, restaurant_lng),
+            );
+            let score = blend_score(
+                text_relevance,
+                rating_score(restaurant.rating, restaurant.total_reviews),
+                distance_km,
+            );
+
+            matched_dishes.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+            matched_dishes.truncate(DISHES_PER_RESULT);
+
+            Some(SearchHit {
+                restaurant: RestaurantResponse::from(candidate.restaurant),
+                score,
+                distance_km,
+                price_band,
+                matched_dishes,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::search::models::PriceBand;
+    use crate::search::ranking::*;
+    use crate::search::service::prefix_patterns;
+    use crate::search::text::*;
+
+    #[test]
+    fn test_spelling_variants_share_a_canonical_term() {
+        for query in ["biryani", "Biriyani", "BIRYAANI", "briyani"] {
+            let normalized = normalize_query(query);
+            assert_eq!(normalized.canonical_text(), "biryani", "query {}", query);
+        }
+
+        let normalized = normalize_query("murgh tikka");
+        assert_eq!(normalized.canonical_text(), "chicken tikka");
+        assert_eq!(
+            normalized.to_tsquery().unwrap(),
+            "(chicken:* | murgh:* | murg:* | chikan:*) & (tikka:* | tika:*)"
+        );
+    }
+
+    #[test]
+    fn test_query_normalization_drops_noise() {
+        let normalized = normalize_query("  Paneer & the  dal!! near me ");
+        assert_eq!(normalized.canonical_text(), "paneer dal");
+
+        // Repeated synonyms collapse into one term
+        assert_eq!(normalize_query("dal daal").terms.len(), 1);
+        assert!(normalize_query("!!! the").to_tsquery().is_none());
+    }
+
+    #[test]
+    fn test_devanagari_transliteration() {
+        assert_eq!(transliterate_devanagari("बिरयानी"), "biryani");
+        assert_eq!(transliterate_devanagari("पनीर"), "panir");
+        assert_eq!(transliterate_devanagari("मसाला डोसा"), "masala dosa");
+        assert_eq!(transliterate_devanagari("dal मखनी"), "dal makhni");
+
+        // Hindi queries land on the same canonical terms as English ones
+        assert_eq!(normalize_query("पनीर टिक्का").canonical_text(), "paneer tikka");
+    }
+
+    #[test]
+    fn test_fold_spelling() {
+        assert_eq!(fold_spelling("Paneer"), fold_spelling("panir"));
+        assert_eq!(fold_spelling("chhole"), "chole");
+        assert_eq!(fold_spelling("wada"), fold_spelling("vada"));
+    }
+
+    #[test]
+    fn test_autocomplete_prefix_expansion() {
+        assert!(expand_prefix("biri").contains(&"biryani".to_string()));
+        assert!(expand_prefix("murg").contains(&"chicken".to_string()));
+        // Short prefixes are not expanded
+        assert_eq!(expand_prefix("bi"), vec!["bi".to_string()]);
+
+        let patterns = prefix_patterns("Chicken Biri");
+        assert!(patterns.starts_with.contains(&"chicken biri%".to_string()));
+        assert!(patterns.starts_with.contains(&"chicken biryani%".to_string()));
+        assert!(patterns.word_starts_with.contains(&"% chicken biryani%".to_string()));
+        // LIKE wildcards in the input never reach the pattern
+        assert_eq!(prefix_patterns("50%_off").starts_with[0], "50 off%");
+        assert!(prefix_patterns("%%").is_empty());
+    }
+
+    #[test]
+    fn test_rating_score_needs_reviews_to_count() {
+        let one_review = rating_score(5.0, 1);
+        let many_reviews = rating_score(4.5, 500);
+        assert!(many_reviews > one_review);
+        assert!((rating_score(0.0, 0) - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_blend_score_prefers_relevance_then_proximity() {
+        let rating = rating_score(4.2, 100);
+
+        // Closer wins when text and rating are equal
+        assert!(blend_score(0.8, rating, Some(1.0)) > blend_score(0.8, rating, Some(8.0)));
+        // A strong text match outweighs a few kilometres
+        assert!(blend_score(1.0, rating, Some(6.0)) > blend_score(0.5, rating, Some(0.5)));
+        // Without a location scores stay in 0..=1
+        let score = blend_score(1.0, 1.0, None);
+        assert!((score - 1.0).abs() < 1e-9);
+        assert!((distance_score(3.0) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_band_from_average_price() {
+        assert_eq!(PriceBand::from_average_price(Money::from_rupees(150)), PriceBand::Budget);
+        assert_eq!(PriceBand::from_average_price(Money::from_rupees(200)), PriceBand::Mid);
+        assert_eq!(PriceBand::from_average_price(Money::from_rupees(500)), PriceBand::Mid);
+        assert_eq!(PriceBand::from_average_price(Money::from_rupees(650)), PriceBand::Premium);
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // Connaught Place to India Gate is a little over 2 km
+        let km = haversine_km(28.6315, 77.2167, 28.6129, 77.2295);
+        assert!((2.0..2.6).contains(&km), "got {}", km);
+    }
+}
@@ -0,0 +1,329 @@
+//! Query normalisation for restaurant and dish search: Devanagari
+//! transliteration, Hinglish spelling variants and synonym expansion.
+
+/// Spelling variants and synonyms, canonical form first. Lookups go
+/// through [`fold_spelling`], so doubled vowels and similar variants
+/// ("biryaani", "paneer") do not need their own entries.
+const SYNONYM_GROUPS: &[&[&str]] = &[
+    &["biryani", "biriyani", "briyani", "biriani", "beryani"],
+    &["paneer", "panir"],
+    &["chicken", "murgh", "murg", "chikan"],
+    &["mutton", "gosht", "lamb"],
+    &["fish", "machli", "machhi", "macchi"],
+    &["prawn", "prawns", "jhinga", "shrimp"],
+    &["egg", "anda", "eggs"],
+    &["dal", "daal", "dhal", "lentil", "lentils"],
+    &["roti", "chapati", "chapathi", "phulka"],
+    &["paratha", "parantha", "parotta", "porotta"],
+    &["dosa", "dosai", "thosai"],
+    &["idli", "idly", "iddli"],
+    &["vada", "wada", "vadai"],
+    &["chai", "tea"],
+    &["coffee", "kaapi", "kapi"],
+    &["aloo", "alu", "potato"],
+    &["gobi", "gobhi", "cauliflower"],
+    &["bhindi", "okra", "ladyfinger"],
+    &["chole", "chhole", "chana", "channa", "chickpea"],
+    &["rajma", "kidney"],
+    &["tikka", "tika"],
+    &["kebab", "kabab", "kabob", "kebap"],
+    &["masala", "masaala"],
+    &["makhani", "makhni", "butter"],
+    &["curd", "dahi", "yogurt", "yoghurt"],
+    &["rice", "chawal"],
+    &["pulao", "pulav", "pilaf"],
+    &["khichdi", "khichri", "khichadi"],
+    &["samosa", "samosas"],
+    &["momo", "momos", "dumpling", "dumplings"],
+    &["noodles", "noodle", "chowmein"],
+    &["sweets", "mithai", "dessert", "desserts"],
+    &["gulab", "gulaab"],
+    &["rasgulla", "rasagola", "rosogolla"],
+    &["kulfi", "kulphi"],
+    &["lassi", "lussi"],
+    &["pizza", "pizzas"],
+    &["burger", "burgers"],
+    &["veg", "vegetarian", "shakahari"],
+    &["nonveg", "mansahari"],
+    &["thali", "thaali", "meals"],
+];
+
+/// Words dropped from queries; they match almost everything
+const STOP_WORDS: &[&str] = &["and", "the", "with", "of", "in", "near", "me", "aur", "wala", "wali"];
+
+/// A search query split into terms, each with the spellings it should match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedQuery {
+    pub terms: Vec<QueryTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    /// Canonical spelling, used for similarity matching
+    pub canonical: String,
+    /// Every spelling to match, canonical first
+    pub variants: Vec<String>,
+}
+
+impl NormalizedQuery {
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Canonical terms joined back into one string, for trigram similarity
+    pub fn canonical_text(&self) -> String {
+        self.terms
+            .iter()
+            .map(|term| term.canonical.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Postgres `to_tsquery('simple', ..)` input: every term must match one
+    /// of its variants, each as a prefix. Terms are plain ASCII words, so
+    /// nothing needs escaping.
+    pub fn to_tsquery(&self) -> Option<String> {
+        if self.terms.is_empty() {
+            return None;
+        }
+
+        let clauses: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| {
+                let variants: Vec<String> = term
+                    .variants
+                    .iter()
+                    .map(|variant| format!("{}:*", variant))
+                    .collect();
+                format!("({})", variants.join(" | "))
+            })
+            .collect();
+
+        Some(clauses.join(" & "))
+    }
+}
+
+/// Normalise a raw search query: transliterate Devanagari, lowercase,
+/// drop punctuation and stop words, then expand synonyms
+pub fn normalize_query(raw: &str) -> NormalizedQuery {
+    let text = transliterate_devanagari(raw).to_lowercase();
+    let words = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word));
+
+    let mut terms: Vec<QueryTerm> = Vec::new();
+    for word in words {
+        let term = match synonym_group(word) {
+            Some(group) => QueryTerm {
+                canonical: group[0].to_string(),
+                variants: group.iter().map(|variant| variant.to_string()).collect(),
+            },
+            None => QueryTerm {
+                canonical: word.to_string(),
+                variants: vec![word.to_string()],
+            },
+        };
+        if !terms.iter().any(|existing| existing.canonical == term.canonical) {
+            terms.push(term);
+        }
+    }
+
+    NormalizedQuery { terms }
+}
+
+/// Canonical words a partly typed word could be heading for, for
+/// autocomplete ("biri" -> "biryani", "murg" -> "chicken")
+pub fn expand_prefix(prefix: &str) -> Vec<String> {
+    let folded = fold_spelling(prefix);
+    let mut expansions = vec![prefix.to_string()];
+
+    if folded.len() < 3 {
+        return expansions;
+    }
+    for group in SYNONYM_GROUPS {
+        if group.iter().any(|variant| fold_spelling(variant).starts_with(&folded)) {
+            let canonical = group[0].to_string();
+            if !expansions.contains(&canonical) {
+                expansions.push(canonical);
+            }
+        }
+    }
+
+    expansions
+}
+
+fn synonym_group(word: &str) -> Option<&'static [&'static str]> {
+    let folded = fold_spelling(word);
+    SYNONYM_GROUPS
+        .iter()
+        .copied()
+        .find(|group| group.iter().any(|variant| fold_spelling(variant) == folded))
+}
+
+/// Collapse the spelling differences common in romanised Hindi: doubled
+/// letters, long vowels written twice, and a few interchangeable sounds
+pub fn fold_spelling(word: &str) -> String {
+    let replaced = word
+        .to_lowercase()
+        .replace("ee", "i")
+        .replace("oo", "u")
+        .replace("aa", "a")
+        .replace("ph", "f")
+        .replace('w', "v")
+        .replace('z', "j");
+
+    let mut folded = String::with_capacity(replaced.len());
+    for c in replaced.chars() {
+        if folded.ends_with(c) {
+            continue;
+        }
+        folded.push(c);
+    }
+    folded
+}
+
+/// Romanise Devanagari text so queries typed in Hindi match English menus.
+/// Inherent vowels are dropped at word ends and in the middle of words
+/// where spoken Hindi drops them ("बिरयानी" -> "biryani"); other text is
+/// passed through unchanged.
+pub fn transliterate_devanagari(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    // Whether the previous output ended in a vowel within the current word
+    let mut after_vowel = false;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if let Some(consonant) = devanagari_consonant(c) {
+            output.push_str(consonant);
+            let next = chars.get(i + 1).copied();
+
+            match next.and_then(devanagari_matra) {
+                Some(vowel) => {
+                    output.push_str(vowel);
+                    after_vowel = true;
+                    i += 2;
+                    continue;
+                }
+                None if next == Some('\u{094D}') => {
+                    // Virama: no vowel follows this consonant
+                    after_vowel = false;
+                    i += 2;
+                    continue;
+                }
+                None => {}
+            }
+
+            let word_ends = next.is_none_or(|n| !is_devanagari_letter(n));
+            let next_syllable_has_vowel = match (next, chars.get(i + 2).copied()) {
+                (Some(n), following) if devanagari_consonant(n).is_some() => {
+                    following.and_then(devanagari_matra).is_some()
+                }
+                _ => false,
+            };
+            if word_ends || (after_vowel && next_syllable_has_vowel) {
+                after_vowel = false;
+            } else {
+                output.push('a');
+                after_vowel = true;
+            }
+            i += 1;
+            continue;
+        }
+
+        if let Some(vowel) = devanagari_vowel(c) {
+            output.push_str(vowel);
+            after_vowel = true;
+        } else if c == '\u{0902}' || c == '\u{0901}' {
+            // Anusvara / chandrabindu
+            output.push('n');
+        } else if c == '\u{093C}' {
+            // Nukta: keep the base consonant's reading
+        } else {
+            output.push(c);
+            after_vowel = false;
+        }
+        i += 1;
+    }
+
+    output
+}
+
+fn is_devanagari_letter(c: char) -> bool {
+    ('\u{0900}'..='\u{097F}').contains(&c)
+}
+
+fn devanagari_vowel(c: char) -> Option<&'static str> {
+    Some(match c {
+        'अ' => "a",
+        'आ' => "a",
+        'इ' => "i",
+        'ई' => "i",
+        'उ' => "u",
+        'ऊ' => "u",
+        'ऋ' => "ri",
+        'ए' => "e",
+        'ऐ' => "ai",
+        'ओ' => "o",
+        'औ' => "au",
+        _ => return None,
+    })
+}
+
+fn devanagari_matra(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ा' => "a",
+        'ि' => "i",
+        'ी' => "i",
+        'ु' => "u",
+        'ू' => "u",
+        'ृ' => "ri",
+        'े' => "e",
+        'ै' => "ai",
+        'ो' => "o",
+        'ौ' => "au",
+        _ => return None,
+    })
+}
+
+fn devanagari_consonant(c: char) -> Option<&'static str> {
+    Some(match c {
+        'क' => "k",
+        'ख' => "kh",
+        'ग' => "g",
+        'घ' => "gh",
+        'ङ' => "n",
+        'च' => "ch",
+        'छ' => "chh",
+        'ज' => "j",
+        'झ' => "jh",
+        'ञ' => "n",
+        'ट' => "t",
+        'ठ' => "th",
+        'ड' => "d",
+        'ढ' => "dh",
+        'ण' => "n",
+        'त' => "t",
+        'थ' => "th",
+        'द' => "d",
+        'ध' => "dh",
+        'न' => "n",
+        'प' => "p",
+        'फ' => "ph",
+        'ब' => "b",
+        'भ' => "bh",
+        'म' => "m",
+        'य' => "y",
+        'र' => "r",
+        'ल' => "l",
+        'व' => "v",
+        'श' => "sh",
+        'ष' => "sh",
+        'स' => "s",
+        'ह' => "h",
+        _ => return None,
+    })
+}
@@ -46,9 +46,106 @@ impl Server {
         
         let delivery_websocket_manager = std::sync::Arc::new(crate::delivery::DeliveryWebSocketManager::new());
         let enhanced_delivery_service = std::sync::Arc::new(
-            crate::delivery::EnhancedDeliveryService::new(database.clone(), delivery_websocket_manager.clone())
+            crate::delivery::EnhancedDeliveryService::new(
+                database.clone(),
+                delivery_websocket_manager.clone(),
+                chrono::Duration::seconds(self.config.delivery_assignment_timeout_seconds as i64),
+            )
         );
 
+        // Offer pending orders to riders in batches, passing lapsed offers on
+        crate::delivery::DispatchEngine::new(
+            database.clone(),
+            enhanced_delivery_service.clone(),
+            (*delivery_websocket_manager).clone(),
+            crate::delivery::DispatchConfig::from_config(&self.config),
+        )
+        .spawn();
+
+        // Keep the in-memory rider index in step with the database
+        crate::delivery::RiderIndexSync::new(
+            database.clone(),
+            enhanced_delivery_service.rider_index().clone(),
+            std::time::Duration::from_secs(self.config.rider_index_resync_interval_seconds),
+        )
+        .spawn();
+
+        // Drop rider location history once it outlives its retention period
+        crate::delivery::TrailPurger::new(
+            database.clone(),
+            &crate::india::config::ComplianceConfig::default(),
+            std::time::Duration::from_secs(self.config.location_trail_purge_interval_seconds),
+        )
+        .spawn();
+
+        // Apply offer answers riders send over their sockets
+        crate::delivery::OfferResponder::new(
+            database.clone(),
+            (*delivery_websocket_manager).clone(),
+            enhanced_delivery_service.rider_index().clone(),
+        )
+        .spawn();
+
+        // Drive payments to a final state in the background
+        let payment_gateway = Arc::new(crate::payments::HttpPaymentGateway::new(&self.config));
+        crate::payments::PaymentReconciler::new(
+            database.clone(),
+            payment_gateway,
+            crate::payments::ReconcilerConfig::from_config(&self.config),
+        )
+        .spawn();
+
+        // Open and close restaurants according to their opening hours
+        crate::restaurants::OpeningHoursScheduler::new(
+            database.clone(),
+            std::time::Duration::from_secs(self.config.opening_hours_check_interval_seconds),
+        )
+        .spawn();
+
+        // Turn busy mode on when kitchens fill up, and off when spells lapse
+        crate::restaurants::BusyModeMonitor::new(
+            database.clone(),
+            (*delivery_websocket_manager).clone(),
+            std::time::Duration::from_secs(self.config.busy_mode_check_interval_seconds),
+        )
+        .spawn();
+
+        // Reset daily stock levels and tell menu viewers about it
+        let cache = crate::cache::CacheService::new(crate::cache::CacheConfig::default());
+        crate::restaurants::InventoryRestocker::new(
+            database.clone(),
+            (*delivery_websocket_manager).clone(),
+            cache.clone(),
+            std::time::Duration::from_secs(self.config.inventory_restock_check_interval_seconds),
+            self.config.inventory_restock_time,
+        )
+        .spawn();
+
+        // Publish menu versions scheduled for later
+        crate::restaurants::MenuPublishScheduler::new(
+            database.clone(),
+            cache.clone(),
+            std::time::Duration::from_secs(self.config.menu_publish_check_interval_seconds),
+        )
+        .spawn();
+
+        // Relearn each restaurant's prep times from its order history
+        crate::orders::PrepTimeTrainer::new(
+            database.clone(),
+            std::time::Duration::from_secs(self.config.prep_time_training_interval_seconds),
+        )
+        .spawn();
+
+        // Uploaded images, and removal of uploads nothing uses
+        let media = crate::media::MediaStorage::from_config(&self.config)?;
+        crate::media::MediaCleaner::new(
+            database.clone(),
+            media.clone(),
+            std::time::Duration::from_secs(self.config.media_cleanup_interval_seconds),
+            chrono::Duration::hours(self.config.media_orphan_grace_hours),
+        )
+        .spawn();
+
         let app_state = crate::routes::AppState {
             fcm_service: self.fcm_service.clone(),
             database,
@@ -57,6 +154,8 @@ impl Server {
             enhanced_delivery_service,
             metrics: crate::metrics::MetricsCollector::new().unwrap(),
             analytics_service,
+            cache,
+            media,
         };
         
         let app = create_routes(self.firebase_auth.clone(), app_state)
@@ -0,0 +1,79 @@
+use server::delivery::OfferService;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+async fn test_pool() -> PgPool {
+    let database_url = std::env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgresql://localhost/delivery_server_test".to_string());
+    PgPool::connect(&database_url).await.unwrap()
+}
+
+async fn insert_user(conn: &mut PgConnection, role: &str) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO users (firebase_uid, email, role) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(format!("{}@example.com", Uuid::new_v4()))
+    .bind(role)
+    .fetch_one(conn)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_accepting_offer_stores_rider_user_id_on_order() {
+    let pool = test_pool().await;
+    // Everything is rolled back at the end
+    let mut tx = pool.begin().await.unwrap();
+    let customer_id = insert_user(&mut tx, "customer").await;
+    let owner_id = insert_user(&mut tx, "restaurant").await;
+    let rider_user_id = insert_user(&mut tx, "delivery_person").await;
+
+    let restaurant_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO restaurants (owner_id, name, cuisine_type, address, city, state, postal_code, phone)
+        VALUES ($1, 'Test Kitchen', 'North Indian', '1 MG Road', 'Bengaluru', 'Karnataka', '560001', '+919800000000')
+        RETURNING id
+        "#,
+    )
+    .bind(owner_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let order_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO orders (order_number, customer_id, restaurant_id, items, subtotal, total_amount,
+                            delivery_address, restaurant_address)
+        VALUES ($1, $2, $3, '[]', 100.00, 100.00, '{}', '{}')
+        RETURNING id
+        "#,
+    )
+    .bind(format!("TEST-{}", Uuid::new_v4()))
+    .bind(customer_id)
+    .bind(restaurant_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+    let delivery_person_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO delivery_persons (user_id, vehicle_type, license_number) VALUES ($1, 'motorcycle', 'DL0000') RETURNING id",
+    )
+    .bind(rider_user_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap();
+
+    let stored = OfferService::put_rider_on_order(&mut tx, delivery_person_id, order_id)
+        .await
+        .unwrap();
+    let on_order: Option<Uuid> = sqlx::query_scalar("SELECT delivery_person_id FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    // orders.delivery_person_id references users(id), not delivery_persons(id)
+    assert_eq!(stored, rider_user_id);
+    assert_eq!(on_order, Some(rider_user_id));
+    assert_ne!(stored, delivery_person_id);
+}
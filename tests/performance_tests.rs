@@ -1,5 +1,9 @@
 use reqwest::Client;
 use serde_json::json;
+use server::database::Database;
+use server::delivery::{DeliveryService, RiderGeoIndex, RiderIndexSync, NEARBY_RIDER_LIMIT};
+use server::restaurants::serviceability::GeoPoint;
+use uuid::Uuid;
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
 
@@ -363,4 +367,81 @@ async fn test_timeout_handling() {
             println!("⏰ Timeout handling works correctly");
         }
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_rider_index_query_performance() {
+    let index = RiderGeoIndex::new();
+    // 10,000 riders spread over about 40 km of Bengaluru
+    for i in 0..10_000u32 {
+        let latitude = 12.80 + (i % 100) as f64 * 0.0036;
+        let longitude = 77.45 + (i / 100) as f64 * 0.0037;
+        index.upsert(Uuid::new_v4(), GeoPoint::new(latitude, longitude));
+    }
+    let center = GeoPoint::new(12.9716, 77.5946);
+    let rounds = 1_000u32;
+
+    let start = Instant::now();
+    for _ in 0..rounds {
+        std::hint::black_box(index.nearest(&center, NEARBY_RIDER_LIMIT, 10.0));
+    }
+    let nearest_time = start.elapsed() / rounds;
+
+    let start = Instant::now();
+    for _ in 0..rounds {
+        std::hint::black_box(index.within(&center, 2.0));
+    }
+    let within_time = start.elapsed() / rounds;
+
+    println!("📍 Rider Index Performance (10,000 riders):");
+    println!("   k-nearest (k = {}): {:?}", NEARBY_RIDER_LIMIT, nearest_time);
+    println!("   Within 2 km: {:?}", within_time);
+
+    assert!(nearest_time < Duration::from_millis(1),
+           "Nearest-rider lookups should take microseconds");
+    assert!(within_time < Duration::from_millis(1),
+           "Radius lookups should take microseconds");
+}
+
+#[tokio::test]
+async fn test_nearby_riders_index_vs_sql_performance() {
+    let database_url = std::env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgresql://localhost/delivery_server_test".to_string());
+    let database = Database::new(sqlx::PgPool::connect(&database_url).await.unwrap());
+
+    let index = RiderGeoIndex::new();
+    let riders = RiderIndexSync::new(database.clone(), index.clone(), Duration::from_secs(60))
+        .run_once()
+        .await
+        .unwrap();
+    let delivery_service = DeliveryService::new(database);
+    let (latitude, longitude, radius_km) = (19.0760, 72.8777, 10.0);
+    let rounds = 50u32;
+
+    let start = Instant::now();
+    let mut from_sql = Vec::new();
+    for _ in 0..rounds {
+        from_sql = delivery_service
+            .get_nearby_delivery_persons_from_database(latitude, longitude, radius_km)
+            .await
+            .unwrap();
+    }
+    let sql_time = start.elapsed() / rounds;
+
+    let start = Instant::now();
+    let mut from_index = Vec::new();
+    for _ in 0..rounds {
+        from_index = index.nearest(&GeoPoint::new(latitude, longitude), NEARBY_RIDER_LIMIT, radius_km);
+    }
+    let index_time = start.elapsed() / rounds;
+
+    println!("🛵 Nearby Riders: Index vs SQL ({} available riders):", riders);
+    println!("   SQL haversine scan: {:?}", sql_time);
+    println!("   In-memory index: {:?}", index_time);
+
+    // Both paths should find the same riders in the same order
+    let sql_ids: Vec<Uuid> = from_sql.iter().map(|nearby| nearby.delivery_person.id).collect();
+    let index_ids: Vec<Uuid> = from_index.iter().map(|nearby| nearby.delivery_person_id).collect();
+    assert_eq!(sql_ids, index_ids, "Index and SQL should agree on the nearest riders");
+    assert!(index_time < sql_time, "The index should answer faster than the SQL scan");
+}